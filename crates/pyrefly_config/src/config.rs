@@ -623,6 +623,12 @@ pub struct ConfigFile {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub extra_file_extensions: Vec<String>,
 
+    /// Runtime-only cap on how many of this config's project files the language server indexes,
+    /// set from the workspace's `maxIndexedFiles` setting when the config is loaded. Never
+    /// serialized.
+    #[serde(skip)]
+    pub max_indexed_files: Option<usize>,
+
     /// Runtime-only metadata. Populated by `resolve_unconfigured_config`
     /// when this `ConfigFile` was synthesized rather than loaded from a
     /// `pyrefly.toml` / `[tool.pyrefly]` section, and by the `--preset`
@@ -667,6 +673,7 @@ impl Default for ConfigFile {
             output_format: None,
             skip_lsp_config_indexing: false,
             extra_file_extensions: Vec::new(),
+            max_indexed_files: None,
             synthesized_preset_reason: None,
         }
     }
@@ -1823,6 +1830,7 @@ mod tests {
                 min_severity: None,
                 skip_lsp_config_indexing: false,
                 extra_file_extensions: Vec::new(),
+                max_indexed_files: None,
                 synthesized_preset_reason: None,
             }
         );
@@ -2066,6 +2074,7 @@ mod tests {
             min_severity: None,
             skip_lsp_config_indexing: false,
             extra_file_extensions: Vec::new(),
+            max_indexed_files: None,
             synthesized_preset_reason: None,
         };
 
@@ -2130,6 +2139,7 @@ mod tests {
             min_severity: None,
             skip_lsp_config_indexing: false,
             extra_file_extensions: Vec::new(),
+            max_indexed_files: None,
             synthesized_preset_reason: None,
         };
         assert_eq!(config, expected_config);
@@ -3596,4 +3606,28 @@ output-format = "omit-errors"
             "without pytorch-efficiency-lints flag, lints should default to Ignore"
         );
     }
+
+    #[test]
+    fn test_paths_to_watch_dedup_nested_search_paths() {
+        let root = PathBuf::from("/project");
+        let config = ConfigFile {
+            search_path_from_file: vec![root.clone(), root.join("src"), root.join("src/lib")],
+            python_environment: PythonEnvironment {
+                site_package_path: Some(vec![root.join("venv/site-packages")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let configs = SmallSet::from_iter([ArcId::new(config)]);
+        let patterns = ConfigFile::get_paths_to_watch(&configs);
+        let suffix_count = PYTHON_EXTENSIONS.len() + COMPILED_FILE_SUFFIXES.len();
+        assert_eq!(patterns.len(), 4 * suffix_count);
+        // Every search path, and the site packages, live under `/project`.
+        let deduped = WatchPattern::dedup_nested_roots(patterns);
+        assert_eq!(deduped.len(), suffix_count);
+        assert!(deduped.iter().all(|pattern| matches!(
+            pattern,
+            WatchPattern::Root(watched, _) if **watched == *root
+        )));
+    }
 }
@@ -11,12 +11,14 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
 use std::sync::LazyLock;
+use std::time::SystemTime;
 
 use anyhow::Context;
 use anyhow::anyhow;
 use itertools::Itertools;
 use pyrefly_python::sys_info::PythonPlatform;
 use pyrefly_python::sys_info::PythonVersion;
+use pyrefly_util::absolutize::Absolutize as _;
 use pyrefly_util::lock::Mutex;
 use pyrefly_util::stdlib::register_stdlib_paths;
 use serde::Deserialize;
@@ -26,8 +28,13 @@ use tracing::warn;
 
 use crate::environment::interpreters::Interpreters;
 
+/// Interpreter query results, keyed by absolute interpreter path. Symlinks are
+/// deliberately not resolved: a venv's `bin/python` links to its base
+/// interpreter but has its own site-packages, so each venv needs its own entry.
+/// Each entry records the interpreter's modification time when it was queried,
+/// so a changed binary (e.g. a venv recreated in place) is queried again.
 static INTERPRETER_ENV_REGISTRY: LazyLock<
-    Mutex<SmallMap<PathBuf, Result<PythonEnvironment, String>>>,
+    Mutex<SmallMap<PathBuf, (Option<SystemTime>, Result<PythonEnvironment, String>)>>,
 > = LazyLock::new(|| Mutex::new(SmallMap::new()));
 
 /// Values representing the environment of the Python interpreter.
@@ -187,23 +194,62 @@ print(json.dumps({'python_platform': platform, 'python_version': version, 'site_
 
     /// Given a path to an interpreter, query the interpreter with
     /// [`Self::get_env_from_interpreter()`] and cache the result. If a cached
-    /// result already exists, return that.
+    /// result already exists for the same interpreter path and the
+    /// interpreter has not been modified since, return that.
     ///
     /// In the case of failure, log an error message and return Pyrefly's
     /// [`PythonEnvironment::default()`].
     pub fn get_interpreter_env(interpreter: &Path) -> (PythonEnvironment, Option<anyhow::Error>) {
-        let env = INTERPRETER_ENV_REGISTRY.lock()
-        .entry(interpreter.to_path_buf()).or_insert_with(move || {
-            Self::get_env_from_interpreter(interpreter).map_err(|e| {
-                format!("Failed to query interpreter at {}, falling back to default Python environment settings\n{}", interpreter.display(), e)
-            })
-        }).clone();
+        Self::lookup_interpreter_env(interpreter, false)
+    }
+
+    /// Like [`Self::get_interpreter_env()`], but always re-queries the
+    /// interpreter and replaces any cached result.
+    pub fn refresh_interpreter_env(
+        interpreter: &Path,
+    ) -> (PythonEnvironment, Option<anyhow::Error>) {
+        Self::lookup_interpreter_env(interpreter, true)
+    }
+
+    fn lookup_interpreter_env(
+        interpreter: &Path,
+        force_refresh: bool,
+    ) -> (PythonEnvironment, Option<anyhow::Error>) {
+        let key = interpreter.absolutize();
+        let modified = Self::interpreter_modified(&key);
+        let mut registry = INTERPRETER_ENV_REGISTRY.lock();
+        let env = match registry.get(&key) {
+            Some((cached_modified, env)) if !force_refresh && *cached_modified == modified => {
+                env.clone()
+            }
+            _ => {
+                let env = Self::get_env_from_interpreter(interpreter).map_err(|e| {
+                    format!("Failed to query interpreter at {}, falling back to default Python environment settings\n{}", interpreter.display(), e)
+                });
+                registry.insert(key, (modified, env.clone()));
+                env
+            }
+        };
+        drop(registry);
         match env {
             Ok(env) => (env, None),
             Err(message) => (Self::pyrefly_default(), Some(anyhow::anyhow!(message))),
         }
     }
 
+    /// When the interpreter was last modified. For a symlink this is the later of
+    /// the link itself (recreated along with its venv) and its target (replaced
+    /// when the base interpreter is upgraded).
+    fn interpreter_modified(interpreter: &Path) -> Option<SystemTime> {
+        let link = std::fs::symlink_metadata(interpreter)
+            .and_then(|m| m.modified())
+            .ok();
+        let target = std::fs::metadata(interpreter)
+            .and_then(|m| m.modified())
+            .ok();
+        link.max(target)
+    }
+
     fn cache_interpreter_stdlib_path(path: Vec<PathBuf>) {
         register_stdlib_paths(path);
     }
@@ -300,4 +346,65 @@ mod tests {
             env2.interpreter_site_package_path
         );
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_interpreter_env_cached_per_symlinked_venv() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        let log = root.join("queries.log");
+        // A fake base interpreter that records each invocation and, like a real
+        // venv interpreter, reports site-packages relative to the path it was
+        // invoked through, so the test doesn't depend on a real Python.
+        let base = root.join("base").join("python");
+        std::fs::create_dir_all(base.parent().unwrap()).unwrap();
+        std::fs::write(
+            &base,
+            format!(
+                r#"#!/bin/sh
+echo query >> '{}'
+site_packages="$(dirname "$0")/site-packages"
+echo "{{\"python_platform\": \"linux\", \"python_version\": \"3.12.0\", \"site_package_path\": [\"$site_packages\"]}}"
+"#,
+                log.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&base, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let venv_interpreter = |name: &str| {
+            let bin = root.join(name).join("bin");
+            std::fs::create_dir_all(&bin).unwrap();
+            let link = bin.join("python");
+            std::os::unix::fs::symlink(&base, &link).unwrap();
+            link
+        };
+        let venv1 = venv_interpreter("venv1");
+        let venv2 = venv_interpreter("venv2");
+        let query_count = || std::fs::read_to_string(&log).unwrap().lines().count();
+
+        // Venvs built on the same base interpreter each get their own environment.
+        let (env1, error) = PythonEnvironment::get_interpreter_env(&venv1);
+        assert!(error.is_none(), "{error:?}");
+        let (env2, error) = PythonEnvironment::get_interpreter_env(&venv2);
+        assert!(error.is_none(), "{error:?}");
+        assert_eq!(
+            env1.interpreter_site_package_path,
+            vec![root.join("venv1/bin/site-packages")]
+        );
+        assert_eq!(
+            env2.interpreter_site_package_path,
+            vec![root.join("venv2/bin/site-packages")]
+        );
+        assert_eq!(query_count(), 2);
+
+        // Looking up the same venv again is served from the cache.
+        let (cached, _) = PythonEnvironment::get_interpreter_env(&venv1);
+        assert_eq!(cached, env1);
+        assert_eq!(query_count(), 2);
+
+        PythonEnvironment::refresh_interpreter_env(&venv1);
+        assert_eq!(query_count(), 3);
+    }
 }
@@ -10,6 +10,7 @@ use ruff_python_ast::Expr;
 use ruff_python_ast::Stmt;
 use ruff_python_ast::visitor::Visitor;
 use ruff_python_ast::visitor::walk_body;
+use ruff_source_file::PositionEncoding;
 use ruff_text_size::TextRange;
 use ruff_text_size::TextSize;
 
@@ -115,7 +116,7 @@ pub fn folding_ranges(
             };
 
             if let Some(range) = range {
-                let lsp_range = self.module.to_lsp_range(range);
+                let lsp_range = self.module.to_lsp_range(range, PositionEncoding::Utf16);
                 if lsp_range.start.line != lsp_range.end.line {
                     self.ranges.push((range, None));
                 }
@@ -30,6 +30,15 @@ const PYTHON_3_5_KEYWORDS: &[&str] = &["async", "await"];
 /// Additional keywords introduced in Python 3.10.
 const PYTHON_3_10_KEYWORDS: &[&str] = &["case", "match"];
 
+/// Keywords that can never be used as a name in any supported Python version. Soft keywords
+/// (`match`, `case`, `type`, `_`) are valid identifiers and are not included.
+const HARD_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
 /// Subset of Python keywords known to appear as directory names in configerator
 /// repos. When a directory is named with a keyword (e.g. `if`), Python module
 /// names escape it with a trailing underscore (e.g. `if_`). This list matches
@@ -50,6 +59,17 @@ pub fn is_keyword(name: &str) -> bool {
     KEYWORD_ESCAPED_SET.contains(name)
 }
 
+/// Returns true if `name` can be used as a Python name: it is an identifier
+/// (equivalent to `str.isidentifier()`) and not a hard keyword.
+pub fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c == '_' || c.is_alphabetic() => {}
+        _ => return false,
+    }
+    chars.all(|c| c == '_' || c.is_alphanumeric()) && !HARD_KEYWORDS.contains(&name)
+}
+
 /// Returns a Vec containing all Python keywords for the specified Python version.
 pub fn get_keywords(version: PythonVersion) -> Vec<&'static str> {
     let mut keywords: Vec<&'static str> = EXPRESSION_KEYWORDS.to_vec();
@@ -127,6 +147,20 @@ mod tests {
         assert!(get_expression_keywords(PythonVersion::new(3, 5, 0)).contains(&"await"));
     }
 
+    #[test]
+    fn test_is_valid_identifier() {
+        assert!(is_valid_identifier("foo"));
+        assert!(is_valid_identifier("_bar2"));
+        assert!(is_valid_identifier("match"));
+        assert!(is_valid_identifier("type"));
+        assert!(!is_valid_identifier("class"));
+        assert!(!is_valid_identifier("None"));
+        assert!(!is_valid_identifier("new name"));
+        assert!(!is_valid_identifier("2x"));
+        assert!(!is_valid_identifier("a.b"));
+        assert!(!is_valid_identifier(""));
+    }
+
     #[test]
     fn test_is_keyword_escaped_dirs() {
         // All 12 supported keywords should match.
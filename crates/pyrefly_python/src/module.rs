@@ -16,6 +16,7 @@ use pyrefly_util::lined_buffer::DisplayRange;
 use pyrefly_util::lined_buffer::LinedBuffer;
 use ruff_notebook::Notebook;
 use ruff_python_ast::PySourceType;
+use ruff_source_file::PositionEncoding;
 use ruff_text_size::TextRange;
 use ruff_text_size::TextSize;
 use starlark_map::small_set::SmallSet;
@@ -111,12 +112,14 @@ impl Module {
         self.0.contents.display_pos(offset, self.notebook())
     }
 
-    pub fn to_lsp_range(&self, x: TextRange) -> lsp_types::Range {
-        self.lined_buffer().to_lsp_range(x, self.notebook())
+    pub fn to_lsp_range(&self, x: TextRange, encoding: PositionEncoding) -> lsp_types::Range {
+        self.lined_buffer()
+            .to_lsp_range(x, self.notebook(), encoding)
     }
 
-    pub fn to_lsp_position(&self, x: TextSize) -> lsp_types::Position {
-        self.lined_buffer().to_lsp_position(x, self.notebook())
+    pub fn to_lsp_position(&self, x: TextSize, encoding: PositionEncoding) -> lsp_types::Position {
+        self.lined_buffer()
+            .to_lsp_position(x, self.notebook(), encoding)
     }
 
     /// If the module is a notebook, take an input position relative to the concatenated contents
@@ -132,10 +135,12 @@ impl Module {
         &self,
         position: lsp_types::Position,
         notebook_cell: Option<usize>,
+        encoding: PositionEncoding,
     ) -> TextSize {
         self.lined_buffer().from_lsp_position(
             position,
             notebook_cell.map(|c| (self.notebook().unwrap(), c)),
+            encoding,
         )
     }
 
@@ -146,10 +151,12 @@ impl Module {
         &self,
         position: lsp_types::Range,
         notebook_cell: Option<usize>,
+        encoding: PositionEncoding,
     ) -> TextRange {
         self.lined_buffer().from_lsp_range(
             position,
             notebook_cell.map(|c| (self.notebook().unwrap(), c)),
+            encoding,
         )
     }
 
@@ -16,6 +16,7 @@ use dupe::Dupe;
 use pyrefly_util::interned_path::InternedPath;
 use pyrefly_util::visit::Visit;
 use pyrefly_util::visit::VisitMut;
+use serde::Deserialize;
 use serde::Serialize;
 use serde::Serializer;
 
@@ -56,7 +57,18 @@ impl<To: 'static> VisitMut<To> for ModulePath {
     fn recurse_mut(&mut self, _: &mut dyn FnMut(&mut To)) {}
 }
 
-#[derive(Debug, Clone, Dupe, PartialOrd, Ord, PartialEq, Eq, Hash, Serialize)]
+#[derive(
+    Debug,
+    Clone,
+    Dupe,
+    PartialOrd,
+    Ord,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize
+)]
 pub enum ModulePathDetails {
     /// The module source comes from a file on disk. Probably a `.py` or `.pyi` file.
     FileSystem(InternedPath),
@@ -149,6 +161,10 @@ impl ModulePath {
         Self(details)
     }
 
+    pub fn from_details(details: ModulePathDetails) -> Self {
+        Self::new(details)
+    }
+
     pub fn filesystem(path: PathBuf) -> Self {
         Self::new(ModulePathDetails::FileSystem(InternedPath::new(path)))
     }
@@ -44,6 +44,7 @@ use crate::type_alias::TypeAliasRef;
 use crate::type_alias::TypeAliasStyle;
 use crate::type_output::DisplayOutput;
 use crate::type_output::OutputWithLocations;
+use crate::type_output::TruncatedOutput;
 use crate::type_output::TypeOutput;
 use crate::type_var::Restriction;
 use crate::typed_dict::AnonymousTypedDictInner;
@@ -1482,9 +1483,23 @@ impl Type {
         &self,
         fallback_name: Option<&str>,
         mode: LspDisplayMode,
+    ) -> String {
+        self.as_lsp_string_with_options(fallback_name, mode, false)
+    }
+
+    /// Like `as_lsp_string_with_fallback_name`, but if `fully_qualified` is set, every name is
+    /// shown with its module, so same-named classes from different modules can be told apart.
+    pub fn as_lsp_string_with_options(
+        &self,
+        fallback_name: Option<&str>,
+        mode: LspDisplayMode,
+        fully_qualified: bool,
     ) -> String {
         let mut c = TypeDisplayContext::new(&[self]);
         c.set_lsp_display_mode(mode);
+        if fully_qualified {
+            c.always_display_module_name();
+        }
         let rendered = c.display(self).to_string();
         if let Some(name) = fallback_name
             && self.is_toplevel_callable()
@@ -1497,6 +1512,45 @@ impl Type {
         rendered
     }
 
+    /// Like `as_lsp_string_with_fallback_name`, but if the result is longer than `max_len`
+    /// characters, cut it off after the last type component that fits and end it with `...`.
+    /// Returns `None` if the full rendering already fits.
+    pub fn as_truncated_lsp_string(
+        &self,
+        fallback_name: Option<&str>,
+        mode: LspDisplayMode,
+        fully_qualified: bool,
+        max_len: usize,
+    ) -> Option<String> {
+        const ELLIPSIS: &str = "...";
+        let full = self.as_lsp_string_with_options(fallback_name, mode, fully_qualified);
+        if full.chars().count() <= max_len {
+            return None;
+        }
+        let prefix = match fallback_name {
+            Some(name) if full.starts_with(&format!("def {name}(")) => format!("def {name}"),
+            _ => String::new(),
+        };
+        let mut c = TypeDisplayContext::new(&[self]);
+        c.set_lsp_display_mode(mode);
+        if fully_qualified {
+            c.always_display_module_name();
+        }
+        let mut output = TruncatedOutput::new(
+            &c,
+            max_len.saturating_sub(prefix.chars().count() + ELLIPSIS.len()),
+        );
+        // Formatting stops with an error at the first component that doesn't fit.
+        let _ = c.fmt_helper_generic(self, true, &mut output);
+        let (text, _) = output.finish();
+        let text = if prefix.is_empty() {
+            text.as_str()
+        } else {
+            text.trim_start()
+        };
+        Some(format!("{prefix}{text}{ELLIPSIS}"))
+    }
+
     pub fn get_types_with_locations(
         &self,
         stdlib: Option<&Stdlib>,
@@ -1978,6 +2032,33 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_truncated_lsp_string() {
+        let ty = Type::union(vec![Type::None, Type::LiteralString(LitStyle::Implicit)]);
+        assert_eq!(
+            ty.as_truncated_lsp_string(None, LspDisplayMode::Hover, false, 20),
+            None
+        );
+        // Stops before `LiteralString` rather than splitting it.
+        assert_eq!(
+            ty.as_truncated_lsp_string(None, LspDisplayMode::Hover, false, 12),
+            Some("None | ...".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_truncated_lsp_string_counts_characters() {
+        let ty = Type::union(vec![
+            Lit::Str("ééééé".into()).to_implicit_type(),
+            Type::LiteralString(LitStyle::Implicit),
+        ]);
+        // `Literal['ééééé'] | ` is 19 characters (24 bytes), exactly what fits before the `...`.
+        assert_eq!(
+            ty.as_truncated_lsp_string(None, LspDisplayMode::Hover, false, 22),
+            Some("Literal['ééééé'] | ...".to_owned())
+        );
+    }
+
     #[test]
     fn test_display_single_param_callable() {
         let param1 = Param::Pos(Name::new_static("hello"), Type::None, Required::Required);
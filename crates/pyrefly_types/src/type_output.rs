@@ -9,6 +9,7 @@ use std::fmt;
 
 use pyrefly_python::module::TextRangeWithModule;
 use pyrefly_python::qname::QName;
+use pyrefly_util::display::Fmt;
 
 use crate::display::TypeDisplayContext;
 use crate::literal::Lit;
@@ -164,6 +165,81 @@ impl TypeOutput for OutputWithLocations<'_> {
     }
 }
 
+/// Implementation of `TypeOutput` that renders types as plain text, but stops at the first
+/// component that would take the text past `max_len` characters. Since every write is a whole
+/// component (a name, a literal, a separator), the text never ends in the middle of one.
+pub struct TruncatedOutput<'a> {
+    context: &'a TypeDisplayContext<'a>,
+    text: String,
+    /// Length of `text` in characters, which is what `max_len` limits.
+    len: usize,
+    max_len: usize,
+    truncated: bool,
+}
+
+impl<'a> TruncatedOutput<'a> {
+    pub fn new(context: &'a TypeDisplayContext<'a>, max_len: usize) -> Self {
+        Self {
+            context,
+            text: String::new(),
+            len: 0,
+            max_len,
+            truncated: false,
+        }
+    }
+
+    /// The text written so far, and whether anything was cut off.
+    pub fn finish(self) -> (String, bool) {
+        (self.text, self.truncated)
+    }
+
+    /// Stop formatting (by returning an error) once a component no longer fits.
+    fn push(&mut self, s: &str) -> fmt::Result {
+        let len = s.chars().count();
+        if self.truncated || self.len + len > self.max_len {
+            self.truncated = true;
+            return Err(fmt::Error);
+        }
+        self.text.push_str(s);
+        self.len += len;
+        Ok(())
+    }
+}
+
+impl TypeOutput for TruncatedOutput<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push(s)
+    }
+
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> fmt::Result {
+        self.push(&args.to_string())
+    }
+
+    fn write_qname(&mut self, qname: &QName) -> fmt::Result {
+        let ctx = self.context;
+        self.push(&Fmt(|f| ctx.fmt_qname(qname, f)).to_string())
+    }
+
+    fn write_lit(&mut self, lit: &Lit) -> fmt::Result {
+        let ctx = self.context;
+        self.push(&Fmt(|f| ctx.fmt_lit(lit, f)).to_string())
+    }
+
+    fn write_targs(&mut self, targs: &TArgs) -> fmt::Result {
+        let ctx = self.context;
+        ctx.fmt_targs(targs, self)
+    }
+
+    fn write_type(&mut self, ty: &Type) -> fmt::Result {
+        // Recurse rather than rendering `ty` in one go, so we can stop inside it.
+        self.context.fmt_helper_generic(ty, false, self)
+    }
+
+    fn write_builtin(&mut self, name: &str, _qname: Option<&QName>) -> fmt::Result {
+        self.push(name)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -195,6 +271,7 @@ mod tests {
     use crate::type_var::Restriction;
     use crate::types::TArgs;
     use crate::types::TParams;
+    use crate::types::Union;
 
     fn fake_class(name: &str, module: &str, range: u32) -> Class {
         let mi = Module::new(
@@ -556,4 +633,31 @@ mod tests {
         assert_eq!(parts[3].0, "]");
         assert!(parts[3].1.is_none(), "] should not have location");
     }
+
+    #[test]
+    fn test_truncated_output_write_type_is_nested() {
+        let union_type = Type::Union(Box::new(Union {
+            members: vec![Type::None, Type::LiteralString(LitStyle::Implicit)],
+            display_name: Some((ModuleName::unknown(), Name::new("MyUnion"))),
+        }));
+        let context = TypeDisplayContext::new(&[&union_type]);
+        let mut output = TruncatedOutput::new(&context, 100);
+
+        // A type written inside another one keeps its alias name rather than being expanded.
+        output.write_str("list[").unwrap();
+        output.write_type(&union_type).unwrap();
+        output.write_str("]").unwrap();
+        assert_eq!(output.finish(), ("list[MyUnion]".to_owned(), false));
+    }
+
+    #[test]
+    fn test_truncated_output_counts_characters() {
+        let context = TypeDisplayContext::default();
+        let mut output = TruncatedOutput::new(&context, 5);
+
+        output.write_str("éé").unwrap();
+        output.write_str("ééé").unwrap();
+        assert!(output.write_str("é").is_err());
+        assert_eq!(output.finish(), ("ééééé".to_owned(), true));
+    }
 }
@@ -200,14 +200,19 @@ impl LinedBuffer {
         self.lines.line_start(line.to_one_indexed(), &self.buffer)
     }
 
-    /// Translates a text range to a LSP range.
+    /// Translates a text range to a LSP range, counting characters in `encoding`.
     /// For notebook, the input range is relative to the concatenated contents of the whole notebook
     /// and the output range is relative to a specific cell.
-    pub fn to_lsp_range(&self, x: TextRange, notebook: Option<&Notebook>) -> lsp_types::Range {
+    pub fn to_lsp_range(
+        &self,
+        x: TextRange,
+        notebook: Option<&Notebook>,
+        encoding: PositionEncoding,
+    ) -> lsp_types::Range {
         let start_cell = self.to_cell_for_lsp(x.start(), notebook);
         let end_cell = self.to_cell_for_lsp(x.end(), notebook);
-        let start = self.to_lsp_position(x.start(), notebook);
-        let mut end = self.to_lsp_position(x.end(), notebook);
+        let start = self.to_lsp_position(x.start(), notebook, encoding);
+        let mut end = self.to_lsp_position(x.end(), notebook, encoding);
         if let Some(start_cell) = start_cell
             && let Some(end_cell) = end_cell
             && end_cell != start_cell
@@ -222,14 +227,17 @@ impl LinedBuffer {
         lsp_types::Range::new(start, end)
     }
 
-    /// Translates a text size to a LSP position.
+    /// Translates a text size to a LSP position, counting characters in `encoding`.
     /// For notebook, the input position is relative to the concatenated contents of the whole notebook
     /// and the output position is relative to a specific cell.
-    pub fn to_lsp_position(&self, x: TextSize, notebook: Option<&Notebook>) -> lsp_types::Position {
+    pub fn to_lsp_position(
+        &self,
+        x: TextSize,
+        notebook: Option<&Notebook>,
+        encoding: PositionEncoding,
+    ) -> lsp_types::Position {
         let x = self.clamp_position(x);
-        let loc = self
-            .lines
-            .source_location(x, &self.buffer, PositionEncoding::Utf16);
+        let loc = self.lines.source_location(x, &self.buffer, encoding);
         if let Some(notebook) = notebook
             && let Some((_, cell_line)) = self.get_cell_and_line_from_concatenated_line(
                 notebook,
@@ -275,7 +283,7 @@ impl LinedBuffer {
         }
     }
 
-    /// Translates an LSP position to a text size.
+    /// Translates an LSP position, whose characters are counted in `encoding`, to a text size.
     /// For notebooks, the input position is relative to a notebook cell and the output
     /// position is relative to the concatenated contents of the notebook.
     ///
@@ -285,6 +293,7 @@ impl LinedBuffer {
         &self,
         position: lsp_types::Position,
         notebook_and_cell: Option<(&Notebook, usize)>,
+        encoding: PositionEncoding,
     ) -> TextSize {
         let line = if let Some((notebook, cell)) = notebook_and_cell
             && let Some(concatenated_line) = self.get_concatenated_line_from_cell_and_range(
@@ -313,7 +322,7 @@ impl LinedBuffer {
                 character_offset: OneIndexed::from_zero_indexed(position.character as usize),
             },
             &self.buffer,
-            PositionEncoding::Utf16,
+            encoding,
         );
         // line_end includes the trailing newline. Clamp to the content end
         // (excluding the newline) so that out-of-bounds positions land on the
@@ -339,10 +348,11 @@ impl LinedBuffer {
         &self,
         position: lsp_types::Range,
         notebook_and_cell: Option<(&Notebook, usize)>,
+        encoding: PositionEncoding,
     ) -> TextRange {
         TextRange::new(
-            self.from_lsp_position(position.start, notebook_and_cell),
-            self.from_lsp_position(position.end, notebook_and_cell),
+            self.from_lsp_position(position.start, notebook_and_cell, encoding),
+            self.from_lsp_position(position.end, notebook_and_cell, encoding),
         )
     }
 
@@ -705,7 +715,7 @@ mod tests {
         let lined_buffer = LinedBuffer::new(Arc::clone(&contents));
         let past_eof = TextSize::new(contents.len() as u32 + 100);
         // This should not panic - it should clamp to the end of the buffer.
-        let _pos = lined_buffer.to_lsp_position(past_eof, None);
+        let _pos = lined_buffer.to_lsp_position(past_eof, None, PositionEncoding::Utf16);
     }
 
     /// Same as above but for `to_cell_for_lsp`. Even for non-notebook files,
@@ -729,7 +739,7 @@ mod tests {
         let past_eof = TextSize::new(contents.len() as u32 + 100);
         let range = TextRange::new(TextSize::new(0), past_eof);
         // This should not panic - it should clamp to the end of the buffer.
-        let _lsp_range = lined_buffer.to_lsp_range(range, None);
+        let _lsp_range = lined_buffer.to_lsp_range(range, None, PositionEncoding::Utf16);
     }
 
     /// Regression test: `from_lsp_position` must not panic when the LSP client
@@ -751,10 +761,23 @@ mod tests {
             character: 0,
         };
         // Should clamp to EOF, not panic.
-        let offset = lined_buffer.from_lsp_position(position, None);
+        let offset = lined_buffer.from_lsp_position(position, None, PositionEncoding::Utf16);
         assert_eq!(offset, TextSize::new(contents.len() as u32));
     }
 
+    #[test]
+    fn test_lsp_position_encodings() {
+        // `😀` is four bytes in UTF-8 and a surrogate pair (two code units) in UTF-16.
+        let contents = Arc::new("s = \"😀\"; x = 1\n".to_owned());
+        let lined_buffer = LinedBuffer::new(Arc::clone(&contents));
+        let x = TextSize::new(contents.find('x').unwrap() as u32);
+        for (encoding, character) in [(PositionEncoding::Utf16, 10), (PositionEncoding::Utf8, 12)] {
+            let position = lined_buffer.to_lsp_position(x, None, encoding);
+            assert_eq!(position, lsp_types::Position { line: 0, character });
+            assert_eq!(lined_buffer.from_lsp_position(position, None, encoding), x);
+        }
+    }
+
     /// Bug: `LspNotebook::get_code_cell_index` returns an index among ALL cells
     /// (code + markdown), but `Notebook::cell_offsets()` is indexed by valid
     /// CODE cells only. When a notebook has markdown cells interspersed with
@@ -781,7 +804,8 @@ mod tests {
 
         // Correct: code_1 is at code-cell index 1, so cell_offsets[1] points
         // to the start of "y = 2".
-        let correct_offset = lined_buffer.from_lsp_position(position, Some((&notebook, 1)));
+        let correct_offset =
+            lined_buffer.from_lsp_position(position, Some((&notebook, 1)), PositionEncoding::Utf16);
         assert_eq!(correct_offset, TextSize::new(6)); // offset of 'y'
         assert_eq!(
             &source[correct_offset.to_usize()..correct_offset.to_usize() + 5],
@@ -793,7 +817,8 @@ mod tests {
         // length) and from_lsp_position would resolve to EOF. This is
         // prevented by LspNotebook::get_code_cell_index translating to the
         // code-cell index.
-        let wrong_offset = lined_buffer.from_lsp_position(position, Some((&notebook, 2)));
+        let wrong_offset =
+            lined_buffer.from_lsp_position(position, Some((&notebook, 2)), PositionEncoding::Utf16);
         assert_ne!(
             correct_offset, wrong_offset,
             "all-cells index 2 must differ from code-cell index 1 — \
@@ -23,7 +23,7 @@ use crate::lock::Mutex;
 #[derive(Debug)]
 pub struct Cancelled;
 
-#[derive(Clone, Dupe)]
+#[derive(Clone, Dupe, Default)]
 pub struct CancellationHandle(Arc<AtomicBool>);
 
 impl CancellationHandle {
@@ -31,6 +31,11 @@ impl CancellationHandle {
         Self(Arc::new(AtomicBool::new(false)))
     }
 
+    /// Whether `self` and `other` share the same cancellation flag.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
     pub fn is_cancelled(&self) -> bool {
         self.0.load(Ordering::Relaxed)
     }
@@ -44,6 +44,9 @@ pub enum QueueName {
     LspQueue,
     RecheckQueue,
     FindReferenceQueue,
+    CompletionQueue,
+    SemanticTokensQueue,
+    WorkspaceSymbolQueue,
     SourceDbQueue,
 }
 
@@ -59,6 +62,9 @@ impl QueueName {
             Self::LspQueue => "lsp_queue",
             Self::RecheckQueue => "recheck_queue",
             Self::FindReferenceQueue => "find_reference_queue",
+            Self::CompletionQueue => "completion_queue",
+            Self::SemanticTokensQueue => "semantic_tokens_queue",
+            Self::WorkspaceSymbolQueue => "workspace_symbol_queue",
             Self::SourceDbQueue => "sourcedb_queue",
         }
     }
@@ -74,6 +80,7 @@ pub enum TelemetryEventKind {
     InvalidateEvents,
     InvalidateConfig,
     InvalidateOnClose,
+    PublishDependentDiagnostics,
     PopulateProjectFiles,
     PopulateWorkspaceFiles,
     WorkspaceDiagnosticsRepopulation,
@@ -82,6 +89,7 @@ pub enum TelemetryEventKind {
     FindFromDefinition,
     ExternalReferences,
     ExternalWorkspaceSymbols,
+    ExportPysa,
     LspStartup,
 }
 
@@ -11,6 +11,8 @@ use std::fmt::Formatter;
 use std::path::MAIN_SEPARATOR_STR;
 use std::path::PathBuf;
 
+use starlark_map::small_set::SmallSet;
+
 use crate::interned_path::InternedPath;
 
 /// Some kind of pattern that can be used by filesystem watchers. Some filesystem watchers
@@ -32,6 +34,35 @@ impl WatchPattern {
     pub fn root(root: InternedPath, pattern: String) -> Self {
         Self::Root(root, pattern)
     }
+
+    /// Drop recursive (`**/...`) root patterns that are already covered by the same pattern
+    /// on an ancestor root, e.g. `/a/b/**/*.py` when `/a/**/*.py` is present. Patterns are only
+    /// compared against identical patterns, so config-file watchers never subsume source-file
+    /// watchers or vice versa.
+    pub fn dedup_nested_roots(patterns: SmallSet<WatchPattern>) -> SmallSet<WatchPattern> {
+        // Visit shallower roots first, so any covering root is kept before what it covers.
+        let mut sorted = patterns.into_iter().collect::<Vec<_>>();
+        sorted.sort_by_key(|pattern| match pattern {
+            Self::File(_) => 0,
+            Self::Root(root, _) => root.components().count(),
+        });
+        let mut result: SmallSet<WatchPattern> = SmallSet::new();
+        for pattern in sorted {
+            if let Self::Root(root, glob) = &pattern
+                && glob.starts_with("**/")
+                && result.iter().any(|kept| match kept {
+                    Self::Root(kept_root, kept_glob) => {
+                        kept_glob == glob && kept_root != root && root.starts_with(&**kept_root)
+                    }
+                    Self::File(_) => false,
+                })
+            {
+                continue;
+            }
+            result.insert(pattern);
+        }
+        result
+    }
 }
 
 impl Debug for WatchPattern {
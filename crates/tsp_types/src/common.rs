@@ -198,6 +198,32 @@ impl GetTypeParams {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Batched GetTypes request (manually added; not part of the generated protocol)
+// ---------------------------------------------------------------------------
+
+/// Method name of the batched computed-type request. It is not in the
+/// generated `TSPRequests`, so servers dispatch it by name.
+pub const TYPE_SERVER_GET_TYPES_METHOD: &str = "typeServer/getTypes";
+
+/// Parameters for a `typeServer/getTypes` request, which returns the computed
+/// type of every node in `args` in a single response.
+///
+/// The client sends `{ "args": (Node | Declaration)[], "snapshot": number }`
+/// and receives a list with one entry (a type or `null`) per arg, in order.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTypesParams {
+    /// The nodes or declarations to query for type information.
+    pub args: Vec<GetTypeArg>,
+
+    /// Snapshot version — the server returns `ServerCancelled` when stale.
+    pub snapshot: i32,
+}
+
+/// Response to a `typeServer/getTypes` request.
+pub type GetTypesResponse = Vec<Option<tsp::Type>>;
+
 /// Creates a snapshot outdated error
 #[allow(dead_code)]
 pub fn snapshot_outdated_error() -> ResponseError {
@@ -254,4 +280,50 @@ mod tests {
             serde_json::from_value(obj_with_fields);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_get_type_params_position_based_node() {
+        // A plain Node and a Declaration's nested node both carry an LSP
+        // line/character range, so clients never need to compute byte offsets.
+        let node_json = serde_json::json!({
+            "arg": {
+                "uri": "file:///a.py",
+                "range": {
+                    "start": {"line": 1, "character": 2},
+                    "end": {"line": 1, "character": 5}
+                }
+            },
+            "snapshot": 3
+        });
+        let params: GetTypeParams = serde_json::from_value(node_json).unwrap();
+        assert_eq!(params.uri(), "file:///a.py");
+        assert_eq!(
+            (params.position().line, params.position().character),
+            (1, 2)
+        );
+        assert_eq!(
+            (params.end_position().line, params.end_position().character),
+            (1, 5)
+        );
+
+        let decl_json = serde_json::json!({
+            "arg": {
+                "node": {
+                    "uri": "file:///a.py",
+                    "range": {
+                        "start": {"line": 4, "character": 0},
+                        "end": {"line": 4, "character": 1}
+                    }
+                },
+                "name": "x"
+            },
+            "snapshot": 3
+        });
+        let params: GetTypeParams = serde_json::from_value(decl_json).unwrap();
+        assert!(matches!(params.arg, GetTypeArg::Declaration { .. }));
+        assert_eq!(
+            (params.position().line, params.position().character),
+            (4, 0)
+        );
+    }
 }
@@ -81,6 +81,20 @@ fn test_type_server_version_round_trip() {
     assert_eq!(back, v);
 }
 
+#[test]
+fn test_supported_protocol_version_is_semver() {
+    let version = serde_json::to_value(TSP_PROTOCOL_VERSION).unwrap();
+    let version = version
+        .as_str()
+        .expect("protocol version should be a string");
+    let parts = version
+        .split('.')
+        .map(|part| part.parse::<u32>())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap_or_else(|_| panic!("`{version}` is not a semver version"));
+    assert_eq!(parts.len(), 3, "`{version}` is not a semver version");
+}
+
 // ---------------------------------------------------------------------------
 // Struct construction and serialization tests
 // ---------------------------------------------------------------------------
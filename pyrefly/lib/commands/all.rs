@@ -23,6 +23,7 @@ use crate::commands::dump_config::DumpConfigArgs;
 use crate::commands::infer::InferArgs;
 use crate::commands::init::InitArgs;
 use crate::commands::lsp::LspArgs;
+use crate::commands::semantic_tokens_legend::SemanticTokensLegendArgs;
 use crate::commands::stubgen::StubgenArgs;
 use crate::commands::suppress::SuppressArgs;
 use crate::commands::tsp::TspArgs;
@@ -72,6 +73,8 @@ pub enum Command {
     Suppress(SuppressArgs),
     /// Generate .pyi stub files from Python source files.
     Stubgen(StubgenArgs),
+    /// Print the LSP semantic token legend as JSON, for tools that decode pyrefly's tokens.
+    SemanticTokensLegend(SemanticTokensLegendArgs),
 }
 
 impl Command {
@@ -124,6 +127,7 @@ impl Command {
             Command::Stubgen(args) => {
                 Ok((args.run(config_configurer_wrapper, thread_count)?, None))
             }
+            Command::SemanticTokensLegend(args) => Ok((args.run()?, None)),
         }
     }
 }
@@ -152,8 +152,12 @@ fn compute_errors(
         Forgetter::new(state.as_ref().new_transaction(default_require, None), true);
 
     if let Some(pysa_directory) = report_pysa {
-        let reporter =
-            report::pysa::PysaReporter::new(pysa_directory, &modules_to_check, report_pysa_format)?;
+        let reporter = report::pysa::PysaReporter::new(
+            pysa_directory,
+            &modules_to_check,
+            report_pysa_format,
+            report::pysa::context::PysaExportOptions::default(),
+        )?;
         transaction.as_mut().set_pysa_reporter(Some(reporter));
     }
 
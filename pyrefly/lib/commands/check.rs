@@ -269,12 +269,33 @@ struct OutputArgs {
     /// Generate a Glean-compatible JSON file for each module
     #[arg(long, value_name = "OUTPUT_FILE")]
     report_glean: Option<PathBuf>,
+    /// Generate a SCIP index (`index.scip`) of the checked files, for Sourcegraph-compatible
+    /// code navigation. Paths are relative to the project's config directory (or the current
+    /// directory without one), widened to the deepest directory containing every checked file.
+    #[arg(long, value_name = "OUTPUT_FILE")]
+    report_scip: Option<PathBuf>,
     /// Generate a Pysa-compatible JSON file for each module
     #[arg(long, value_name = "OUTPUT_FILE")]
     report_pysa: Option<PathBuf>,
     /// Format for pysa report output (json or capnp)
     #[arg(long, value_enum, default_value_t = report::pysa::PysaFormat::Capnp)]
     report_pysa_format: report::pysa::PysaFormat,
+    /// Render builtin types in the pysa report with their module name too
+    /// (e.g. `builtins.int` rather than `int`)
+    #[arg(long)]
+    report_pysa_fully_qualified_type_names: bool,
+    /// Also record the literal value of exported expressions in the pysa report,
+    /// alongside their promoted type (json format only)
+    #[arg(long)]
+    report_pysa_literal_values: bool,
+    /// Don't copy the bundled typeshed stubs into the pysa report, only record
+    /// the digest of the bundled typeshed (json format only)
+    #[arg(long)]
+    report_pysa_skip_typeshed: bool,
+    /// Keep a manifest of the exported modules in the pysa report directory, and write the
+    /// modules and definitions changed since the previous run to `pyrefly.pysa.changes.json`
+    #[arg(long)]
+    report_pysa_watch: bool,
     /// Report the cross-module demand tree (aggregated summary of LookupAnswer
     /// and LookupExport calls). Useful for analyzing laziness properties.
     #[arg(long, value_name = "OUTPUT_FILE")]
@@ -1159,7 +1180,8 @@ impl CheckArgs {
         let retain = self.output.report_binding_memory.is_some()
             || self.output.debug_info.is_some()
             || self.output.report_trace.is_some()
-            || self.output.report_glean.is_some();
+            || self.output.report_glean.is_some()
+            || self.output.report_scip.is_some();
         RequireLevels {
             specified: if retain {
                 Require::Everything
@@ -1196,6 +1218,12 @@ impl CheckArgs {
                 pysa_directory,
                 handles,
                 self.output.report_pysa_format,
+                report::pysa::context::PysaExportOptions {
+                    fully_qualified_type_names: self.output.report_pysa_fully_qualified_type_names,
+                    literal_values: self.output.report_pysa_literal_values,
+                    skip_typeshed: self.output.report_pysa_skip_typeshed,
+                    watch: self.output.report_pysa_watch,
+                },
             )?;
             transaction.set_pysa_reporter(Some(reporter));
         }
@@ -1476,6 +1504,23 @@ impl CheckArgs {
                 )?;
             }
         }
+        if let Some(path) = &self.output.report_scip {
+            // Root the index at the project's config, falling back to the current directory
+            // (which is what we check) when there is no config file. It is widened to cover
+            // files outside it, e.g. those belonging to another config.
+            let project_root = match handles
+                .first()
+                .and_then(|handle| transaction.get_config(handle))
+                .and_then(|config| config.source.root().map(Path::to_owned))
+            {
+                Some(root) => root,
+                None => std::env::current_dir()?,
+            };
+            fs_anyhow::write(
+                path,
+                report::scip::scip(transaction, handles, &project_root)?,
+            )?;
+        }
         if let Some(pysa_reporter) = transaction.take_pysa_reporter() {
             report::pysa::write_project_file(&pysa_reporter, transaction, handles, &output_errors)?;
         }
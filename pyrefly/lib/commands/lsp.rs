@@ -8,7 +8,9 @@
 use std::collections::HashSet;
 use std::ffi::OsString;
 use std::io::Write;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
+use std::time::Duration;
 use std::time::Instant;
 
 use clap::CommandFactory;
@@ -62,10 +64,30 @@ pub struct LspArgs {
     #[arg(long, default_value_t = if cfg!(fbcode_build) {0} else {2000})]
     pub workspace_indexing_limit: usize,
 
+    /// Number of project files checked and committed at a time when indexing a config, so that
+    /// IDE features light up incrementally on large projects. Only useful for testing.
+    #[arg(long, hide = true)]
+    pub project_indexing_batch_size: Option<NonZeroUsize>,
+
     /// Block for build system operations, only using fallback heuristics after checking
     /// an up-to-date source DB. Only useful for benchmarking.
     #[arg(long)]
     pub build_system_blocking: bool,
+
+    /// How long (in milliseconds) to wait after a `textDocument/didChange` for further edits
+    /// before rechecking open files. Edits arriving within this window share a single recheck.
+    #[arg(long, default_value_t = 150)]
+    pub did_change_debounce_ms: u64,
+
+    /// How long (in milliseconds) a read-only request such as completion may run before the
+    /// server gives up on it, cancels its work and responds with an error, so a pathological
+    /// input can't hold up later requests indefinitely. `0` (the default) disables the timeout.
+    /// Requests handled on the main loop, such as hover, are still answered with an error on
+    /// time, but keep blocking later messages until their handler returns. Only requests handed
+    /// to a background queue, such as completion, find-references and workspace symbols, stop
+    /// holding up the server.
+    #[arg(long, default_value_t = 0)]
+    pub request_timeout_ms: u64,
 }
 
 /// Drop flags after the `lsp` subcommand that aren't declared on `LspArgs` or
@@ -145,7 +167,10 @@ pub fn run_lsp(
             initialize_info,
             args.indexing_mode,
             args.workspace_indexing_limit,
+            args.project_indexing_batch_size,
             args.build_system_blocking,
+            Duration::from_millis(args.did_change_debounce_ms),
+            (args.request_timeout_ms > 0).then(|| Duration::from_millis(args.request_timeout_ms)),
             path_remapper,
             thrift_remapper,
             telemetry,
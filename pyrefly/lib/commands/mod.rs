@@ -21,6 +21,8 @@ pub mod init;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod lsp;
 #[cfg(not(target_arch = "wasm32"))]
+pub mod semantic_tokens_legend;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod stubgen;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod suppress;
@@ -0,0 +1,24 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use clap::Parser;
+
+use crate::commands::util::CommandExitStatus;
+use crate::state::semantic_tokens::SemanticTokensLegends;
+
+/// Print the semantic token legend the language server advertises, as JSON.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Parser, Clone)]
+pub struct SemanticTokensLegendArgs {}
+
+impl SemanticTokensLegendArgs {
+    pub fn run(self) -> anyhow::Result<CommandExitStatus> {
+        let legend = SemanticTokensLegends::lsp_semantic_token_legends();
+        println!("{}", serde_json::to_string_pretty(&legend)?);
+        Ok(CommandExitStatus::Success)
+    }
+}
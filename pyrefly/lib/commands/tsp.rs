@@ -6,7 +6,9 @@
  */
 
 use std::io::Write;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
+use std::time::Duration;
 use std::time::Instant;
 
 use clap::Parser;
@@ -38,6 +40,10 @@ pub struct TspArgs {
     /// Note that indexing files is a performance-intensive task.
     #[arg(long, default_value_t = if cfg!(fbcode_build) {0} else {2000})]
     pub(crate) workspace_indexing_limit: usize,
+    /// Number of project files checked and committed at a time when indexing a config.
+    /// Only useful for testing.
+    #[arg(long, hide = true)]
+    pub(crate) project_indexing_batch_size: Option<NonZeroUsize>,
     /// Selects the transport for the main JSON-RPC connection.
     /// Use `stdio` (default) or `ipc://<name>` for a local socket / named pipe.
     #[arg(long, default_value = "stdio")]
@@ -67,7 +73,10 @@ pub fn run_tsp(
             initialize_info.supports_diagnostic_markdown,
             args.indexing_mode,
             args.workspace_indexing_limit,
+            args.project_indexing_batch_size,
             false,
+            Duration::ZERO,
+            None,
             surface,
             agent_session_id,
             agent_invocation_id,
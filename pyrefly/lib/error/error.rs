@@ -27,6 +27,7 @@ use ruff_annotate_snippets::Level;
 use ruff_annotate_snippets::Message;
 use ruff_annotate_snippets::Renderer;
 use ruff_annotate_snippets::Snippet;
+use ruff_source_file::PositionEncoding;
 use ruff_text_size::Ranged;
 use ruff_text_size::TextRange;
 use starlark_map::small_map::SmallMap;
@@ -316,8 +317,8 @@ impl Error {
         self.severity
     }
 
-    /// Create a diagnostic suitable for use in LSP.
-    pub fn to_diagnostic(&self) -> Diagnostic {
+    /// Create a diagnostic suitable for use in LSP, with its range in `encoding`.
+    pub fn to_diagnostic(&self, encoding: PositionEncoding) -> Diagnostic {
         let code = self.error_kind().to_name().to_owned();
         let code_description = Url::parse(&self.error_kind().docs_url())
             .ok()
@@ -325,7 +326,7 @@ impl Error {
         // TODO: Map secondary_annotations to DiagnosticRelatedInformation for LSP clients.
         // This requires constructing a Url from the module path, which may not always succeed.
         Diagnostic {
-            range: self.module.to_lsp_range(self.range()),
+            range: self.module.to_lsp_range(self.range(), encoding),
             severity: Some(match self.severity() {
                 Severity::Error => lsp_types::DiagnosticSeverity::ERROR,
                 Severity::Warn => lsp_types::DiagnosticSeverity::WARNING,
@@ -5,6 +5,8 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
 use std::path::PathBuf;
 
 use pyrefly_python::module_path::ModulePath;
@@ -38,14 +40,9 @@ pub fn to_real_path(path: &ModulePath) -> Option<PathBuf> {
         #[cfg(not(target_arch = "wasm32"))]
         ModulePathDetails::BundledTypeshed(path) => {
             let typeshed = typeshed().ok()?;
-            let typeshed_path = match typeshed.materialized_path_on_disk() {
-                Ok(typeshed_path) => Some(typeshed_path),
-                Err(err) => {
-                    warn!("Builtins unable to be loaded on disk, {}", err);
-                    None
-                }
-            }?;
-            Some(typeshed_path.join(&**path))
+            bundled_typeshed_real_path(path, typeshed.materialized_path_on_disk(), |path| {
+                typeshed.materialized_module_on_disk(path)
+            })
         }
         #[cfg(not(target_arch = "wasm32"))]
         ModulePathDetails::BundledTypeshedThirdParty(path) => {
@@ -77,22 +74,74 @@ pub fn to_real_path(path: &ModulePath) -> Option<PathBuf> {
     }
 }
 
-pub fn collect_symbol_def_paths(t: &Type) -> Vec<(QName, PathBuf)> {
+/// The path on disk of the bundled typeshed file at `path`, given the result of materializing all
+/// of typeshed. If that failed, fall back to writing out just the module we need with
+/// `materialize_module`.
+#[cfg(not(target_arch = "wasm32"))]
+fn bundled_typeshed_real_path(
+    path: &Path,
+    materialized_typeshed: anyhow::Result<PathBuf>,
+    materialize_module: impl FnOnce(&Path) -> anyhow::Result<PathBuf>,
+) -> Option<PathBuf> {
+    match materialized_typeshed {
+        Ok(typeshed_path) => Some(typeshed_path.join(path)),
+        Err(err) => {
+            warn!("Builtins unable to be loaded on disk, {}", err);
+            match materialize_module(path) {
+                Ok(module_path) => Some(module_path),
+                Err(err) => {
+                    warn!(
+                        "Typeshed module `{}` unable to be loaded on disk, {}",
+                        path.display(),
+                        err
+                    );
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Collect the definitions of the named types appearing in `t`, along with a path on disk for each
+/// one. The path is `None` when it can't be materialized (e.g. bundled typeshed on wasm, or when
+/// writing it out fails), so callers can omit links rather than point at a missing file.
+pub fn collect_symbol_def_paths(t: &Type) -> Vec<(QName, Option<PathBuf>)> {
     let mut tracked_def_locs = SmallSet::new();
     t.universe(&mut |t| tracked_def_locs.extend(t.qname()));
     tracked_def_locs
         .into_iter()
         .map(|qname| {
-            let module_path = qname.module_path();
-            let file_path = match module_path.details() {
-                ModulePathDetails::BundledTypeshed(_)
-                | ModulePathDetails::BundledTypeshedThirdParty(_)
-                | ModulePathDetails::BundledThirdParty(_) => {
-                    to_real_path(module_path).unwrap_or_else(|| module_path.as_path().to_path_buf())
-                }
-                _ => module_path.as_path().to_path_buf(),
-            };
+            let file_path = to_real_path(qname.module_path());
             (qname.clone(), file_path)
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::*;
+
+    #[test]
+    fn test_bundled_typeshed_real_path_falls_back_to_single_module() {
+        let typeshed = typeshed().unwrap();
+        let builtins = Path::new("builtins.pyi");
+        let path = bundled_typeshed_real_path(
+            builtins,
+            Err(anyhow!("materializing typeshed failed")),
+            |path| typeshed.materialized_module_on_disk(path),
+        )
+        .unwrap();
+        assert_eq!(
+            path,
+            typeshed.materialized_module_on_disk(builtins).unwrap()
+        );
+        // Go-to-definition on `list` lands in this file.
+        assert!(
+            std::fs::read_to_string(&path)
+                .unwrap()
+                .contains("class list(")
+        );
+    }
+}
@@ -29,6 +29,7 @@ use ruff_python_ast::Expr;
 use ruff_python_ast::ModModule;
 use ruff_python_ast::PySourceType;
 use ruff_python_ast::StmtFunctionDef;
+use ruff_source_file::PositionEncoding;
 use ruff_text_size::Ranged;
 use ruff_text_size::TextRange;
 use ruff_text_size::TextSize;
@@ -118,6 +119,7 @@ pub fn find_containing_function_for_call(
 pub fn transform_incoming_calls(
     callers: Vec<(Module, Vec<CallerInfo>)>,
     path_remapper: Option<&PathRemapper>,
+    position_encoding: PositionEncoding,
 ) -> Vec<CallHierarchyIncomingCall> {
     let mut incoming_calls = Vec::new();
     for (caller_module, call_sites) in callers {
@@ -137,14 +139,14 @@ pub fn transform_incoming_calls(
                 tags: None,
                 detail: Some(caller.name),
                 uri: caller_uri,
-                range: caller_module.to_lsp_range(caller.full_range),
-                selection_range: caller_module.to_lsp_range(caller.name_range),
+                range: caller_module.to_lsp_range(caller.full_range, position_encoding),
+                selection_range: caller_module.to_lsp_range(caller.name_range, position_encoding),
                 data: None,
             };
 
             incoming_calls.push(CallHierarchyIncomingCall {
                 from,
-                from_ranges: vec![caller_module.to_lsp_range(caller.call_range)],
+                from_ranges: vec![caller_module.to_lsp_range(caller.call_range, position_encoding)],
             });
         }
     }
@@ -159,6 +161,7 @@ pub fn transform_outgoing_calls(
     callees: Vec<(Module, Vec<(TextRange, TextRange)>)>,
     source_module: &Module,
     fallback_uri: &lsp_types::Url,
+    position_encoding: PositionEncoding,
 ) -> Vec<CallHierarchyOutgoingCall> {
     let mut outgoing_calls = Vec::new();
     for (target_module, calls) in callees {
@@ -175,14 +178,14 @@ pub fn transform_outgoing_calls(
                 tags: None,
                 detail: Some(target_name),
                 uri: target_uri.clone(),
-                range: target_module.to_lsp_range(target_def_range),
-                selection_range: target_module.to_lsp_range(target_def_range),
+                range: target_module.to_lsp_range(target_def_range, position_encoding),
+                selection_range: target_module.to_lsp_range(target_def_range, position_encoding),
                 data: None,
             };
 
             outgoing_calls.push(CallHierarchyOutgoingCall {
                 to,
-                from_ranges: vec![source_module.to_lsp_range(call_range)],
+                from_ranges: vec![source_module.to_lsp_range(call_range, position_encoding)],
             });
         }
     }
@@ -247,9 +250,11 @@ fn module_name_from_path(path: &Path) -> ModuleName {
 ///
 /// For each external file, reads the source, parses the AST, filters to actual
 /// call expressions (via `find_enclosing_call_range`), and finds the enclosing
-/// function (via `find_containing_function_for_call`).
+/// function (via `find_containing_function_for_call`). External references are
+/// always UTF-16; the returned ranges use `position_encoding`, like local results.
 pub fn convert_external_references_to_incoming_calls(
     external_refs: Vec<(Url, Vec<Range>)>,
+    position_encoding: PositionEncoding,
 ) -> Vec<CallHierarchyIncomingCall> {
     let mut results = Vec::new();
 
@@ -278,7 +283,7 @@ pub fn convert_external_references_to_incoming_calls(
         let (ast, _, _) = Ast::parse(module.contents(), source_type);
 
         for range in ranges {
-            let position = module.from_lsp_position(range.start, None);
+            let position = module.from_lsp_position(range.start, None, PositionEncoding::Utf16);
 
             let Some(call_range) = find_enclosing_call_range(&ast, position) else {
                 continue;
@@ -298,11 +303,11 @@ pub fn convert_external_references_to_incoming_calls(
                     tags: None,
                     detail: Some(caller_name),
                     uri: url.clone(),
-                    range: module.to_lsp_range(caller_full_range),
-                    selection_range: module.to_lsp_range(caller_name_range),
+                    range: module.to_lsp_range(caller_full_range, position_encoding),
+                    selection_range: module.to_lsp_range(caller_name_range, position_encoding),
                     data: None,
                 },
-                from_ranges: vec![module.to_lsp_range(call_range)],
+                from_ranges: vec![module.to_lsp_range(call_range, position_encoding)],
             });
         }
     }
@@ -318,6 +323,7 @@ pub fn prepare_call_hierarchy_item(
     func_def: &StmtFunctionDef,
     module: &Module,
     uri: lsp_types::Url,
+    position_encoding: PositionEncoding,
 ) -> CallHierarchyItem {
     let name = func_def.name.id.to_string();
     let detail = Some(format!("{}.{}", module.name(), name));
@@ -328,8 +334,8 @@ pub fn prepare_call_hierarchy_item(
         tags: None,
         detail,
         uri,
-        range: module.to_lsp_range(func_def.range()),
-        selection_range: module.to_lsp_range(func_def.name.range()),
+        range: module.to_lsp_range(func_def.range(), position_encoding),
+        selection_range: module.to_lsp_range(func_def.name.range(), position_encoding),
         data: None,
     }
 }
@@ -523,6 +529,7 @@ mod tests {
     use pyrefly_python::ast::Ast;
     use pyrefly_python::module_name::ModuleName;
     use ruff_python_ast::PySourceType;
+    use ruff_source_file::PositionEncoding;
     use ruff_text_size::TextSize;
 
     use super::find_containing_function_for_call;
@@ -701,7 +708,8 @@ x: target = None
         };
 
         let external_refs = vec![(url.clone(), vec![call_range, import_range])];
-        let results = convert_external_references_to_incoming_calls(external_refs);
+        let results =
+            convert_external_references_to_incoming_calls(external_refs, PositionEncoding::Utf16);
 
         // Only the call expression should produce an incoming call, not the import
         assert_eq!(results.len(), 1);
@@ -753,7 +761,58 @@ x: target = None
         };
 
         let external_refs = vec![(url, vec![import_range, annotation_range])];
-        let results = convert_external_references_to_incoming_calls(external_refs);
+        let results =
+            convert_external_references_to_incoming_calls(external_refs, PositionEncoding::Utf16);
         assert!(results.is_empty());
     }
+
+    #[test]
+    fn test_convert_external_references_to_utf8() {
+        use std::io::Write;
+
+        use lsp_types::Url;
+        use tempfile::NamedTempFile;
+
+        use super::convert_external_references_to_incoming_calls;
+
+        let source = r#"from other import target
+
+def caller_func():
+    s = "😀"; target()
+"#;
+        let mut file = NamedTempFile::with_suffix(".py").unwrap();
+        write!(file, "{}", source).unwrap();
+        let url = Url::from_file_path(file.path()).unwrap();
+
+        // The emoji is two UTF-16 code units but four UTF-8 bytes.
+        let call_range = lsp_types::Range {
+            start: lsp_types::Position {
+                line: 3,
+                character: 14,
+            },
+            end: lsp_types::Position {
+                line: 3,
+                character: 20,
+            },
+        };
+
+        let results = convert_external_references_to_incoming_calls(
+            vec![(url, vec![call_range])],
+            PositionEncoding::Utf8,
+        );
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].from_ranges,
+            vec![lsp_types::Range {
+                start: lsp_types::Position {
+                    line: 3,
+                    character: 16,
+                },
+                end: lsp_types::Position {
+                    line: 3,
+                    character: 24,
+                },
+            }]
+        );
+    }
 }
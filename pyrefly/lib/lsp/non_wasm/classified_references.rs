@@ -0,0 +1,35 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use lsp_types::Range;
+use lsp_types::ReferenceParams;
+use lsp_types::Url;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::state::lsp::ReferenceKind;
+
+/// A reference returned by `pyrefly/textDocument/classifiedReferences`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassifiedLocation {
+    pub uri: Url,
+    pub range: Range,
+    /// How the reference uses the symbol. Missing for references reported by an external
+    /// provider, which can't be classified.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ReferenceKind>,
+}
+
+/// Custom variant of `textDocument/references` that also says whether each reference is the
+/// declaration, a read or a write, so clients can group or style them.
+pub enum ClassifiedReferencesRequest {}
+
+impl lsp_types::request::Request for ClassifiedReferencesRequest {
+    type Params = ReferenceParams;
+    type Result = Option<Vec<ClassifiedLocation>>;
+    const METHOD: &'static str = "pyrefly/textDocument/classifiedReferences";
+}
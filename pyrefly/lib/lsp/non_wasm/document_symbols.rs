@@ -12,12 +12,13 @@ use pyrefly_python::module::Module;
 use pyrefly_util::visit::Visit;
 use ruff_python_ast::Expr;
 use ruff_python_ast::Stmt;
+use ruff_source_file::PositionEncoding;
 use ruff_text_size::Ranged;
 
 use crate::state::state::Transaction;
 
 impl<'a> Transaction<'a> {
-    /// Return document symbols for the file behind `handle`.
+    /// Return document symbols for the file behind `handle`, with ranges in `position_encoding`.
     /// When `limit_cell_idx` is `Some`, only symbols whose range falls within that
     /// notebook cell are returned (mirroring semantic-token cell filtering).
     #[allow(deprecated)] // The `deprecated` field
@@ -25,6 +26,7 @@ impl<'a> Transaction<'a> {
         &self,
         handle: &Handle,
         limit_cell_idx: Option<usize>,
+        position_encoding: PositionEncoding,
     ) -> Option<Vec<DocumentSymbol>> {
         let ast = self.get_ast(handle)?;
         let module_info = self.get_module_info(handle)?;
@@ -45,6 +47,7 @@ impl<'a> Transaction<'a> {
             &mut result,
             &module_info,
             limit_cell_idx,
+            position_encoding,
         );
 
         Some(result)
@@ -63,6 +66,7 @@ fn build_symbols_with_sections(
     result: &mut Vec<DocumentSymbol>,
     module_info: &Module,
     limit_cell_idx: Option<usize>,
+    position_encoding: PositionEncoding,
 ) {
     use ruff_text_size::Ranged;
 
@@ -78,7 +82,10 @@ fn build_symbols_with_sections(
         {
             continue;
         }
-        let stmt_line = module_info.to_lsp_range(stmt.range()).start.line;
+        let stmt_line = module_info
+            .to_lsp_range(stmt.range(), position_encoding)
+            .start
+            .line;
 
         // Process any comment sections that come before this statement
         while section_idx < sections.len() && sections[section_idx].line_number <= stmt_line {
@@ -99,8 +106,8 @@ fn build_symbols_with_sections(
                 kind: lsp_types::SymbolKind::STRING,
                 tags: None,
                 deprecated: None,
-                range: module_info.to_lsp_range(section.range),
-                selection_range: module_info.to_lsp_range(section.range),
+                range: module_info.to_lsp_range(section.range, position_encoding),
+                selection_range: module_info.to_lsp_range(section.range, position_encoding),
                 children: Some(Vec::new()),
             };
 
@@ -127,10 +134,10 @@ fn build_symbols_with_sections(
         if let Some((_, path)) = section_stack.last() {
             // Navigate to the current section and add symbol as its child
             let current = navigate_to_path_mut(result, path);
-            recurse_stmt_adding_symbols(stmt, current, module_info);
+            recurse_stmt_adding_symbols(stmt, current, module_info, position_encoding);
         } else {
             // No section context, add at top level
-            recurse_stmt_adding_symbols(stmt, result, module_info);
+            recurse_stmt_adding_symbols(stmt, result, module_info, position_encoding);
         }
     }
 
@@ -152,8 +159,8 @@ fn build_symbols_with_sections(
             kind: lsp_types::SymbolKind::STRING,
             tags: None,
             deprecated: None,
-            range: module_info.to_lsp_range(section.range),
-            selection_range: module_info.to_lsp_range(section.range),
+            range: module_info.to_lsp_range(section.range, position_encoding),
+            selection_range: module_info.to_lsp_range(section.range, position_encoding),
             children: Some(Vec::new()),
         };
 
@@ -192,9 +199,12 @@ fn recurse_stmt_adding_symbols<'a>(
     stmt: &'a Stmt,
     symbols: &'a mut Vec<DocumentSymbol>,
     module_info: &Module,
+    position_encoding: PositionEncoding,
 ) {
     let mut recursed_symbols = Vec::new();
-    stmt.recurse(&mut |stmt| recurse_stmt_adding_symbols(stmt, &mut recursed_symbols, module_info));
+    stmt.recurse(&mut |stmt| {
+        recurse_stmt_adding_symbols(stmt, &mut recursed_symbols, module_info, position_encoding)
+    });
 
     match stmt {
         Stmt::FunctionDef(stmt_function_def) => {
@@ -211,8 +221,9 @@ fn recurse_stmt_adding_symbols<'a>(
                 kind: lsp_types::SymbolKind::FUNCTION,
                 tags: None,
                 deprecated: None,
-                range: module_info.to_lsp_range(stmt_function_def.range),
-                selection_range: module_info.to_lsp_range(stmt_function_def.name.range),
+                range: module_info.to_lsp_range(stmt_function_def.range, position_encoding),
+                selection_range: module_info
+                    .to_lsp_range(stmt_function_def.name.range, position_encoding),
 
                 children: Some(children),
             });
@@ -238,8 +249,9 @@ fn recurse_stmt_adding_symbols<'a>(
                 kind: lsp_types::SymbolKind::CLASS,
                 tags: None,
                 deprecated: None,
-                range: module_info.to_lsp_range(stmt_class_def.range),
-                selection_range: module_info.to_lsp_range(stmt_class_def.name.range),
+                range: module_info.to_lsp_range(stmt_class_def.range, position_encoding),
+                selection_range: module_info
+                    .to_lsp_range(stmt_class_def.name.range, position_encoding),
                 children: Some(children),
             });
         }
@@ -256,8 +268,8 @@ fn recurse_stmt_adding_symbols<'a>(
                         kind: lsp_types::SymbolKind::VARIABLE,
                         tags: None,
                         deprecated: None,
-                        range: module_info.to_lsp_range(stmt_assign.range),
-                        selection_range: module_info.to_lsp_range(name.range),
+                        range: module_info.to_lsp_range(stmt_assign.range, position_encoding),
+                        selection_range: module_info.to_lsp_range(name.range, position_encoding),
                         children: None,
                     });
                 }
@@ -277,8 +289,8 @@ fn recurse_stmt_adding_symbols<'a>(
                     kind: lsp_types::SymbolKind::VARIABLE,
                     tags: None,
                     deprecated: None,
-                    range: module_info.to_lsp_range(stmt_ann_assign.range),
-                    selection_range: module_info.to_lsp_range(name.range),
+                    range: module_info.to_lsp_range(stmt_ann_assign.range, position_encoding),
+                    selection_range: module_info.to_lsp_range(name.range, position_encoding),
                     children: None,
                 });
             }
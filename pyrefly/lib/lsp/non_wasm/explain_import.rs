@@ -0,0 +1,44 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::module_path::ModulePath;
+
+use crate::config::config::ConfigFile;
+use crate::module::finder::trace_import;
+use crate::state::loader::Finding;
+use crate::state::loader::FindingOrError;
+
+/// The `workspace/executeCommand` command that explains how an import is resolved.
+/// Takes the module name and the URI of the importing file as its arguments.
+pub const EXPLAIN_IMPORT_COMMAND: &str = "pyrefly.explainImport";
+
+/// Describe the lookup of `module` from `origin`: every candidate path in the order it was
+/// probed, marked as missing or found, followed by any error the lookup reported.
+pub fn explain_import(config: &ConfigFile, module: ModuleName, origin: &ModulePath) -> String {
+    let (result, missing) = trace_import(config, module, Some(origin));
+    let mut lines = vec![format!("Resolving `{module}` from `{origin}`:")];
+    lines.extend(
+        missing
+            .iter()
+            .map(|path| format!("  [missing] {}", path.display())),
+    );
+    let error = match result {
+        FindingOrError::Finding(Finding { finding, error }) => {
+            lines.push(format!("  [found]   {finding}"));
+            error
+        }
+        FindingOrError::Error(error) => {
+            lines.push(format!("`{module}` could not be resolved."));
+            Some(error)
+        }
+    };
+    if let Some(error) = error {
+        lines.extend(error.display().1);
+    }
+    lines.join("\n")
+}
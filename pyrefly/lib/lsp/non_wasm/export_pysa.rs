@@ -0,0 +1,48 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::path::Path;
+
+use pyrefly_build::handle::Handle;
+
+use crate::report::pysa::PysaFormat;
+use crate::report::pysa::PysaReporter;
+use crate::report::pysa::context::PysaExportOptions;
+use crate::report::pysa::write_project_file;
+use crate::state::require::Require;
+use crate::state::state::State;
+
+/// The `workspace/executeCommand` command that writes a Pysa report for the indexed project.
+/// Takes the output directory as its only argument.
+pub const EXPORT_PYSA_COMMAND: &str = "pyrefly.exportPysa";
+
+/// Type check `handles` with a Pysa reporter attached and write the report to `output_directory`.
+///
+/// Pysa output is written as each module reaches the solutions step, so the modules already
+/// solved in the language server's `state` are recomputed in a transaction that is never
+/// committed, leaving the server's results untouched.
+pub fn export_pysa(
+    state: &State,
+    handles: &[Handle],
+    output_directory: &Path,
+) -> anyhow::Result<()> {
+    let mut transaction = state.new_committable_transaction(Require::Errors, None);
+    let transaction = transaction.as_mut();
+    transaction.invalidate_all();
+    transaction.set_pysa_reporter(Some(PysaReporter::new(
+        output_directory,
+        handles,
+        PysaFormat::Capnp,
+        PysaExportOptions::default(),
+    )?));
+    transaction.run(handles, Require::Errors, None);
+    let pysa_reporter = transaction
+        .take_pysa_reporter()
+        .expect("Pysa reporter was set before the run");
+    let errors = transaction.get_errors(handles).collect_errors().ordinary;
+    write_project_file(&pysa_reporter, transaction, handles, &errors)
+}
@@ -5,6 +5,7 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::sync::Arc;
 use std::time::Duration;
 
 use dupe::Dupe;
@@ -13,12 +14,17 @@ use lsp_types::SymbolInformation;
 use lsp_types::Url;
 use pyrefly_build::handle::Handle;
 use pyrefly_python::ast::Ast;
+use pyrefly_python::module::Module;
 use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::module_path::ModulePath;
 use pyrefly_python::symbol_kind::SymbolKind;
+use pyrefly_util::prelude::VecExt;
 use pyrefly_util::telemetry::SubTaskTelemetry;
 use ruff_python_ast::AnyNodeRef;
 use ruff_python_ast::ModModule;
+use ruff_source_file::PositionEncoding;
 use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
 use ruff_text_size::TextSize;
 
 use crate::report::glean::convert::ScopeType;
@@ -73,6 +79,38 @@ impl ExternalProvider for NoExternalProvider {
     }
 }
 
+/// External providers report ranges in UTF-16, the LSP default. Convert `references` to
+/// `position_encoding` using the contents of each file on disk. Ranges in files that can't be
+/// read are left as they are.
+pub(crate) fn external_references_in_encoding(
+    references: Vec<(Url, Vec<Range>)>,
+    position_encoding: PositionEncoding,
+) -> Vec<(Url, Vec<Range>)> {
+    if matches!(position_encoding, PositionEncoding::Utf16) {
+        return references;
+    }
+    references.into_map(|(url, ranges)| {
+        let Some(module) = url.to_file_path().ok().and_then(|path| {
+            let contents = std::fs::read_to_string(&path).ok()?;
+            Some(Module::new(
+                ModuleName::unknown(),
+                ModulePath::filesystem(path),
+                Arc::new(contents),
+            ))
+        }) else {
+            return (url, ranges);
+        };
+        let ranges = ranges.into_map(|range| {
+            let range = TextRange::new(
+                module.from_lsp_position(range.start, None, PositionEncoding::Utf16),
+                module.from_lsp_position(range.end, None, PositionEncoding::Utf16),
+            );
+            module.to_lsp_range(range, position_encoding)
+        });
+        (url, ranges)
+    })
+}
+
 /// Compute the fully-qualified name for the symbol defined at `position`
 /// in the AST, using the same `.<locals>` convention as the Glean indexer.
 ///
@@ -12,6 +12,7 @@ use lsp_server::ResponseError;
 use lsp_types::TextDocumentContentChangeEvent;
 use ruff_source_file::LineIndex;
 use ruff_source_file::OneIndexed;
+use ruff_source_file::PositionEncoding;
 use ruff_source_file::SourceLocation;
 use serde::de::DeserializeOwned;
 
@@ -104,22 +105,23 @@ where
     }
 }
 
-pub fn apply_change_events(original: &str, changes: Vec<TextDocumentContentChangeEvent>) -> String {
+pub fn apply_change_events(
+    original: &str,
+    changes: Vec<TextDocumentContentChangeEvent>,
+    position_encoding: PositionEncoding,
+) -> String {
     /// Convert lsp_types::Position to usize index for a given text.
     fn position_to_usize(
         position: lsp_types::Position,
         index: &LineIndex,
         source_text: &str,
+        position_encoding: PositionEncoding,
     ) -> usize {
         let source_location = SourceLocation {
             line: OneIndexed::from_zero_indexed(position.line as usize),
             character_offset: OneIndexed::from_zero_indexed(position.character as usize),
         };
-        let text_size = index.offset(
-            source_location,
-            source_text,
-            ruff_source_file::PositionEncoding::Utf16,
-        );
+        let text_size = index.offset(source_location, source_text, position_encoding);
         text_size.to_usize()
     }
 
@@ -131,8 +133,8 @@ pub fn apply_change_events(original: &str, changes: Vec<TextDocumentContentChang
             None => result = text,
             Some(range) => {
                 let index = LineIndex::from_source_text(&result);
-                let start = position_to_usize(range.start, &index, &result);
-                let end = position_to_usize(range.end, &index, &result);
+                let start = position_to_usize(range.start, &index, &result, position_encoding);
+                let end = position_to_usize(range.end, &index, &result, position_encoding);
                 result.replace_range(start..end, &text);
             }
         }
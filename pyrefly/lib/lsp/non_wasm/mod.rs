@@ -7,19 +7,28 @@
 
 mod build_system;
 pub mod call_hierarchy;
+pub mod classified_references;
 pub mod code_lens;
 pub mod connection;
 pub mod convert_module_package;
 pub mod document_symbols;
+pub mod explain_import;
+pub mod export_pysa;
 pub mod external_provider;
 pub mod folding_ranges;
 pub mod lsp;
 pub mod module_helpers;
+pub mod moniker;
 pub mod move_symbol_new_file;
 mod mru;
+pub mod on_type_formatting;
+pub mod peek_definition;
 pub mod protocol;
 pub mod queue;
+pub mod rename_module;
+pub mod request_deadline;
 pub mod safe_delete_file;
+pub mod semantic_tokens_delta;
 pub mod server;
 pub mod stdlib;
 pub mod transaction_manager;
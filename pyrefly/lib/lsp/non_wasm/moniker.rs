@@ -0,0 +1,72 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use lsp_types::Moniker;
+use lsp_types::MonikerKind;
+use lsp_types::UniquenessLevel;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::module_name::ModuleName;
+use ruff_text_size::TextSize;
+
+use crate::lsp::non_wasm::external_provider::compute_qualified_name;
+use crate::state::lsp::FindDefinitionItemWithDocstring;
+use crate::state::lsp::FindPreference;
+use crate::state::state::Transaction;
+
+pub const MONIKER_SCHEME: &str = "pyrefly";
+
+/// The monikers for the symbol at `position`, one per distinct definition. The identifier is the
+/// module-qualified name of the definition, so indexers can match a symbol exported by one
+/// repository with its imports in another.
+pub(crate) fn monikers(
+    transaction: &Transaction<'_>,
+    handle: &Handle,
+    position: TextSize,
+) -> Option<Vec<Moniker>> {
+    let definitions = transaction
+        .find_definition(handle, position, FindPreference::default())
+        .ok()?;
+    let mut monikers: Vec<Moniker> = Vec::new();
+    for definition in definitions {
+        if let Some(moniker) = moniker_for_definition(transaction, handle, &definition)
+            && !monikers.contains(&moniker)
+        {
+            monikers.push(moniker);
+        }
+    }
+    if monikers.is_empty() {
+        None
+    } else {
+        Some(monikers)
+    }
+}
+
+fn moniker_for_definition(
+    transaction: &Transaction<'_>,
+    handle: &Handle,
+    definition: &FindDefinitionItemWithDocstring,
+) -> Option<Moniker> {
+    let module_name = definition.module.name();
+    let mut identifier = compute_qualified_name(transaction, handle, definition)?;
+    // Qualified names leave builtins unprefixed to match Glean, but monikers always carry the
+    // module.
+    if module_name == ModuleName::builtins() && identifier != module_name.as_str() {
+        identifier = format!("{module_name}.{identifier}");
+    }
+    // A symbol defined in this file is exported from it; anything else was imported.
+    let kind = if definition.module.path() == handle.path() {
+        MonikerKind::Export
+    } else {
+        MonikerKind::Import
+    };
+    Some(Moniker {
+        scheme: MONIKER_SCHEME.to_owned(),
+        identifier,
+        unique: UniquenessLevel::Scheme,
+        kind: Some(kind),
+    })
+}
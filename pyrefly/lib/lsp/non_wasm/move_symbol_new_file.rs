@@ -31,6 +31,7 @@ use pyrefly_build::handle::Handle;
 use pyrefly_python::PYTHON_EXTENSIONS;
 use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::module_path::ModulePath;
+use ruff_source_file::PositionEncoding;
 use ruff_text_size::TextRange;
 
 use crate::lsp::non_wasm::module_helpers::PathRemapper;
@@ -79,6 +80,7 @@ pub(crate) fn move_symbol_to_new_file_code_action(
     selection: TextRange,
     import_format: ImportFormat,
     path_remapper: Option<&PathRemapper>,
+    position_encoding: PositionEncoding,
 ) -> Option<CodeActionOrCommand> {
     if !supports_workspace_edit_document_changes(capabilities) {
         return None;
@@ -150,7 +152,7 @@ pub(crate) fn move_symbol_to_new_file_code_action(
             continue;
         };
         changes.entry(edit_uri).or_default().push(TextEdit {
-            range: module.to_lsp_range(range),
+            range: module.to_lsp_range(range, position_encoding),
             new_text,
         });
     }
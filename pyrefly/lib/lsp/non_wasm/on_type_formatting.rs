@@ -0,0 +1,175 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use pyrefly_python::ast::Ast;
+use pyrefly_python::sys_info::PythonVersion;
+use ruff_python_ast::PySourceType;
+use ruff_python_ast::token::TokenKind;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+
+/// The character that triggers `textDocument/onTypeFormatting`.
+pub const ON_TYPE_FORMATTING_TRIGGER: &str = "\n";
+
+fn leading_whitespace(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// If the last logical line of `source` opens a block (ends with a `:` token, ignoring any
+/// trailing comment), return the offset of the first token of that logical line.
+fn block_opener_start(source: &str) -> Option<TextSize> {
+    let (parsed, _, _) =
+        Ast::parse_with_version(source, PythonVersion::default(), PySourceType::Python);
+    let tokens = parsed.tokens();
+    let mut rest = tokens.iter().rev().skip_while(|token| {
+        matches!(
+            token.kind(),
+            TokenKind::EndOfFile
+                | TokenKind::Dedent
+                | TokenKind::Indent
+                | TokenKind::NonLogicalNewline
+                | TokenKind::Comment
+        )
+    });
+    // Blank lines and comments end in non-logical newlines, so a logical newline here means the
+    // last code line is complete, i.e. not inside brackets.
+    if rest.next()?.kind() != TokenKind::Newline {
+        return None;
+    }
+    let mut rest = rest.skip_while(|token| token.kind() == TokenKind::Comment);
+    if rest.next()?.kind() != TokenKind::Colon {
+        return None;
+    }
+    // Walk back to the start of the logical line, which may span several physical lines.
+    let mut depth = 0usize;
+    let mut start = None;
+    for token in rest {
+        match token.kind() {
+            TokenKind::Rpar | TokenKind::Rsqb | TokenKind::Rbrace => depth += 1,
+            TokenKind::Lpar | TokenKind::Lsqb | TokenKind::Lbrace => {
+                depth = depth.saturating_sub(1)
+            }
+            TokenKind::Newline
+            | TokenKind::NonLogicalNewline
+            | TokenKind::Indent
+            | TokenKind::Dedent
+                if depth == 0 =>
+            {
+                break;
+            }
+            _ => {}
+        }
+        start = Some(token.start());
+    }
+    start
+}
+
+/// Compute the indentation for the line containing `position` after a newline has been typed.
+///
+/// This is deliberately conservative: we only act when the previous logical line opens a block
+/// (its last token is a `:`), in which case the new line is indented one `indent_unit` deeper
+/// than that line. Returns the range of the current line's existing indentation and its
+/// replacement, or `None` if nothing should change.
+pub fn newline_indentation(
+    contents: &str,
+    position: TextSize,
+    indent_unit: &str,
+) -> Option<(TextRange, String)> {
+    let position = position.to_usize().min(contents.len());
+    let line_start = contents[..position].rfind('\n').map_or(0, |i| i + 1);
+    let opener = block_opener_start(&contents[..line_start])?.to_usize();
+    let opener_line_start = contents[..opener].rfind('\n').map_or(0, |i| i + 1);
+    let indentation = format!(
+        "{}{indent_unit}",
+        leading_whitespace(&contents[opener_line_start..])
+    );
+
+    let current_line = contents[line_start..]
+        .split('\n')
+        .next()
+        .unwrap_or_default();
+    let current_indentation = leading_whitespace(current_line);
+    if current_indentation == indentation {
+        return None;
+    }
+    let start = TextSize::new(line_start as u32);
+    Some((
+        TextRange::at(start, TextSize::of(current_indentation)),
+        indentation,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(contents: &str, line: usize) -> Option<String> {
+        let position = contents
+            .split_inclusive('\n')
+            .take(line)
+            .map(|l| l.len())
+            .sum::<usize>();
+        let (range, indentation) =
+            newline_indentation(contents, TextSize::new(position as u32), "    ")?;
+        let mut result = contents.to_owned();
+        result.replace_range(
+            range.start().to_usize()..range.end().to_usize(),
+            &indentation,
+        );
+        Some(result)
+    }
+
+    #[test]
+    fn test_indents_after_block_opener() {
+        assert_eq!(
+            apply("def foo():\n\n", 1).as_deref(),
+            Some("def foo():\n    \n")
+        );
+        assert_eq!(
+            apply("class A:\n    def f(self):  \n\n", 2).as_deref(),
+            Some("class A:\n    def f(self):  \n        \n")
+        );
+    }
+
+    #[test]
+    fn test_replaces_existing_indentation() {
+        assert_eq!(
+            apply("if x:\n  pass\n", 1).as_deref(),
+            Some("if x:\n    pass\n")
+        );
+        assert_eq!(apply("if x:\n    \n", 1), None);
+    }
+
+    #[test]
+    fn test_skips_blank_lines_and_non_openers() {
+        assert_eq!(
+            apply("for x in y:\n\n\n", 2).as_deref(),
+            Some("for x in y:\n\n    \n")
+        );
+        assert_eq!(apply("x = 1\n\n", 1), None);
+        assert_eq!(apply("# note:\n\n", 1), None);
+        assert_eq!(apply("\n", 0), None);
+    }
+
+    #[test]
+    fn test_trailing_comment_after_block_opener() {
+        assert_eq!(
+            apply("if x:  # note\n\n", 1).as_deref(),
+            Some("if x:  # note\n    \n")
+        );
+        assert_eq!(apply("x = 1  # note:\n\n", 1), None);
+    }
+
+    #[test]
+    fn test_block_opener_spanning_lines() {
+        assert_eq!(
+            apply("def foo(\n    a,\n    b,\n):\n\n", 4).as_deref(),
+            Some("def foo(\n    a,\n    b,\n):\n    \n")
+        );
+    }
+}
@@ -0,0 +1,104 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use lsp_types::Location;
+use lsp_types::TextDocumentPositionParams;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::module::TextRangeWithModule;
+use pyrefly_util::lined_buffer::LineNumber;
+use ruff_text_size::TextSize;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::state::lsp::FindPreference;
+use crate::state::state::Transaction;
+
+/// Number of lines shown on each side of the definition when the client doesn't ask for a
+/// specific amount.
+pub const DEFAULT_PEEK_CONTEXT_LINES: u32 = 3;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeekDefinitionParams {
+    #[serde(flatten)]
+    pub text_document_position_params: TextDocumentPositionParams,
+    /// Number of lines to include before and after the line of the definition.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_lines: Option<u32>,
+}
+
+/// A definition returned by `pyrefly/textDocument/peekDefinition`, with the source around it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeekDefinition {
+    pub location: Location,
+    /// Zero-indexed line of the definition's file that `snippet` starts at.
+    pub snippet_start_line: u32,
+    pub snippet: String,
+}
+
+/// Custom request returning the definitions of the symbol at a position together with the
+/// surrounding source lines, so clients can show an inline peek without reading the target file.
+pub enum PeekDefinitionRequest {}
+
+impl lsp_types::request::Request for PeekDefinitionRequest {
+    type Params = PeekDefinitionParams;
+    type Result = Option<Vec<PeekDefinition>>;
+    const METHOD: &'static str = "pyrefly/textDocument/peekDefinition";
+}
+
+/// The source lines around a definition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DefinitionSnippet {
+    pub definition: TextRangeWithModule,
+    /// Zero-indexed line that `text` starts at.
+    pub start_line: u32,
+    pub text: String,
+}
+
+/// The definitions of the symbol at `position`, each with `context_lines` lines of source on
+/// either side of the line it is on.
+pub(crate) fn definition_snippets(
+    transaction: &Transaction<'_>,
+    handle: &Handle,
+    position: TextSize,
+    context_lines: u32,
+) -> Option<Vec<DefinitionSnippet>> {
+    let definitions = transaction
+        .find_definition(handle, position, FindPreference::default())
+        .ok()?;
+    Some(
+        definitions
+            .into_iter()
+            .map(|definition| {
+                let module = &definition.module;
+                let line = module
+                    .display_pos(definition.definition_range.start())
+                    .line_within_file()
+                    .to_zero_indexed();
+                let last_line = (module.line_count() as u32).saturating_sub(1);
+                let start_line = line.saturating_sub(context_lines);
+                let end_line = line.saturating_add(context_lines).min(last_line);
+                let text = module
+                    .lined_buffer()
+                    .content_in_line_range(
+                        LineNumber::from_zero_indexed(start_line),
+                        LineNumber::from_zero_indexed(end_line),
+                    )
+                    .to_owned();
+                DefinitionSnippet {
+                    definition: TextRangeWithModule::new(
+                        definition.module,
+                        definition.definition_range,
+                    ),
+                    start_line,
+                    text,
+                }
+            })
+            .collect(),
+    )
+}
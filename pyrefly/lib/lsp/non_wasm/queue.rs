@@ -7,6 +7,7 @@
 
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
 use std::time::Instant;
 
 use crossbeam_channel::Receiver;
@@ -31,6 +32,7 @@ use tracing::info;
 use crate::lsp::non_wasm::protocol::Request;
 use crate::lsp::non_wasm::protocol::Response;
 use crate::lsp::non_wasm::server::Server;
+use crate::lsp::non_wasm::transaction_manager::TransactionManager;
 use crate::lsp::wasm::notebook::DidChangeNotebookDocumentParams;
 use crate::lsp::wasm::notebook::DidCloseNotebookDocumentParams;
 use crate::lsp::wasm::notebook::DidOpenNotebookDocumentParams;
@@ -191,15 +193,49 @@ impl LspQueue {
         }
         Ok((last_mutation != 0, x, queue_time))
     }
+
+    /// Block until another event is queued or `timeout` elapses, without consuming anything.
+    /// Return whether a subsequent mutation event is now in the queue.
+    ///
+    /// Any event (not just a mutation) ends the wait early, so queries are never delayed
+    /// by more than they would have been by processing the queue in order.
+    pub fn wait_for_subsequent_mutation(&self, timeout: Duration) -> bool {
+        if self.last_mutation.load(Ordering::Relaxed) == 0 && !timeout.is_zero() {
+            let mut event_receiver_selector = Select::new_biased();
+            event_receiver_selector.recv(&self.priority.1);
+            event_receiver_selector.recv(&self.normal.1);
+            let _ = event_receiver_selector.ready_timeout(timeout);
+        }
+        self.last_mutation.load(Ordering::Relaxed) != 0
+    }
 }
 
-pub struct HeavyTask(
-    Box<dyn FnOnce(&Server, &dyn Telemetry, &mut TelemetryEvent) + Send + Sync + 'static>,
-);
+type ReadTask = Box<
+    dyn for<'a> FnOnce(&'a Server, &mut TransactionManager<'a>, &dyn Telemetry, &mut TelemetryEvent)
+        + Send
+        + Sync
+        + 'static,
+>;
+
+pub enum HeavyTask {
+    Plain(Box<dyn FnOnce(&Server, &dyn Telemetry, &mut TelemetryEvent) + Send + Sync + 'static>),
+    /// A read-only request, which is given the queue's own `TransactionManager` so that the
+    /// transaction it used can be saved and reused by the next request on the same queue.
+    Read(ReadTask),
+}
 
 impl HeavyTask {
-    fn run(self, server: &Server, telemetry: &dyn Telemetry, telemetry_event: &mut TelemetryEvent) {
-        self.0(server, telemetry, telemetry_event);
+    fn run<'a>(
+        self,
+        server: &'a Server,
+        transaction_manager: &mut TransactionManager<'a>,
+        telemetry: &dyn Telemetry,
+        telemetry_event: &mut TelemetryEvent,
+    ) {
+        match self {
+            Self::Plain(f) => f(server, telemetry, telemetry_event),
+            Self::Read(f) => f(server, transaction_manager, telemetry, telemetry_event),
+        }
     }
 }
 
@@ -232,8 +268,29 @@ impl HeavyTaskQueue {
         kind: TelemetryEventKind,
         f: Box<dyn FnOnce(&Server, &dyn Telemetry, &mut TelemetryEvent) + Send + Sync + 'static>,
     ) {
+        self.send_task(HeavyTask::Plain(f), kind);
+    }
+
+    /// Queue a read-only request task. Every read task on this queue shares one
+    /// `TransactionManager`, owned by the queue's thread.
+    pub fn queue_read_task(
+        &self,
+        kind: TelemetryEventKind,
+        f: impl for<'a> FnOnce(
+            &'a Server,
+            &mut TransactionManager<'a>,
+            &dyn Telemetry,
+            &mut TelemetryEvent,
+        ) + Send
+        + Sync
+        + 'static,
+    ) {
+        self.send_task(HeavyTask::Read(Box::new(f)), kind);
+    }
+
+    fn send_task(&self, task: HeavyTask, kind: TelemetryEventKind) {
         self.task_sender
-            .send((HeavyTask(f), kind, Instant::now()))
+            .send((task, kind, Instant::now()))
             .expect("Failed to queue heavy task");
         debug!("Enqueued task on {} heavy task queue", self.queue_name);
     }
@@ -244,6 +301,7 @@ impl HeavyTaskQueue {
         // so we register priority_events_receiver first.
         let stop_receiver_index = receiver_selector.recv(&self.stop_receiver);
         let task_receiver_index = receiver_selector.recv(&self.task_receiver);
+        let mut transaction_manager = TransactionManager::default();
         loop {
             let selected = receiver_selector.select();
             match selected.index() {
@@ -266,7 +324,12 @@ impl HeavyTaskQueue {
                         self.queue_name,
                         task_id,
                     );
-                    task.run(server, telemetry, &mut telemetry_event);
+                    task.run(
+                        server,
+                        &mut transaction_manager,
+                        telemetry,
+                        &mut telemetry_event,
+                    );
                     let process_duration = telemetry_event.finish_and_record(telemetry, None);
                     info!(
                         "Ran task on {} heavy task queue. Queue time: {:.2}, task time: {:.2}",
@@ -0,0 +1,158 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use lsp_types::ClientCapabilities;
+use lsp_types::DocumentChangeOperation;
+use lsp_types::DocumentChanges;
+use lsp_types::RenameFile;
+use lsp_types::ResourceOp;
+use lsp_types::ResourceOperationKind;
+use lsp_types::TextEdit;
+use lsp_types::Url;
+use lsp_types::WorkspaceEdit;
+use pyrefly_python::keywords::is_valid_identifier;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::module_path::ModulePathDetails;
+use ruff_source_file::PositionEncoding;
+
+use crate::ModuleInfo;
+use crate::lsp::non_wasm::module_helpers::PathRemapper;
+use crate::lsp::non_wasm::will_rename_files::import_edits_for_rename;
+use crate::lsp::non_wasm::will_rename_files::text_document_edit_operations;
+use crate::state::state::State;
+use crate::state::state::Transaction;
+
+fn supports_workspace_edit_document_changes(capabilities: &ClientCapabilities) -> bool {
+    capabilities
+        .workspace
+        .as_ref()
+        .and_then(|workspace| workspace.workspace_edit.as_ref())
+        .and_then(|workspace_edit| workspace_edit.document_changes)
+        .unwrap_or(false)
+}
+
+fn supports_workspace_edit_resource_ops(
+    capabilities: &ClientCapabilities,
+    required: &[ResourceOperationKind],
+) -> bool {
+    let supported = capabilities
+        .workspace
+        .as_ref()
+        .and_then(|workspace| workspace.workspace_edit.as_ref())
+        .and_then(|workspace_edit| workspace_edit.resource_operations.as_ref());
+    required
+        .iter()
+        .all(|kind| supported.is_some_and(|ops| ops.contains(kind)))
+}
+
+/// A `textDocument/rename` whose target is a module: rename the module's file (or its package
+/// directory, for an `__init__` file) to a new name and update the imports of every file that
+/// depends on it.
+pub(crate) struct ModuleRename {
+    module_name: ModuleName,
+    old_path: PathBuf,
+    new_path: PathBuf,
+    old_uri: Url,
+    new_uri: Url,
+}
+
+impl ModuleRename {
+    /// Returns `None` if the client can't apply file renames, `new_name` is not a valid
+    /// identifier, the module is not backed by a file, or the destination already exists.
+    pub(crate) fn new(
+        capabilities: &ClientCapabilities,
+        module: &ModuleInfo,
+        new_name: &str,
+    ) -> Option<Self> {
+        if !supports_workspace_edit_document_changes(capabilities)
+            || !supports_workspace_edit_resource_ops(capabilities, &[ResourceOperationKind::Rename])
+        {
+            return None;
+        }
+        if !is_valid_identifier(new_name) {
+            return None;
+        }
+        let old_path = match module.path().details() {
+            ModulePathDetails::FileSystem(path) | ModulePathDetails::Memory(path) => {
+                path.to_path_buf()
+            }
+            _ => return None,
+        };
+        // Renaming a package renames its directory; renaming a module renames its file.
+        let (old_resource, new_resource, new_path) = if module.path().is_init() {
+            let package_dir = old_path.parent()?;
+            let new_package_dir = package_dir.with_file_name(new_name);
+            let new_path = new_package_dir.join(old_path.file_name()?);
+            (package_dir.to_path_buf(), new_package_dir, new_path)
+        } else {
+            let extension = old_path.extension()?.to_str()?;
+            let new_path = old_path.with_file_name(format!("{new_name}.{extension}"));
+            (old_path.clone(), new_path.clone(), new_path)
+        };
+        if new_resource.exists() {
+            return None;
+        }
+        Some(Self {
+            module_name: module.name(),
+            old_uri: Url::from_file_path(&old_resource).ok()?,
+            new_uri: Url::from_file_path(&new_resource).ok()?,
+            old_path,
+            new_path,
+        })
+    }
+
+    /// Build the `WorkspaceEdit` for the rename. This parses every file that depends on the
+    /// module, so it should not run on the main loop.
+    ///
+    /// Returns an error message if a dependent file imports the module with a plain `import`:
+    /// only import statements are rewritten, so its qualified uses would be left pointing at the
+    /// old name.
+    pub(crate) fn workspace_edit(
+        &self,
+        state: &State,
+        transaction: &Transaction<'_>,
+        path_remapper: Option<&PathRemapper>,
+        position_encoding: PositionEncoding,
+    ) -> Result<WorkspaceEdit, String> {
+        let import_edits = import_edits_for_rename(
+            state,
+            transaction,
+            &self.old_path,
+            &self.new_path,
+            path_remapper,
+            position_encoding,
+        );
+        if let Some(importer) = import_edits.qualified_importers.first() {
+            return Err(format!(
+                "Cannot rename module `{}`: `{}` imports it without `as`, and its qualified \
+                 uses of the module would not be updated",
+                self.module_name, importer
+            ));
+        }
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        for (uri, edits) in import_edits.edits {
+            changes.entry(uri).or_default().extend(edits);
+        }
+        // Edit the importers first, while their URIs (and possibly the module's own) still exist.
+        let mut operations = text_document_edit_operations(changes);
+        operations.push(DocumentChangeOperation::Op(ResourceOp::Rename(
+            RenameFile {
+                old_uri: self.old_uri.clone(),
+                new_uri: self.new_uri.clone(),
+                options: None,
+                annotation_id: None,
+            },
+        )));
+        Ok(WorkspaceEdit {
+            document_changes: Some(DocumentChanges::Operations(operations)),
+            ..Default::default()
+        })
+    }
+}
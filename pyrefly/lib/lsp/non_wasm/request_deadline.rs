@@ -0,0 +1,173 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Soft deadlines for requests.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use dupe::Dupe;
+use lsp_server::RequestId;
+use pyrefly_util::lock::Condvar;
+use pyrefly_util::lock::Mutex;
+use pyrefly_util::task_heap::CancellationHandle;
+
+/// The deadlines of the requests being handled, enforced by a single watchdog thread.
+///
+/// A request is [`start`](Self::start)ed when it is dequeued, with the cancellation handle of
+/// the transaction it runs against. If it has not [`finish`](Self::finish)ed once the timeout
+/// elapses, the watchdog cancels that handle and calls `on_expire` (typically to send a failure
+/// response), and `finish` then tells the handler not to respond, so that each request is
+/// answered exactly once. The watchdog can't interrupt a handler, so one running on the main
+/// loop still holds up later messages until it returns.
+pub struct RequestDeadlines {
+    timeout: Duration,
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    deadlines: Mutex<Deadlines>,
+    /// Notified when a deadline is added, or when the watchdog should stop.
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct Deadlines {
+    /// Requests still running, with when they expire and the handle that cancels their work.
+    running: HashMap<RequestId, (Instant, CancellationHandle)>,
+    /// Requests that expired before they finished, and were answered by the watchdog.
+    expired: HashSet<RequestId>,
+    stopped: bool,
+}
+
+impl RequestDeadlines {
+    pub fn new(timeout: Duration, on_expire: impl Fn(RequestId) + Send + 'static) -> Self {
+        let shared = Arc::new(Shared {
+            deadlines: Mutex::new(Deadlines::default()),
+            changed: Condvar::new(),
+        });
+        let watchdog = shared.dupe();
+        std::thread::spawn(move || watchdog.watch(on_expire));
+        Self { timeout, shared }
+    }
+
+    /// Start the clock for `request_id`, whose work is cancelled through `cancellation` if it
+    /// runs out of time. Restarts the clock of a request that is already running, e.g. one that
+    /// was moved from the main loop to a background queue.
+    pub fn start(&self, request_id: RequestId, cancellation: CancellationHandle) {
+        let mut deadlines = self.shared.deadlines.lock();
+        if deadlines.expired.contains(&request_id) {
+            return;
+        }
+        deadlines
+            .running
+            .insert(request_id, (Instant::now() + self.timeout, cancellation));
+        self.shared.changed.notify_one();
+    }
+
+    /// Stop the clock for `request_id` without answering it, e.g. because the rest of its work
+    /// moved to a background queue that will start it again.
+    pub fn pause(&self, request_id: &RequestId) {
+        self.shared.deadlines.lock().running.remove(request_id);
+    }
+
+    /// Stop the clock for `request_id`. Returns `false` if its deadline already expired and the
+    /// watchdog responded instead, in which case the caller must not respond.
+    pub fn finish(&self, request_id: &RequestId) -> bool {
+        let mut deadlines = self.shared.deadlines.lock();
+        deadlines.running.remove(request_id);
+        !deadlines.expired.remove(request_id)
+    }
+}
+
+impl Drop for RequestDeadlines {
+    fn drop(&mut self) {
+        self.shared.deadlines.lock().stopped = true;
+        self.shared.changed.notify_one();
+    }
+}
+
+impl Shared {
+    fn watch(&self, on_expire: impl Fn(RequestId)) {
+        let mut deadlines = self.deadlines.lock();
+        while !deadlines.stopped {
+            let now = Instant::now();
+            let expired = deadlines
+                .running
+                .iter()
+                .filter(|(_, (expires, _))| *expires <= now)
+                .map(|(request_id, _)| request_id.clone())
+                .collect::<Vec<_>>();
+            for request_id in expired {
+                if let Some((_, cancellation)) = deadlines.running.remove(&request_id) {
+                    cancellation.cancel();
+                }
+                deadlines.expired.insert(request_id.clone());
+                on_expire(request_id);
+            }
+            let next = deadlines
+                .running
+                .values()
+                .map(|(expires, _)| *expires)
+                .min();
+            deadlines = match next {
+                Some(next) => {
+                    self.changed
+                        .wait_timeout(deadlines, next.saturating_duration_since(now))
+                        .0
+                }
+                None => self.changed.wait(deadlines),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrefly_util::task_heap::TaskHeap;
+
+    use super::*;
+
+    fn cancellation_handle() -> CancellationHandle {
+        TaskHeap::<u32, ()>::new().get_cancellation_handle()
+    }
+
+    #[test]
+    fn test_slow_request_expires() {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let deadlines = RequestDeadlines::new(Duration::from_millis(10), move |request_id| {
+            sender.send(request_id).unwrap();
+        });
+        let request_id = RequestId::from(1);
+        let cancellation = cancellation_handle();
+        deadlines.start(request_id.clone(), cancellation.dupe());
+        // An artificially slow handler, which only stops once its work is cancelled.
+        while !cancellation.is_cancelled() {
+            std::thread::yield_now();
+        }
+        assert_eq!(receiver.recv().unwrap(), request_id);
+        assert!(!deadlines.finish(&request_id));
+    }
+
+    #[test]
+    fn test_fast_request_does_not_expire() {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let deadlines = RequestDeadlines::new(Duration::from_secs(60), move |request_id| {
+            sender.send(request_id).unwrap();
+        });
+        let request_id = RequestId::from(1);
+        let cancellation = cancellation_handle();
+        deadlines.start(request_id.clone(), cancellation.dupe());
+        assert!(deadlines.finish(&request_id));
+        drop(deadlines);
+        assert!(!cancellation.is_cancelled());
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,175 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::collections::HashMap;
+
+use lsp_types::SemanticToken;
+use lsp_types::SemanticTokensEdit;
+use lsp_types::Url;
+
+/// Each semantic token is encoded as five integers on the wire, and edit offsets count integers.
+const INTEGERS_PER_TOKEN: u32 = 5;
+
+/// Most documents whose tokens are kept. Entries are normally dropped when a document is closed;
+/// this bounds the cache if a client never sends the close.
+const MAX_CACHED_DOCUMENTS: usize = 256;
+
+/// The most recent semantic tokens sent for each document, so that
+/// `textDocument/semanticTokens/full/delta` can answer with an edit against them.
+#[derive(Default)]
+pub struct SemanticTokensCache {
+    next_result_id: u64,
+    documents: HashMap<Url, (u64, Vec<SemanticToken>)>,
+}
+
+impl SemanticTokensCache {
+    /// Record `tokens` as the latest result for `uri`, returning the result id to send with them.
+    /// If the cache is full, the document whose tokens were sent longest ago is evicted.
+    pub fn insert(&mut self, uri: Url, tokens: Vec<SemanticToken>) -> String {
+        self.next_result_id += 1;
+        let result_id = self.next_result_id;
+        self.documents.insert(uri, (result_id, tokens));
+        if self.documents.len() > MAX_CACHED_DOCUMENTS
+            && let Some(oldest) = self
+                .documents
+                .iter()
+                .min_by_key(|(_, (id, _))| *id)
+                .map(|(uri, _)| uri.clone())
+        {
+            self.documents.remove(&oldest);
+        }
+        result_id.to_string()
+    }
+
+    /// The tokens last sent for `uri`, if they were sent under `result_id`.
+    pub fn get(&self, uri: &Url, result_id: &str) -> Option<&[SemanticToken]> {
+        self.documents
+            .get(uri)
+            .filter(|(id, _)| id.to_string() == result_id)
+            .map(|(_, tokens)| tokens.as_slice())
+    }
+
+    pub fn remove(&mut self, uri: &Url) {
+        self.documents.remove(uri);
+    }
+}
+
+/// The edits that turn `previous` into `current`: a single replacement of everything between
+/// their common prefix and common suffix, or nothing if they are equal.
+pub fn semantic_tokens_edits(
+    previous: &[SemanticToken],
+    current: &[SemanticToken],
+) -> Vec<SemanticTokensEdit> {
+    let prefix = previous
+        .iter()
+        .zip(current)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = previous[prefix..]
+        .iter()
+        .rev()
+        .zip(current[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let deleted = previous.len() - prefix - suffix;
+    let inserted = &current[prefix..current.len() - suffix];
+    if deleted == 0 && inserted.is_empty() {
+        return Vec::new();
+    }
+    vec![SemanticTokensEdit {
+        start: prefix as u32 * INTEGERS_PER_TOKEN,
+        delete_count: deleted as u32 * INTEGERS_PER_TOKEN,
+        data: (!inserted.is_empty()).then(|| inserted.to_vec()),
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(length: u32) -> SemanticToken {
+        SemanticToken {
+            delta_line: 1,
+            delta_start: 0,
+            length,
+            token_type: 0,
+            token_modifiers_bitset: 0,
+        }
+    }
+
+    #[test]
+    fn test_equal_tokens_have_no_edits() {
+        let tokens = [token(1), token(2)];
+        assert_eq!(semantic_tokens_edits(&tokens, &tokens), Vec::new());
+    }
+
+    #[test]
+    fn test_replaces_changed_middle() {
+        let previous = [token(1), token(2), token(3)];
+        let current = [token(1), token(4), token(5), token(3)];
+        assert_eq!(
+            semantic_tokens_edits(&previous, &current),
+            vec![SemanticTokensEdit {
+                start: 5,
+                delete_count: 5,
+                data: Some(vec![token(4), token(5)]),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_deletes_and_appends() {
+        let previous = [token(1), token(2), token(3)];
+        assert_eq!(
+            semantic_tokens_edits(&previous, &[token(1), token(3)]),
+            vec![SemanticTokensEdit {
+                start: 5,
+                delete_count: 5,
+                data: None,
+            }]
+        );
+        assert_eq!(
+            semantic_tokens_edits(&previous[..1], &previous),
+            vec![SemanticTokensEdit {
+                start: 5,
+                delete_count: 0,
+                data: Some(vec![token(2), token(3)]),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_cache_matches_latest_result_id() {
+        let uri = Url::parse("file:///a.py").unwrap();
+        let mut cache = SemanticTokensCache::default();
+        let first = cache.insert(uri.clone(), vec![token(1)]);
+        let second = cache.insert(uri.clone(), vec![token(2)]);
+        assert_ne!(first, second);
+        assert_eq!(cache.get(&uri, &first), None);
+        assert_eq!(cache.get(&uri, &second), Some([token(2)].as_slice()));
+        cache.remove(&uri);
+        assert_eq!(cache.get(&uri, &second), None);
+    }
+
+    #[test]
+    fn test_cache_is_bounded() {
+        let uri = |i: usize| Url::parse(&format!("file:///{i}.py")).unwrap();
+        let mut cache = SemanticTokensCache::default();
+        let result_ids = (0..MAX_CACHED_DOCUMENTS + 10)
+            .map(|i| cache.insert(uri(i), vec![token(1)]))
+            .collect::<Vec<_>>();
+        assert_eq!(cache.documents.len(), MAX_CACHED_DOCUMENTS);
+        // The documents whose tokens were sent longest ago are the ones evicted.
+        assert_eq!(cache.get(&uri(0), &result_ids[0]), None);
+        assert_eq!(cache.get(&uri(9), &result_ids[9]), None);
+        let last = MAX_CACHED_DOCUMENTS + 9;
+        assert_eq!(
+            cache.get(&uri(last), &result_ids[last]),
+            Some([token(1)].as_slice())
+        );
+    }
+}
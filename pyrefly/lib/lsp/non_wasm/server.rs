@@ -5,6 +5,7 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::cmp::Reverse;
 use std::cmp::min;
 use std::collections::HashMap;
 use std::collections::HashSet;
@@ -67,8 +68,12 @@ use lsp_types::DocumentDiagnosticReportKind;
 use lsp_types::DocumentHighlight;
 use lsp_types::DocumentHighlightKind;
 use lsp_types::DocumentHighlightParams;
+use lsp_types::DocumentOnTypeFormattingOptions;
+use lsp_types::DocumentOnTypeFormattingParams;
 use lsp_types::DocumentSymbolParams;
 use lsp_types::DocumentSymbolResponse;
+use lsp_types::ExecuteCommandOptions;
+use lsp_types::ExecuteCommandParams;
 use lsp_types::FileEvent;
 use lsp_types::FileSystemWatcher;
 use lsp_types::FoldingRange;
@@ -88,9 +93,18 @@ use lsp_types::InlayHint;
 use lsp_types::InlayHintLabel;
 use lsp_types::InlayHintLabelPart;
 use lsp_types::InlayHintParams;
+use lsp_types::InlineValue;
+use lsp_types::InlineValueParams;
+use lsp_types::InlineValueText;
+use lsp_types::LinkedEditingRangeParams;
+use lsp_types::LinkedEditingRangeServerCapabilities;
+use lsp_types::LinkedEditingRanges;
 use lsp_types::Location;
 use lsp_types::MarkupContent;
 use lsp_types::MarkupKind;
+use lsp_types::MessageType;
+use lsp_types::Moniker;
+use lsp_types::MonikerParams;
 use lsp_types::NotebookCellLanguage;
 use lsp_types::NotebookDocumentFilterWithCells;
 use lsp_types::NotebookDocumentSyncFilter;
@@ -115,6 +129,9 @@ use lsp_types::RenameOptions;
 use lsp_types::RenameParams;
 use lsp_types::SaveOptions;
 use lsp_types::SemanticTokens;
+use lsp_types::SemanticTokensDelta;
+use lsp_types::SemanticTokensDeltaParams;
+use lsp_types::SemanticTokensFullDeltaResult;
 use lsp_types::SemanticTokensFullOptions;
 use lsp_types::SemanticTokensOptions;
 use lsp_types::SemanticTokensParams;
@@ -124,6 +141,7 @@ use lsp_types::SemanticTokensResult;
 use lsp_types::SemanticTokensServerCapabilities;
 use lsp_types::ServerCapabilities;
 use lsp_types::ServerInfo;
+use lsp_types::ShowMessageParams;
 use lsp_types::SignatureHelp;
 use lsp_types::SignatureHelpOptions;
 use lsp_types::SignatureHelpParams;
@@ -152,7 +170,9 @@ use lsp_types::WorkDoneProgressReport;
 use lsp_types::WorkspaceClientCapabilities;
 use lsp_types::WorkspaceEdit;
 use lsp_types::WorkspaceFoldersServerCapabilities;
+use lsp_types::WorkspaceLocation;
 use lsp_types::WorkspaceServerCapabilities;
+use lsp_types::WorkspaceSymbolOptions;
 use lsp_types::WorkspaceSymbolResponse;
 use lsp_types::notification::Cancel;
 use lsp_types::notification::DidChangeConfiguration;
@@ -167,6 +187,8 @@ use lsp_types::notification::Initialized;
 use lsp_types::notification::Notification as _;
 use lsp_types::notification::Progress;
 use lsp_types::notification::PublishDiagnostics;
+use lsp_types::notification::ShowMessage;
+use lsp_types::notification::TelemetryEvent as LspTelemetryEvent;
 use lsp_types::request::CallHierarchyIncomingCalls;
 use lsp_types::request::CallHierarchyOutgoingCalls;
 use lsp_types::request::CallHierarchyPrepare;
@@ -176,6 +198,7 @@ use lsp_types::request::Completion;
 use lsp_types::request::DocumentDiagnosticRequest;
 use lsp_types::request::DocumentHighlightRequest;
 use lsp_types::request::DocumentSymbolRequest;
+use lsp_types::request::ExecuteCommand;
 use lsp_types::request::FoldingRangeRequest;
 use lsp_types::request::GotoDeclaration;
 use lsp_types::request::GotoDefinition;
@@ -187,13 +210,19 @@ use lsp_types::request::GotoTypeDefinitionParams;
 use lsp_types::request::GotoTypeDefinitionResponse;
 use lsp_types::request::HoverRequest;
 use lsp_types::request::Initialize;
+use lsp_types::request::InlayHintRefreshRequest;
 use lsp_types::request::InlayHintRequest;
+use lsp_types::request::InlineValueRequest;
+use lsp_types::request::LinkedEditingRange;
+use lsp_types::request::MonikerRequest;
+use lsp_types::request::OnTypeFormatting;
 use lsp_types::request::PrepareRenameRequest;
 use lsp_types::request::References;
 use lsp_types::request::RegisterCapability;
 use lsp_types::request::Rename;
 use lsp_types::request::Request as _;
 use lsp_types::request::ResolveCompletionItem;
+use lsp_types::request::SemanticTokensFullDeltaRequest;
 use lsp_types::request::SemanticTokensFullRequest;
 use lsp_types::request::SemanticTokensRangeRequest;
 use lsp_types::request::SemanticTokensRefresh;
@@ -207,16 +236,21 @@ use lsp_types::request::WillRenameFiles;
 use lsp_types::request::WorkDoneProgressCreate;
 use lsp_types::request::WorkspaceConfiguration;
 use lsp_types::request::WorkspaceSymbolRequest;
+use lsp_types::request::WorkspaceSymbolResolve;
 use pyrefly_build::handle::Handle;
 use pyrefly_build::source_db::SourceDatabase;
 use pyrefly_config::config::ConfigSource;
 use pyrefly_config::error_kind::Severity;
 use pyrefly_python::PYTHON_EXTENSIONS;
 use pyrefly_python::ast::Ast;
+use pyrefly_python::ignore::find_comment_start_in_line;
+use pyrefly_python::keywords::is_valid_identifier;
+use pyrefly_python::module::Module;
 use pyrefly_python::module::TextRangeWithModule;
 use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::module_name::ModuleNameWithKind;
 use pyrefly_python::module_path::ModulePath;
+use pyrefly_python::module_path::ModulePathDetails;
 use pyrefly_util::absolutize::Absolutize as _;
 use pyrefly_util::arc_id::ArcId;
 use pyrefly_util::events::CategorizedEvents;
@@ -245,12 +279,16 @@ use pyrefly_util::telemetry::TelemetryServerState;
 use pyrefly_util::thread_pool::ThreadCount;
 use pyrefly_util::thread_pool::ThreadPool;
 use pyrefly_util::watch_pattern::WatchPattern;
+use rayon::prelude::*;
 use ruff_python_ast::name::Name;
+use ruff_source_file::PositionEncoding;
 use ruff_text_size::Ranged;
 use ruff_text_size::TextRange;
 use ruff_text_size::TextSize;
+use serde::Deserialize;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
+use serde_json::Map;
 use serde_json::Value;
 use starlark_map::Hashed;
 use starlark_map::small_map::SmallMap;
@@ -277,11 +315,18 @@ use crate::lsp::non_wasm::call_hierarchy::find_function_at_position_in_ast;
 use crate::lsp::non_wasm::call_hierarchy::prepare_call_hierarchy_item;
 use crate::lsp::non_wasm::call_hierarchy::transform_incoming_calls;
 use crate::lsp::non_wasm::call_hierarchy::transform_outgoing_calls;
+use crate::lsp::non_wasm::classified_references::ClassifiedLocation;
+use crate::lsp::non_wasm::classified_references::ClassifiedReferencesRequest;
 use crate::lsp::non_wasm::code_lens::runnable_lsp_code_lens;
 use crate::lsp::non_wasm::convert_module_package::convert_module_package_code_actions;
 use crate::lsp::non_wasm::document_symbols::flatten_to_symbol_information;
+use crate::lsp::non_wasm::explain_import::EXPLAIN_IMPORT_COMMAND;
+use crate::lsp::non_wasm::explain_import::explain_import;
+use crate::lsp::non_wasm::export_pysa::EXPORT_PYSA_COMMAND;
+use crate::lsp::non_wasm::export_pysa::export_pysa;
 use crate::lsp::non_wasm::external_provider::ExternalProvider;
 use crate::lsp::non_wasm::external_provider::compute_qualified_name;
+use crate::lsp::non_wasm::external_provider::external_references_in_encoding;
 use crate::lsp::non_wasm::lsp::apply_change_events;
 use crate::lsp::non_wasm::lsp::as_notification;
 use crate::lsp::non_wasm::lsp::as_request;
@@ -293,15 +338,28 @@ use crate::lsp::non_wasm::module_helpers::ThriftRemapper;
 use crate::lsp::non_wasm::module_helpers::handle_from_module_path;
 use crate::lsp::non_wasm::module_helpers::make_open_handle;
 use crate::lsp::non_wasm::module_helpers::module_info_to_uri;
+use crate::lsp::non_wasm::module_helpers::path_to_uri;
+use crate::lsp::non_wasm::moniker::monikers;
 use crate::lsp::non_wasm::move_symbol_new_file::move_symbol_to_new_file_code_action;
 use crate::lsp::non_wasm::mru::CompletionMru;
+use crate::lsp::non_wasm::on_type_formatting::ON_TYPE_FORMATTING_TRIGGER;
+use crate::lsp::non_wasm::on_type_formatting::newline_indentation;
+use crate::lsp::non_wasm::peek_definition::DEFAULT_PEEK_CONTEXT_LINES;
+use crate::lsp::non_wasm::peek_definition::PeekDefinition;
+use crate::lsp::non_wasm::peek_definition::PeekDefinitionParams;
+use crate::lsp::non_wasm::peek_definition::PeekDefinitionRequest;
+use crate::lsp::non_wasm::peek_definition::definition_snippets;
 use crate::lsp::non_wasm::protocol::Message;
 use crate::lsp::non_wasm::protocol::Request;
 use crate::lsp::non_wasm::protocol::Response;
 use crate::lsp::non_wasm::queue::HeavyTaskQueue;
 use crate::lsp::non_wasm::queue::LspEvent;
 use crate::lsp::non_wasm::queue::LspQueue;
+use crate::lsp::non_wasm::rename_module::ModuleRename;
+use crate::lsp::non_wasm::request_deadline::RequestDeadlines;
 use crate::lsp::non_wasm::safe_delete_file::safe_delete_file_code_action;
+use crate::lsp::non_wasm::semantic_tokens_delta::SemanticTokensCache;
+use crate::lsp::non_wasm::semantic_tokens_delta::semantic_tokens_edits;
 use crate::lsp::non_wasm::stdlib::should_show_stdlib_error;
 use crate::lsp::non_wasm::transaction_manager::TransactionManager;
 use crate::lsp::non_wasm::type_error_display_status::TypeErrorDisplayStatus;
@@ -340,6 +398,7 @@ use crate::state::lsp::FindDefinitionItemWithDocstring;
 use crate::state::lsp::FindPreference;
 use crate::state::lsp::ImportBehavior;
 use crate::state::lsp::LocalRefactorCodeAction;
+use crate::state::lsp::ReferenceKind;
 use crate::state::notebook::LspNotebook;
 use crate::state::require::Require;
 use crate::state::semantic_tokens::SemanticTokensLegends;
@@ -353,12 +412,15 @@ use crate::state::subscriber::PublishDiagnosticsSubscriber;
 use crate::state::subscriber::Subscriber;
 use crate::tsp::type_conversion::StdlibClasses;
 use crate::tsp::type_conversion::convert_type_with_resolvers;
+use crate::tsp::validation::TspRequestError;
 use crate::types::class::ClassDefIndex;
 use crate::types::class::ClassType;
 
 enum RequestError {
     Cancelled,
     Internal(String),
+    /// The request can't be done as asked; the message is shown to the user.
+    Rejected(String),
 }
 
 impl From<Cancelled> for RequestError {
@@ -457,9 +519,14 @@ pub trait TspInterface: Send + Sync + 'static {
     /// computing a type already demands its `Stdlib`, that transaction is warm,
     /// so the export lookups during conversion cannot hit a cold `get_stdlib`.
     ///
-    /// Returns `None` when the URI cannot be resolved, the position is invalid,
-    /// or no type information is available at that location.
-    fn type_at_position(&self, uri: &str, line: u32, character: u32) -> Option<tsp_types::Type>;
+    /// Returns `Ok(None)` when no type information is available at that
+    /// location, and an error when the file itself cannot be queried.
+    fn type_at_position(
+        &self,
+        uri: &str,
+        line: u32,
+        character: u32,
+    ) -> Result<Option<tsp_types::Type>, TspRequestError>;
 
     /// Return the computed (inferred) type for a node spanning the given range,
     /// converted to the TSP wire format.
@@ -485,7 +552,17 @@ pub trait TspInterface: Send + Sync + 'static {
         start_character: u32,
         end_line: u32,
         end_character: u32,
-    ) -> Option<tsp_types::Type>;
+    ) -> Result<Option<tsp_types::Type>, TspRequestError>;
+
+    /// As [`TspInterface::computed_type_at_range`] for every `(uri, range)` in
+    /// `nodes`, in order. All nodes are answered from a single transaction, and
+    /// each file is loaded once however many of its nodes are queried.
+    ///
+    /// Fails if any of the files cannot be queried.
+    fn computed_types_at_ranges(
+        &self,
+        nodes: &[(&str, tsp_types::Range)],
+    ) -> Result<Vec<Option<tsp_types::Type>>, TspRequestError>;
 
     /// As [`TspInterface::type_at_position`], but returns the contextually
     /// expected type — a call argument's parameter type, an annotated target's
@@ -496,7 +573,7 @@ pub trait TspInterface: Send + Sync + 'static {
         uri: &str,
         line: u32,
         character: u32,
-    ) -> Option<tsp_types::Type>;
+    ) -> Result<Option<tsp_types::Type>, TspRequestError>;
 
     /// Resolve a URI to a filesystem path.
     ///
@@ -704,6 +781,38 @@ fn diagnostic_markdown_support(params: &Value) -> bool {
         .unwrap_or(false)
 }
 
+/// Where a workspace symbol is defined, carried in its `data` until the client
+/// resolves its location, at which point the module is looked up again to build
+/// its uri and range.
+#[derive(Serialize, Deserialize)]
+struct WorkspaceSymbolData {
+    module: ModuleName,
+    path: ModulePathDetails,
+    start: u32,
+    end: u32,
+}
+
+/// Drop diagnostics that repeat an earlier one's range, severity, code and message, so that
+/// cascading errors reported at the same place show up once in the editor. The severity is part of
+/// the key so that a baselined copy downgraded to a hint never hides the error itself.
+fn dedup_diagnostics(diags: &mut Vec<Diagnostic>) {
+    let mut seen: HashMap<
+        (Range, Option<DiagnosticSeverity>, Option<NumberOrString>),
+        Vec<DiagnosticMessage>,
+    > = HashMap::new();
+    diags.retain(|diag| {
+        let messages = seen
+            .entry((diag.range, diag.severity, diag.code.clone()))
+            .or_default();
+        if messages.contains(&diag.message) {
+            false
+        } else {
+            messages.push(diag.message.clone());
+            true
+        }
+    });
+}
+
 /// Rewrite a diagnostic's plain-text message into a markdown
 /// message for clients that advertise `markupMessageSupport` (LSP 3.18)
 fn diagnostic_message_to_markdown(diagnostic: &mut Diagnostic) {
@@ -771,11 +880,143 @@ fn format_diagnostic_message_for_markdown(message: &str) -> String {
 
 #[cfg(test)]
 mod tests {
+    use dupe::Dupe;
     use lsp_types::CodeActionKind;
-
+    use lsp_types::Diagnostic;
+    use lsp_types::DiagnosticSeverity;
+    use lsp_types::NumberOrString;
+    use lsp_types::Position;
+    use lsp_types::Range;
+    use pyrefly_build::handle::Handle;
+    use pyrefly_python::module_name::ModuleName;
+    use pyrefly_python::module_path::ModulePath;
+    use pyrefly_python::sys_info::SysInfo;
+    use pyrefly_util::arc_id::ArcId;
+    use pyrefly_util::task_heap::TaskHeap;
+    use ruff_source_file::PositionEncoding;
+
+    use super::ProjectIndexingTasks;
     use super::SOURCE_FIX_ALL_PYREFLY;
+    use super::cap_project_handles;
+    use super::dedup_diagnostics;
     use super::format_diagnostic_message_for_markdown;
     use super::matches_fix_all_kind;
+    use crate::config::config::ConfigFile;
+    use crate::test::util::TestEnv;
+
+    fn diagnostic(line: u32, code: &str, message: &str) -> Diagnostic {
+        Diagnostic {
+            range: Range::new(Position::new(line, 0), Position::new(line, 1)),
+            code: Some(NumberOrString::String(code.to_owned())),
+            message: message.to_owned().into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_dedup_diagnostics_drops_identical_repeats() {
+        let mut diags = vec![
+            diagnostic(0, "bad-assignment", "first"),
+            diagnostic(0, "bad-assignment", "first"),
+            diagnostic(0, "bad-assignment", "second"),
+            diagnostic(0, "bad-return", "first"),
+            diagnostic(1, "bad-assignment", "first"),
+        ];
+        dedup_diagnostics(&mut diags);
+        let expected = vec![
+            diagnostic(0, "bad-assignment", "first"),
+            diagnostic(0, "bad-assignment", "second"),
+            diagnostic(0, "bad-return", "first"),
+            diagnostic(1, "bad-assignment", "first"),
+        ];
+        assert_eq!(diags, expected);
+    }
+
+    #[test]
+    fn test_dedup_diagnostics_keeps_one_copy_of_a_checked_error() {
+        let (state, handle) = TestEnv::one("main", "x: int = ''\n").to_state();
+        let errors = state
+            .transaction()
+            .get_errors(&[handle("main")])
+            .collect_errors()
+            .ordinary;
+        assert_eq!(errors.len(), 1);
+        let error = errors[0].to_diagnostic(PositionEncoding::Utf16);
+        // The same module checked under two handles reports the error twice, and a baseline
+        // matching it adds a hint that has to survive next to the error.
+        let mut baselined = error.clone();
+        baselined.severity = Some(DiagnosticSeverity::HINT);
+        let mut diags = vec![error.clone(), baselined.clone(), error.clone()];
+        dedup_diagnostics(&mut diags);
+        assert_eq!(diags, vec![error, baselined]);
+    }
+
+    #[test]
+    fn test_project_indexing_tasks_cancel_on_restart_and_config_change() {
+        let new_handle = || TaskHeap::<(), ()>::new().get_cancellation_handle();
+        let config = ArcId::new(ConfigFile::default());
+        let other_config = ArcId::new(ConfigFile::default());
+        let mut tasks = ProjectIndexingTasks::default();
+
+        // Restarting indexing for a config replaces the pass that is already running.
+        let first = new_handle();
+        let first_id = tasks.start(config.dupe());
+        assert!(tasks.track(&config, first_id, first.dupe()));
+        let second = new_handle();
+        let second_id = tasks.start(config.dupe());
+        assert!(tasks.track(&config, second_id, second.dupe()));
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+        // The replaced pass must stop before its next batch.
+        assert!(!tasks.track(&config, first_id, new_handle()));
+
+        // A replaced pass finishing late must not forget the newer one.
+        tasks.finish(&config, first_id);
+        let other = new_handle();
+        let other_id = tasks.start(other_config.dupe());
+        assert!(tasks.track(&other_config, other_id, other.dupe()));
+        tasks.cancel_all();
+        assert!(second.is_cancelled());
+        assert!(other.is_cancelled());
+        assert!(!tasks.is_running(&config, second_id));
+
+        // Once a pass finishes normally, a config change has nothing left to cancel.
+        let third = new_handle();
+        let third_id = tasks.start(config.dupe());
+        assert!(tasks.track(&config, third_id, third.dupe()));
+        tasks.finish(&config, third_id);
+        tasks.cancel_all();
+        assert!(!third.is_cancelled());
+    }
+
+    #[test]
+    fn test_cap_project_handles_prefers_files_near_open_documents() {
+        let handle = |path: &str| {
+            Handle::new(
+                ModuleName::from_str("m"),
+                ModulePath::filesystem(PathBuf::from(path)),
+                SysInfo::default(),
+            )
+        };
+        let handles = vec![
+            handle("/repo/other/a.py"),
+            handle("/repo/pkg/sub/b.py"),
+            handle("/repo/pkg/c.py"),
+            handle("/repo/other/d.py"),
+        ];
+        let open_paths = vec![PathBuf::from("/repo/pkg/sub/open.py")];
+
+        let capped = cap_project_handles(handles.clone(), &open_paths, 2);
+        assert_eq!(
+            capped,
+            vec![handle("/repo/pkg/sub/b.py"), handle("/repo/pkg/c.py")]
+        );
+        assert_eq!(
+            cap_project_handles(handles.clone(), &open_paths, 10),
+            handles
+        );
+        assert!(cap_project_handles(handles, &open_paths, 0).is_empty());
+    }
 
     #[test]
     fn test_format_diagnostic_message_for_markdown() {
@@ -843,15 +1084,31 @@ pub struct Server {
     lsp_queue: LspQueue,
     recheck_queue: HeavyTaskQueue,
     find_reference_queue: HeavyTaskQueue,
+    /// Potentially slow read-only requests run off the main loop, so that the main loop can
+    /// still process a `$/cancelRequest` for them. Each kind has its own queue, so that a slow
+    /// workspace symbol search does not hold up completion or semantic tokens.
+    completion_queue: HeavyTaskQueue,
+    semantic_tokens_queue: HeavyTaskQueue,
+    workspace_symbol_queue: HeavyTaskQueue,
     sourcedb_queue: HeavyTaskQueue,
     /// Any configs whose find cache should be invalidated.
     invalidated_source_dbs: Mutex<SmallSet<ArcId<Box<dyn SourceDatabase + 'static>>>>,
     /// Custom initialization options are provided via initialize_params.initializationOptions
     /// The type should match `LspConfig`
     initialize_params: InitializeParams,
+    /// The encoding of the `character` offsets in LSP positions, negotiated with the client
+    /// from `general.positionEncodings`.
+    position_encoding: PositionEncoding,
     indexing_mode: IndexingMode,
     workspace_indexing_limit: usize,
+    /// Number of project files checked and committed at a time when indexing a config.
+    project_indexing_batch_size: NonZeroUsize,
     build_system_blocking: bool,
+    /// How long to wait for further edits after a `didChange` before validating open files.
+    did_change_debounce: Duration,
+    /// How long a request may take, from when it is dequeued, before it is cancelled and
+    /// answered with an error, if at all.
+    request_deadlines: Option<RequestDeadlines>,
     state: State,
     /// This is a mapping from open notebook cells to the paths of the notebooks they belong to,
     /// which can be used to look up the notebook contents in `open_files`.
@@ -867,6 +1124,12 @@ pub struct Server {
     unsaved_file_tracker: UnsavedFileTracker,
     /// A set of configs where we have already indexed all the files within the config.
     indexed_configs: Mutex<HashSet<ArcId<ConfigFile>>>,
+    /// Project indexing passes that are still running, so they can be cancelled when the
+    /// config changes or a new pass for the same config starts.
+    project_indexing_tasks: Mutex<ProjectIndexingTasks>,
+    /// The config roots and `maxIndexedFiles` caps we have already warned about hitting, so
+    /// the warning is shown once per config rather than on every indexing pass.
+    max_indexed_files_warnings: Mutex<HashSet<(PathBuf, usize)>>,
     /// A set of workspaces where we have already performed best-effort indexing.
     /// The user might open vscode at the root of the filesystem, so workspace indexing is
     /// performed with best effort up to certain limit of user files. When the workspace changes,
@@ -881,6 +1144,8 @@ pub struct Server {
     uris_pending_close: Mutex<HashMap<String, usize>>,
     workspaces: Arc<Workspaces>,
     completion_mru: Mutex<CompletionMru>,
+    /// The last semantic tokens sent for each document, the baseline for delta requests.
+    semantic_tokens_cache: Mutex<SemanticTokensCache>,
     outgoing_request_id: AtomicI32,
     outgoing_requests: Mutex<HashMap<RequestId, Request>>,
     next_progress_token_id: AtomicUsize,
@@ -895,6 +1160,13 @@ pub struct Server {
     /// Whether to include comment section folding ranges (FoldingRangeKind::Region).
     /// Defaults to false.
     comment_folding_ranges: bool,
+    /// When set, the method and start time of every request still awaiting its response, so
+    /// that a `telemetry/event` with its duration can be sent once the response goes out. Set
+    /// via the `requestTimingTelemetry` initialization option.
+    request_start_times: Option<Mutex<HashMap<RequestId, (String, Instant)>>>,
+    /// The maximum number of results returned for a `workspace/symbol` request. Set via the
+    /// `workspaceSymbolLimit` initialization option.
+    workspace_symbol_limit: usize,
     /// During a recheck with a committable transaction, we stream diagnostics to the client
     /// as files are validated. This field tracks the snapshot of open files that are
     /// eligible for streaming.
@@ -1231,12 +1503,26 @@ fn client_augments_syntax_tokens(initialization_params: &InitializeParams) -> bo
         .unwrap_or(false)
 }
 
+/// Use UTF-8 offsets if the client supports them, since they are what we store natively, and
+/// otherwise fall back to the UTF-16 offsets every client must support.
+fn negotiate_position_encoding(initialization_params: &InitializeParams) -> PositionEncoding {
+    let supports_utf8 = initialization_params
+        .capabilities
+        .general
+        .as_ref()
+        .and_then(|general| general.position_encodings.as_ref())
+        .is_some_and(|encodings| encodings.contains(&PositionEncodingKind::UTF8));
+    if supports_utf8 {
+        PositionEncoding::Utf8
+    } else {
+        PositionEncoding::Utf16
+    }
+}
+
 pub fn capabilities(
     indexing_mode: IndexingMode,
     initialization_params: &InitializeParams,
 ) -> ServerCapabilitiesWithTypeHierarchy {
-    let augments_syntax_tokens = client_augments_syntax_tokens(initialization_params);
-
     // Parse syncNotebooks from initialization options, defaults to true
     let sync_notebooks = initialization_params
         .initialization_options
@@ -1252,7 +1538,10 @@ pub fn capabilities(
     };
 
     let base = ServerCapabilities {
-        position_encoding: Some(PositionEncodingKind::UTF16),
+        position_encoding: Some(match negotiate_position_encoding(initialization_params) {
+            PositionEncoding::Utf8 => PositionEncodingKind::UTF8,
+            _ => PositionEncodingKind::UTF16,
+        }),
         text_document_sync: Some(TextDocumentSyncCapability::Options(
             TextDocumentSyncOptions {
                 open_close: Some(true),
@@ -1266,7 +1555,13 @@ pub fn capabilities(
         definition_provider: Some(OneOf::Left(true)),
         declaration_provider: Some(DeclarationCapability::Simple(true)),
         type_definition_provider: Some(TypeDefinitionProviderCapability::Simple(true)),
-        implementation_provider: Some(ImplementationProviderCapability::Simple(true)),
+        // Implementations are found in subclasses anywhere in the project, so this needs indexing.
+        implementation_provider: match indexing_mode {
+            IndexingMode::None => None,
+            IndexingMode::LazyNonBlockingBackground | IndexingMode::LazyBlocking => {
+                Some(ImplementationProviderCapability::Simple(true))
+            }
+        },
         code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
             code_action_kinds: Some(vec![
                 CodeActionKind::QUICKFIX,
@@ -1277,6 +1572,7 @@ pub fn capabilities(
                 CodeActionKind::REFACTOR_INLINE,
                 CodeActionKind::SOURCE_FIX_ALL,
                 CodeActionKind::new(SOURCE_FIX_ALL_PYREFLY),
+                CodeActionKind::new(SOURCE_ADD_RETURN_ANNOTATIONS_PYREFLY),
             ]),
             ..Default::default()
         })),
@@ -1289,6 +1585,8 @@ pub fn capabilities(
             ..Default::default()
         }),
         document_highlight_provider: Some(OneOf::Left(true)),
+        linked_editing_range_provider: Some(LinkedEditingRangeServerCapabilities::Simple(true)),
+        moniker_provider: Some(OneOf::Left(true)),
         // Find references won't work properly if we don't know all the files.
         references_provider: match indexing_mode {
             IndexingMode::None => None,
@@ -1311,9 +1609,25 @@ pub fn capabilities(
         }),
         hover_provider: Some(HoverProviderCapability::Simple(true)),
         inlay_hint_provider: Some(OneOf::Left(true)),
+        inline_value_provider: Some(OneOf::Left(true)),
         document_symbol_provider: Some(OneOf::Left(true)),
-        workspace_symbol_provider: Some(OneOf::Left(true)),
+        workspace_symbol_provider: Some(OneOf::Right(WorkspaceSymbolOptions {
+            resolve_provider: Some(true),
+            ..Default::default()
+        })),
+        execute_command_provider: Some(ExecuteCommandOptions {
+            commands: vec![
+                EXPLAIN_IMPORT_COMMAND.to_owned(),
+                EXPORT_PYSA_COMMAND.to_owned(),
+                PRINT_INTERNAL_TYPE_COMMAND.to_owned(),
+            ],
+            work_done_progress_options: Default::default(),
+        }),
         folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+        document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+            first_trigger_character: ON_TYPE_FORMATTING_TRIGGER.to_owned(),
+            more_trigger_character: None,
+        }),
         // Call hierarchy needs indexing to find cross-file callers/callees
         call_hierarchy_provider: match indexing_mode {
             IndexingMode::None => None,
@@ -1321,26 +1635,17 @@ pub fn capabilities(
                 Some(CallHierarchyServerCapability::Simple(true))
             }
         },
-        semantic_tokens_provider: if augments_syntax_tokens {
-            // We currently only return partial tokens (e.g. no tokens for keywords right now).
-            // If the client doesn't support `augments_syntax_tokens` to fallback baseline
-            // syntax highlighting for tokens we don't provide, it will be a regression
-            // (e.g. users might lose keyword highlighting).
-            // Therefore, we should not produce semantic tokens if the client doesn't support `augments_syntax_tokens`.
-            // We now have an implementation path for a full semantic token stream that fills in
-            // syntax tokens, but we do not advertise that capability to non-augmenting clients yet.
-            // todo(kylei): enable semantic tokens to non-augmenting clients
-            Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
-                SemanticTokensOptions {
-                    legend: SemanticTokensLegends::lsp_semantic_token_legends(),
-                    full: Some(SemanticTokensFullOptions::Bool(true)),
-                    range: Some(true),
-                    ..Default::default()
-                },
-            ))
-        } else {
-            None
-        },
+        // Clients that don't augment their own syntax highlighting are sent keyword, operator
+        // and literal tokens too (see `include_syntax_tokens`), so pyrefly can be their only
+        // highlighter.
+        semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
+            SemanticTokensOptions {
+                legend: SemanticTokensLegends::lsp_semantic_token_legends(),
+                full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                range: Some(true),
+                ..Default::default()
+            },
+        )),
         workspace: Some(WorkspaceServerCapabilities {
             workspace_folders: Some(WorkspaceFoldersServerCapabilities {
                 supported: Some(true),
@@ -1393,11 +1698,110 @@ pub enum ProcessEvent {
 
 const PYTHON_SECTION: &str = "python";
 const SOURCE_FIX_ALL_PYREFLY: &str = "source.fixAll.pyrefly";
+const SOURCE_ADD_RETURN_ANNOTATIONS_PYREFLY: &str = "source.addReturnAnnotations.pyrefly";
+/// Default cap on `workspace/symbol` results, to keep responses small for short queries.
+const DEFAULT_WORKSPACE_SYMBOL_LIMIT: usize = 500;
+/// Default number of project files checked and committed at a time when indexing a config.
+const PROJECT_INDEXING_BATCH_SIZE: NonZeroUsize = NonZeroUsize::new(1000).unwrap();
+/// Debugging aid: returns the `Debug` form of pyrefly's internal type at a position.
+/// Only runs when `enableDebugCommands` is set for the file's workspace.
+const PRINT_INTERNAL_TYPE_COMMAND: &str = "pyrefly.printInternalType";
+
+/// The project indexing passes that are queued or running, keyed by config. A pass is
+/// registered when it is queued, so that a config change also cancels passes still waiting on
+/// the recheck queue. Each pass may span several transactions, so it records the cancellation
+/// handle of the one in flight.
+#[derive(Default)]
+struct ProjectIndexingTasks {
+    next_id: usize,
+    running: HashMap<ArcId<ConfigFile>, (usize, Option<CancellationHandle>)>,
+}
+
+impl ProjectIndexingTasks {
+    /// Register a pass for `config`, cancelling any pass that is already running for it.
+    /// Returns an id to pass to `track` and `finish`.
+    fn start(&mut self, config: ArcId<ConfigFile>) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        if let Some((_, Some(previous))) = self.running.insert(config, (id, None)) {
+            previous.cancel();
+        }
+        id
+    }
+
+    /// Record the transaction the pass `id` is about to run. Returns `false` if the pass has
+    /// been cancelled or replaced since it started, in which case it should stop.
+    fn track(
+        &mut self,
+        config: &ArcId<ConfigFile>,
+        id: usize,
+        cancellation: CancellationHandle,
+    ) -> bool {
+        match self.running.get_mut(config) {
+            Some((x, current)) if *x == id => {
+                *current = Some(cancellation);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn is_running(&self, config: &ArcId<ConfigFile>, id: usize) -> bool {
+        self.running.get(config).is_some_and(|(x, _)| *x == id)
+    }
+
+    /// Forget the pass `id` for `config`, unless it has already been replaced by a newer one.
+    fn finish(&mut self, config: &ArcId<ConfigFile>, id: usize) {
+        if self.is_running(config, id) {
+            self.running.remove(config);
+        }
+    }
+
+    fn cancel_all(&mut self) {
+        for (_, (_, cancellation)) in self.running.drain() {
+            if let Some(cancellation) = cancellation {
+                cancellation.cancel();
+            }
+        }
+    }
+}
+
+/// Keep at most `max` of `handles`, preferring the files that share the longest directory
+/// prefix with one of `open_paths`.
+fn cap_project_handles(
+    mut handles: Vec<Handle>,
+    open_paths: &[PathBuf],
+    max: usize,
+) -> Vec<Handle> {
+    if handles.len() <= max {
+        return handles;
+    }
+    handles.sort_by_cached_key(|handle| {
+        let path = handle.path().as_path();
+        let closeness = open_paths
+            .iter()
+            .map(|open| {
+                path.components()
+                    .zip(open.components())
+                    .take_while(|(a, b)| a == b)
+                    .count()
+            })
+            .max()
+            .unwrap_or(0);
+        Reverse(closeness)
+    });
+    handles.truncate(max);
+    handles
+}
 
 fn matches_fix_all_kind(kind: &CodeActionKind) -> bool {
     kind == &CodeActionKind::SOURCE_FIX_ALL || kind.as_str() == SOURCE_FIX_ALL_PYREFLY
 }
 
+fn matches_add_return_annotations_kind(kind: &CodeActionKind) -> bool {
+    kind == &CodeActionKind::SOURCE || kind.as_str() == SOURCE_ADD_RETURN_ANNOTATIONS_PYREFLY
+}
+
 struct TypeHierarchyTarget {
     def_index: ClassDefIndex,
     module_path: ModulePath,
@@ -1411,7 +1815,10 @@ pub fn lsp_loop(
     initialization: InitializeInfo,
     indexing_mode: IndexingMode,
     workspace_indexing_limit: usize,
+    project_indexing_batch_size: Option<NonZeroUsize>,
     build_system_blocking: bool,
+    did_change_debounce: Duration,
+    request_timeout: Option<Duration>,
     path_remapper: Option<PathRemapper>,
     thrift_remapper: Option<ThriftRemapper>,
     telemetry: &dyn Telemetry,
@@ -1432,7 +1839,10 @@ pub fn lsp_loop(
         initialization.supports_diagnostic_markdown,
         indexing_mode,
         workspace_indexing_limit,
+        project_indexing_batch_size,
         build_system_blocking,
+        did_change_debounce,
+        request_timeout,
         from,
         agent_session_id,
         agent_invocation_id,
@@ -1505,6 +1915,9 @@ pub fn lsp_loop(
                 info!("waiting for connection to close");
                 server.recheck_queue.stop();
                 server.find_reference_queue.stop();
+                server.completion_queue.stop();
+                server.semantic_tokens_queue.stop();
+                server.workspace_symbol_queue.stop();
                 server.sourcedb_queue.stop();
             })
             .expect("failed to spawn LSP event loop thread");
@@ -1516,6 +1929,21 @@ pub fn lsp_loop(
                 .find_reference_queue
                 .run_until_stopped(&server, telemetry);
         });
+        scope.spawn(|| {
+            server
+                .completion_queue
+                .run_until_stopped(&server, telemetry);
+        });
+        scope.spawn(|| {
+            server
+                .semantic_tokens_queue
+                .run_until_stopped(&server, telemetry);
+        });
+        scope.spawn(|| {
+            server
+                .workspace_symbol_queue
+                .run_until_stopped(&server, telemetry);
+        });
         scope.spawn(|| {
             server.sourcedb_queue.run_until_stopped(&server, telemetry);
         });
@@ -1643,6 +2071,9 @@ impl Server {
         for (path, diags) in diags {
             if let Some(url) = notebook_cell_urls.get(&path) {
                 self.publish_diagnostics_for_uri(url.clone(), diags, None, source)
+            } else if let Some(url) = self.unsaved_file_tracker.uri_for_path(&path) {
+                let version = version_info.get(&path).copied();
+                self.publish_diagnostics_for_uri(url, diags, version, source)
             } else {
                 let path = path.absolutize();
                 let version = version_info.get(&path).copied();
@@ -1990,10 +2421,16 @@ impl Server {
                 // Store cancellation handle so the recheck thread can cancel this
                 // request if it needs to commit.
                 let request_id_for_cancel = x.id.clone();
-                self.cancellation_handles.lock().insert(
-                    request_id_for_cancel.clone(),
-                    transaction.get_cancellation_handle(),
-                );
+                let cancellation_handle = transaction.get_cancellation_handle();
+                self.cancellation_handles
+                    .lock()
+                    .insert(request_id_for_cancel.clone(), cancellation_handle.dupe());
+                if let Some(deadlines) = &self.request_deadlines {
+                    deadlines.start(
+                        request_id_for_cancel.clone(),
+                        transaction.get_cancellation_handle(),
+                    );
+                }
 
                 // Set up immediate per-call telemetry for ad-hoc solves. Each solve event is
                 // logged the instant it completes rather than batched.
@@ -2014,6 +2451,11 @@ impl Server {
                     Some(&self.lsp_thread_pool),
                 );
                 info!("Handling non-canceled request {} ({})", x.method, &x.id);
+                if let Some(start_times) = &self.request_start_times {
+                    start_times
+                        .lock()
+                        .insert(x.id.clone(), (x.method.clone(), Instant::now()));
+                }
                 if let Some(params) = as_request::<GotoDefinition>(&x) {
                     if let Some(params) = self
                         .extract_request_params_or_send_err_response::<GotoDefinition>(
@@ -2095,18 +2537,23 @@ impl Server {
                     if let Some(params) = self
                         .extract_request_params_or_send_err_response::<Completion>(params, &x.id)
                     {
-                        match self.completion(&transaction, params) {
-                            Ok(response) => {
-                                self.send_response(new_response(x.id, Ok(response)));
-                            }
-                            Err(reason) => {
-                                self.send_response(new_response(
-                                    x.id,
-                                    Ok(None::<CompletionResponse>),
-                                ));
-                                telemetry_event.set_empty_response_reason(reason);
-                            }
-                        }
+                        let uri = params.text_document_position.text_document.uri.clone();
+                        self.async_read_request(
+                            &self.completion_queue,
+                            x.id,
+                            Completion::METHOD,
+                            Some(uri),
+                            telemetry_event.activity_key.clone(),
+                            move |server, transaction, _telemetry, telemetry_event| match server
+                                .completion(transaction, params)
+                            {
+                                Ok(response) => Ok(Some(response)),
+                                Err(reason) => {
+                                    telemetry_event.set_empty_response_reason(reason);
+                                    Ok(None)
+                                }
+                            },
+                        );
                     }
                 } else if let Some(params) = as_request::<ResolveCompletionItem>(&x) {
                     if let Some(params) = self
@@ -2132,6 +2579,51 @@ impl Server {
                         };
                         self.send_response(new_response(x.id, Ok(response)));
                     }
+                } else if let Some(params) = as_request::<LinkedEditingRange>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<LinkedEditingRange>(
+                            params, &x.id,
+                        )
+                    {
+                        let response = match self.linked_editing_range(&transaction, params) {
+                            Ok(response) => response,
+                            Err(reason) => {
+                                telemetry_event.set_empty_response_reason(reason);
+                                None
+                            }
+                        };
+                        self.send_response(new_response(x.id, Ok(response)));
+                    }
+                } else if let Some(params) = as_request::<MonikerRequest>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<MonikerRequest>(
+                            params, &x.id,
+                        )
+                    {
+                        let response = match self.moniker(&transaction, params) {
+                            Ok(response) => response,
+                            Err(reason) => {
+                                telemetry_event.set_empty_response_reason(reason);
+                                None
+                            }
+                        };
+                        self.send_response(new_response(x.id, Ok(response)));
+                    }
+                } else if let Some(params) = as_request::<PeekDefinitionRequest>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<PeekDefinitionRequest>(
+                            params, &x.id,
+                        )
+                    {
+                        let response = match self.peek_definition(&transaction, params) {
+                            Ok(response) => response,
+                            Err(reason) => {
+                                telemetry_event.set_empty_response_reason(reason);
+                                None
+                            }
+                        };
+                        self.send_response(new_response(x.id, Ok(response)));
+                    }
                 } else if let Some(params) = as_request::<References>(&x) {
                     if let Some(params) = self
                         .extract_request_params_or_send_err_response::<References>(params, &x.id)
@@ -2145,6 +2637,21 @@ impl Server {
                         self.send_response(new_response(x.id, Ok(None::<()>)));
                         telemetry_event.set_empty_response_reason(reason);
                     }
+                } else if let Some(params) = as_request::<ClassifiedReferencesRequest>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<ClassifiedReferencesRequest>(
+                            params, &x.id,
+                        )
+                        && let Err(reason) = self.classified_references(
+                            x.id.clone(),
+                            &transaction,
+                            params,
+                            telemetry_event.activity_key.clone(),
+                        )
+                    {
+                        self.send_response(new_response(x.id, Ok(None::<()>)));
+                        telemetry_event.set_empty_response_reason(reason);
+                    }
                 } else if let Some(params) = as_request::<PrepareRenameRequest>(&x) {
                     if let Some(params) = self
                         .extract_request_params_or_send_err_response::<PrepareRenameRequest>(
@@ -2164,37 +2671,53 @@ impl Server {
                     if let Some(params) =
                         self.extract_request_params_or_send_err_response::<Rename>(params, &x.id)
                     {
-                        // First check if rename is allowed via prepare_rename. If a rename is not allowed we
-                        // send back an error. Otherwise we continue with the rename operation.
-                        match self
-                            .prepare_rename(&transaction, params.text_document_position.clone())
-                        {
-                            Ok(Some(_range)) => {
-                                if let Err(reason) = self.rename(
-                                    x.id.clone(),
-                                    &transaction,
-                                    params,
-                                    telemetry_event.activity_key.clone(),
-                                ) {
+                        if !is_valid_identifier(&params.new_name) {
+                            self.send_response(Response {
+                                id: x.id,
+                                result: None,
+                                error: Some(ResponseError {
+                                    code: ErrorCode::InvalidParams as i32,
+                                    message: format!(
+                                        "`{}` is not a valid Python identifier",
+                                        params.new_name
+                                    ),
+                                    data: None,
+                                }),
+                            });
+                        } else {
+                            // First check if rename is allowed via prepare_rename. If a rename is not allowed we
+                            // send back an error. Otherwise we continue with the rename operation.
+                            match self
+                                .prepare_rename(&transaction, params.text_document_position.clone())
+                            {
+                                Ok(Some(_range)) => {
+                                    if let Err(reason) = self.rename(
+                                        x.id.clone(),
+                                        &transaction,
+                                        params,
+                                        telemetry_event.activity_key.clone(),
+                                    ) {
+                                        self.send_response(new_response(x.id, Ok(None::<()>)));
+                                        telemetry_event.set_empty_response_reason(reason);
+                                    }
+                                }
+                                Ok(None) => {
+                                    self.send_response(Response {
+                                        id: x.id,
+                                        result: None,
+                                        error: Some(ResponseError {
+                                            code: ErrorCode::InvalidRequest as i32,
+                                            message: "Third-party symbols cannot be renamed"
+                                                .to_owned(),
+                                            data: None,
+                                        }),
+                                    });
+                                }
+                                Err(reason) => {
                                     self.send_response(new_response(x.id, Ok(None::<()>)));
                                     telemetry_event.set_empty_response_reason(reason);
                                 }
                             }
-                            Ok(None) => {
-                                self.send_response(Response {
-                                    id: x.id,
-                                    result: None,
-                                    error: Some(ResponseError {
-                                        code: ErrorCode::InvalidRequest as i32,
-                                        message: "Third-party symbols cannot be renamed".to_owned(),
-                                        data: None,
-                                    }),
-                                });
-                            }
-                            Err(reason) => {
-                                self.send_response(new_response(x.id, Ok(None::<()>)));
-                                telemetry_event.set_empty_response_reason(reason);
-                            }
                         }
                     }
                 } else if let Some(params) = as_request::<SignatureHelpRequest>(&x) {
@@ -2240,6 +2763,21 @@ impl Server {
                         };
                         self.send_response(new_response(x.id, Ok(response)));
                     }
+                } else if let Some(params) = as_request::<InlineValueRequest>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<InlineValueRequest>(
+                            params, &x.id,
+                        )
+                    {
+                        let response = match self.inline_values(&transaction, params) {
+                            Ok(response) => response,
+                            Err(reason) => {
+                                telemetry_event.set_empty_response_reason(reason);
+                                None
+                            }
+                        };
+                        self.send_response(new_response(x.id, Ok(response)));
+                    }
                 } else if let Some(params) = as_request::<CodeLensRequest>(&x) {
                     if let Some(params) = self
                         .extract_request_params_or_send_err_response::<CodeLensRequest>(
@@ -2258,7 +2796,32 @@ impl Server {
                             params, &x.id,
                         )
                     {
-                        let response = match self.semantic_tokens_full(&transaction, params) {
+                        let uri = params.text_document.uri.clone();
+                        self.async_read_request(
+                            &self.semantic_tokens_queue,
+                            x.id,
+                            SemanticTokensFullRequest::METHOD,
+                            Some(uri),
+                            telemetry_event.activity_key.clone(),
+                            move |server, transaction, _telemetry, telemetry_event| match server
+                                .semantic_tokens_full(transaction, params)
+                            {
+                                Ok(response) => Ok(response),
+                                Err(reason) => {
+                                    telemetry_event.set_empty_response_reason(reason);
+                                    Ok(None)
+                                }
+                            },
+                        );
+                    }
+                } else if let Some(params) = as_request::<SemanticTokensFullDeltaRequest>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<SemanticTokensFullDeltaRequest>(
+                            params, &x.id,
+                        )
+                    {
+                        let response = match self.semantic_tokens_full_delta(&transaction, params)
+                        {
                             Ok(response) => response,
                             Err(reason) => {
                                 telemetry_event.set_empty_response_reason(reason);
@@ -2303,15 +2866,30 @@ impl Server {
                             params, &x.id,
                         )
                     {
-                        self.send_response(new_response(
+                        self.async_read_request(
+                            &self.workspace_symbol_queue,
                             x.id,
-                            Ok(WorkspaceSymbolResponse::Flat(self.workspace_symbols(
-                                &transaction,
-                                &params.query,
-                                telemetry,
-                                telemetry_event,
-                            )?)),
-                        ));
+                            WorkspaceSymbolRequest::METHOD,
+                            None,
+                            telemetry_event.activity_key.clone(),
+                            move |server, transaction, telemetry, telemetry_event| {
+                                Ok(Some(server.workspace_symbols(
+                                    transaction,
+                                    &params.query,
+                                    telemetry,
+                                    telemetry_event,
+                                )?))
+                            },
+                        );
+                    }
+                } else if let Some(params) = as_request::<WorkspaceSymbolResolve>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<WorkspaceSymbolResolve>(
+                            params, &x.id,
+                        )
+                    {
+                        let response = self.resolve_workspace_symbol(&transaction, params);
+                        self.send_response(new_response(x.id, Ok(response)));
                     }
                 } else if let Some(params) = as_request::<DocumentDiagnosticRequest>(&x) {
                     if let Some(params) = self
@@ -2369,6 +2947,17 @@ impl Server {
                             )),
                         ));
                     }
+                } else if let Some(params) = as_request::<ExecuteCommand>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<ExecuteCommand>(
+                            params, &x.id,
+                        )
+                    {
+                        self.send_response(new_response(
+                            x.id,
+                            self.execute_command(&transaction, params),
+                        ));
+                    }
                 } else if let Some(params) = as_request::<FoldingRangeRequest>(&x) {
                     if let Some(params) = self
                         .extract_request_params_or_send_err_response::<FoldingRangeRequest>(
@@ -2384,6 +2973,17 @@ impl Server {
                         };
                         self.send_response(new_response(x.id, Ok(result)));
                     }
+                } else if let Some(params) = as_request::<OnTypeFormatting>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<OnTypeFormatting>(
+                            params, &x.id,
+                        )
+                    {
+                        self.send_response(new_response(
+                            x.id,
+                            Ok(self.on_type_formatting(&transaction, params)),
+                        ));
+                    }
                 } else if let Some(params) = as_request::<CallHierarchyPrepare>(&x) {
                     if let Some(params) = self
                         .extract_request_params_or_send_err_response::<CallHierarchyPrepare>(
@@ -2517,10 +3117,27 @@ impl Server {
                     ));
                     info!("Unhandled request: {x:?}");
                 }
-                self.cancellation_handles
-                    .lock()
-                    .remove(&request_id_for_cancel);
-                ide_transaction_manager.save(transaction, telemetry_event);
+                {
+                    // A request handed to a background queue has registered its own handle,
+                    // which must stay in place so that it can still be cancelled.
+                    let mut cancellation_handles = self.cancellation_handles.lock();
+                    if cancellation_handles
+                        .get(&request_id_for_cancel)
+                        .is_some_and(|handle| handle.ptr_eq(&cancellation_handle))
+                    {
+                        cancellation_handles.remove(&request_id_for_cancel);
+                    }
+                }
+                // Requests answered above already stopped their clock. The others continue on
+                // a background queue, which starts it again when it picks them up.
+                if let Some(deadlines) = &self.request_deadlines {
+                    deadlines.pause(&request_id_for_cancel);
+                }
+                // A transaction cancelled by the recheck thread or an expired deadline would
+                // leave every later request that reuses it cancelled too, so drop it instead.
+                if !cancellation_handle.is_cancelled() {
+                    ide_transaction_manager.save(transaction, telemetry_event);
+                }
             }
         }
         Ok(ProcessEvent::Continue)
@@ -2533,7 +3150,10 @@ impl Server {
         diagnostic_markdown_support: bool,
         indexing_mode: IndexingMode,
         workspace_indexing_limit: usize,
+        project_indexing_batch_size: Option<NonZeroUsize>,
         build_system_blocking: bool,
+        did_change_debounce: Duration,
+        request_timeout: Option<Duration>,
         surface: Option<String>,
         agent_session_id: Option<String>,
         agent_invocation_id: Option<String>,
@@ -2568,6 +3188,21 @@ impl Server {
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
 
+        let request_start_times = initialize_params
+            .initialization_options
+            .as_ref()
+            .and_then(|opts| opts.get("requestTimingTelemetry"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+            .then(|| Mutex::new(HashMap::new()));
+
+        let workspace_symbol_limit = initialize_params
+            .initialization_options
+            .as_ref()
+            .and_then(|opts| opts.get("workspaceSymbolLimit"))
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_WORKSPACE_SYMBOL_LIMIT, |limit| limit as usize);
+
         let type_error_display_status_version = negotiate_type_error_display_status_version(
             initialize_params.initialization_options.as_ref(),
         );
@@ -2578,23 +3213,49 @@ impl Server {
             .as_ref()
             .and_then(|workspace| workspace.configuration)
             == Some(true);
+        let position_encoding = negotiate_position_encoding(&initialize_params);
+        let request_deadlines = request_timeout.map(|timeout| {
+            let sender = connection.sender.clone();
+            RequestDeadlines::new(timeout, move |request_id| {
+                let message = format!(
+                    "Request {request_id} exceeded the {}ms request timeout",
+                    timeout.as_millis()
+                );
+                tracing::warn!("{message}");
+                let _ = sender.send(Message::Response(Response::new_err(
+                    request_id,
+                    ErrorCode::RequestFailed as i32,
+                    message,
+                )));
+            })
+        });
         let s = Self {
             connection: ServerConnection(connection),
             lsp_queue,
             recheck_queue: HeavyTaskQueue::new(QueueName::RecheckQueue),
             find_reference_queue: HeavyTaskQueue::new(QueueName::FindReferenceQueue),
+            completion_queue: HeavyTaskQueue::new(QueueName::CompletionQueue),
+            semantic_tokens_queue: HeavyTaskQueue::new(QueueName::SemanticTokensQueue),
+            workspace_symbol_queue: HeavyTaskQueue::new(QueueName::WorkspaceSymbolQueue),
             sourcedb_queue: HeavyTaskQueue::new(QueueName::SourceDbQueue),
             invalidated_source_dbs: Mutex::new(SmallSet::new()),
             initialize_params,
+            position_encoding,
             indexing_mode,
             workspace_indexing_limit,
+            project_indexing_batch_size: project_indexing_batch_size
+                .unwrap_or(PROJECT_INDEXING_BATCH_SIZE),
             build_system_blocking,
+            did_change_debounce,
+            request_deadlines,
             state: State::new(config_finder, thread_count),
             open_notebook_cells: RwLock::new(HashMap::new()),
             open_files: RwLock::new(HashMap::new()),
             published_workspace_diagnostics: Mutex::new(HashMap::new()),
             unsaved_file_tracker: UnsavedFileTracker::new(),
             indexed_configs: Mutex::new(HashSet::new()),
+            project_indexing_tasks: Mutex::new(ProjectIndexingTasks::default()),
+            max_indexed_files_warnings: Mutex::new(HashSet::new()),
             indexed_workspaces: Mutex::new(HashSet::new()),
             cancellation_handles: Mutex::new(HashMap::new()),
             lsp_thread_pool: ThreadPool::new(ThreadCount::NumThreads(
@@ -2603,6 +3264,7 @@ impl Server {
             uris_pending_close: Mutex::new(HashMap::new()),
             workspaces,
             completion_mru: Mutex::new(CompletionMru::default()),
+            semantic_tokens_cache: Mutex::new(SemanticTokensCache::default()),
             outgoing_request_id: AtomicI32::new(1),
             outgoing_requests: Mutex::new(HashMap::new()),
             next_progress_token_id: AtomicUsize::new(1),
@@ -2614,6 +3276,8 @@ impl Server {
             agent_session_id,
             agent_invocation_id,
             comment_folding_ranges,
+            request_start_times,
+            workspace_symbol_limit,
             currently_streaming_diagnostics_for_handles: RwLock::new(None),
             diagnostic_markdown_support,
             type_error_display_status_version,
@@ -2702,8 +3366,35 @@ impl Server {
         Some(cwd.to_string_lossy().into_owned())
     }
 
-    fn send_response(&self, x: Response) {
-        self.connection.send(Message::Response(x))
+    /// Send the `telemetry/event` with the duration of request `id`, now that it has been
+    /// answered, if request timing telemetry is enabled.
+    fn finish_request_timing(&self, id: &RequestId) {
+        if let Some(start_times) = &self.request_start_times
+            && let Some((method, start)) = start_times.lock().remove(id)
+        {
+            let mut payload = Map::new();
+            payload.insert("method".to_owned(), Value::String(method));
+            payload.insert(
+                "durationMs".to_owned(),
+                Value::from(start.elapsed().as_millis() as u64),
+            );
+            self.connection.send(Message::Notification(
+                new_notification::<LspTelemetryEvent>(OneOf::Left(payload)),
+            ));
+        }
+    }
+
+    fn send_response(&self, x: Response) {
+        let id = x.id.clone();
+        // A request whose deadline expired was already answered with an error.
+        if self
+            .request_deadlines
+            .as_ref()
+            .is_none_or(|deadlines| deadlines.finish(&x.id))
+        {
+            self.connection.send(Message::Response(x))
+        }
+        self.finish_request_timing(&id);
     }
 
     fn send_request<T>(&self, params: T::Params)
@@ -2777,11 +3468,25 @@ impl Server {
             .collect()
     }
 
+    /// Whether `path` shows diagnostics at all, whether it is open or not: it has to be part of
+    /// the project (covered by `project-includes` and not by `project-excludes`), and type errors
+    /// can't be disabled for it.
+    fn shows_diagnostics_for(
+        config: &ConfigFile,
+        type_error_status: TypeErrorDisplayStatus,
+        path: &Path,
+    ) -> bool {
+        config.project_includes.covers(path)
+            && !config.project_excludes.covers(path)
+            && type_error_status.is_enabled()
+    }
+
     fn get_diag_if_shown(
         &self,
         e: &Error,
         open_files: &HashMap<PathBuf, Arc<LspFile>>,
         cell_uri: Option<&Url>, // If the file is a notebook, only show diagnostics for the matching cell
+        show_closed_files: bool, // Show errors in non-open files even outside workspace diagnostic mode
     ) -> Option<(PathBuf, Diagnostic)> {
         if let Some(path) = to_real_path(e.path()) {
             // When no file covers this, we'll get the default configured config which includes "everything"
@@ -2799,17 +3504,27 @@ impl Server {
             if is_python_stdlib_file(&path) && !should_show_stdlib_error {
                 return None;
             }
+            if !Self::shows_diagnostics_for(&config, type_error_status, &path) {
+                return None;
+            }
 
             // The resolved config's preset (Basic / Off / migrated) is
             // the single source of truth for which errors are silenced;
             // the `typeCheckingMode` IDE setting reaches us through the
             // resolver at config synthesis time, not per-diagnostic.
+            // The one exception is the client's `diagnosticSeverity` map,
+            // which only changes how an error is presented in the IDE.
+            let overridden;
+            let e = match self.workspaces.diagnostic_severity(&path, e.error_kind()) {
+                Some(Severity::Ignore) => return None,
+                Some(severity) => {
+                    overridden = e.with_severity(severity);
+                    &overridden
+                }
+                None => e,
+            };
 
-            if let Some(lsp_file) = open_files.get(&path)
-                && config.project_includes.covers(&path)
-                && !config.project_excludes.covers(&path)
-                && type_error_status.is_enabled()
-            {
+            if let Some(lsp_file) = open_files.get(&path) {
                 return match &**lsp_file {
                     LspFile::Notebook(notebook) => {
                         let error_cell = e.get_notebook_cell()?;
@@ -2819,10 +3534,15 @@ impl Server {
                         {
                             None
                         } else {
-                            Some((PathBuf::from(error_cell_uri.to_string()), e.to_diagnostic()))
+                            Some((
+                                PathBuf::from(error_cell_uri.to_string()),
+                                e.to_diagnostic(self.position_encoding),
+                            ))
                         }
                     }
-                    LspFile::Source(_) => Some((path.to_path_buf(), e.to_diagnostic())),
+                    LspFile::Source(_) => {
+                        Some((path.to_path_buf(), e.to_diagnostic(self.position_encoding)))
+                    }
                 };
             }
 
@@ -2832,12 +3552,10 @@ impl Server {
             // diagnostics (warnings, info) are restricted to open files.
             if open_files.get(&path).is_none()
                 && e.severity() >= Severity::Error
-                && self.workspaces.diagnostic_mode(&path) == DiagnosticMode::Workspace
-                && config.project_includes.covers(&path)
-                && !config.project_excludes.covers(&path)
-                && type_error_status.is_enabled()
+                && (show_closed_files
+                    || self.workspaces.diagnostic_mode(&path) == DiagnosticMode::Workspace)
             {
-                return Some((path.to_path_buf(), e.to_diagnostic()));
+                return Some((path.to_path_buf(), e.to_diagnostic(self.position_encoding)));
             }
         }
         None
@@ -2851,7 +3569,13 @@ impl Server {
         let uri = &params.text_document.uri;
         let handle = self.make_handle_if_enabled(uri, None).ok()?;
         let notebook_cell = self.maybe_get_code_cell_index(uri);
-        provide_type(transaction, &handle, params.positions, notebook_cell)
+        provide_type(
+            transaction,
+            &handle,
+            params.positions,
+            notebook_cell,
+            self.position_encoding,
+        )
     }
 
     fn type_error_display_status(&self, path: &Path) -> TypeErrorDisplayStatus {
@@ -2949,16 +3673,37 @@ impl Server {
             .unwrap_or(false)
     }
 
-    /// Helper to append all additional diagnostics (unreachable, unused parameters/imports/variables)
+    /// Helper to append all additional diagnostics (unreachable, unused parameters/imports/variables,
+    /// unused ignore comments)
     fn append_ide_specific_diagnostics(
         transaction: &Transaction<'_>,
         handle: &Handle,
+        position_encoding: PositionEncoding,
+        show_unused_ignores: bool,
         diagnostics: &mut Vec<Diagnostic>,
     ) {
-        Self::append_unreachable_diagnostics(transaction, handle, diagnostics);
-        Self::append_unused_parameter_diagnostics(transaction, handle, diagnostics);
-        Self::append_unused_import_diagnostics(transaction, handle, diagnostics);
-        Self::append_unused_variable_diagnostics(transaction, handle, diagnostics);
+        Self::append_unreachable_diagnostics(transaction, handle, position_encoding, diagnostics);
+        Self::append_unused_parameter_diagnostics(
+            transaction,
+            handle,
+            position_encoding,
+            diagnostics,
+        );
+        Self::append_unused_import_diagnostics(transaction, handle, position_encoding, diagnostics);
+        Self::append_unused_variable_diagnostics(
+            transaction,
+            handle,
+            position_encoding,
+            diagnostics,
+        );
+        if show_unused_ignores {
+            Self::append_unused_ignore_diagnostics(
+                transaction,
+                handle,
+                position_encoding,
+                diagnostics,
+            );
+        }
     }
 
     /// Publish diagnostics & send a semantic token refresh for the given handles
@@ -2967,6 +3712,18 @@ impl Server {
         transaction: &Transaction<'a>,
         handles: &[Handle],
         source: DiagnosticSource,
+    ) {
+        self.publish_for_handles_impl(transaction, handles, source, false)
+    }
+
+    /// Like `publish_for_handles`, but if `show_closed_files` is set, non-open files get
+    /// diagnostics even when they are not in a workspace with `DiagnosticMode::Workspace`.
+    fn publish_for_handles_impl<'a>(
+        &self,
+        transaction: &Transaction<'a>,
+        handles: &[Handle],
+        source: DiagnosticSource,
+        show_closed_files: bool,
     ) {
         let mut diags: SmallMap<PathBuf, Vec<Diagnostic>> = SmallMap::new();
         let open_files = self.open_files.read();
@@ -2990,8 +3747,9 @@ impl Server {
                         diags.insert(handle_path_buf, Vec::new());
                     }
                 }
-            } else if self.workspaces.diagnostic_mode(handle.path().as_path())
-                == DiagnosticMode::Workspace
+            } else if show_closed_files
+                || self.workspaces.diagnostic_mode(handle.path().as_path())
+                    == DiagnosticMode::Workspace
             {
                 // Non-open file in workspace diagnostic mode: create a diagnostic
                 // slot directly. No notebook handling needed since workspace
@@ -3002,36 +3760,56 @@ impl Server {
         let (normal_errors, baseline_errors) = transaction
             .get_errors(handles)
             .collect_lsp_errors_with_baselines();
-        for e in normal_errors {
-            if let Some((path, diag)) = self.get_diag_if_shown(&e, &open_files, None) {
-                diags.entry(path.to_owned()).or_default().push(diag);
-            }
-        }
-        for e in baseline_errors {
-            // Errors in open files that match a baseline file are downgraded to HINT.
-            if let Some((path, mut diag)) = self.get_diag_if_shown(&e, &open_files, None) {
-                if to_real_path(e.path()).is_some_and(|p| open_files.contains_key(&p)) {
+        // Deciding whether an error is shown looks up its config, which adds up over many open
+        // files, so it is done in parallel. Collecting keeps the errors in order, so the
+        // published diagnostics don't depend on scheduling.
+        let open_files_map: &HashMap<PathBuf, Arc<LspFile>> = &open_files;
+        let shown = normal_errors
+            .par_iter()
+            .map(|e| (e, false))
+            .chain(baseline_errors.par_iter().map(|e| (e, true)))
+            .filter_map(|(e, is_baseline)| {
+                let (path, mut diag) =
+                    self.get_diag_if_shown(e, open_files_map, None, show_closed_files)?;
+                // Errors in open files that match a baseline file are downgraded to HINT.
+                if is_baseline
+                    && to_real_path(e.path()).is_some_and(|p| open_files_map.contains_key(&p))
+                {
                     diag.severity = Some(DiagnosticSeverity::HINT);
                 }
-                diags.entry(path.to_owned()).or_default().push(diag);
-            }
+                Some((path, diag))
+            })
+            .collect::<Vec<_>>();
+        for (path, diag) in shown {
+            diags.entry(path).or_default().push(diag);
         }
         drop(open_files);
-        for (path, diagnostics) in diags.iter_mut() {
-            for diagnostic in diagnostics.iter_mut() {
-                diagnostic.data = serde_json::to_value(source).ok()
-            }
-            if notebook_cell_urls.contains_key(path) {
-                continue;
-            }
-            // Skip IDE-specific diagnostics (unreachable code, unused params, etc.)
-            // for non-open workspace files to reduce noise.
-            if !open_diag_paths.contains(path) {
-                continue;
-            }
-            let handle = make_open_handle(&self.state, path);
-            Self::append_ide_specific_diagnostics(transaction, &handle, diagnostics);
-        }
+        diags
+            .iter_mut()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .for_each(|(path, diagnostics)| {
+                dedup_diagnostics(diagnostics);
+                for diagnostic in diagnostics.iter_mut() {
+                    diagnostic.data = serde_json::to_value(source).ok()
+                }
+                if notebook_cell_urls.contains_key(path) {
+                    return;
+                }
+                // Skip IDE-specific diagnostics (unreachable code, unused params, etc.)
+                // for non-open workspace files to reduce noise.
+                if !open_diag_paths.contains(path) {
+                    return;
+                }
+                let handle = make_open_handle(&self.state, path);
+                Self::append_ide_specific_diagnostics(
+                    transaction,
+                    &handle,
+                    self.position_encoding,
+                    self.workspaces.should_show_unused_ignores(path),
+                    diagnostics,
+                );
+            });
         self.publish_diagnostics(
             diags,
             notebook_cell_urls,
@@ -3137,24 +3915,56 @@ impl Server {
                 IndexingMode::None => {}
                 IndexingMode::LazyNonBlockingBackground => {
                     if self.indexed_configs.lock().insert(config.dupe()) {
+                        let task_id = self.project_indexing_tasks.lock().start(config.dupe());
                         self.recheck_queue.queue_task(
                             TelemetryEventKind::PopulateProjectFiles,
                             Box::new(move |server, _telemetry, telemetry_event| {
-                                server
-                                    .populate_all_project_files_in_config(config, telemetry_event);
+                                server.populate_all_project_files_in_config(
+                                    config,
+                                    task_id,
+                                    telemetry_event,
+                                );
                             }),
                         );
                     }
                 }
                 IndexingMode::LazyBlocking => {
                     if self.indexed_configs.lock().insert(config.dupe()) {
-                        self.populate_all_project_files_in_config(config, telemetry);
+                        let task_id = self.project_indexing_tasks.lock().start(config.dupe());
+                        self.populate_all_project_files_in_config(config, task_id, telemetry);
                     }
                 }
             }
         }
     }
 
+    /// Forget which configs have been indexed and re-populate the project files of the configs
+    /// that now govern the open files. Must be queued after the config has been invalidated.
+    fn repopulate_project_files_after_config_change(&self) {
+        if self.indexing_mode == IndexingMode::None {
+            return;
+        }
+        // Stop any indexing pass that is still queued or running against the old config,
+        // otherwise the restarted pass below would have to wait behind it on the recheck queue.
+        self.project_indexing_tasks.lock().cancel_all();
+        let open_handles = self.get_open_file_handles();
+        self.recheck_queue.queue_task(
+            TelemetryEventKind::PopulateProjectFiles,
+            Box::new(move |server, _telemetry, telemetry_event| {
+                server.indexed_configs.lock().clear();
+                let config_finder = server.state.config_finder();
+                let configs: SmallSet<ArcId<ConfigFile>> = open_handles
+                    .iter()
+                    .map(|handle| config_finder.python_file(handle.module_kind(), handle.path()))
+                    .collect();
+                server.populate_project_files_for_configs(
+                    configs.into_iter().collect(),
+                    telemetry_event,
+                );
+            }),
+        );
+    }
+
     /// Populate project files for multiple configs
     ///
     /// Deduplication is handled by `indexed_configs`
@@ -3171,8 +3981,121 @@ impl Server {
                 continue;
             }
             if self.indexed_configs.lock().insert(config.dupe()) {
-                self.populate_all_project_files_in_config(config, telemetry);
+                let task_id = self.project_indexing_tasks.lock().start(config.dupe());
+                self.populate_all_project_files_in_config(config, task_id, telemetry);
+            }
+        }
+    }
+
+    fn execute_command(
+        &self,
+        transaction: &Transaction<'_>,
+        params: ExecuteCommandParams,
+    ) -> anyhow::Result<Option<Value>> {
+        match params.command.as_str() {
+            EXPORT_PYSA_COMMAND => {
+                let Some(Value::String(output_directory)) = params.arguments.first() else {
+                    return Err(anyhow::anyhow!(
+                        "`{EXPORT_PYSA_COMMAND}` expects an output directory as its only argument"
+                    ));
+                };
+                if self.indexing_mode == IndexingMode::None {
+                    return Err(anyhow::anyhow!(
+                        "`{EXPORT_PYSA_COMMAND}` requires project indexing, which is disabled"
+                    ));
+                }
+                let output_directory = PathBuf::from(output_directory);
+                let configs = self.indexed_configs.lock().iter().cloned().collect_vec();
+                let handles: SmallSet<Handle> = configs
+                    .iter()
+                    .flat_map(|config| self.project_handles_in_config(config))
+                    .collect();
+                if handles.is_empty() {
+                    return Err(anyhow::anyhow!(
+                        "`{EXPORT_PYSA_COMMAND}` found no indexed project files to export"
+                    ));
+                }
+                let handles = handles.into_iter().collect_vec();
+                self.recheck_queue.queue_task(
+                    TelemetryEventKind::ExportPysa,
+                    Box::new(move |server, _telemetry, _telemetry_event| {
+                        let (typ, message) =
+                            match export_pysa(&server.state, &handles, &output_directory) {
+                                Ok(()) => (
+                                    MessageType::INFO,
+                                    format!(
+                                        "Wrote Pysa report for {} files to {}",
+                                        handles.len(),
+                                        output_directory.display()
+                                    ),
+                                ),
+                                Err(e) => {
+                                    (MessageType::ERROR, format!("Pysa export failed: {e:#}"))
+                                }
+                            };
+                        server
+                            .connection
+                            .send(Message::Notification(new_notification::<ShowMessage>(
+                                ShowMessageParams { typ, message },
+                            )));
+                    }),
+                );
+                Ok(None)
             }
+            EXPLAIN_IMPORT_COMMAND => {
+                let [Value::String(module), Value::String(uri)] = params.arguments.as_slice()
+                else {
+                    return Err(anyhow::anyhow!(
+                        "`{EXPLAIN_IMPORT_COMMAND}` expects a module name and a source URI as its arguments"
+                    ));
+                };
+                let path = Url::parse(uri)
+                    .ok()
+                    .and_then(|uri| self.path_for_uri_or_notebook_cell(&uri))
+                    .ok_or_else(|| anyhow::anyhow!("Cannot resolve a file path for {uri}"))?;
+                let origin = ModulePath::filesystem(path);
+                let config = self.state.config_finder().python_file(
+                    ModuleNameWithKind::guaranteed(ModuleName::unknown()),
+                    &origin,
+                );
+                Ok(Some(Value::String(explain_import(
+                    &config,
+                    ModuleName::from_str(module),
+                    &origin,
+                ))))
+            }
+            PRINT_INTERNAL_TYPE_COMMAND => {
+                let Some(Ok(position_params)) = params
+                    .arguments
+                    .first()
+                    .map(|arg| serde_json::from_value::<TextDocumentPositionParams>(arg.clone()))
+                else {
+                    return Err(anyhow::anyhow!(
+                        "`{PRINT_INTERNAL_TYPE_COMMAND}` expects a text document position as its only argument"
+                    ));
+                };
+                let uri = &position_params.text_document.uri;
+                let enabled = self.path_for_uri_or_notebook_cell(uri).is_some_and(|path| {
+                    self.workspaces
+                        .get_with(path, |(_, workspace)| workspace.enable_debug_commands)
+                });
+                if !enabled {
+                    return Err(anyhow::anyhow!(
+                        "`{PRINT_INTERNAL_TYPE_COMMAND}` requires `enableDebugCommands` to be set"
+                    ));
+                }
+                let handle = self
+                    .make_handle_if_enabled(uri, None)
+                    .map_err(|_| anyhow::anyhow!("Cannot resolve a module for {uri}"))?;
+                let Some(info) = transaction.get_module_info(&handle) else {
+                    return Ok(None);
+                };
+                let position = self.from_lsp_position(uri, &info, position_params.position);
+                Ok(transaction
+                    .get_type_at(&handle, position)
+                    .map(|ty| Value::String(format!("{ty:#?}"))))
+            }
+            command => Err(anyhow::anyhow!("Unknown command: {command}")),
         }
     }
 
@@ -3297,49 +4220,118 @@ impl Server {
         );
     }
 
-    /// Certain IDE features (e.g. find-references) require us to know the dependency graph of the
-    /// entire project to work. This blocking function should be called when we know that a project
-    /// file is opened and if we intend to provide features like find-references, and should be
-    /// called when config changes (currently this is a TODO).
-    fn populate_all_project_files_in_config(
-        &self,
-        config: ArcId<ConfigFile>,
-        telemetry: &mut TelemetryEvent,
-    ) {
+    /// Handles for every file matched by `config`'s project globs that `config` is
+    /// responsible for, i.e. excluding files claimed by a nested config.
+    fn project_handles_in_config(&self, config: &ArcId<ConfigFile>) -> Vec<Handle> {
         let unknown = ModuleName::unknown();
-
-        info!("Populating all files in the config ({:?}).", config.source);
-
-        let project_path_blobs = config.get_filtered_globs(None);
         let mut handles = Vec::new();
-        if let Ok(paths) = project_path_blobs.files_iter() {
+        if let Ok(paths) = config.get_filtered_globs(None).files_iter() {
             for path in paths {
                 let module_path = ModulePath::filesystem(path.clone());
                 let path_config = self
                     .state
                     .config_finder()
                     .python_file(ModuleNameWithKind::guaranteed(unknown), &module_path);
-                if config != path_config {
+                if *config != path_config {
                     continue;
                 }
                 handles.push(handle_from_module_path(&self.state, module_path));
             }
         }
+        handles
+    }
+
+    /// Certain IDE features (e.g. find-references) require us to know the dependency graph of the
+    /// entire project to work. This blocking function should be called when we know that a project
+    /// file is opened and if we intend to provide features like find-references, and is called
+    /// again when config changes (see `repopulate_project_files_after_config_change`).
+    ///
+    /// Files are checked and committed in batches, so IDE features light up incrementally on
+    /// large projects. `task_id` is the pass registered for `config` in `project_indexing_tasks`
+    /// when it was queued; a cancelled batch is dropped without committing and the remaining
+    /// batches are skipped.
+    fn populate_all_project_files_in_config(
+        &self,
+        config: ArcId<ConfigFile>,
+        task_id: usize,
+        telemetry: &mut TelemetryEvent,
+    ) {
+        info!("Populating all files in the config ({:?}).", config.source);
+
+        let mut handles = self.project_handles_in_config(&config);
+        if let Some(max_indexed_files) = config.max_indexed_files
+            && handles.len() > max_indexed_files
+        {
+            let open_paths = self.open_files.read().keys().cloned().collect_vec();
+            let total = handles.len();
+            handles = cap_project_handles(handles, &open_paths, max_indexed_files);
+            info!("Indexing {max_indexed_files} of {total} files in the config (maxIndexedFiles).");
+            let root = config.source.root().map(Path::to_owned).unwrap_or_default();
+            if self
+                .max_indexed_files_warnings
+                .lock()
+                .insert((root, max_indexed_files))
+            {
+                self.connection
+                    .send(Message::Notification(new_notification::<ShowMessage>(
+                        ShowMessageParams {
+                            typ: MessageType::WARNING,
+                            message: format!(
+                                "Pyrefly indexed only {max_indexed_files} of {total} project files \
+                                 (`maxIndexedFiles`), so find-references and other project-wide \
+                                 features may be incomplete."
+                            ),
+                        },
+                    )));
+            }
+        }
 
         info!("Prepare to check {} files.", handles.len());
-        let mut transaction = self
-            .state
-            .new_committable_transaction(Require::Exports, None);
         let validate_start = Instant::now();
-        transaction.as_mut().run(&handles, Require::Indexing, None);
-        telemetry.set_validate_duration(validate_start.elapsed());
-        self.state.commit_transaction(transaction, Some(telemetry));
+        // Always run at least one batch, so that an empty project still commits and notifies.
+        let batches = if handles.is_empty() {
+            vec![&handles[..]]
+        } else {
+            handles
+                .chunks(self.project_indexing_batch_size.get())
+                .collect()
+        };
+        for batch in batches {
+            let mut transaction = self
+                .state
+                .new_committable_transaction(Require::Exports, None);
+            let cancellation = transaction.as_ref().get_cancellation_handle();
+            if !self
+                .project_indexing_tasks
+                .lock()
+                .track(&config, task_id, cancellation.dupe())
+            {
+                break;
+            }
+            transaction.as_mut().run(batch, Require::Indexing, None);
+            if cancellation.is_cancelled() {
+                break;
+            }
+            self.state
+                .commit_transaction(transaction, Some(&mut *telemetry));
 
-        // After committing project population, send RecheckFinished to
-        // the main event loop of the server. As a result, the server can do a revalidation of
-        // all the in-memory files based on the fresh main State as soon as possible.
-        info!("Populated all files in the project path, prepare to recheck open files.");
-        let _ = self.lsp_queue.send(LspEvent::RecheckFinished);
+            // After committing each batch, send RecheckFinished to the main event loop of the
+            // server. As a result, the server can do a revalidation of all the in-memory files
+            // based on the fresh main State as soon as possible.
+            let _ = self.lsp_queue.send(LspEvent::RecheckFinished);
+        }
+        telemetry.set_validate_duration(validate_start.elapsed());
+        let mut tasks = self.project_indexing_tasks.lock();
+        if tasks.is_running(&config, task_id) {
+            tasks.finish(&config, task_id);
+            info!("Populated all files in the project path, prepare to recheck open files.");
+        } else {
+            telemetry.canceled = true;
+            info!(
+                "Cancelled populating files in the config ({:?}).",
+                config.source
+            );
+        }
     }
 
     fn populate_all_workspaces_files(
@@ -3522,10 +4514,75 @@ impl Server {
 
     fn did_save(&self, url: Url) {
         if let Some(path) = self.path_for_uri(&url) {
+            let publish_dependents = self
+                .workspaces
+                .should_publish_dependent_diagnostics_on_save(&path);
+            let saved_path = path.clone();
             self.invalidate(TelemetryEventKind::InvalidateDisk, None, move |t| {
-                t.invalidate_disk(&[path])
-            })
+                t.invalidate_disk(&[saved_path])
+            });
+            if publish_dependents {
+                // The recheck queue runs tasks in order, so this sees the state committed by
+                // the invalidation above.
+                self.recheck_queue.queue_task(
+                    TelemetryEventKind::PublishDependentDiagnostics,
+                    Box::new(move |server, _telemetry, telemetry_event| {
+                        server.publish_dependent_diagnostics(&path, telemetry_event)
+                    }),
+                );
+            }
+        }
+    }
+
+    /// Publish diagnostics for the non-open files that depend on the saved file at `path`,
+    /// limited to files covered by the same config. Files whose diagnostics are unchanged, or
+    /// that have no diagnostics now and had none published before, are skipped by
+    /// `should_publish_diagnostics`, so this doesn't flood the client with empty publishes.
+    ///
+    /// Closed dependents may only have been loaded to `Require::Exports`, which computes no
+    /// errors, so they are checked at `Require::Errors` before publishing.
+    fn publish_dependent_diagnostics(&self, path: &Path, telemetry_event: &mut TelemetryEvent) {
+        let module_path = ModulePath::filesystem(path.to_path_buf());
+        let config_finder = self.state.config_finder();
+        let unknown = ModuleNameWithKind::guaranteed(ModuleName::unknown());
+        let config = config_finder.python_file(unknown, &module_path);
+        let saved = handle_from_module_path(&self.state, module_path);
+        let dependents: Vec<Handle> = {
+            let transaction = self.state.transaction();
+            let open_files = self.open_files.read();
+            transaction
+                .get_transitive_rdeps(saved.dupe())
+                .into_iter()
+                .filter(|handle| {
+                    *handle != saved
+                        && !handle.path().is_memory()
+                        && !open_files.contains_key(handle.path().as_path())
+                        && config_finder.python_file(unknown, handle.path()) == config
+                })
+                .collect()
+        };
+        if dependents.is_empty() {
+            return;
         }
+        info!(
+            "Publishing diagnostics for {} dependents of saved file {}.",
+            dependents.len(),
+            path.display()
+        );
+        let mut transaction = self
+            .state
+            .new_committable_transaction(Require::Exports, None);
+        let validate_start = Instant::now();
+        transaction.as_mut().run(&dependents, Require::Errors, None);
+        telemetry_event.set_validate_duration(validate_start.elapsed());
+        self.publish_for_handles_impl(
+            transaction.as_ref(),
+            &dependents,
+            DiagnosticSource::CommittingTransaction,
+            true,
+        );
+        self.state
+            .commit_transaction(transaction, Some(telemetry_event));
     }
 
     fn did_open<'a>(
@@ -3622,11 +4679,18 @@ impl Server {
         *original = Arc::new(LspFile::from_source(apply_change_events(
             original.get_string(),
             params.content_changes,
+            self.position_encoding,
         )));
         drop(lock);
         // Update version_info only after the mutation has fully succeeded.
         self.version_info.lock().insert(file_path.clone(), version);
-        if !subsequent_mutation {
+        // Fast typing produces a `didChange` per keystroke. Give the next edit a short window
+        // to arrive so a burst of edits only pays for one validation of the final buffer.
+        if !subsequent_mutation
+            && !self
+                .lsp_queue
+                .wait_for_subsequent_mutation(self.did_change_debounce)
+        {
             info!(
                 "File {} changed, prepare to validate open files.",
                 file_path.display()
@@ -3775,7 +4839,8 @@ impl Server {
                         .iter()
                         .filter_map(|v| serde_json::from_value(v.clone()).ok())
                         .collect();
-                    let new_text = apply_change_events(original_text, content_changes);
+                    let new_text =
+                        apply_change_events(original_text, content_changes, self.position_encoding);
                     cell_content_map.insert(cell_uri, new_text);
                 }
             }
@@ -3863,6 +4928,11 @@ impl Server {
         });
 
         let should_requery_build_system = should_requery_build_system(&events);
+        let config_changed = events.iter().any(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| ConfigFile::CONFIG_FILE_NAMES.contains(&name))
+        });
 
         // Rewatch files if necessary (config changed, files added/removed, etc.)
         if Self::should_rewatch(&events) {
@@ -3887,6 +4957,13 @@ impl Server {
             },
         );
 
+        // `invalidate_events` re-reads changed configs, but the project files indexed under the
+        // old configs (and their search paths) are stale. This runs after the invalidation above
+        // because both tasks go through the recheck queue.
+        if config_changed {
+            self.repopulate_project_files_after_config_change();
+        }
+
         // If a non-Python, non-config file was changed, then try rebuilding build systems.
         // If no build system file was changed, then we should just not do anything. If
         // a build system file was changed, then the change should take effect soon.
@@ -3905,10 +4982,11 @@ impl Server {
         let Some(path) = self.path_for_uri(&url) else {
             return;
         };
+        self.semantic_tokens_cache.lock().remove(&url);
         let version = self
             .version_info
             .lock()
-            .remove(&path)
+            .get(&path)
             .map(|version| version + 1);
         let mut open_files = self.open_files.write();
         let Entry::Occupied(entry) = open_files.entry(path.clone()) else {
@@ -3925,6 +5003,7 @@ impl Server {
                             version,
                             DiagnosticSource::DidClose,
                         );
+                        self.semantic_tokens_cache.lock().remove(&cell);
                         self.open_notebook_cells.write().remove(&cell);
                     }
                     entry.remove();
@@ -3957,6 +5036,9 @@ impl Server {
             },
         }
         drop(open_files);
+        // Only forget the version once the file is really closed: a close of the wrong kind
+        // returns above and leaves the file open.
+        self.version_info.lock().remove(&path);
         self.unsaved_file_tracker.forget_uri_path(&url);
         self.queue_source_db_rebuild_and_recheck(telemetry, telemetry_event, false);
         self.recheck_queue.queue_task(
@@ -4016,14 +5098,10 @@ impl Server {
     }
 
     fn did_change_configuration<'a>(&'a self, params: DidChangeConfigurationParams) {
-        if let Some(workspace) = &self.initialize_params.capabilities.workspace
-            && workspace.configuration == Some(true)
-        {
-            self.request_settings_for_all_workspaces();
-            return;
-        }
-
+        // Some clients push settings in the notification even though they also support
+        // `workspace/configuration`, so apply any pushed settings before pulling.
         let mut modified = false;
+        let max_indexed_files = self.workspaces.max_indexed_files_settings();
         if let Some(python) = params.settings.get(PYTHON_SECTION) {
             self.workspaces
                 .apply_client_configuration(&mut modified, &None, python.clone());
@@ -4031,6 +5109,32 @@ impl Server {
 
         if modified {
             self.invalidate_config_and_validate_in_memory();
+            self.refresh_inlay_hints();
+            if self.workspaces.max_indexed_files_settings() != max_indexed_files {
+                self.repopulate_project_files_after_config_change();
+            }
+        }
+
+        if let Some(workspace) = &self.initialize_params.capabilities.workspace
+            && workspace.configuration == Some(true)
+        {
+            self.request_settings_for_all_workspaces();
+        }
+    }
+
+    /// Ask the client to re-request inlay hints, which it caches per document, so that
+    /// changes to the inlay hint settings apply without reopening files.
+    fn refresh_inlay_hints(&self) {
+        if self
+            .initialize_params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|w| w.inlay_hint.as_ref())
+            .and_then(|ih| ih.refresh_support)
+            .unwrap_or(false)
+        {
+            self.send_request::<InlayHintRefreshRequest>(());
         }
     }
 
@@ -4046,6 +5150,7 @@ impl Server {
             .swap(false, Ordering::Relaxed);
 
         let mut modified = false;
+        let max_indexed_files = self.workspaces.max_indexed_files_settings();
         for (i, id) in request.items.iter().enumerate() {
             if let Some(value) = response.get(i) {
                 self.workspaces.apply_client_configuration(
@@ -4062,6 +5167,11 @@ impl Server {
 
         if modified {
             self.invalidate_config_and_validate_in_memory();
+            self.refresh_inlay_hints();
+            // Indexed configs carry the cap, so index again under the new one.
+            if self.workspaces.max_indexed_files_settings() != max_indexed_files {
+                self.repopulate_project_files_after_config_change();
+            }
         }
 
         // Sync workspace diagnostics with the current diagnostic mode.
@@ -4277,10 +5387,16 @@ impl Server {
         params: GotoImplementationParams,
         activity_key: Option<ActivityKey>,
     ) -> Result<(), EmptyResponseReason> {
+        // The provider isn't advertised without indexing, since implementations are found in
+        // subclasses anywhere in the project.
+        if self.indexing_mode == IndexingMode::None {
+            return Err(EmptyResponseReason::MethodDisabled);
+        }
         let uri = &params.text_document_position_params.text_document.uri;
         let handle = self.make_handle_if_enabled(uri, Some(GotoImplementation::METHOD))?;
         let path_remapper = self.path_remapper.clone();
         let open_notebooks = self.snapshot_open_notebooks();
+        let position_encoding = self.position_encoding;
         self.async_find_from_definition_helper(
             request_id,
             transaction,
@@ -4303,10 +5419,15 @@ impl Server {
                 // find_global_implementations_from_definition returns Vec<TextRangeWithModule>
                 // but we need to return Vec<(ModuleInfo, Vec<TextRange>)> to match the helper's
                 // expected format. Group implementations by module while preserving order.
-                let implementations = transaction.find_global_implementations_from_definition(
+                let mut implementations = transaction.find_global_implementations_from_definition(
                     *handle.sys_info(),
-                    TextRangeWithModule::new(module, definition_range),
+                    TextRangeWithModule::new(module.dupe(), definition_range),
                 )?;
+                // Nothing overrides the symbol (e.g. it isn't a method), so behave like
+                // go-to-definition.
+                if implementations.is_empty() {
+                    implementations.push(TextRangeWithModule::new(module, definition_range));
+                }
 
                 // Group consecutive implementations by module, preserving the sorted order
                 let mut grouped: Vec<(ModuleInfo, Vec<TextRange>)> = Vec::new();
@@ -4336,7 +5457,7 @@ impl Server {
                             }
                             lsp_targets.push(Location {
                                 uri: uri.clone(),
-                                range: info.to_lsp_range(range),
+                                range: info.to_lsp_range(range, position_encoding),
                             });
                         }
                     }
@@ -4366,6 +5487,9 @@ impl Server {
         let complete_function_parens = lsp_config
             .and_then(|c| c.complete_function_parens)
             .unwrap_or(false);
+        let complete_function_params = lsp_config
+            .and_then(|c| c.complete_function_params)
+            .unwrap_or(false);
         let auto_import = lsp_config
             .and_then(|c| c.auto_import_completions)
             .unwrap_or(true);
@@ -4375,7 +5499,9 @@ impl Server {
             supports_snippet_completions: supports_snippet_completions(
                 &self.initialize_params.capabilities,
             ),
+            complete_function_params,
             auto_import,
+            position_encoding: self.position_encoding,
         };
         let mru_snapshot = self.completion_mru.lock().clone();
         let info = transaction
@@ -4422,6 +5548,8 @@ impl Server {
         let allow_quickfix = only_kinds
             .is_none_or(|kinds| kinds.iter().any(|kind| kind == &CodeActionKind::QUICKFIX));
         let allow_fix_all = only_kinds.is_none_or(|kinds| kinds.iter().any(matches_fix_all_kind));
+        let allow_add_return_annotations =
+            only_kinds.is_none_or(|kinds| kinds.iter().any(matches_add_return_annotations_kind));
         let allow_refactor = only_kinds.is_none_or(|kinds| {
             kinds
                 .iter()
@@ -4544,6 +5672,39 @@ impl Server {
         {
             return Ok((!actions.is_empty()).then_some(actions));
         }
+        // Inferring every return type in the file is expensive too, so it is also skipped above.
+        if allow_add_return_annotations {
+            let start = Instant::now();
+            if let Some(edits) =
+                transaction.add_missing_return_annotations_edits(&handle, import_format)
+            {
+                let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+                for (module, edit_range, new_text) in edits {
+                    let Some(lsp_location) = self.to_lsp_location(&TextRangeWithModule {
+                        module,
+                        range: edit_range,
+                    }) else {
+                        continue;
+                    };
+                    changes.entry(lsp_location.uri).or_default().push(TextEdit {
+                        range: lsp_location.range,
+                        new_text,
+                    });
+                }
+                if !changes.is_empty() {
+                    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                        title: "Add all missing return type annotations".to_owned(),
+                        kind: Some(CodeActionKind::new(SOURCE_ADD_RETURN_ANNOTATIONS_PYREFLY)),
+                        edit: Some(WorkspaceEdit {
+                            changes: Some(changes),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }));
+                }
+            }
+            record_code_action_telemetry("add_return_annotations", start);
+        }
         if allow_refactor {
             let mut push_refactor_actions = |refactors: Vec<LocalRefactorCodeAction>| {
                 for action in refactors {
@@ -4647,6 +5808,10 @@ impl Server {
                 "convert_dict",
                 transaction.convert_dict_code_actions(&handle, range)
             );
+            timed_refactor_action!(
+                "convert_to_fstring",
+                transaction.convert_to_fstring_code_actions(&handle, range)
+            );
             timed_refactor_action!(
                 "pytest_fixture_type_annotation",
                 transaction.pytest_fixture_type_annotation_code_actions(
@@ -4655,6 +5820,10 @@ impl Server {
                     import_format
                 )
             );
+            timed_refactor_action!(
+                "add_type_annotation",
+                transaction.add_type_annotation_code_actions(&handle, range, import_format)
+            );
             let start = Instant::now();
             if let Some(action) =
                 convert_module_package_code_actions(&self.initialize_params.capabilities, uri)
@@ -4671,6 +5840,7 @@ impl Server {
                 range,
                 import_format,
                 self.path_remapper.as_ref(),
+                self.position_encoding,
             ) {
                 actions.push(action);
             }
@@ -4705,7 +5875,7 @@ impl Server {
             transaction
                 .find_local_references(&handle, position, true)
                 .into_map(|range| DocumentHighlight {
-                    range: info.to_lsp_range(range),
+                    range: info.to_lsp_range(range, self.position_encoding),
                     kind: Some(match transaction.identifier_at(&handle, range.start()) {
                         Some(id) if id.context.is_write() => DocumentHighlightKind::WRITE,
                         Some(_) => DocumentHighlightKind::READ,
@@ -4715,12 +5885,102 @@ impl Server {
         ))
     }
 
-    /// Compute references or implementations of a symbol at a given position. This is a non-blocking
-    /// function that will send a response to the LSP client once the results are found and
-    /// transformed by `transform_result`.
-    ///
-    /// The `find_fn` closure is called with the cancellable transaction, handle, and definition
-    /// information, and should return a generic result type `T`.
+    /// Debuggers ask for inline values to annotate variables while stopped. We don't know runtime
+    /// values, so answer with each variable's inferred type instead.
+    fn inline_values(
+        &self,
+        transaction: &Transaction<'_>,
+        params: InlineValueParams,
+    ) -> Result<Option<Vec<InlineValue>>, EmptyResponseReason> {
+        let uri = &params.text_document.uri;
+        let handle = self.make_handle_if_enabled(uri, Some(InlineValueRequest::METHOD))?;
+        let info = transaction
+            .get_module_info(&handle)
+            .ok_or(EmptyResponseReason::ModuleInfoNotFound)?;
+        let range = self.from_lsp_range(uri, &info, params.range);
+        Ok(transaction
+            .inline_value_types(&handle, range)
+            .map(|values| {
+                values.into_map(|(range, text)| {
+                    InlineValue::Text(InlineValueText {
+                        range: info.to_lsp_range(range, self.position_encoding),
+                        text,
+                    })
+                })
+            }))
+    }
+
+    fn linked_editing_range(
+        &self,
+        transaction: &Transaction<'_>,
+        params: LinkedEditingRangeParams,
+    ) -> Result<Option<LinkedEditingRanges>, EmptyResponseReason> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let handle = self.make_handle_if_enabled(uri, Some(LinkedEditingRange::METHOD))?;
+        let info = transaction
+            .get_module_info(&handle)
+            .ok_or(EmptyResponseReason::ModuleInfoNotFound)?;
+        let position =
+            self.from_lsp_position(uri, &info, params.text_document_position_params.position);
+        Ok(transaction
+            .linked_editing_ranges(&handle, position)
+            .map(|ranges| LinkedEditingRanges {
+                ranges: ranges.into_map(|range| info.to_lsp_range(range, self.position_encoding)),
+                word_pattern: None,
+            }))
+    }
+
+    fn moniker(
+        &self,
+        transaction: &Transaction<'_>,
+        params: MonikerParams,
+    ) -> Result<Option<Vec<Moniker>>, EmptyResponseReason> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let handle = self.make_handle_if_enabled(uri, Some(MonikerRequest::METHOD))?;
+        let info = transaction
+            .get_module_info(&handle)
+            .ok_or(EmptyResponseReason::ModuleInfoNotFound)?;
+        let position =
+            self.from_lsp_position(uri, &info, params.text_document_position_params.position);
+        Ok(monikers(transaction, &handle, position))
+    }
+
+    fn peek_definition(
+        &self,
+        transaction: &Transaction<'_>,
+        params: PeekDefinitionParams,
+    ) -> Result<Option<Vec<PeekDefinition>>, EmptyResponseReason> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let handle = self.make_handle_if_enabled(uri, Some(PeekDefinitionRequest::METHOD))?;
+        let info = transaction
+            .get_module_info(&handle)
+            .ok_or(EmptyResponseReason::ModuleInfoNotFound)?;
+        let position =
+            self.from_lsp_position(uri, &info, params.text_document_position_params.position);
+        let context_lines = params.context_lines.unwrap_or(DEFAULT_PEEK_CONTEXT_LINES);
+        let Some(snippets) = definition_snippets(transaction, &handle, position, context_lines)
+        else {
+            return Ok(None);
+        };
+        let peeks = snippets
+            .into_iter()
+            .filter_map(|snippet| {
+                Some(PeekDefinition {
+                    location: self.to_lsp_location(&snippet.definition)?,
+                    snippet_start_line: snippet.start_line,
+                    snippet: snippet.text,
+                })
+            })
+            .collect::<Vec<_>>();
+        Ok((!peeks.is_empty()).then_some(peeks))
+    }
+
+    /// Compute references or implementations of a symbol at a given position. This is a non-blocking
+    /// function that will send a response to the LSP client once the results are found and
+    /// transformed by `transform_result`.
+    ///
+    /// The `find_fn` closure is called with the cancellable transaction, handle, and definition
+    /// information, and should return a generic result type `T`.
     ///
     /// The `transform_result` closure transforms the result of type `T` into the final response
     /// type `V` that will be sent to the LSP client.
@@ -4774,44 +6034,140 @@ impl Server {
                     telemetry_event,
                     None,
                 );
-                match find_fn(
+                let result = find_fn(
                     &mut transaction,
                     &handle,
                     definition,
                     telemetry,
                     telemetry_event,
-                ) {
-                    Ok(results) => {
-                        server.cancellation_handles.lock().remove(&request_id);
-                        server.connection.send(Message::Response(new_response(
-                            request_id,
-                            Ok(Some(transform_result(results))),
-                        )));
-                    }
-                    Err(RequestError::Cancelled) => {
-                        let message = format!("Request {request_id} is canceled");
-                        info!("{message}");
-                        server.connection.send(Message::Response(Response::new_err(
-                            request_id,
-                            ErrorCode::RequestCanceled as i32,
-                            message,
-                        )));
-                    }
-                    Err(RequestError::Internal(detail)) => {
-                        let message = format!("Request {request_id} failed: {detail}");
-                        tracing::warn!("{message}");
-                        server.connection.send(Message::Response(Response::new_err(
-                            request_id,
-                            ErrorCode::InternalError as i32,
-                            message,
-                        )));
-                    }
+                );
+                if result.is_ok() {
+                    server.cancellation_handles.lock().remove(&request_id);
                 }
+                server.send_request_result(
+                    request_id,
+                    result.map(|results| Some(transform_result(results))),
+                );
             }),
         );
         Ok(())
     }
 
+    /// Run a read-only request on `queue`, against the queue's saved IDE transaction, whose
+    /// cancellation handle is registered under `request_id` so that a `$/cancelRequest` stops
+    /// the transaction's work at its next chunk boundary. `handler` returns `Ok(None)` for an
+    /// empty response, or `Err(RequestError::Cancelled)` if it noticed the cancellation.
+    fn async_read_request<V: serde::Serialize>(
+        &self,
+        queue: &HeavyTaskQueue,
+        request_id: RequestId,
+        method: &'static str,
+        uri: Option<Url>,
+        activity_key: Option<ActivityKey>,
+        handler: impl FnOnce(
+            &Server,
+            &Transaction<'_>,
+            &dyn Telemetry,
+            &mut TelemetryEvent,
+        ) -> Result<Option<V>, RequestError>
+        + Send
+        + Sync
+        + 'static,
+    ) {
+        // Registered until the task starts, so that a `$/cancelRequest` for a request that is
+        // still waiting in the queue is not lost.
+        let queued_cancellation = CancellationHandle::default();
+        self.cancellation_handles
+            .lock()
+            .insert(request_id.clone(), queued_cancellation.dupe());
+        queue.queue_read_task(
+            TelemetryEventKind::LspEvent(method.to_owned()),
+            move |server, transaction_manager, telemetry, telemetry_event| {
+                if let Some(uri) = uri {
+                    server.set_file_stats(uri, telemetry_event);
+                }
+                telemetry_event.set_activity_key(activity_key);
+                let mut transaction =
+                    transaction_manager.non_committable_transaction(&server.state);
+                server
+                    .cancellation_handles
+                    .lock()
+                    .insert(request_id.clone(), transaction.get_cancellation_handle());
+                if let Some(deadlines) = &server.request_deadlines {
+                    deadlines.start(request_id.clone(), transaction.get_cancellation_handle());
+                }
+                let result = if queued_cancellation.is_cancelled() {
+                    Err(RequestError::Cancelled)
+                } else {
+                    server.validate_in_memory_for_transaction(
+                        &mut transaction,
+                        telemetry_event,
+                        None,
+                    );
+                    handler(server, &transaction, telemetry, telemetry_event)
+                };
+                server.cancellation_handles.lock().remove(&request_id);
+                // A cancelled transaction would make every later request on this queue fail,
+                // so only save it for reuse if it ran to completion.
+                if !transaction.get_cancellation_handle().is_cancelled() {
+                    transaction_manager.save(transaction, telemetry_event);
+                }
+                if server
+                    .request_deadlines
+                    .as_ref()
+                    .is_none_or(|deadlines| deadlines.finish(&request_id))
+                {
+                    server.send_request_result(request_id, result);
+                } else {
+                    server.finish_request_timing(&request_id);
+                }
+            },
+        );
+    }
+
+    /// Send the result of a request that was answered off the main loop.
+    fn send_request_result<V: serde::Serialize>(
+        &self,
+        request_id: RequestId,
+        result: Result<V, RequestError>,
+    ) {
+        match result {
+            Ok(response) => {
+                self.connection.send(Message::Response(new_response(
+                    request_id.clone(),
+                    Ok(response),
+                )));
+            }
+            Err(RequestError::Cancelled) => {
+                let message = format!("Request {request_id} is canceled");
+                info!("{message}");
+                self.connection.send(Message::Response(Response::new_err(
+                    request_id.clone(),
+                    ErrorCode::RequestCanceled as i32,
+                    message,
+                )));
+            }
+            Err(RequestError::Internal(detail)) => {
+                let message = format!("Request {request_id} failed: {detail}");
+                tracing::warn!("{message}");
+                self.connection.send(Message::Response(Response::new_err(
+                    request_id.clone(),
+                    ErrorCode::InternalError as i32,
+                    message,
+                )));
+            }
+            Err(RequestError::Rejected(message)) => {
+                info!("Request {request_id} rejected: {message}");
+                self.connection.send(Message::Response(Response::new_err(
+                    request_id.clone(),
+                    ErrorCode::InvalidRequest as i32,
+                    message,
+                )));
+            }
+        }
+        self.finish_request_timing(&request_id);
+    }
+
     /// Compute references of a symbol at a given position using the standard find_global_references_from_definition
     /// strategy. This is a convenience wrapper around async_find_from_definition_helper that handles
     /// the common case of finding references, including external references.
@@ -4823,13 +6179,20 @@ impl Server {
         uri: &Url,
         position: Position,
         include_declaration: bool,
+        classify: bool,
+        max_results: Option<usize>,
         activity_key: Option<ActivityKey>,
-        map_result: impl FnOnce(Vec<(Url, Vec<Range>)>) -> V + Send + Sync + 'static,
+        map_result: impl FnOnce(Vec<(Url, Vec<(Range, Option<ReferenceKind>)>)>) -> V
+        + Send
+        + Sync
+        + 'static,
     ) -> Result<(), EmptyResponseReason> {
         let path_remapper = self.path_remapper.clone();
         let external_references = self.external_references.clone();
         let source_uri = uri.clone();
         let open_notebooks = self.snapshot_open_notebooks();
+        let position_encoding = self.position_encoding;
+        let sender = self.connection.0.sender.clone();
 
         self.async_find_from_definition_helper(
             request_id,
@@ -4853,6 +6216,7 @@ impl Server {
                     docstring_range: _,
                     ..
                 } = definition;
+                let definition = TextRangeWithModule::new(module, definition_range);
 
                 let sub_task_telemetry = SubTaskTelemetry::new(telemetry, telemetry_event);
 
@@ -4871,30 +6235,64 @@ impl Server {
                         })
                     });
 
-                    let local_results = transaction.find_global_references_from_definition(
-                        *handle.sys_info(),
-                        metadata,
-                        TextRangeWithModule::new(module, definition_range),
-                        include_declaration,
-                    );
+                    let local_results = transaction
+                        .find_global_references_from_definition_with_limit(
+                            *handle.sys_info(),
+                            metadata,
+                            definition.clone(),
+                            include_declaration,
+                            max_results,
+                        );
 
                     let external_results = ext_handle.and_then(|h| h.join().ok());
                     (local_results, external_results)
                 });
 
-                let external_results = external_results
-                    .transpose()
-                    .map_err(|e| RequestError::Internal(e.to_string()))?
-                    .unwrap_or_default();
-                Ok((local_results?, external_results))
+                let external_results = external_references_in_encoding(
+                    external_results
+                        .transpose()
+                        .map_err(|e| RequestError::Internal(e.to_string()))?
+                        .unwrap_or_default(),
+                    position_encoding,
+                );
+                let (local_results, truncated) = local_results?;
+                let local_results = if classify {
+                    transaction.as_ref().classify_references(
+                        handle.sys_info(),
+                        &definition,
+                        local_results,
+                    )
+                } else {
+                    local_results.into_map(|(module, ranges)| {
+                        (module, ranges.into_map(|range| (range, None)))
+                    })
+                };
+                Ok((local_results, external_results, truncated))
             },
-            move |results: (Vec<(ModuleInfo, Vec<TextRange>)>, Vec<(Url, Vec<Range>)>)| {
-                let (local_results, external_results) = results;
+            move |results: (
+                Vec<(ModuleInfo, Vec<(TextRange, Option<ReferenceKind>)>)>,
+                Vec<(Url, Vec<Range>)>,
+                bool,
+            )| {
+                let (local_results, external_results, truncated) = results;
+                if truncated && let Some(max_results) = max_results {
+                    info!("Find references stopped after {max_results} results (maxReferences).");
+                    let _ = sender.send(Message::Notification(new_notification::<ShowMessage>(
+                        ShowMessageParams {
+                            typ: MessageType::INFO,
+                            message: format!(
+                                "Showing the first {max_results} references. Increase \
+                                 `maxReferences` to see them all."
+                            ),
+                        },
+                    )));
+                }
 
-                let mut locations: SmallMap<Url, Vec<Range>> = SmallMap::new();
+                let mut locations: SmallMap<Url, Vec<(Range, Option<ReferenceKind>)>> =
+                    SmallMap::new();
                 for (info, ranges) in local_results {
                     if let Some(mut uri) = module_info_to_uri(&info, path_remapper.as_ref()) {
-                        for range in ranges {
+                        for (range, kind) in ranges {
                             // Remap file URIs to notebook cell URIs when the target is in a notebook
                             if let Some(cell_idx) = info.to_cell_for_lsp(range.start())
                                 && let Some(path) = to_real_path(info.path())
@@ -4906,7 +6304,7 @@ impl Server {
                             locations
                                 .entry(uri.clone())
                                 .or_default()
-                                .push(info.to_lsp_range(range));
+                                .push((info.to_lsp_range(range, position_encoding), kind));
                         }
                     }
                 }
@@ -4914,8 +6312,8 @@ impl Server {
                 for (ext_url, ext_ranges) in external_results {
                     let entry = locations.entry(ext_url).or_default();
                     for r in ext_ranges {
-                        if !entry.contains(&r) {
-                            entry.push(r);
+                        if !entry.iter().any(|(range, _)| *range == r) {
+                            entry.push((r, None));
                         }
                     }
                 }
@@ -4934,6 +6332,7 @@ impl Server {
     ) -> Result<(), EmptyResponseReason> {
         let uri = &params.text_document_position.text_document.uri;
         let handle = self.make_handle_if_enabled(uri, Some(References::METHOD))?;
+        let max_results = self.workspaces.max_references(handle.path().as_path());
         self.async_find_references_helper(
             request_id,
             transaction,
@@ -4941,11 +6340,13 @@ impl Server {
             uri,
             params.text_document_position.position,
             params.context.include_declaration,
+            false,
+            max_results,
             activity_key,
             move |results| {
                 let mut locations = Vec::new();
                 for (uri, ranges) in results {
-                    for range in ranges {
+                    for (range, _) in ranges {
                         locations.push(Location {
                             uri: uri.clone(),
                             range,
@@ -4957,6 +6358,42 @@ impl Server {
         )
     }
 
+    fn classified_references<'a>(
+        &'a self,
+        request_id: RequestId,
+        transaction: &Transaction<'a>,
+        params: ReferenceParams,
+        activity_key: Option<ActivityKey>,
+    ) -> Result<(), EmptyResponseReason> {
+        let uri = &params.text_document_position.text_document.uri;
+        let handle = self.make_handle_if_enabled(uri, Some(References::METHOD))?;
+        let max_results = self.workspaces.max_references(handle.path().as_path());
+        self.async_find_references_helper(
+            request_id,
+            transaction,
+            handle,
+            uri,
+            params.text_document_position.position,
+            params.context.include_declaration,
+            true,
+            max_results,
+            activity_key,
+            move |results| {
+                let mut locations = Vec::new();
+                for (uri, ranges) in results {
+                    for (range, kind) in ranges {
+                        locations.push(ClassifiedLocation {
+                            uri: uri.clone(),
+                            range,
+                            kind,
+                        })
+                    }
+                }
+                locations
+            },
+        )
+    }
+
     fn rename<'a>(
         &'a self,
         request_id: RequestId,
@@ -4966,6 +6403,41 @@ impl Server {
     ) -> Result<(), EmptyResponseReason> {
         let uri = &params.text_document_position.text_document.uri;
         let handle = self.make_handle_if_enabled(uri, Some(Rename::METHOD))?;
+        // Renaming a module name in an import renames the module's file itself. Finding its
+        // importers relies on the project having been indexed.
+        if self.indexing_mode != IndexingMode::None
+            && let Some(info) = transaction.get_module_info(&handle)
+            && let Some(module) = transaction.imported_module_at(
+                &handle,
+                self.from_lsp_position(uri, &info, params.text_document_position.position),
+            )
+            && let Some(module_rename) = ModuleRename::new(
+                &self.initialize_params.capabilities,
+                &module,
+                &params.new_name,
+            )
+        {
+            // Building the edit parses every importer of the module, so keep it off the main loop.
+            self.async_read_request(
+                &self.find_reference_queue,
+                request_id,
+                Rename::METHOD,
+                Some(uri.clone()),
+                activity_key,
+                move |server, transaction, _telemetry, _telemetry_event| {
+                    module_rename
+                        .workspace_edit(
+                            &server.state,
+                            transaction,
+                            server.path_remapper.as_ref(),
+                            server.position_encoding,
+                        )
+                        .map(Some)
+                        .map_err(RequestError::Rejected)
+                },
+            );
+            return Ok(());
+        }
         self.async_find_references_helper(
             request_id,
             transaction,
@@ -4973,13 +6445,15 @@ impl Server {
             uri,
             params.text_document_position.position,
             true,
+            false,
+            None,
             activity_key,
             move |results| {
                 let mut changes = HashMap::new();
                 for (uri, ranges) in results {
                     changes.insert(
                         uri,
-                        ranges.into_map(|range| TextEdit {
+                        ranges.into_map(|(range, _)| TextEdit {
                             range,
                             new_text: params.new_name.clone(),
                         }),
@@ -5004,9 +6478,9 @@ impl Server {
             .get_module_info(&handle)
             .ok_or(EmptyResponseReason::ModuleInfoNotFound)?;
         let position = self.from_lsp_position(uri, &info, params.position);
-        Ok(transaction
-            .prepare_rename(&handle, position)
-            .map(|range| PrepareRenameResponse::Range(info.to_lsp_range(range))))
+        Ok(transaction.prepare_rename(&handle, position).map(|range| {
+            PrepareRenameResponse::Range(info.to_lsp_range(range, self.position_encoding))
+        }))
     }
 
     fn signature_help(
@@ -5040,7 +6514,18 @@ impl Server {
         let show_go_to_links = lsp_config
             .and_then(|c| c.show_hover_go_to_links)
             .unwrap_or(true);
-        Ok(get_hover(transaction, &handle, position, show_go_to_links))
+        let max_hover_length = lsp_config.and_then(|c| c.max_hover_length);
+        let fully_qualified_names = lsp_config
+            .and_then(|c| c.hover)
+            .is_some_and(|c| c.fully_qualified_names);
+        Ok(get_hover(
+            transaction,
+            &handle,
+            position,
+            show_go_to_links,
+            max_hover_length,
+            fully_qualified_names,
+        ))
     }
 
     fn inlay_hints(
@@ -5073,7 +6558,7 @@ impl Server {
                 if info.to_cell_for_lsp(text_size) != maybe_cell_idx {
                     return None;
                 }
-                let position = info.to_lsp_position(text_size);
+                let position = info.to_lsp_position(text_size, self.position_encoding);
                 // The range is half-open, so the end position is exclusive according to the spec.
                 if position >= range.start && position < range.end {
                     let label = InlayHintLabel::LabelParts(
@@ -5144,7 +6629,7 @@ impl Server {
             if info.to_cell_for_lsp(entry.range.start()) != maybe_cell_idx {
                 continue;
             }
-            let range = info.to_lsp_range(entry.range);
+            let range = info.to_lsp_range(entry.range, self.position_encoding);
             lenses.push(runnable_lsp_code_lens(uri, range, entry, cwd.as_deref()));
         }
 
@@ -5160,14 +6645,59 @@ impl Server {
         let maybe_cell_idx = self.maybe_get_code_cell_index(uri);
         let handle = self.make_handle_if_enabled(uri, Some(SemanticTokensFullRequest::METHOD))?;
         let include_syntax_tokens = !client_augments_syntax_tokens(&self.initialize_params);
+        let data = transaction
+            .semantic_tokens(
+                &handle,
+                None,
+                maybe_cell_idx,
+                include_syntax_tokens,
+                self.position_encoding,
+            )
+            .unwrap_or_default();
+        let result_id = self
+            .semantic_tokens_cache
+            .lock()
+            .insert(uri.clone(), data.clone());
         Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
-            result_id: None,
-            data: transaction
-                .semantic_tokens(&handle, None, maybe_cell_idx, include_syntax_tokens)
-                .unwrap_or_default(),
+            result_id: Some(result_id),
+            data,
         })))
     }
 
+    /// Like `semantic_tokens_full`, but when the client's previous result is still cached,
+    /// respond with the edits from it rather than the whole token array.
+    fn semantic_tokens_full_delta(
+        &self,
+        transaction: &Transaction<'_>,
+        params: SemanticTokensDeltaParams,
+    ) -> Result<Option<SemanticTokensFullDeltaResult>, EmptyResponseReason> {
+        let uri = &params.text_document.uri;
+        let maybe_cell_idx = self.maybe_get_code_cell_index(uri);
+        let handle =
+            self.make_handle_if_enabled(uri, Some(SemanticTokensFullDeltaRequest::METHOD))?;
+        let include_syntax_tokens = !client_augments_syntax_tokens(&self.initialize_params);
+        let data = transaction
+            .semantic_tokens(
+                &handle,
+                None,
+                maybe_cell_idx,
+                include_syntax_tokens,
+                self.position_encoding,
+            )
+            .unwrap_or_default();
+        let mut cache = self.semantic_tokens_cache.lock();
+        let edits = cache
+            .get(uri, &params.previous_result_id)
+            .map(|previous| semantic_tokens_edits(previous, &data));
+        let result_id = Some(cache.insert(uri.clone(), data.clone()));
+        Ok(Some(match edits {
+            Some(edits) => {
+                SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta { result_id, edits })
+            }
+            None => SemanticTokensFullDeltaResult::Tokens(SemanticTokens { result_id, data }),
+        }))
+    }
+
     fn semantic_tokens_ranged(
         &self,
         transaction: &Transaction<'_>,
@@ -5184,7 +6714,13 @@ impl Server {
         Ok(Some(SemanticTokensRangeResult::Tokens(SemanticTokens {
             result_id: None,
             data: transaction
-                .semantic_tokens(&handle, Some(range), maybe_cell_idx, include_syntax_tokens)
+                .semantic_tokens(
+                    &handle,
+                    Some(range),
+                    maybe_cell_idx,
+                    include_syntax_tokens,
+                    self.position_encoding,
+                )
                 .unwrap_or_default(),
         })))
     }
@@ -5221,7 +6757,7 @@ impl Server {
             == Some(true);
 
         let handle = self.make_handle_if_enabled(uri, Some(DocumentSymbolRequest::METHOD))?;
-        let symbols = transaction.symbols(&handle, maybe_cell_idx);
+        let symbols = transaction.symbols(&handle, maybe_cell_idx, self.position_encoding);
         Ok(symbols.map(|syms| {
             if supports_hierarchical {
                 DocumentSymbolResponse::Nested(syms)
@@ -5231,9 +6767,25 @@ impl Server {
         }))
     }
 
+    /// Whether the client can fill in the range of a workspace symbol's location
+    /// with `workspaceSymbol/resolve`.
+    fn supports_workspace_symbol_resolve(&self) -> bool {
+        self.initialize_params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|w| w.symbol.as_ref())
+            .and_then(|s| s.resolve_support.as_ref())
+            .is_some_and(|r| r.properties.iter().any(|p| p == "location.range"))
+    }
+
     /// Run local and external workspace symbol queries in parallel, merging
     /// results with local results taking priority (external results for files
     /// already covered by local results are skipped).
+    ///
+    /// If the client supports resolving workspace symbols, local results only carry
+    /// their uri, and their range is computed by `resolve_workspace_symbol` for the
+    /// symbols the client actually displays.
     #[allow(deprecated)] // SymbolInformation's `deprecated` field is itself marked #[deprecated]
     fn workspace_symbols(
         &self,
@@ -5241,7 +6793,8 @@ impl Server {
         query: &str,
         telemetry: &dyn Telemetry,
         telemetry_event: &mut TelemetryEvent,
-    ) -> anyhow::Result<Vec<SymbolInformation>> {
+    ) -> Result<WorkspaceSymbolResponse, RequestError> {
+        let resolve_lazily = self.supports_workspace_symbol_resolve();
         let external_provider = self.external_references.clone();
         let workspace_uri = self
             .initialize_params
@@ -5265,46 +6818,168 @@ impl Server {
                 })
             });
 
-            let local_results: Vec<SymbolInformation> = transaction
-                .workspace_symbols(query, Some(&self.lsp_thread_pool))
-                .unwrap_or_default()
-                .into_iter()
-                .filter_map(|(name, kind, location)| {
-                    self.to_lsp_location(&location)
-                        .map(|location| SymbolInformation {
-                            name,
-                            kind,
-                            location,
-                            tags: None,
-                            deprecated: None,
-                            container_name: None,
+            let local_results = transaction
+                .workspace_symbols(
+                    query,
+                    &self.get_open_file_handles(),
+                    Some(&self.lsp_thread_pool),
+                )
+                .map(|symbols| {
+                    symbols
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|(name, kind, location, container)| {
+                            // Notebook cell uris depend on the range, and bundled modules
+                            // only have a uri once written to disk, so neither is deferred.
+                            let (location, data) = if resolve_lazily
+                                && !location.module.is_notebook()
+                                && !location.module.path().is_bundled()
+                            {
+                                // The protocol requires a uri up front, which is just the
+                                // module's path here. The location, with its range, is built
+                                // by `resolve_workspace_symbol` from `data`.
+                                let uri = path_to_uri(
+                                    location.module.path().as_path(),
+                                    self.path_remapper.as_ref(),
+                                )?;
+                                let data = WorkspaceSymbolData {
+                                    module: location.module.name(),
+                                    path: location.module.path().details().clone(),
+                                    start: location.range.start().into(),
+                                    end: location.range.end().into(),
+                                };
+                                (
+                                    OneOf::Right(WorkspaceLocation { uri }),
+                                    serde_json::to_value(data).ok(),
+                                )
+                            } else {
+                                (OneOf::Left(self.to_lsp_location(&location)?), None)
+                            };
+                            Some(lsp_types::WorkspaceSymbol {
+                                name,
+                                kind,
+                                tags: None,
+                                container_name: Some(container),
+                                location,
+                                data,
+                            })
                         })
-                })
-                .collect();
+                        .collect::<Vec<_>>()
+                });
 
             let external_results = ext_handle.and_then(|h| h.join().ok());
             (local_results, external_results)
         });
 
-        let external_results = external_results.transpose()?.unwrap_or_default();
+        let local_results = local_results?;
+        let external_results = external_results
+            .transpose()
+            .map_err(|e| RequestError::Internal(e.to_string()))?
+            .unwrap_or_default();
 
         // Local results take priority; skip external results for files already covered.
         let local_uris: HashSet<Url> = local_results
             .iter()
-            .map(|s| s.location.uri.clone())
+            .map(|s| match &s.location {
+                OneOf::Left(location) => location.uri.clone(),
+                OneOf::Right(location) => location.uri.clone(),
+            })
             .collect();
         let mut merged = local_results;
         for sym in external_results {
             if !local_uris.contains(&sym.location.uri) {
-                merged.push(sym);
+                merged.push(lsp_types::WorkspaceSymbol {
+                    name: sym.name,
+                    kind: sym.kind,
+                    tags: sym.tags,
+                    container_name: sym.container_name,
+                    location: OneOf::Left(sym.location),
+                    data: None,
+                });
             }
         }
-        Ok(merged)
+        // Local results are ordered best match first and come before external ones, so
+        // truncating keeps the best local matches.
+        merged.truncate(self.workspace_symbol_limit);
+        if resolve_lazily {
+            return Ok(WorkspaceSymbolResponse::Nested(merged));
+        }
+        Ok(WorkspaceSymbolResponse::Flat(
+            merged
+                .into_iter()
+                .filter_map(|sym| match sym.location {
+                    OneOf::Left(location) => Some(SymbolInformation {
+                        name: sym.name,
+                        kind: sym.kind,
+                        tags: sym.tags,
+                        deprecated: None,
+                        location,
+                        container_name: sym.container_name,
+                    }),
+                    OneOf::Right(_) => None,
+                })
+                .collect(),
+        ))
+    }
+
+    /// Fill in the location of a workspace symbol that `workspace_symbols` returned
+    /// with only a uri, from the module and range in its `data`. Symbols that already
+    /// have a location are returned unchanged.
+    fn resolve_workspace_symbol(
+        &self,
+        transaction: &Transaction<'_>,
+        mut symbol: lsp_types::WorkspaceSymbol,
+    ) -> lsp_types::WorkspaceSymbol {
+        if let OneOf::Right(_) = &symbol.location
+            && let Some(data) = symbol
+                .data
+                .as_ref()
+                .and_then(|data| serde_json::from_value::<WorkspaceSymbolData>(data.clone()).ok())
+            && let Some(location) = self.workspace_symbol_location(transaction, data)
+        {
+            symbol.location = OneOf::Left(location);
+            symbol.data = None;
+        }
+        symbol
+    }
+
+    /// The location of a workspace symbol from its `data`, built the same way as for
+    /// the symbols `workspace_symbols` returns with a location.
+    fn workspace_symbol_location(
+        &self,
+        transaction: &Transaction<'_>,
+        data: WorkspaceSymbolData,
+    ) -> Option<Location> {
+        let path = ModulePath::from_details(data.path);
+        let config = self
+            .state
+            .config_finder()
+            .python_file(ModuleNameWithKind::guaranteed(data.module), &path);
+        let handle = Handle::new(data.module, path, config.get_sys_info());
+        let module = transaction.get_module_info(&handle)?;
+        // The range comes back from the client, and the file may have changed since the symbol
+        // was returned, so only trust it if it still describes a range within the file.
+        let contents = module.contents();
+        let len = TextSize::of(contents.as_str());
+        if data.start > data.end {
+            return None;
+        }
+        let range = TextRange::new(
+            TextSize::new(data.start).min(len),
+            TextSize::new(data.end).min(len),
+        );
+        if !contents.is_char_boundary(range.start().to_usize())
+            || !contents.is_char_boundary(range.end().to_usize())
+        {
+            return None;
+        }
+        self.to_lsp_location(&TextRangeWithModule { module, range })
     }
 
     fn append_unreachable_diagnostics(
         transaction: &Transaction<'_>,
         handle: &Handle,
+        position_encoding: PositionEncoding,
         items: &mut Vec<Diagnostic>,
     ) {
         if let (Some(ast), Some(module_info)) = (
@@ -5317,7 +6992,7 @@ impl Server {
                 if range.is_empty() || !seen.insert(range) {
                     continue;
                 }
-                let lsp_range = module_info.to_lsp_range(range);
+                let lsp_range = module_info.to_lsp_range(range, position_encoding);
                 items.push(Diagnostic {
                     range: lsp_range,
                     severity: Some(DiagnosticSeverity::HINT),
@@ -5338,6 +7013,7 @@ impl Server {
     fn append_unused_parameter_diagnostics(
         transaction: &Transaction<'_>,
         handle: &Handle,
+        position_encoding: PositionEncoding,
         items: &mut Vec<Diagnostic>,
     ) {
         if let Some(bindings) = transaction.get_bindings(handle) {
@@ -5346,7 +7022,7 @@ impl Server {
                 if Ast::is_intentionally_unused(unused.name.as_str()) {
                     continue;
                 }
-                let lsp_range = module_info.to_lsp_range(unused.range);
+                let lsp_range = module_info.to_lsp_range(unused.range, position_encoding);
                 items.push(Diagnostic {
                     range: lsp_range,
                     severity: Some(DiagnosticSeverity::HINT),
@@ -5365,12 +7041,13 @@ impl Server {
     fn append_unused_import_diagnostics(
         transaction: &Transaction<'_>,
         handle: &Handle,
+        position_encoding: PositionEncoding,
         items: &mut Vec<Diagnostic>,
     ) {
         if let Some(bindings) = transaction.get_bindings(handle) {
             let module_info = bindings.module();
             for unused in bindings.unused_imports() {
-                let lsp_range = module_info.to_lsp_range(unused.range);
+                let lsp_range = module_info.to_lsp_range(unused.range, position_encoding);
                 items.push(Diagnostic {
                     range: lsp_range,
                     severity: Some(DiagnosticSeverity::HINT),
@@ -5389,6 +7066,7 @@ impl Server {
     fn append_unused_variable_diagnostics(
         transaction: &Transaction<'_>,
         handle: &Handle,
+        position_encoding: PositionEncoding,
         items: &mut Vec<Diagnostic>,
     ) {
         if let Some(bindings) = transaction.get_bindings(handle) {
@@ -5397,7 +7075,7 @@ impl Server {
                 if Ast::is_intentionally_unused(unused.name.as_str()) {
                     continue;
                 }
-                let lsp_range = module_info.to_lsp_range(unused.range);
+                let lsp_range = module_info.to_lsp_range(unused.range, position_encoding);
                 items.push(Diagnostic {
                     range: lsp_range,
                     severity: Some(DiagnosticSeverity::HINT),
@@ -5413,6 +7091,79 @@ impl Server {
         }
     }
 
+    /// Point out `# pyrefly: ignore` comments that suppress nothing, for clients that opt in
+    /// with `showUnusedIgnores`. When `unused-ignore` is enabled in the config these are already
+    /// reported as ordinary errors, so this only covers the default case where it is disabled.
+    fn append_unused_ignore_diagnostics(
+        transaction: &Transaction<'_>,
+        handle: &Handle,
+        position_encoding: PositionEncoding,
+        items: &mut Vec<Diagnostic>,
+    ) {
+        for error in transaction
+            .get_errors([handle])
+            .collect_disabled_unused_ignore_errors()
+        {
+            let module_info = error.module();
+            // The error starts at the beginning of the comment's line; narrow it to the comment.
+            let line_start = error.range().start();
+            let line = module_info.contents()[line_start.to_usize()..]
+                .lines()
+                .next()
+                .unwrap_or_default();
+            let Some(comment_start) = find_comment_start_in_line(line) else {
+                continue;
+            };
+            let range = TextRange::new(
+                line_start + TextSize::try_from(comment_start).unwrap(),
+                line_start + TextSize::try_from(line.trim_end().len()).unwrap(),
+            );
+            items.push(Diagnostic {
+                range: module_info.to_lsp_range(range, position_encoding),
+                severity: Some(DiagnosticSeverity::INFORMATION),
+                source: Some("Pyrefly".to_owned()),
+                message: error.msg().into(),
+                code: Some(NumberOrString::String(
+                    error.error_kind().to_name().to_owned(),
+                )),
+                code_description: None,
+                related_information: None,
+                tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+                data: None,
+            });
+        }
+    }
+
+    fn on_type_formatting(
+        &self,
+        transaction: &Transaction<'_>,
+        params: DocumentOnTypeFormattingParams,
+    ) -> Option<Vec<TextEdit>> {
+        if params.ch != ON_TYPE_FORMATTING_TRIGGER {
+            return None;
+        }
+        let uri = &params.text_document_position.text_document.uri;
+        let handle = self
+            .make_handle_if_enabled(uri, Some(OnTypeFormatting::METHOD))
+            .ok()?;
+        let module = transaction.get_module_info(&handle)?;
+        let position = module.from_lsp_position(
+            params.text_document_position.position,
+            self.maybe_get_code_cell_index(uri),
+            self.position_encoding,
+        );
+        let indent_unit = if params.options.insert_spaces {
+            " ".repeat(params.options.tab_size as usize)
+        } else {
+            "\t".to_owned()
+        };
+        let (range, new_text) = newline_indentation(module.contents(), position, &indent_unit)?;
+        Some(vec![TextEdit {
+            range: module.to_lsp_range(range, self.position_encoding),
+            new_text,
+        }])
+    }
+
     fn docstring_ranges(
         &self,
         transaction: &Transaction<'_>,
@@ -5430,7 +7181,7 @@ impl Server {
                     maybe_cell_idx.is_none()
                         || module.to_cell_for_lsp(range.start()) == maybe_cell_idx
                 })
-                .map(|range| module.to_lsp_range(range))
+                .map(|range| module.to_lsp_range(range, self.position_encoding))
                 .collect(),
         )
     }
@@ -5464,7 +7215,7 @@ impl Server {
                     if !self.comment_folding_ranges && kind == Some(FoldingRangeKind::Region) {
                         return None;
                     }
-                    let lsp_range = module.to_lsp_range(range);
+                    let lsp_range = module.to_lsp_range(range, self.position_encoding);
                     if lsp_range.start.line >= lsp_range.end.line {
                         return None;
                     }
@@ -5520,20 +7271,26 @@ impl Server {
             .get_errors(once(&handle))
             .collect_lsp_errors_with_baselines();
         for e in normal_errors {
-            if let Some((_, diag)) = self.get_diag_if_shown(&e, open_files, cell_uri) {
+            if let Some((_, diag)) = self.get_diag_if_shown(&e, open_files, cell_uri, false) {
                 items.push(diag);
             }
         }
         for e in baseline_errors {
             // Errors in open files that match a baseline file are downgraded to HINT.
-            if let Some((_, mut diag)) = self.get_diag_if_shown(&e, open_files, cell_uri) {
+            if let Some((_, mut diag)) = self.get_diag_if_shown(&e, open_files, cell_uri, false) {
                 if to_real_path(e.path()).is_some_and(|p| open_files.contains_key(&p)) {
                     diag.severity = Some(DiagnosticSeverity::HINT);
                 }
                 items.push(diag);
             }
         }
-        Self::append_ide_specific_diagnostics(transaction, &handle, &mut items);
+        Self::append_ide_specific_diagnostics(
+            transaction,
+            &handle,
+            self.position_encoding,
+            self.workspaces.should_show_unused_ignores(&path),
+            &mut items,
+        );
         DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
             full_document_diagnostic_report: FullDocumentDiagnosticReport {
                 items,
@@ -5590,6 +7347,9 @@ impl Server {
                     });
                 }
                 glob_patterns.extend(ConfigFile::get_paths_to_watch(&configs));
+                // Nested search paths and workspace roots would otherwise each register their
+                // own recursive watchers for the same files.
+                let glob_patterns = WatchPattern::dedup_nested_roots(glob_patterns);
                 let mut watched_patterns = self.watched_patterns.lock();
 
                 let should_rewatch = watched_patterns.difference(&glob_patterns).next().is_some();
@@ -5764,6 +7524,7 @@ impl Server {
             params,
             supports_document_changes,
             self.path_remapper.as_ref(),
+            self.position_encoding,
         )
     }
 
@@ -5785,7 +7546,7 @@ impl Server {
         }
         Some(Location {
             uri,
-            range: definition_module_info.to_lsp_range(*range),
+            range: definition_module_info.to_lsp_range(*range, self.position_encoding),
         })
     }
 
@@ -5809,12 +7570,12 @@ impl Server {
         position: Position,
     ) -> TextSize {
         let notebook_cell = self.maybe_get_code_cell_index(uri);
-        module.from_lsp_position(position, notebook_cell)
+        module.from_lsp_position(position, notebook_cell, self.position_encoding)
     }
 
     pub fn from_lsp_range(&self, uri: &Url, module: &ModuleInfo, position: Range) -> TextRange {
         let notebook_cell = self.maybe_get_code_cell_index(uri);
-        module.from_lsp_range(position, notebook_cell)
+        module.from_lsp_range(position, notebook_cell, self.position_encoding)
     }
 
     /// Asynchronously finds incoming calls (callers) of a function.
@@ -5836,6 +7597,7 @@ impl Server {
         let path_remapper = self.path_remapper.clone();
         let external_references = self.external_references.clone();
         let source_uri = uri.clone();
+        let position_encoding = self.position_encoding;
 
         self.async_find_from_definition_helper(
             request_id,
@@ -5864,7 +7626,10 @@ impl Server {
                                 Duration::from_secs(10),
                                 Some(sub_task_telemetry),
                             )?;
-                            Ok(convert_external_references_to_incoming_calls(external_refs))
+                            Ok(convert_external_references_to_incoming_calls(
+                                external_refs,
+                                position_encoding,
+                            ))
                         })
                     });
 
@@ -5889,8 +7654,11 @@ impl Server {
                 _,
                 Vec<lsp_types::CallHierarchyIncomingCall>,
             )| {
-                let mut incoming_calls =
-                    transform_incoming_calls(local_callers, path_remapper.as_ref());
+                let mut incoming_calls = transform_incoming_calls(
+                    local_callers,
+                    path_remapper.as_ref(),
+                    position_encoding,
+                );
 
                 // Dedup: skip external calls from files already covered by local results
                 let existing_uris: HashSet<Url> =
@@ -5923,6 +7691,7 @@ impl Server {
 
         // Clone uri for use in the transform closure
         let uri_for_transform = uri.clone();
+        let position_encoding = self.position_encoding;
 
         // The CallHierarchyItem we receive is already at the definition position
         // (thanks to prepare_call_hierarchy doing the go-to-definition step).
@@ -5945,7 +7714,12 @@ impl Server {
                 Ok((callees, definition.module))
             },
             move |(callees, source_module)| {
-                transform_outgoing_calls(callees, &source_module, &uri_for_transform)
+                transform_outgoing_calls(
+                    callees,
+                    &source_module,
+                    &uri_for_transform,
+                    position_encoding,
+                )
             },
         )
     }
@@ -5996,7 +7770,12 @@ impl Server {
             if let Some(func_def) =
                 find_function_at_position_in_ast(&ast, def.definition_range.start())
             {
-                let item = prepare_call_hierarchy_item(func_def, &def.module, def_uri);
+                let item = prepare_call_hierarchy_item(
+                    func_def,
+                    &def.module,
+                    def_uri,
+                    self.position_encoding,
+                );
                 return Ok(Some(vec![item]));
             }
         }
@@ -6049,6 +7828,7 @@ impl Server {
         target: &TypeHierarchyTarget,
         handles: Vec<Handle>,
         path_remapper: Option<&PathRemapper>,
+        position_encoding: PositionEncoding,
     ) -> Vec<TypeHierarchyItem> {
         let mut items = Vec::new();
         let mut seen: HashSet<(ModulePath, TextRange)> = HashSet::new();
@@ -6099,6 +7879,7 @@ impl Server {
                     class_def,
                     &module_info,
                     candidate_uri.clone(),
+                    position_encoding,
                 ));
             }
         }
@@ -6140,7 +7921,12 @@ impl Server {
             if let Some(class_def) =
                 find_class_at_position_in_ast(&ast, def.definition_range.start())
             {
-                let item = prepare_type_hierarchy_item(class_def, &def.module, def_uri);
+                let item = prepare_type_hierarchy_item(
+                    class_def,
+                    &def.module,
+                    def_uri,
+                    self.position_encoding,
+                );
                 return Ok(Some(vec![item]));
             }
         }
@@ -6158,12 +7944,13 @@ impl Server {
         let handle = self.make_handle_if_enabled(&uri, Some(TypeHierarchySupertypes::METHOD))?;
 
         let path_remapper = self.path_remapper.clone();
+        let position_encoding = self.position_encoding;
         let type_hierarchy_item_from_class_type =
             move |class_type: &ClassType| -> Option<TypeHierarchyItem> {
                 let class = class_type.class_object();
                 let module = class.module();
                 let uri = module_info_to_uri(module, path_remapper.as_ref())?;
-                let range = module.to_lsp_range(class.range());
+                let range = module.to_lsp_range(class.range(), position_encoding);
                 Some(TypeHierarchyItem {
                     name: class.name().to_string(),
                     kind: SymbolKind::CLASS,
@@ -6228,6 +8015,7 @@ impl Server {
         let handle = self.make_handle_if_enabled(&uri, Some(TypeHierarchySubtypes::METHOD))?;
 
         let path_remapper = self.path_remapper.clone();
+        let position_encoding = self.position_encoding;
         self.async_find_from_definition_helper(
             request_id,
             transaction,
@@ -6255,33 +8043,107 @@ impl Server {
                     &target,
                     handles,
                     path_remapper.as_ref(),
+                    position_encoding,
                 ))
             },
             |items| items,
         )
     }
 
-    /// Open `uri` at `(line, character)`: resolve the path, build a handle, and
-    /// start a transaction, returning it alongside the handle and the resolved
-    /// in-file position.
-    fn open_at_position<'a>(
+    /// Open `uri` for a TSP request: resolve the path, check that language
+    /// services are enabled for it, build a handle, and start a transaction,
+    /// returning it alongside the handle, the module and the notebook cell index.
+    fn open_for_tsp<'a>(
         &'a self,
         uri: &str,
-        line: u32,
-        character: u32,
-    ) -> Option<(Transaction<'a>, Handle, TextSize)> {
+    ) -> Result<(Transaction<'a>, Handle, Module, Option<usize>), TspRequestError> {
+        let transaction = self.state.transaction();
+        let (handle, module_info, notebook_cell) = self.open_in_transaction(&transaction, uri)?;
+        Ok((transaction, handle, module_info, notebook_cell))
+    }
+
+    /// As [`Self::open_for_tsp`], but loads the module into an existing
+    /// `transaction` so several TSP queries can share it.
+    fn open_in_transaction(
+        &self,
+        transaction: &Transaction,
+        uri: &str,
+    ) -> Result<(Handle, Module, Option<usize>), TspRequestError> {
         let url = Url::parse(uri)
             .ok()
-            .or_else(|| Url::from_file_path(uri).ok())?;
-        let path = self.path_for_uri_or_notebook_cell(&url)?;
+            .or_else(|| Url::from_file_path(uri).ok())
+            .ok_or(TspRequestError::NoFilePath)?;
+        let path = self
+            .path_for_uri_or_notebook_cell(&url)
+            .ok_or(TspRequestError::NoFilePath)?;
+        if self.workspaces.get_with(path.clone(), |(_, workspace)| {
+            workspace.disable_language_services
+        }) {
+            return Err(TspRequestError::LanguageServicesDisabled);
+        }
         let notebook_cell = self.maybe_get_code_cell_index(&url);
 
         let handle = make_open_handle(&self.state, &path);
-        let transaction = self.state.transaction();
-        let module_info = transaction.get_module_info(&handle)?;
-        let position =
-            module_info.from_lsp_position(lsp_types::Position { line, character }, notebook_cell);
-        Some((transaction, handle, position))
+        let module_info = transaction
+            .get_module_info(&handle)
+            .ok_or(TspRequestError::ModuleLoadFailed)?;
+        Ok((handle, module_info, notebook_cell))
+    }
+
+    /// The computed type of the node spanning `range` in `module_info`,
+    /// converted to the TSP wire format against `transaction`.
+    fn computed_type_in_transaction(
+        &self,
+        transaction: &Transaction,
+        handle: &Handle,
+        module_info: &Module,
+        notebook_cell: Option<usize>,
+        range: &tsp_types::Range,
+    ) -> Result<Option<tsp_types::Type>, TspRequestError> {
+        let start = module_info.from_lsp_position(
+            lsp_types::Position {
+                line: range.start.line,
+                character: range.start.character,
+            },
+            notebook_cell,
+            PositionEncoding::Utf16,
+        );
+        let end = module_info.from_lsp_position(
+            lsp_types::Position {
+                line: range.end.line,
+                character: range.end.character,
+            },
+            notebook_cell,
+            PositionEncoding::Utf16,
+        );
+        if end < start {
+            return Err(TspRequestError::PositionOutOfRange);
+        }
+        let range = TextRange::new(start, end);
+        // Range-aware lookup: a whole call-expression range resolves to the
+        // call's result type, other ranges to the declaration-preserving type.
+        // Convert against the *same* transaction that produced `ty`, so export
+        // location resolution stays warm and cannot hit a cold `get_stdlib`.
+        Ok(transaction
+            .get_computed_type_at_range(handle, range)
+            .map(|ty| self.convert_type_in_transaction(transaction, handle, &ty)))
+    }
+
+    /// As [`Self::open_for_tsp`], also resolving `(line, character)` to an
+    /// in-file position.
+    fn open_at_position<'a>(
+        &'a self,
+        uri: &str,
+        line: u32,
+        character: u32,
+    ) -> Result<(Transaction<'a>, Handle, TextSize), TspRequestError> {
+        let (transaction, handle, module_info, notebook_cell) = self.open_for_tsp(uri)?;
+        let position = module_info.from_lsp_position(
+            lsp_types::Position { line, character },
+            notebook_cell,
+            PositionEncoding::Utf16,
+        );
+        Ok((transaction, handle, position))
     }
 
     /// Convert `ty` to the TSP wire format, resolving every declaration location
@@ -6369,7 +8231,10 @@ pub(crate) fn resolve_export_location(
         .import_handle(source_handle, module_name, None)
         .finding()?;
     let (module, range) = transaction.lookup_export_location(&target_handle, name)?;
-    Some((module.path().dupe(), module.to_lsp_range(range)))
+    Some((
+        module.path().dupe(),
+        module.to_lsp_range(range, PositionEncoding::Utf16),
+    ))
 }
 
 impl TspInterface for Server {
@@ -6452,15 +8317,15 @@ impl TspInterface for Server {
         // than `structured_import_lookup_path()` because the latter also
         // includes build-system paths and fallback search paths that are
         // internal heuristics, not stable directories the client should depend
-        // on.
+        // on. Paths are canonicalized and deduplicated in order (config
+        // first), and paths that don't exist on disk are skipped.
         let mut seen = std::collections::HashSet::new();
         let mut paths: Vec<String> = config
             .search_path()
             .chain(config.site_package_path())
             .filter_map(|p| {
-                Url::from_file_path(p.canonicalize().unwrap_or_else(|_| p.clone()))
-                    .ok()
-                    .map(|u| u.to_string())
+                let canonical = p.canonicalize().ok()?;
+                Url::from_file_path(canonical).ok().map(|u| u.to_string())
             })
             .filter(|uri| seen.insert(uri.clone()))
             .collect();
@@ -6480,14 +8345,20 @@ impl TspInterface for Server {
         Ok(paths)
     }
 
-    fn type_at_position(&self, uri: &str, line: u32, character: u32) -> Option<tsp_types::Type> {
+    fn type_at_position(
+        &self,
+        uri: &str,
+        line: u32,
+        character: u32,
+    ) -> Result<Option<tsp_types::Type>, TspRequestError> {
         let (transaction, handle, position) = self.open_at_position(uri, line, character)?;
         // For TSP, return the raw declared type without coercing callees in
         // call position. This keeps the function's `Declaration::Regular`
         // intact on the wire, which TSP clients need to re-resolve the
         // signature (parameters, overloads) from source.
-        let ty = transaction.get_type_at_preserving_declaration(&handle, position)?;
-        Some(self.convert_type_in_transaction(&transaction, &handle, &ty))
+        Ok(transaction
+            .get_type_at_preserving_declaration(&handle, position)
+            .map(|ty| self.convert_type_in_transaction(&transaction, &handle, &ty)))
     }
 
     fn computed_type_at_range(
@@ -6497,37 +8368,48 @@ impl TspInterface for Server {
         start_character: u32,
         end_line: u32,
         end_character: u32,
-    ) -> Option<tsp_types::Type> {
-        let url = Url::parse(uri)
-            .ok()
-            .or_else(|| Url::from_file_path(uri).ok())?;
-        let path = self.path_for_uri_or_notebook_cell(&url)?;
-        let notebook_cell = self.maybe_get_code_cell_index(&url);
-
-        let handle = make_open_handle(&self.state, &path);
-        let transaction = self.state.transaction();
-        let module_info = transaction.get_module_info(&handle)?;
-        let start = module_info.from_lsp_position(
-            lsp_types::Position {
+    ) -> Result<Option<tsp_types::Type>, TspRequestError> {
+        let (transaction, handle, module_info, notebook_cell) = self.open_for_tsp(uri)?;
+        let range = tsp_types::Range {
+            start: tsp_types::Position {
                 line: start_line,
                 character: start_character,
             },
-            notebook_cell,
-        );
-        let end = module_info.from_lsp_position(
-            lsp_types::Position {
+            end: tsp_types::Position {
                 line: end_line,
                 character: end_character,
             },
+        };
+        self.computed_type_in_transaction(
+            &transaction,
+            &handle,
+            &module_info,
             notebook_cell,
-        );
-        let range = TextRange::new(start, end);
-        // Range-aware lookup: a whole call-expression range resolves to the
-        // call's result type, other ranges to the declaration-preserving type.
-        // Convert against the *same* transaction that produced `ty`, so export
-        // location resolution stays warm and cannot hit a cold `get_stdlib`.
-        let ty = transaction.get_computed_type_at_range(&handle, range)?;
-        Some(self.convert_type_in_transaction(&transaction, &handle, &ty))
+            &range,
+        )
+    }
+
+    fn computed_types_at_ranges(
+        &self,
+        nodes: &[(&str, tsp_types::Range)],
+    ) -> Result<Vec<Option<tsp_types::Type>>, TspRequestError> {
+        let transaction = self.state.transaction();
+        let mut opened = HashMap::new();
+        let mut types = Vec::with_capacity(nodes.len());
+        for (uri, range) in nodes {
+            let (handle, module_info, notebook_cell) = match opened.entry(*uri) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(self.open_in_transaction(&transaction, uri)?),
+            };
+            types.push(self.computed_type_in_transaction(
+                &transaction,
+                handle,
+                module_info,
+                *notebook_cell,
+                range,
+            )?);
+        }
+        Ok(types)
     }
 
     fn expected_type_at_position(
@@ -6535,15 +8417,15 @@ impl TspInterface for Server {
         uri: &str,
         line: u32,
         character: u32,
-    ) -> Option<tsp_types::Type> {
+    ) -> Result<Option<tsp_types::Type>, TspRequestError> {
         let (transaction, handle, position) = self.open_at_position(uri, line, character)?;
         // Prefer the contextually expected type; fall back to the computed type
         // (preserving declarations) so the result is meaningful even outside an
         // expected-type context.
-        let ty = transaction
+        Ok(transaction
             .get_expected_type_at(&handle, position)
-            .or_else(|| transaction.get_type_at_preserving_declaration(&handle, position))?;
-        Some(self.convert_type_in_transaction(&transaction, &handle, &ty))
+            .or_else(|| transaction.get_type_at_preserving_declaration(&handle, position))
+            .map(|ty| self.convert_type_in_transaction(&transaction, &handle, &ty)))
     }
 
     fn resolve_uri_to_path(&self, uri: &Url) -> Option<PathBuf> {
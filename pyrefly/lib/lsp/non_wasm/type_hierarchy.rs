@@ -13,6 +13,7 @@ use ruff_python_ast::AnyNodeRef;
 use ruff_python_ast::ModModule;
 use ruff_python_ast::Stmt;
 use ruff_python_ast::StmtClassDef;
+use ruff_source_file::PositionEncoding;
 use ruff_text_size::Ranged;
 use ruff_text_size::TextSize;
 
@@ -32,6 +33,7 @@ pub fn prepare_type_hierarchy_item(
     class_def: &StmtClassDef,
     module: &Module,
     uri: lsp_types::Url,
+    position_encoding: PositionEncoding,
 ) -> TypeHierarchyItem {
     TypeHierarchyItem {
         name: class_def.name.id.to_string(),
@@ -39,8 +41,8 @@ pub fn prepare_type_hierarchy_item(
         tags: None,
         detail: Some(format!("{}.{}", module.name(), class_def.name.id)),
         uri,
-        range: module.to_lsp_range(class_def.range()),
-        selection_range: module.to_lsp_range(class_def.name.range),
+        range: module.to_lsp_range(class_def.range(), position_encoding),
+        selection_range: module.to_lsp_range(class_def.name.range, position_encoding),
         data: None,
     }
 }
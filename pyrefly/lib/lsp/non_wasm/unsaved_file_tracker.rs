@@ -90,6 +90,13 @@ impl UnsavedFileTracker {
         self.uri_to_path.read().get(uri).cloned()
     }
 
+    /// The original URI of the unsaved document backed by `path`, so results
+    /// computed against the synthesized path can be reported under the URI the
+    /// client knows.
+    pub fn uri_for_path(&self, path: &Path) -> Option<Url> {
+        self.open_file_uris.read().get(path).cloned()
+    }
+
     pub fn forget_uri_path(&self, uri: &Url) -> Option<PathBuf> {
         let removed = self.uri_to_path.write().remove(uri);
         if let Some(path) = &removed {
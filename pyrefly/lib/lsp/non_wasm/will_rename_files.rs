@@ -6,6 +6,7 @@
  */
 
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 
 use lsp_types::DocumentChangeOperation;
@@ -25,6 +26,7 @@ use pyrefly_util::lined_buffer::LinedBuffer;
 use pyrefly_util::lock::RwLock;
 use rayon::prelude::*;
 use ruff_python_ast::Stmt;
+use ruff_source_file::PositionEncoding;
 use ruff_text_size::Ranged;
 use tracing::info;
 
@@ -38,9 +40,13 @@ use crate::state::state::Transaction;
 /// Visitor that looks for imports of an old module name and creates TextEdits to update them
 struct RenameUsageVisitor<'a> {
     edits: Vec<TextEdit>,
+    /// Whether the file has a plain `import old` (or `import old.sub`) without an `as` name,
+    /// which means the module is also referred to by its qualified name in the file's body.
+    has_qualified_import: bool,
     old_module_name: &'a ModuleName,
     new_module_name: &'a ModuleName,
     lined_buffer: &'a LinedBuffer,
+    position_encoding: PositionEncoding,
 }
 
 impl<'a> RenameUsageVisitor<'a> {
@@ -48,12 +54,15 @@ impl<'a> RenameUsageVisitor<'a> {
         old_module_name: &'a ModuleName,
         new_module_name: &'a ModuleName,
         lined_buffer: &'a LinedBuffer,
+        position_encoding: PositionEncoding,
     ) -> Self {
         Self {
             edits: Vec::new(),
+            has_qualified_import: false,
             old_module_name,
             new_module_name,
             lined_buffer,
+            position_encoding,
         }
     }
 
@@ -67,6 +76,9 @@ impl<'a> RenameUsageVisitor<'a> {
                             .as_str()
                             .starts_with(&format!("{}.", self.old_module_name.as_str()))
                     {
+                        if alias.asname.is_none() {
+                            self.has_qualified_import = true;
+                        }
                         // Replace the module name
                         let new_import_name = if imported_module == *self.old_module_name {
                             self.new_module_name.as_str().to_owned()
@@ -79,7 +91,11 @@ impl<'a> RenameUsageVisitor<'a> {
                         };
 
                         self.edits.push(TextEdit {
-                            range: self.lined_buffer.to_lsp_range(alias.name.range(), None),
+                            range: self.lined_buffer.to_lsp_range(
+                                alias.name.range(),
+                                None,
+                                self.position_encoding,
+                            ),
                             new_text: new_import_name,
                         });
                     }
@@ -105,7 +121,11 @@ impl<'a> RenameUsageVisitor<'a> {
                         };
 
                         self.edits.push(TextEdit {
-                            range: self.lined_buffer.to_lsp_range(module.range(), None),
+                            range: self.lined_buffer.to_lsp_range(
+                                module.range(),
+                                None,
+                                self.position_encoding,
+                            ),
                             new_text: new_import_name,
                         });
                     }
@@ -115,11 +135,156 @@ impl<'a> RenameUsageVisitor<'a> {
         }
     }
 
-    fn take_edits(self) -> Vec<TextEdit> {
-        self.edits
+    fn take_edits(self) -> (Vec<TextEdit>, bool) {
+        (self.edits, self.has_qualified_import)
     }
 }
 
+/// The import edits for renaming a module, grouped by the URI of the file to edit.
+#[derive(Default)]
+pub(crate) struct ImportEditsForRename {
+    pub edits: Vec<(Url, Vec<TextEdit>)>,
+    /// Files that import the module with a plain `import`, sorted by URI. Only their import
+    /// statements are edited, so their qualified uses (`module.attr`) still name the old module.
+    pub qualified_importers: Vec<Url>,
+}
+
+/// Compute the import edits needed in the files that depend on the module at `old_path` when
+/// it is renamed to `new_path`.
+pub(crate) fn import_edits_for_rename(
+    state: &State,
+    transaction: &Transaction<'_>,
+    old_path: &Path,
+    new_path: &Path,
+    path_remapper: Option<&PathRemapper>,
+    position_encoding: PositionEncoding,
+) -> ImportEditsForRename {
+    // Important: only use filesystem handle (never use an in-memory handle)
+    let module_path = ModulePath::filesystem(old_path.to_owned());
+    let old_handle = handle_from_module_path(state, module_path.clone());
+
+    // Convert paths to module names
+    let old_module_name = old_handle.module();
+
+    let config = state
+        .config_finder()
+        .python_file(old_handle.module_kind(), &module_path);
+    let new_module_name = ModuleName::from_path(
+        new_path,
+        config.search_path().chain(
+            config
+                .fallback_search_path
+                .for_directory(new_path.parent())
+                .iter(),
+        ),
+        &config.extra_file_extensions,
+    );
+
+    let new_module_name = match new_module_name {
+        Some(name) => name,
+        None => {
+            info!("    Could not determine new module name, skipping");
+            return ImportEditsForRename::default();
+        }
+    };
+
+    info!(
+        "    Module rename: {} -> {}",
+        old_module_name, new_module_name
+    );
+
+    // If module names are the same, no need to update imports
+    if old_module_name == new_module_name {
+        info!("    Module names are the same, skipping");
+        return ImportEditsForRename::default();
+    }
+
+    // Use get_transitive_rdeps to find all files that depend on this module
+    let rdeps = transaction.get_transitive_rdeps(old_handle.clone());
+
+    info!("    Found {} transitive rdeps", rdeps.len());
+
+    // Deduplicate rdeps by module path string (get_transitive_rdeps might return duplicates
+    // with different variants like FileSystem vs Memory for the same path)
+    let unique_rdeps: Vec<_> = {
+        let mut seen = std::collections::HashSet::new();
+        rdeps
+            .into_iter()
+            .filter(|handle| seen.insert(handle.path().as_path().to_owned()))
+            .collect()
+    };
+
+    // Visit each dependent file to find and update imports (parallelized)
+    let per_file: Vec<(Url, Vec<TextEdit>, bool)> = unique_rdeps
+        .into_par_iter()
+        .filter_map(|rdep_handle| {
+            let module_info = transaction.get_module_info(&rdep_handle)?;
+
+            let ast = Ast::parse(module_info.contents(), module_info.source_type()).0;
+            let mut visitor = RenameUsageVisitor::new(
+                &old_module_name,
+                &new_module_name,
+                module_info.lined_buffer(),
+                position_encoding,
+            );
+
+            for stmt in &ast.body {
+                visitor.visit_stmt(stmt);
+            }
+
+            let (edits_for_file, has_qualified_import) = visitor.take_edits();
+
+            if !edits_for_file.is_empty() {
+                let uri = module_info_to_uri(&module_info, path_remapper)?;
+                info!(
+                    "    Found {} import(s) to update in {}",
+                    edits_for_file.len(),
+                    uri
+                );
+                Some((uri, edits_for_file, has_qualified_import))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let mut result = ImportEditsForRename::default();
+    for (uri, edits, has_qualified_import) in per_file {
+        if has_qualified_import {
+            result.qualified_importers.push(uri.clone());
+        }
+        result.edits.push((uri, edits));
+    }
+    result
+        .qualified_importers
+        .sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    result
+}
+
+/// Turn per-file edits into `documentChanges` operations, sorted by URI for deterministic
+/// ordering.
+pub(crate) fn text_document_edit_operations(
+    changes: HashMap<Url, Vec<TextEdit>>,
+) -> Vec<DocumentChangeOperation> {
+    let mut sorted_changes: Vec<(Url, Vec<TextEdit>)> = changes.into_iter().collect();
+    sorted_changes.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+    sorted_changes
+        .into_iter()
+        .map(|(uri, edits)| {
+            DocumentChangeOperation::Edit(TextDocumentEdit {
+                text_document: OptionalVersionedTextDocumentIdentifier {
+                    uri,
+                    version: None, // None means "any version"
+                },
+                edits: edits
+                    .into_iter()
+                    .map(TextEditOrAnnotatedOrSnippet::TextEdit)
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
 /// Handle workspace/willRenameFiles request to update imports when files are renamed.
 ///
 /// This function:
@@ -137,6 +302,7 @@ pub fn will_rename_files(
     params: RenameFilesParams,
     supports_document_changes: bool,
     path_remapper: Option<&PathRemapper>,
+    position_encoding: PositionEncoding,
 ) -> Option<WorkspaceEdit> {
     info!(
         "will_rename_files called with {} file(s)",
@@ -193,96 +359,16 @@ pub fn will_rename_files(
             continue;
         }
 
-        // Important: only use filesystem handle (never use an in-memory handle)
-        let module_path = ModulePath::filesystem(old_path.clone());
-        let old_handle = handle_from_module_path(state, module_path.clone());
-
-        // Convert paths to module names
-        let old_module_name = old_handle.module();
-
-        let config = state
-            .config_finder()
-            .python_file(old_handle.module_kind(), &module_path);
-        let new_module_name = ModuleName::from_path(
+        for (uri, edits) in import_edits_for_rename(
+            state,
+            transaction,
+            &old_path,
             &new_path,
-            config.search_path().chain(
-                config
-                    .fallback_search_path
-                    .for_directory(new_path.parent())
-                    .iter(),
-            ),
-            &config.extra_file_extensions,
-        );
-
-        let new_module_name = match new_module_name {
-            Some(name) => name,
-            None => {
-                info!("    Could not determine new module name, skipping");
-                continue;
-            }
-        };
-
-        info!(
-            "    Module rename: {} -> {}",
-            old_module_name, new_module_name
-        );
-
-        // If module names are the same, no need to update imports
-        if old_module_name == new_module_name {
-            info!("    Module names are the same, skipping");
-            continue;
-        }
-
-        // Use get_transitive_rdeps to find all files that depend on this module
-        let rdeps = transaction.get_transitive_rdeps(old_handle.clone());
-
-        info!("    Found {} transitive rdeps", rdeps.len());
-
-        // Deduplicate rdeps by module path string (get_transitive_rdeps might return duplicates
-        // with different variants like FileSystem vs Memory for the same path)
-        let unique_rdeps: Vec<_> = {
-            let mut seen = std::collections::HashSet::new();
-            rdeps
-                .into_iter()
-                .filter(|handle| seen.insert(handle.path().as_path().to_owned()))
-                .collect()
-        };
-
-        // Visit each dependent file to find and update imports (parallelized)
-        let rdeps_changes: Vec<(Url, Vec<TextEdit>)> = unique_rdeps
-            .into_par_iter()
-            .filter_map(|rdep_handle| {
-                let module_info = transaction.get_module_info(&rdep_handle)?;
-
-                let ast = Ast::parse(module_info.contents(), module_info.source_type()).0;
-                let mut visitor = RenameUsageVisitor::new(
-                    &old_module_name,
-                    &new_module_name,
-                    module_info.lined_buffer(),
-                );
-
-                for stmt in &ast.body {
-                    visitor.visit_stmt(stmt);
-                }
-
-                let edits_for_file = visitor.take_edits();
-
-                if !edits_for_file.is_empty() {
-                    let uri = module_info_to_uri(&module_info, path_remapper)?;
-                    info!(
-                        "    Found {} import(s) to update in {}",
-                        edits_for_file.len(),
-                        uri
-                    );
-                    Some((uri, edits_for_file))
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        // Merge results into all_changes
-        for (uri, edits) in rdeps_changes {
+            path_remapper,
+            position_encoding,
+        )
+        .edits
+        {
             all_changes.entry(uri).or_default().extend(edits);
         }
     }
@@ -298,25 +384,7 @@ pub fn will_rename_files(
 
         if supports_document_changes {
             // Use document_changes for better ordering guarantees and version checking
-            // Sort by URI for deterministic ordering
-            let mut sorted_changes: Vec<(Url, Vec<TextEdit>)> = all_changes.into_iter().collect();
-            sorted_changes.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
-
-            let document_changes: Vec<DocumentChangeOperation> = sorted_changes
-                .into_iter()
-                .map(|(uri, edits)| {
-                    DocumentChangeOperation::Edit(TextDocumentEdit {
-                        text_document: OptionalVersionedTextDocumentIdentifier {
-                            uri,
-                            version: None, // None means "any version"
-                        },
-                        edits: edits
-                            .into_iter()
-                            .map(TextEditOrAnnotatedOrSnippet::TextEdit)
-                            .collect(),
-                    })
-                })
-                .collect();
+            let document_changes = text_document_edit_operations(all_changes);
 
             Some(WorkspaceEdit {
                 document_changes: Some(DocumentChanges::Operations(document_changes)),
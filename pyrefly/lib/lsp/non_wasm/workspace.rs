@@ -7,6 +7,7 @@
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::iter;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -16,6 +17,8 @@ use lsp_types::WorkspaceFoldersChangeEvent;
 use pyrefly_build::source_db::SourceDatabase;
 use pyrefly_config::config::FallbackSearchPath;
 use pyrefly_config::resolve_unconfigured::UnconfiguredOverride;
+use pyrefly_python::sys_info::PythonPlatform;
+use pyrefly_python::sys_info::PythonVersion;
 use pyrefly_util::arc_id::ArcId;
 use pyrefly_util::arc_id::WeakArcId;
 use pyrefly_util::lock::Mutex;
@@ -35,6 +38,8 @@ use crate::commands::config_finder::standard_config_finder;
 use crate::config::config::ConfigFile;
 use crate::config::config::ConfigSource;
 use crate::config::environment::environment::PythonEnvironment;
+use crate::config::error_kind::ErrorKind;
+use crate::config::error_kind::Severity;
 use crate::config::finder::ConfigFinder;
 use crate::state::lsp::DisplayTypeErrors;
 use crate::state::lsp::ImportFormat;
@@ -42,7 +47,7 @@ use crate::state::lsp::InlayHintConfig;
 use crate::state::lsp::TypeCheckingMode;
 
 /// Information about the Python environment provided by this workspace.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PythonInfo {
     /// The path to the interpreter used to query this `PythonInfo`'s [`PythonEnvironment`].
     interpreter: PathBuf,
@@ -55,8 +60,14 @@ pub struct PythonInfo {
 }
 
 impl PythonInfo {
-    pub fn new(interpreter: PathBuf) -> Self {
-        let (env, query_error) = PythonEnvironment::get_interpreter_env(&interpreter);
+    /// Query `interpreter` for its environment. The cached result is reused unless the
+    /// interpreter was modified since, or `force_refresh` is set.
+    fn query(interpreter: PathBuf, force_refresh: bool) -> Self {
+        let (env, query_error) = if force_refresh {
+            PythonEnvironment::refresh_interpreter_env(&interpreter)
+        } else {
+            PythonEnvironment::get_interpreter_env(&interpreter)
+        };
         if let Some(error) = query_error {
             error!("{error}");
         }
@@ -72,6 +83,8 @@ pub struct Workspace {
     pub disable_language_services: bool,
     pub disabled_language_services: Option<DisabledLanguageServices>,
     pub runnable_code_lens: bool,
+    /// Allow debugging commands such as `pyrefly.printInternalType`.
+    pub enable_debug_commands: bool,
     pub display_type_errors: Option<DisplayTypeErrors>,
     pub type_checking_mode: Option<TypeCheckingMode>,
     /// Workspace-scoped IDE-only kill switch. When `true`, all type-error
@@ -83,8 +96,24 @@ pub struct Workspace {
     pub disable_type_errors: bool,
     pub lsp_analysis_config: Option<LspAnalysisConfig>,
     pub stream_diagnostics: Option<bool>,
+    /// When a file is saved, also publish diagnostics for the non-open files that depend on it.
+    pub publish_dependent_diagnostics_on_save: Option<bool>,
+    /// Point out `# pyrefly: ignore` comments that suppress nothing, even when the config
+    /// disables `unused-ignore`.
+    pub show_unused_ignores: Option<bool>,
+    /// Upper bound on the number of project files indexed per config, for very large projects.
+    pub max_indexed_files: Option<usize>,
+    /// Upper bound on the number of locations returned by find-references.
+    pub max_references: Option<usize>,
+    /// Check files in this workspace as if `sys.platform` were this value.
+    pub python_platform: Option<PythonPlatform>,
+    /// Check files in this workspace as if `sys.version_info` were this version.
+    pub python_version: Option<PythonVersion>,
     pub diagnostic_mode: Option<DiagnosticMode>,
     pub workspace_config: Option<PathBuf>,
+    /// IDE-only severity overrides keyed by error kind name (e.g. `bad-assignment`).
+    /// Applied on top of whatever severity the resolved config assigned.
+    pub diagnostic_severity: HashMap<String, Severity>,
 }
 
 impl Workspace {
@@ -172,8 +201,21 @@ impl ConfigConfigurer for WorkspaceConfigConfigurer {
                     // skip interpreter query because we already have the interpreter from the workspace
                     config.interpreters.skip_interpreter_query = true;
                 }
+                // Applied last so they win over both the config file and the interpreter.
+                if let Some(python_platform) = &w.python_platform {
+                    config.python_environment.python_platform = Some(python_platform.clone());
+                }
+                if let Some(python_version) = w.python_version {
+                    config.python_environment.python_version = Some(python_version);
+                }
             })
         };
+        // Like the checking mode above, a rootless config falls back to the default workspace.
+        config.max_indexed_files = self
+            .0
+            .get_with(root.map(Path::to_owned).unwrap_or_default(), |(_, w)| {
+                w.max_indexed_files
+            });
 
         // we print the errors here instead of returning them since
         // it gives the most immediate feedback for config loading errors
@@ -245,13 +287,25 @@ struct PyreflyClientConfig {
     disable_language_services: Option<bool>,
     extra_paths: Option<Vec<PathBuf>>,
     runnable_code_lens: Option<bool>,
+    enable_debug_commands: Option<bool>,
     diagnostic_mode: Option<DiagnosticMode>,
     #[serde(default, deserialize_with = "deserialize_analysis")]
     analysis: Option<LspAnalysisConfig>,
     #[serde(default)]
     disabled_language_services: Option<DisabledLanguageServices>,
     stream_diagnostics: Option<bool>,
+    publish_dependent_diagnostics_on_save: Option<bool>,
+    show_unused_ignores: Option<bool>,
+    max_indexed_files: Option<usize>,
+    max_references: Option<usize>,
+    /// Overrides the `sys.platform` value used for checking, e.g. `"win32"`.
+    python_platform: Option<String>,
+    /// Overrides the `sys.version_info` value used for checking, e.g. `"3.13"`.
+    python_version: Option<String>,
     config_path: Option<PathBuf>,
+    /// Maps error kind names to `"error"`, `"warning"`, `"info"` or `"none"`.
+    /// See [`parse_diagnostic_severity`].
+    diagnostic_severity: Option<HashMap<String, String>>,
 }
 
 #[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
@@ -328,6 +382,14 @@ impl DisabledLanguageServices {
     }
 }
 
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoverConfig {
+    /// Show every name in a hovered type with its module, e.g. `foo.Widget` instead of `Widget`.
+    #[serde(default)]
+    pub fully_qualified_names: bool,
+}
+
 /// https://code.visualstudio.com/docs/python/settings-reference#_pylance-language-server
 #[derive(Clone, Copy, Debug, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -335,6 +397,9 @@ pub struct LspAnalysisConfig {
     pub diagnostic_mode: Option<DiagnosticMode>,
     pub import_format: Option<ImportFormat>,
     pub complete_function_parens: Option<bool>,
+    /// When true alongside `complete_function_parens`, function completions insert a
+    /// snippet with a tab stop for each required parameter. Defaults to false.
+    pub complete_function_params: Option<bool>,
     /// When false, completions no longer offer symbols that require adding a new
     /// import (and the corresponding import edit). Defaults to true, matching Pylance.
     pub auto_import_completions: Option<bool>,
@@ -342,6 +407,9 @@ pub struct LspAnalysisConfig {
     // TODO: this is not a pylance setting. it should be in pyrefly settings
     #[serde(default)]
     pub show_hover_go_to_links: Option<bool>,
+    /// Longest type, in characters, that hover renders before truncating it.
+    pub max_hover_length: Option<usize>,
+    pub hover: Option<HoverConfig>,
 }
 
 fn deserialize_analysis<'de, D>(deserializer: D) -> Result<Option<LspAnalysisConfig>, D::Error>
@@ -415,6 +483,40 @@ fn resolve_disable_type_errors(new: bool, legacy: Option<DisplayTypeErrors>) ->
     new || matches!(legacy, Some(DisplayTypeErrors::ForceOff))
 }
 
+/// Converts the client's `diagnosticSeverity` map into [`Severity`] values.
+/// The accepted names follow the LSP severity names rather than pyrefly's
+/// config syntax, with `"none"` mapping to [`Severity::Ignore`]. Entries with
+/// an unrecognized severity are dropped with a warning.
+fn parse_diagnostic_severity(raw: HashMap<String, String>) -> HashMap<String, Severity> {
+    raw.into_iter()
+        .filter_map(|(kind, severity)| {
+            let severity = match severity.as_str() {
+                "error" => Severity::Error,
+                "warning" => Severity::Warn,
+                "info" => Severity::Info,
+                "none" => Severity::Ignore,
+                _ => {
+                    warn!("Ignoring unknown diagnostic severity `{severity}` for `{kind}`");
+                    return None;
+                }
+            };
+            Some((kind, severity))
+        })
+        .collect()
+}
+
+/// Parses the client's `pythonVersion` override. An unparseable version is dropped with a
+/// warning, leaving the version from the config or interpreter in place.
+fn parse_python_version_override(raw: &str) -> Option<PythonVersion> {
+    match raw.parse() {
+        Ok(version) => Some(version),
+        Err(e) => {
+            warn!("Ignoring invalid `pythonVersion` setting `{raw}`: {e}");
+            None
+        }
+    }
+}
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct LspConfig {
@@ -533,13 +635,33 @@ impl Workspaces {
             if let Some(runnable_code_lens) = pyrefly.runnable_code_lens {
                 self.update_runnable_code_lens(scope_uri, runnable_code_lens);
             }
+            if let Some(enable_debug_commands) = pyrefly.enable_debug_commands {
+                self.update_enable_debug_commands(scope_uri, enable_debug_commands);
+            }
             if let Some(stream_diagnostics) = pyrefly.stream_diagnostics {
                 self.update_stream_diagnostics(scope_uri, stream_diagnostics);
             }
+            if let Some(publish_dependent_diagnostics_on_save) =
+                pyrefly.publish_dependent_diagnostics_on_save
+            {
+                self.update_publish_dependent_diagnostics_on_save(
+                    scope_uri,
+                    publish_dependent_diagnostics_on_save,
+                );
+            }
+            if let Some(show_unused_ignores) = pyrefly.show_unused_ignores {
+                self.update_show_unused_ignores(scope_uri, show_unused_ignores);
+            }
+            if let Some(max_indexed_files) = pyrefly.max_indexed_files {
+                self.update_max_indexed_files(modified, scope_uri, max_indexed_files);
+            }
+            if let Some(max_references) = pyrefly.max_references {
+                self.update_max_references(scope_uri, max_references);
+            }
             if let Some(diagnostic_mode) = pyrefly.diagnostic_mode {
                 self.update_diagnostic_mode(scope_uri, diagnostic_mode);
             }
-            // Always write a definitive value for each of these three
+            // Always write a definitive value for each of the following
             // settings — including `None` when absent — so that removing a
             // setting from VS Code clears the previously-stored workspace
             // value. The `update_*` helpers compare against the current
@@ -560,6 +682,20 @@ impl Workspaces {
                     pyrefly.display_type_errors,
                 ),
             );
+            self.update_diagnostic_severity(
+                modified,
+                scope_uri,
+                parse_diagnostic_severity(pyrefly.diagnostic_severity.unwrap_or_default()),
+            );
+            self.update_python_environment_overrides(
+                modified,
+                scope_uri,
+                pyrefly.python_platform.as_deref().map(PythonPlatform::new),
+                pyrefly
+                    .python_version
+                    .as_deref()
+                    .and_then(parse_python_version_override),
+            );
             // Handle analysis config nested under pyrefly (e.g., pyrefly.analysis)
             if let Some(analysis) = pyrefly.analysis {
                 self.update_ide_settings(modified, scope_uri, analysis);
@@ -628,6 +764,20 @@ impl Workspaces {
         }
     }
 
+    fn update_enable_debug_commands(&self, scope_uri: &Option<Url>, enable_debug_commands: bool) {
+        let mut workspaces = self.workspaces.write();
+        match scope_uri {
+            Some(scope_uri) => {
+                if let Ok(path) = scope_uri.to_file_path()
+                    && let Some(workspace) = workspaces.get_mut(&path)
+                {
+                    workspace.enable_debug_commands = enable_debug_commands;
+                }
+            }
+            None => self.default.write().enable_debug_commands = enable_debug_commands,
+        }
+    }
+
     /// Update streamDiagnostics setting for scope_uri, None if default workspace
     fn update_stream_diagnostics(&self, scope_uri: &Option<Url>, stream_diagnostics: bool) {
         let mut workspaces = self.workspaces.write();
@@ -643,6 +793,86 @@ impl Workspaces {
         }
     }
 
+    /// Update publishDependentDiagnosticsOnSave setting for scope_uri, None if default workspace
+    fn update_publish_dependent_diagnostics_on_save(
+        &self,
+        scope_uri: &Option<Url>,
+        publish_dependent_diagnostics_on_save: bool,
+    ) {
+        let mut workspaces = self.workspaces.write();
+        match scope_uri {
+            Some(scope_uri) => {
+                if let Ok(path) = scope_uri.to_file_path()
+                    && let Some(workspace) = workspaces.get_mut(&path)
+                {
+                    workspace.publish_dependent_diagnostics_on_save =
+                        Some(publish_dependent_diagnostics_on_save);
+                }
+            }
+            None => {
+                self.default.write().publish_dependent_diagnostics_on_save =
+                    Some(publish_dependent_diagnostics_on_save)
+            }
+        }
+    }
+
+    /// Update showUnusedIgnores setting for scope_uri, None if default workspace
+    fn update_show_unused_ignores(&self, scope_uri: &Option<Url>, show_unused_ignores: bool) {
+        let mut workspaces = self.workspaces.write();
+        match scope_uri {
+            Some(scope_uri) => {
+                if let Ok(path) = scope_uri.to_file_path()
+                    && let Some(workspace) = workspaces.get_mut(&path)
+                {
+                    workspace.show_unused_ignores = Some(show_unused_ignores);
+                }
+            }
+            None => self.default.write().show_unused_ignores = Some(show_unused_ignores),
+        }
+    }
+
+    /// Update maxIndexedFiles setting for scope_uri, None if default workspace. Loaded configs
+    /// carry the cap, so changing it invalidates them.
+    fn update_max_indexed_files(
+        &self,
+        modified: &mut bool,
+        scope_uri: &Option<Url>,
+        max_indexed_files: usize,
+    ) {
+        let mut workspaces = self.workspaces.write();
+        let update = |workspace: &mut Workspace, modified: &mut bool| {
+            if workspace.max_indexed_files != Some(max_indexed_files) {
+                *modified = true;
+                workspace.max_indexed_files = Some(max_indexed_files);
+            }
+        };
+        match scope_uri {
+            Some(scope_uri) => {
+                if let Ok(path) = scope_uri.to_file_path()
+                    && let Some(workspace) = workspaces.get_mut(&path)
+                {
+                    update(workspace, modified);
+                }
+            }
+            None => update(&mut self.default.write(), modified),
+        }
+    }
+
+    /// Update maxReferences setting for scope_uri, None if default workspace
+    fn update_max_references(&self, scope_uri: &Option<Url>, max_references: usize) {
+        let mut workspaces = self.workspaces.write();
+        match scope_uri {
+            Some(scope_uri) => {
+                if let Ok(path) = scope_uri.to_file_path()
+                    && let Some(workspace) = workspaces.get_mut(&path)
+                {
+                    workspace.max_references = Some(max_references);
+                }
+            }
+            None => self.default.write().max_references = Some(max_references),
+        }
+    }
+
     /// Update diagnosticMode setting for scope_uri, None if default workspace
     fn update_diagnostic_mode(&self, scope_uri: &Option<Url>, diagnostic_mode: DiagnosticMode) {
         let mut workspaces = self.workspaces.write();
@@ -740,6 +970,33 @@ impl Workspaces {
         }
     }
 
+    fn update_diagnostic_severity(
+        &self,
+        modified: &mut bool,
+        scope_uri: &Option<Url>,
+        diagnostic_severity: HashMap<String, Severity>,
+    ) {
+        let mut workspaces = self.workspaces.write();
+        match scope_uri {
+            Some(scope_uri) => {
+                if let Ok(path) = scope_uri.to_file_path()
+                    && let Some(workspace) = workspaces.get_mut(&path)
+                    && workspace.diagnostic_severity != diagnostic_severity
+                {
+                    *modified = true;
+                    workspace.diagnostic_severity = diagnostic_severity;
+                }
+            }
+            None => {
+                let mut default = self.default.write();
+                if default.diagnostic_severity != diagnostic_severity {
+                    *modified = true;
+                    default.diagnostic_severity = diagnostic_severity;
+                }
+            }
+        }
+    }
+
     fn update_ide_settings(
         &self,
         modified: &mut bool,
@@ -768,20 +1025,32 @@ impl Workspaces {
     fn update_pythonpath(&self, modified: &mut bool, scope_uri: &Option<Url>, python_path: &str) {
         let mut workspaces = self.workspaces.write();
         let interpreter = PathBuf::from(python_path);
-        let python_info = Some(PythonInfo::new(interpreter));
+        // Clients that pass `pythonPath` in `initializationOptions` usually send it again in
+        // `workspace/configuration`, and workspaces often share an interpreter, so the cached
+        // query is reused unless the interpreter was modified. Only switching a workspace to a
+        // different interpreter forces a fresh query. Skip the recheck that `modified` triggers
+        // when the interpreter and its environment are unchanged.
+        let update = |workspace: &mut Workspace, modified: &mut bool| {
+            let switched = workspace
+                .python_info
+                .as_ref()
+                .is_some_and(|info| info.interpreter != interpreter);
+            let python_info = PythonInfo::query(interpreter.clone(), switched);
+            if workspace.python_info.as_ref() == Some(&python_info) {
+                return;
+            }
+            *modified = true;
+            workspace.python_info = Some(python_info);
+        };
         match scope_uri {
             Some(scope_uri) => {
                 if let Ok(workspace_path) = scope_uri.to_file_path()
                     && let Some(workspace) = workspaces.get_mut(&workspace_path)
                 {
-                    *modified = true;
-                    workspace.python_info = python_info;
+                    update(workspace, modified);
                 }
             }
-            None => {
-                *modified = true;
-                self.default.write().python_info = python_info;
-            }
+            None => update(&mut self.default.write(), modified),
         }
     }
 
@@ -793,19 +1062,52 @@ impl Workspaces {
         search_paths: Vec<PathBuf>,
     ) {
         let mut workspaces = self.workspaces.write();
+        let update = |workspace: &mut Workspace, modified: &mut bool| {
+            if workspace.search_path.as_ref() != Some(&search_paths) {
+                *modified = true;
+                workspace.search_path = Some(search_paths);
+            }
+        };
         match scope_uri {
             Some(scope_uri) => {
                 if let Ok(workspace_path) = scope_uri.to_file_path()
                     && let Some(workspace) = workspaces.get_mut(&workspace_path)
                 {
-                    *modified = true;
-                    workspace.search_path = Some(search_paths);
+                    update(workspace, modified);
                 }
             }
-            None => {
+            None => update(&mut self.default.write(), modified),
+        }
+    }
+
+    /// Update the `pythonPlatform` and `pythonVersion` overrides for scope_uri, None if default
+    /// workspace. Either changing invalidates the loaded configs, since they bake in `sys_info`.
+    fn update_python_environment_overrides(
+        &self,
+        modified: &mut bool,
+        scope_uri: &Option<Url>,
+        python_platform: Option<PythonPlatform>,
+        python_version: Option<PythonVersion>,
+    ) {
+        let mut workspaces = self.workspaces.write();
+        let update = |workspace: &mut Workspace, modified: &mut bool| {
+            if workspace.python_platform != python_platform
+                || workspace.python_version != python_version
+            {
                 *modified = true;
-                self.default.write().search_path = Some(search_paths);
+                workspace.python_platform = python_platform;
+                workspace.python_version = python_version;
+            }
+        };
+        match scope_uri {
+            Some(scope_uri) => {
+                if let Ok(workspace_path) = scope_uri.to_file_path()
+                    && let Some(workspace) = workspaces.get_mut(&workspace_path)
+                {
+                    update(workspace, modified);
+                }
             }
+            None => update(&mut self.default.write(), modified),
         }
     }
 
@@ -877,6 +1179,53 @@ impl Workspaces {
         })
     }
 
+    /// Check if saving the file at the given path should also publish diagnostics for the
+    /// non-open files that depend on it. Defaults to false if not explicitly configured.
+    pub fn should_publish_dependent_diagnostics_on_save(&self, path: &Path) -> bool {
+        self.get_with(path.to_path_buf(), |(_, workspace)| {
+            workspace
+                .publish_dependent_diagnostics_on_save
+                .unwrap_or(false)
+        })
+    }
+
+    /// Check if unused `# pyrefly: ignore` comments in the file at the given path should be
+    /// pointed out when the config disables `unused-ignore`. Defaults to false if not explicitly
+    /// configured.
+    pub fn should_show_unused_ignores(&self, path: &Path) -> bool {
+        self.get_with(path.to_path_buf(), |(_, workspace)| {
+            workspace.show_unused_ignores.unwrap_or(false)
+        })
+    }
+
+    /// The `maxIndexedFiles` setting of the default workspace followed by every workspace folder,
+    /// to tell whether applying a client configuration changed any of them.
+    pub fn max_indexed_files_settings(&self) -> Vec<Option<usize>> {
+        let workspaces = self.workspaces.read();
+        iter::once(self.default.read().max_indexed_files)
+            .chain(
+                workspaces
+                    .values()
+                    .map(|workspace| workspace.max_indexed_files),
+            )
+            .collect()
+    }
+
+    /// Get the maximum number of find-references results for a file at the given path.
+    /// Returns `None` (no limit) if not explicitly configured.
+    pub fn max_references(&self, path: &Path) -> Option<usize> {
+        self.get_with(path.to_path_buf(), |(_, workspace)| {
+            workspace.max_references
+        })
+    }
+
+    /// Get the client-configured severity override for errors of `kind` in the file at `path`.
+    pub fn diagnostic_severity(&self, path: &Path, kind: ErrorKind) -> Option<Severity> {
+        self.get_with(path.to_path_buf(), |(_, workspace)| {
+            workspace.diagnostic_severity.get(kind.to_name()).copied()
+        })
+    }
+
     /// Get the diagnostic mode for a file at the given path.
     /// Checks `pyrefly.diagnosticMode` first, then falls back to
     /// `analysis.diagnosticMode`, and defaults to `OpenFilesOnly`.
@@ -1269,6 +1618,36 @@ mod tests {
             assert_eq!(workspaces.default.read().type_checking_mode, None);
         }
 
+        /// Setting `diagnosticSeverity` flags `modified` and maps the
+        /// LSP-style names onto pyrefly severities; unknown values are dropped.
+        #[test]
+        fn diagnostic_severity_change_flags_modified() {
+            let workspaces = Workspaces::new(Workspace::new(), &[]);
+            let mut modified = false;
+            workspaces.apply_client_configuration(
+                &mut modified,
+                &None,
+                json!({ "pyrefly": { "diagnosticSeverity": {
+                    "bad-assignment": "warning",
+                    "bad-return": "none",
+                    "bad-index": "loud",
+                } } }),
+            );
+            assert!(modified);
+            assert_eq!(
+                workspaces.diagnostic_severity(Path::new("/a.py"), ErrorKind::BadAssignment),
+                Some(Severity::Warn)
+            );
+            assert_eq!(
+                workspaces.diagnostic_severity(Path::new("/a.py"), ErrorKind::BadReturn),
+                Some(Severity::Ignore)
+            );
+            assert_eq!(
+                workspaces.diagnostic_severity(Path::new("/a.py"), ErrorKind::BadIndex),
+                None
+            );
+        }
+
         /// Re-asserting the same `typeCheckingMode` value must not flag
         /// `modified` (otherwise a partial payload re-stating the
         /// current value triggers a full recheck).
@@ -1284,5 +1663,119 @@ mod tests {
             );
             assert!(!modified);
         }
+
+        /// Writes a fake interpreter to `dir` that reports the environment stored in
+        /// `dir/env.json`, so the environment can change without touching the interpreter.
+        /// Each query is logged to `dir/queries.log`.
+        #[cfg(unix)]
+        fn fake_interpreter(dir: &Path, site_packages: &str) -> PathBuf {
+            use std::os::unix::fs::PermissionsExt;
+
+            let interpreter = dir.join("python");
+            std::fs::write(
+                &interpreter,
+                "#!/bin/sh\necho query >> \"$(dirname \"$0\")/queries.log\"\ncat \"$(dirname \"$0\")/env.json\"\n",
+            )
+            .unwrap();
+            std::fs::set_permissions(&interpreter, std::fs::Permissions::from_mode(0o755)).unwrap();
+            set_fake_site_packages(dir, site_packages);
+            interpreter
+        }
+
+        #[cfg(unix)]
+        fn set_fake_site_packages(dir: &Path, site_packages: &str) {
+            std::fs::write(
+                dir.join("env.json"),
+                json!({
+                    "python_platform": "linux",
+                    "python_version": "3.12.0",
+                    "site_package_path": [site_packages],
+                })
+                .to_string(),
+            )
+            .unwrap();
+        }
+
+        /// Sending the same `pythonPath` again (e.g. in `initializationOptions` and then in
+        /// `workspace/configuration`) must only trigger one recheck.
+        #[cfg(unix)]
+        #[test]
+        fn same_python_path_flags_modified_once() {
+            let tempdir = tempfile::tempdir().unwrap();
+            let interpreter = fake_interpreter(tempdir.path(), "/site-packages");
+            let config = json!({ "pythonPath": interpreter.to_str().unwrap() });
+            let workspaces = Workspaces::new(Workspace::new(), &[]);
+            let mut rechecks = 0;
+            for _ in 0..2 {
+                let mut modified = false;
+                workspaces.apply_client_configuration(&mut modified, &None, config.clone());
+                if modified {
+                    rechecks += 1;
+                }
+            }
+            assert_eq!(rechecks, 1);
+        }
+
+        #[cfg(unix)]
+        fn query_count(dir: &Path) -> usize {
+            std::fs::read_to_string(dir.join("queries.log"))
+                .unwrap()
+                .lines()
+                .count()
+        }
+
+        /// Workspaces sharing an interpreter share one query of it.
+        #[cfg(unix)]
+        #[test]
+        fn same_python_path_in_two_workspaces_queries_once() {
+            let tempdir = tempfile::tempdir().unwrap();
+            let interpreter = fake_interpreter(tempdir.path(), "/site-packages");
+            let config = json!({ "pythonPath": interpreter.to_str().unwrap() });
+            let folders = [tempdir.path().join("a"), tempdir.path().join("b")];
+            let workspaces = Workspaces::new(Workspace::new(), &folders);
+            for folder in &folders {
+                let mut modified = false;
+                let scope_uri = Some(Url::from_file_path(folder).unwrap());
+                workspaces.apply_client_configuration(&mut modified, &scope_uri, config.clone());
+                assert!(modified);
+            }
+            assert_eq!(query_count(tempdir.path()), 1);
+        }
+
+        /// A `pythonPath` that is unchanged but whose interpreter was reinstalled, changing its
+        /// environment, is queried again.
+        #[cfg(unix)]
+        #[test]
+        fn same_python_path_with_changed_env_flags_modified() {
+            let tempdir = tempfile::tempdir().unwrap();
+            let interpreter = fake_interpreter(tempdir.path(), "/old-site-packages");
+            let config = json!({ "pythonPath": interpreter.to_str().unwrap() });
+            let workspaces = Workspaces::new(Workspace::new(), &[]);
+            let mut modified = false;
+            workspaces.apply_client_configuration(&mut modified, &None, config.clone());
+            assert!(modified);
+
+            set_fake_site_packages(tempdir.path(), "/new-site-packages");
+            std::fs::File::options()
+                .write(true)
+                .open(&interpreter)
+                .unwrap()
+                .set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(60))
+                .unwrap();
+            let mut modified = false;
+            workspaces.apply_client_configuration(&mut modified, &None, config);
+            assert!(modified);
+            assert_eq!(
+                workspaces
+                    .default
+                    .read()
+                    .python_info
+                    .as_ref()
+                    .unwrap()
+                    .env
+                    .interpreter_site_package_path,
+                vec![PathBuf::from("/new-site-packages")]
+            );
+        }
     }
 }
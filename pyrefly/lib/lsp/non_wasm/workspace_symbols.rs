@@ -5,40 +5,117 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::cmp::Reverse;
+
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
 use lsp_types::SymbolKind;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::module::Module;
 use pyrefly_python::module::TextRangeWithModule;
+use pyrefly_util::task_heap::Cancelled;
 use pyrefly_util::thread_pool::ThreadPool;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::StmtClassDef;
+use ruff_text_size::Ranged;
 
 use crate::state::lsp::MIN_CHARACTERS_TYPED_AUTOIMPORT;
 use crate::state::state::Transaction;
 
+/// A `workspace/symbol` match: its name, kind, definition and the name of the class or
+/// module that contains it.
+pub type WorkspaceSymbol = (String, SymbolKind, TextRangeWithModule, String);
+
+/// Collect the methods and nested classes of `class` (and, recursively, of its nested
+/// classes) whose names match `query`, with the dotted class path as their container.
+fn class_member_symbols(
+    matcher: &SkimMatcherV2,
+    query: &str,
+    module: &Module,
+    class: &StmtClassDef,
+    container: &str,
+    result: &mut Vec<(i64, WorkspaceSymbol)>,
+) {
+    for stmt in &class.body {
+        let (name, kind) = match stmt {
+            Stmt::FunctionDef(func) => (&func.name, SymbolKind::METHOD),
+            Stmt::ClassDef(nested) => {
+                let nested_container = format!("{container}.{}", nested.name);
+                class_member_symbols(matcher, query, module, nested, &nested_container, result);
+                (&nested.name, SymbolKind::CLASS)
+            }
+            _ => continue,
+        };
+        if let Some(score) = matcher.fuzzy_match(name.as_str(), query) {
+            let location = TextRangeWithModule {
+                module: module.clone(),
+                range: name.range(),
+            };
+            result.push((
+                score,
+                (name.to_string(), kind, location, container.to_owned()),
+            ));
+        }
+    }
+}
+
 impl Transaction<'_> {
+    /// Fuzzy-match `query` against every module's exports, plus the class members of
+    /// `member_handles` (typically the open files, whose ASTs are retained). Methods and nested
+    /// classes of other indexed files are not searched, since that would mean reparsing every
+    /// file on each request.
+    ///
+    /// Returns `Err(Cancelled)` if the transaction is cancelled part way through.
     pub fn workspace_symbols(
         &self,
         query: &str,
+        member_handles: &[Handle],
         custom_thread_pool: Option<&ThreadPool>,
-    ) -> Option<Vec<(String, SymbolKind, TextRangeWithModule)>> {
+    ) -> Result<Option<Vec<WorkspaceSymbol>>, Cancelled> {
         if query.len() < MIN_CHARACTERS_TYPED_AUTOIMPORT {
-            return None;
+            return Ok(None);
         }
+        let matcher = SkimMatcherV2::default().smart_case();
         let mut result = Vec::new();
-        for (handle, name, export) in self
-            .search_exports_fuzzy(query, custom_thread_pool)
-            .unwrap_or_default()
-        {
+        for (handle, name, export) in self.search_exports_fuzzy(query, custom_thread_pool)? {
             if let Some(module) = self.get_module_info(&handle) {
+                let score = matcher.fuzzy_match(&name, query).unwrap_or_default();
                 let kind = export
                     .symbol_kind
                     .map_or(SymbolKind::VARIABLE, |k| k.to_lsp_symbol_kind());
+                let container = module.name().to_string();
                 let location = TextRangeWithModule {
                     module,
                     range: export.location,
                 };
-                result.push((name, kind, location));
+                result.push((score, (name, kind, location, container)));
+            }
+        }
+        for handle in member_handles {
+            if self.get_cancellation_handle().is_cancelled() {
+                return Err(Cancelled);
+            }
+            if let Some(ast) = self.get_ast(handle)
+                && let Some(module) = self.get_module_info(handle)
+            {
+                for stmt in &ast.body {
+                    if let Stmt::ClassDef(class) = stmt {
+                        class_member_symbols(
+                            &matcher,
+                            query,
+                            &module,
+                            class,
+                            class.name.as_str(),
+                            &mut result,
+                        );
+                    }
+                }
             }
         }
-        // Keep shared fuzzy ordering intact while preferring non-`__init__.py` matches here.
-        result.sort_by_key(|(_, _, location)| location.module.path().is_init());
-        Some(result)
+        // Prefer non-`__init__.py` matches, then the best matches first.
+        result.sort_by_key(|(score, (_, _, location, _))| {
+            (location.module.path().is_init(), Reverse(*score))
+        });
+        Ok(Some(result.into_iter().map(|(_, symbol)| symbol).collect()))
     }
 }
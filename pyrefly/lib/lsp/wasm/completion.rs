@@ -8,6 +8,7 @@
 use dupe::Dupe;
 use fuzzy_matcher::FuzzyMatcher;
 use fuzzy_matcher::skim::SkimMatcherV2;
+use itertools::Itertools;
 use lsp_types::CompletionItem;
 use lsp_types::CompletionItemKind;
 use lsp_types::CompletionItemLabelDetails;
@@ -33,6 +34,7 @@ use ruff_python_ast::Identifier;
 use ruff_python_ast::ModModule;
 use ruff_python_ast::StmtImportFrom;
 use ruff_python_ast::name::Name;
+use ruff_source_file::PositionEncoding;
 use ruff_text_size::Ranged;
 use ruff_text_size::TextRange;
 use ruff_text_size::TextSize;
@@ -51,8 +53,10 @@ use crate::state::lsp::IdentifierContext;
 use crate::state::lsp::IdentifierWithContext;
 use crate::state::lsp::ImportFormat;
 use crate::state::lsp::MIN_CHARACTERS_TYPED_AUTOIMPORT;
+use crate::state::lsp::attribute_symbol_kind_from_type;
 use crate::state::state::Transaction;
 use crate::types::callable::Param;
+use crate::types::callable::Required;
 use crate::types::types::Type;
 
 /// Classification of a completion item's source, used for ranking.
@@ -74,6 +78,9 @@ pub(crate) struct RankedCompletion {
     item: CompletionItem,
     source: CompletionSource,
     is_incompatible: bool,
+    /// Snippet text for the arguments of a call to this completion, with one tab stop per
+    /// required parameter. Only set for callables whose signature is known.
+    call_snippet_args: Option<String>,
 }
 
 impl RankedCompletion {
@@ -83,6 +90,75 @@ impl RankedCompletion {
             item,
             source: CompletionSource::Local,
             is_incompatible: false,
+            call_snippet_args: None,
+        }
+    }
+}
+
+/// Upper bound on the number of completions returned once the user has typed part of the name.
+const MAX_RANKED_COMPLETIONS: usize = 200;
+
+/// How well `label` matches the text typed so far: `0` for a case-insensitive prefix match,
+/// `1` for any other fuzzy match, and `None` if it doesn't match.
+fn typed_text_match_rank(label: &str, typed: &str, matcher: &SkimMatcherV2) -> Option<u8> {
+    if label
+        .get(..typed.len())
+        .is_some_and(|start| start.eq_ignore_ascii_case(typed))
+    {
+        Some(0)
+    } else if matcher.fuzzy_match(label, typed).is_some() {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+/// Re-ranks completions against the text typed before the cursor, so prefix matches come before
+/// fuzzy matches and non-matching names come last. Non-matching names are kept (the client does
+/// its own filtering) unless there are more than `MAX_RANKED_COMPLETIONS` completions, in which
+/// case they are dropped first and the worst matches after them. Returns whether any completion
+/// was dropped, so the response can be marked incomplete.
+fn rank_by_typed_text(result: &mut Vec<RankedCompletion>, typed: &str) -> bool {
+    let matcher = SkimMatcherV2::default();
+    let mut is_match = Vec::with_capacity(result.len());
+    for ranked in result.iter_mut() {
+        let rank = typed_text_match_rank(&ranked.item.label, typed, &matcher);
+        is_match.push(rank.is_some());
+        let sort_text = ranked.item.sort_text.take().unwrap_or_default();
+        ranked.item.sort_text = Some(format!("{}.{sort_text}", rank.unwrap_or(2)));
+    }
+    if result.len() <= MAX_RANKED_COMPLETIONS {
+        return false;
+    }
+    let mut is_match = is_match.into_iter();
+    result.retain(|_| is_match.next().unwrap_or_default());
+    if result.len() > MAX_RANKED_COMPLETIONS {
+        result.sort_by(|a, b| {
+            a.item
+                .sort_text
+                .cmp(&b.item.sort_text)
+                .then_with(|| a.item.label.cmp(&b.item.label))
+        });
+        result.truncate(MAX_RANKED_COMPLETIONS);
+    }
+    true
+}
+
+/// Once something other than `_` has been typed, gives `_private` and `__dunder__` names a
+/// `filter_text` without the leading underscores, so the client matches them on the name proper.
+///
+/// Nothing is stripped before anything is typed, since the next character may be a `_`, which the
+/// stripped filter text would no longer match. That way the filter text never depends on text the
+/// client has yet to send, and the response need not be marked incomplete.
+fn strip_underscores_from_filter_text(result: &mut [RankedCompletion], typed: Option<&str>) {
+    if typed.is_none_or(|typed| typed.is_empty() || typed.starts_with('_')) {
+        return;
+    }
+    for ranked in result {
+        let label = &ranked.item.label;
+        let name = label.trim_start_matches('_');
+        if ranked.item.filter_text.is_none() && !name.is_empty() && name.len() != label.len() {
+            ranked.item.filter_text = Some(name.to_owned());
         }
     }
 }
@@ -152,13 +228,31 @@ fn autoimport_source(module_name: &str) -> CompletionSource {
 }
 
 /// Options that influence completion item formatting and behavior.
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug)]
 pub struct CompletionOptions {
     pub supports_completion_item_details: bool,
     pub complete_function_parens: bool,
     pub supports_snippet_completions: bool,
+    /// When inserting parentheses as a snippet, also insert a tab stop for each required
+    /// parameter of the completed function.
+    pub complete_function_params: bool,
     /// When false, suppress completions that would insert a new import.
     pub auto_import: bool,
+    /// The encoding used for the ranges of any additional import edits.
+    pub position_encoding: PositionEncoding,
+}
+
+impl Default for CompletionOptions {
+    fn default() -> Self {
+        Self {
+            supports_completion_item_details: false,
+            complete_function_parens: false,
+            supports_snippet_completions: false,
+            complete_function_params: false,
+            auto_import: false,
+            position_encoding: PositionEncoding::Utf16,
+        }
+    }
 }
 
 /// Returns true if the client supports snippet completions in completion items.
@@ -173,6 +267,26 @@ pub(crate) fn supports_snippet_completions(capabilities: &lsp_types::ClientCapab
 }
 
 impl Transaction<'_> {
+    /// The part of `identifier` typed before the cursor at `position`, if any.
+    fn typed_text(
+        &self,
+        handle: &Handle,
+        identifier: &Identifier,
+        position: TextSize,
+    ) -> Option<String> {
+        let range = identifier.range();
+        if range.start() >= position || position > range.end() {
+            return None;
+        }
+        let module_info = self.get_module_info(handle)?;
+        Some(
+            module_info
+                .code_at(TextRange::new(range.start(), position))
+                .to_owned(),
+        )
+        .filter(|typed| !typed.is_empty())
+    }
+
     /// Adds a common alias auto-import completion (e.g. `np` -> `numpy`).
     /// Returns the module name that was aliased when a completion was added.
     fn add_common_alias_autoimport_completion(
@@ -182,6 +296,7 @@ impl Transaction<'_> {
         module_info: &Module,
         identifier_text: &str,
         supports_completion_item_details: bool,
+        position_encoding: PositionEncoding,
         completions: &mut Vec<RankedCompletion>,
     ) -> Option<ModuleName> {
         let module_name_str = common_alias_target_module(identifier_text)?;
@@ -193,7 +308,8 @@ impl Transaction<'_> {
         let (position, import_text, completion_label) =
             import_regular_import_edit(ast, module_handle, Some(identifier_text));
         let import_text_edit = TextEdit {
-            range: module_info.to_lsp_range(TextRange::at(position, TextSize::new(0))),
+            range: module_info
+                .to_lsp_range(TextRange::at(position, TextSize::new(0)), position_encoding),
             new_text: import_text.clone(),
         };
         let auto_import_label_detail = format!(" (import {module_name_str} as {identifier_text})");
@@ -214,6 +330,7 @@ impl Transaction<'_> {
             },
             source: autoimport_source(module_name_str),
             is_incompatible: false,
+            call_snippet_args: None,
         });
         Some(module_name)
     }
@@ -298,8 +415,42 @@ impl Transaction<'_> {
         });
     }
 
+    /// Builds the argument snippet for a call to `ty`, e.g. `${1:a}, ${2:b}`, from the
+    /// parameters a caller must supply. Parameters with defaults are skipped, as is a leading
+    /// `self`/`cls` parameter. Unnamed positional-only parameters get an `argN` placeholder.
+    fn call_snippet_args(ty: &Type) -> Option<String> {
+        let params = Self::normalize_singleton_function_type_into_params(ty.clone())?;
+        let args = params
+            .iter()
+            .filter_map(|param| match param {
+                Param::PosOnly(name, _, Required::Required) => Some((name.as_ref(), false)),
+                Param::Pos(name, _, Required::Required) => Some((Some(name), false)),
+                Param::KwOnly(name, _, Required::Required) => Some((Some(name), true)),
+                _ => None,
+            })
+            .enumerate()
+            .map(|(i, (name, is_keyword))| {
+                let index = i + 1;
+                let placeholder = match name {
+                    Some(name) => format!("${{{index}:{name}}}"),
+                    None => format!("${{{index}:arg{index}}}"),
+                };
+                match name {
+                    Some(name) if is_keyword => format!("{name}={placeholder}"),
+                    _ => placeholder,
+                }
+            })
+            .join(", ");
+        Some(args)
+    }
+
     /// Adds function/method completion inserts with parentheses, using snippets when supported.
-    fn add_function_call_parens(completions: &mut [RankedCompletion], supports_snippets: bool) {
+    /// With `complete_params`, the snippet also contains a tab stop per required parameter.
+    fn add_function_call_parens(
+        completions: &mut [RankedCompletion],
+        supports_snippets: bool,
+        complete_params: bool,
+    ) {
         for ranked in completions {
             let item = &mut ranked.item;
             if item.insert_text.is_some() || item.text_edit.is_some() {
@@ -313,7 +464,12 @@ impl Transaction<'_> {
             }
 
             if supports_snippets {
-                item.insert_text = Some(format!("{}($0)", item.label));
+                item.insert_text = match &ranked.call_snippet_args {
+                    Some(args) if complete_params && !args.is_empty() => {
+                        Some(format!("{}({args})", item.label))
+                    }
+                    _ => Some(format!("{}($0)", item.label)),
+                };
                 item.insert_text_format = Some(InsertTextFormat::SNIPPET);
             } else {
                 item.insert_text = Some(format!("{}()", item.label));
@@ -337,7 +493,35 @@ impl Transaction<'_> {
         Some(documentation)
     }
 
+    /// Names passed as keyword arguments to the call whose callee is at `callee_range`, other
+    /// than the keyword argument the cursor is in.
+    fn supplied_keyword_names(
+        &self,
+        handle: &Handle,
+        position: TextSize,
+        callee_range: TextRange,
+    ) -> SmallSet<Name> {
+        let Some(ast) = self.get_ast(handle) else {
+            return SmallSet::new();
+        };
+        Ast::locate_node(&ast, position)
+            .into_iter()
+            .find_map(|node| match node {
+                AnyNodeRef::ExprCall(call) if call.func.range() == callee_range => Some(
+                    call.arguments
+                        .keywords
+                        .iter()
+                        .filter(|keyword| !keyword.range().contains_inclusive(position))
+                        .filter_map(|keyword| keyword.arg.as_ref().map(|arg| arg.id.clone()))
+                        .collect(),
+                ),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
     /// Adds keyword argument completions (e.g., `arg=`) for function/method calls.
+    /// Parameters the call already supplies, positionally or by keyword, are ranked last.
     fn add_kwargs_completions(
         &self,
         handle: &Handle,
@@ -346,17 +530,29 @@ impl Transaction<'_> {
     ) {
         if let Some(CallInfo {
             callables,
+            callee_range,
             provided_arg_ranges,
             ..
         }) = self.get_callables_from_call(handle, position)
         {
+            let supplied_keywords = self.supplied_keyword_names(handle, position, callee_range);
             let callables =
                 self.filter_compatible_overloads(handle, callables, &provided_arg_ranges);
             let mut seen = SmallSet::new();
             for callable in callables {
                 if let Some(params) = Self::normalize_singleton_function_type_into_params(callable)
                 {
+                    let mut positional_index = 0;
                     for param in params {
+                        let supplied_positionally = match &param {
+                            Param::Pos(name, ..) | Param::PosOnly(Some(name), ..)
+                                if name.as_str() != "self" =>
+                            {
+                                positional_index += 1;
+                                positional_index <= provided_arg_ranges.len()
+                            }
+                            _ => false,
+                        };
                         match param {
                             Param::Pos(name, ty, _)
                             | Param::PosOnly(Some(name), ty, _)
@@ -367,12 +563,17 @@ impl Transaction<'_> {
                                 if name.as_str() != "self"
                                     && seen.insert((label.clone(), detail.clone()))
                                 {
-                                    completions.push(RankedCompletion::new(CompletionItem {
+                                    let mut ranked = RankedCompletion::new(CompletionItem {
                                         label,
                                         detail: Some(detail),
                                         kind: Some(CompletionItemKind::VARIABLE),
                                         ..Default::default()
-                                    }));
+                                    });
+                                    // Passing a parameter twice is an error, so rank these
+                                    // after the parameters that are still missing.
+                                    ranked.is_incompatible =
+                                        supplied_positionally || supplied_keywords.contains(&name);
+                                    completions.push(ranked);
                                 }
                             }
                             Param::Varargs(None, _)
@@ -504,12 +705,14 @@ impl Transaction<'_> {
 
     /// Adds completions for local variables and returns true if any were added.
     /// If an identifier is present, filters matches using fuzzy matching.
+    /// With `complete_call_params`, function items carry an argument snippet for their call.
     fn add_local_variable_completions(
         &self,
         handle: &Handle,
         identifier: Option<&Identifier>,
         position: TextSize,
         expected_type: Option<&Type>,
+        complete_call_params: bool,
         completions: &mut Vec<RankedCompletion>,
     ) -> bool {
         let mut has_added_any = false;
@@ -559,6 +762,11 @@ impl Transaction<'_> {
                     }
                 });
                 let detail = ty.as_ref().map(|t| t.to_string());
+                let call_snippet_args = if complete_call_params {
+                    ty.as_ref().and_then(Self::call_snippet_args)
+                } else {
+                    None
+                };
                 let documentation = self.get_documentation_from_export(export_info);
                 let is_incompatible =
                     self.is_incompatible_with_expected_type(handle, expected_type, ty.as_ref());
@@ -579,6 +787,7 @@ impl Transaction<'_> {
                     },
                     source: CompletionSource::Local,
                     is_incompatible,
+                    call_snippet_args,
                 })
             }
         }
@@ -612,6 +821,7 @@ impl Transaction<'_> {
         completions: &mut Vec<RankedCompletion>,
         import_format: ImportFormat,
         supports_completion_item_details: bool,
+        position_encoding: PositionEncoding,
         custom_thread_pool: Option<&ThreadPool>,
     ) {
         // Auto-import can be slow. Let's only return results if there are no local
@@ -629,6 +839,7 @@ impl Transaction<'_> {
                 &module_info,
                 identifier_text,
                 supports_completion_item_details,
+                position_encoding,
                 completions,
             ) {
                 aliased_modules.insert(module_name);
@@ -658,7 +869,7 @@ impl Transaction<'_> {
                         import_format,
                     );
                     let import_text_edit = TextEdit {
-                        range: module_info.to_lsp_range(import_edit.range),
+                        range: module_info.to_lsp_range(import_edit.range, position_encoding),
                         new_text: import_edit.insert_text.clone(),
                     };
                     (
@@ -700,6 +911,7 @@ impl Transaction<'_> {
                     },
                     source: autoimport_source(&imported_module),
                     is_incompatible: false,
+                    call_snippet_args: None,
                 });
             }
 
@@ -716,7 +928,7 @@ impl Transaction<'_> {
                     self.submodule_autoimport_edit(handle, &ast, module_name, import_format)
                 {
                     let import_text_edit = TextEdit {
-                        range: module_info.to_lsp_range(import_edit.range),
+                        range: module_info.to_lsp_range(import_edit.range, position_encoding),
                         new_text: import_edit.insert_text.clone(),
                     };
                     let additional_text_edits = Some(vec![import_text_edit]);
@@ -739,6 +951,7 @@ impl Transaction<'_> {
                         },
                         source,
                         is_incompatible: false,
+                        call_snippet_args: None,
                     });
                 }
                 if let Some(module_handle) = self.import_handle(handle, module_name, None).finding()
@@ -747,8 +960,10 @@ impl Transaction<'_> {
                         let (position, import_text, _) =
                             import_regular_import_edit(&ast, module_handle, None);
                         let import_text_edit = TextEdit {
-                            range: module_info
-                                .to_lsp_range(TextRange::at(position, TextSize::new(0))),
+                            range: module_info.to_lsp_range(
+                                TextRange::at(position, TextSize::new(0)),
+                                position_encoding,
+                            ),
                             new_text: import_text.clone(),
                         };
                         (import_text, Some(vec![import_text_edit]))
@@ -771,6 +986,7 @@ impl Transaction<'_> {
                         },
                         source,
                         is_incompatible: false,
+                        call_snippet_args: None,
                     });
                 }
             }
@@ -946,6 +1162,7 @@ impl Transaction<'_> {
         handle: &Handle,
         base_type: Type,
         expected_type: Option<&Type>,
+        complete_call_params: bool,
         completions: &mut Vec<RankedCompletion>,
     ) {
         self.ad_hoc_solve(handle, "completion_attributes", |solver| {
@@ -958,9 +1175,10 @@ impl Transaction<'_> {
                         Some(Type::Function(_) | Type::Overload(_)) => {
                             Some(CompletionItemKind::FUNCTION)
                         }
-                        Some(Type::Module(_)) => Some(CompletionItemKind::MODULE),
-                        Some(Type::ClassDef(_)) => Some(CompletionItemKind::CLASS),
-                        _ => Some(CompletionItemKind::FIELD),
+                        Some(ref ty) => {
+                            Some(attribute_symbol_kind_from_type(ty).to_lsp_completion_item_kind())
+                        }
+                        None => Some(CompletionItemKind::FIELD),
                     };
                     let detail = attr
                         .ty
@@ -992,6 +1210,11 @@ impl Transaction<'_> {
                         },
                         source,
                         is_incompatible,
+                        call_snippet_args: if complete_call_params {
+                            attr.ty.as_ref().and_then(Self::call_snippet_args)
+                        } else {
+                            None
+                        },
                     });
                 });
         });
@@ -1014,11 +1237,18 @@ impl Transaction<'_> {
             supports_completion_item_details,
             complete_function_parens,
             supports_snippet_completions,
+            complete_function_params,
             auto_import,
+            position_encoding,
         } = options;
         let mut result: Vec<RankedCompletion> = Vec::new();
         let mut is_incomplete = false;
         let mut allow_function_call_parens = false;
+        // Argument snippets are only inserted when parens, snippets and params are all enabled.
+        let complete_call_params =
+            complete_function_parens && supports_snippet_completions && complete_function_params;
+        // Text of the name typed before the cursor, e.g. `ap` in `x.ap`, used to rank completions.
+        let mut typed_text: Option<String> = None;
         let ast = self.get_ast(handle);
         let covering_nodes = ast
             .as_ref()
@@ -1106,11 +1336,12 @@ impl Transaction<'_> {
                 }
             }
             Some(IdentifierWithContext {
-                identifier: _,
+                identifier,
                 context: IdentifierContext::Attribute { base_range, .. },
             }) => {
                 let expected_type = self.get_expected_type_at(handle, position);
                 allow_function_call_parens = true;
+                typed_text = self.typed_text(handle, &identifier, position);
                 if let Some(answers) = self.get_answers(handle)
                     && let Some(base_type) = answers.get_type_trace(base_range)
                 {
@@ -1118,6 +1349,7 @@ impl Transaction<'_> {
                         handle,
                         base_type,
                         expected_type.as_ref(),
+                        complete_call_params,
                         &mut result,
                     );
                 }
@@ -1127,6 +1359,7 @@ impl Transaction<'_> {
                 context,
             }) => {
                 let is_method_def = matches!(context, IdentifierContext::MethodDef { .. });
+                typed_text = self.typed_text(handle, &identifier, position);
                 let expected_type = if matches!(
                     context,
                     IdentifierContext::Expr(ExprContext::Load | ExprContext::Invalid)
@@ -1168,6 +1401,7 @@ impl Transaction<'_> {
                                 handle,
                                 class_type,
                                 None,
+                                complete_call_params,
                                 &mut result,
                             );
                         }
@@ -1192,6 +1426,7 @@ impl Transaction<'_> {
                         Some(&identifier),
                         position,
                         expected_type.as_ref(),
+                        complete_call_params,
                         &mut result,
                     );
                     if auto_import && !has_local_completions {
@@ -1201,6 +1436,7 @@ impl Transaction<'_> {
                             &mut result,
                             import_format,
                             supports_completion_item_details,
+                            position_encoding,
                             custom_thread_pool,
                         );
                     }
@@ -1251,6 +1487,7 @@ impl Transaction<'_> {
                                 None,
                                 position,
                                 expected_type.as_ref(),
+                                complete_call_params,
                                 &mut result,
                             );
                             self.add_builtins_autoimport_completions(handle, None, &mut result);
@@ -1286,12 +1523,22 @@ impl Transaction<'_> {
             }
         }
         if complete_function_parens && allow_function_call_parens {
-            Self::add_function_call_parens(&mut result, supports_snippet_completions);
+            Self::add_function_call_parens(
+                &mut result,
+                supports_snippet_completions,
+                complete_function_params,
+            );
         }
         for ranked in &mut result {
             let mru_rank = mru_index.as_mut().map(|index| (*index)(&ranked.item));
             assign_sort_text(ranked, mru_rank);
         }
+        if let Some(typed) = &typed_text
+            && rank_by_typed_text(&mut result, typed)
+        {
+            is_incomplete = true;
+        }
+        strip_underscores_from_filter_text(&mut result, typed_text.as_deref());
         (result.into_iter().map(|r| r.item).collect(), is_incomplete)
     }
 }
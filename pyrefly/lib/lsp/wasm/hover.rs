@@ -10,6 +10,7 @@
 use std::collections::HashMap;
 use std::sync::LazyLock;
 
+use dupe::Dupe;
 use lsp_types::Hover;
 use lsp_types::HoverContents;
 use lsp_types::MarkupContent;
@@ -22,6 +23,8 @@ use pyrefly_python::docstring::parse_parameter_documentation;
 use pyrefly_python::ignore::Ignore;
 use pyrefly_python::ignore::Tool;
 use pyrefly_python::ignore::find_comment_start_in_line;
+use pyrefly_python::module::Module;
+use pyrefly_python::module::TextRangeWithModule;
 use pyrefly_python::short_identifier::ShortIdentifier;
 use pyrefly_python::symbol_kind::SymbolKind;
 use pyrefly_types::callable::Callable;
@@ -37,9 +40,13 @@ use pyrefly_types::type_var::Variance;
 use pyrefly_types::types::Type;
 use pyrefly_util::absolutize::Absolutize as _;
 use pyrefly_util::lined_buffer::LineNumber;
+use pyrefly_util::prelude::SliceExt;
+use pyrefly_util::prelude::VecExt;
 use pyrefly_util::visit::Visit;
 use regex::Regex;
 use ruff_python_ast::AnyNodeRef;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ModModule;
 use ruff_python_ast::Stmt;
 use ruff_python_ast::name::Name;
 use ruff_text_size::Ranged;
@@ -79,26 +86,50 @@ pub struct HoverValue {
     pub parameter_doc: Option<(String, String)>,
     pub type_sources: Vec<String>,
     pub display: Option<String>,
+    /// Whether `display` was already cut down to `max_type_length`.
+    pub display_truncated: bool,
+    /// The signature of the function before its decorators were applied, shown
+    /// when the decorated type reads differently (e.g. a property getter).
+    pub original_signature: Option<String>,
+    /// One line per definition when the symbol resolves to more than one.
+    pub definitions: Vec<String>,
     pub show_go_to_links: bool,
+    /// Extra go-to targets: the implementation and its sibling stub, when the symbol has both.
+    pub definition_links: Vec<TextRangeWithModule>,
+    /// Truncate the rendered type to this many characters, if set.
+    pub max_type_length: Option<usize>,
+    /// Show every name in the type with its module.
+    pub fully_qualified_names: bool,
 }
 
 impl HoverValue {
     #[cfg(not(target_arch = "wasm32"))]
-    fn format_symbol_def_locations(t: &Type) -> Option<String> {
+    fn format_symbol_def_locations(
+        t: &Type,
+        definition_links: &[TextRangeWithModule],
+    ) -> Option<String> {
         let symbol_paths = collect_symbol_def_paths(t);
-        let linked_names = symbol_paths
-            .into_iter()
-            .filter_map(|(qname, file_path)| {
-                if let Ok(mut url) = Url::from_file_path(&file_path) {
-                    let start_pos = qname.module().display_range(qname.range()).start;
-                    set_display_pos_fragment(&mut url, start_pos);
-                    Some(format!("[{}]({})", qname.id(), url))
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>()
-            .join(" | ");
+        let mut links: Vec<String> = Vec::new();
+        let type_links = symbol_paths.into_iter().filter_map(|(qname, file_path)| {
+            if let Ok(mut url) = Url::from_file_path(file_path?) {
+                let start_pos = qname.module().display_range(qname.range()).start;
+                set_display_pos_fragment(&mut url, start_pos);
+                Some(format!("[{}]({})", qname.id(), url))
+            } else {
+                None
+            }
+        });
+        let definition_links = definition_links.iter().filter_map(|def| {
+            let mut url = Url::from_file_path(to_real_path(def.module.path())?).ok()?;
+            set_display_pos_fragment(&mut url, def.module.display_range(def.range).start);
+            Some(format!("[{}]({})", def.module.code_at(def.range), url))
+        });
+        for link in type_links.chain(definition_links) {
+            if !links.contains(&link) {
+                links.push(link);
+            }
+        }
+        let linked_names = links.join(" | ");
 
         if linked_names.is_empty() {
             None
@@ -108,7 +139,10 @@ impl HoverValue {
     }
 
     #[cfg(target_arch = "wasm32")]
-    fn format_symbol_def_locations(t: &Type) -> Option<String> {
+    fn format_symbol_def_locations(
+        t: &Type,
+        definition_links: &[TextRangeWithModule],
+    ) -> Option<String> {
         None
     }
 
@@ -207,8 +241,7 @@ impl HoverValue {
             .ok()?;
 
         let def = defs.into_vec().into_iter().next()?;
-        let file_path = to_real_path(def.module.path())
-            .unwrap_or_else(|| def.module.path().as_path().to_path_buf());
+        let file_path = to_real_path(def.module.path())?;
         let abs_path = file_path.absolutize();
         let mut url = Url::from_file_path(&abs_path).ok()?;
         set_display_pos_fragment(
@@ -240,27 +273,37 @@ impl HoverValue {
                     let cleaned = doc.trim().replace('\n', "  \n");
                     format!("{prefix}**Parameter `{}`**\n{}", name, cleaned)
                 });
-        let kind_formatted = self
-            .resolve_symbol_kind()
-            .map(|kind| format!("{} ", kind.display_for_hover()))
-            .or_else(|| {
-                if self.type_.is_toplevel_callable() {
-                    Some("(function) ".to_owned())
-                } else {
-                    None
-                }
-            })
-            .unwrap_or_default();
+        let kind_formatted = if self.type_.is_property_getter() {
+            "(property) ".to_owned()
+        } else {
+            self.resolve_symbol_kind()
+                .map(|kind| format!("{} ", kind.display_for_hover()))
+                .or_else(|| {
+                    if self.type_.is_toplevel_callable() {
+                        Some("(function) ".to_owned())
+                    } else {
+                        None
+                    }
+                })
+                .unwrap_or_default()
+        };
         let name_formatted = self
             .name
             .as_ref()
             .map(|s| format!("{s}: "))
             .unwrap_or_default();
         let symbol_def_formatted = if self.show_go_to_links {
-            HoverValue::format_symbol_def_locations(&self.type_).unwrap_or_default()
+            HoverValue::format_symbol_def_locations(&self.type_, &self.definition_links)
+                .unwrap_or_default()
         } else {
             String::new()
         };
+        let original_signature_formatted = self
+            .original_signature
+            .as_ref()
+            .map_or(String::new(), |signature| {
+                format!("\n---\n**Original signature**\n```python\n{signature}\n```")
+            });
         let type_source_formatted = if self.type_sources.is_empty() {
             String::new()
         } else {
@@ -272,20 +315,43 @@ impl HoverValue {
             }
             section
         };
-        let type_display = self.display.clone().unwrap_or_else(|| {
-            self.type_
-                .as_lsp_string_with_fallback_name(self.name.as_deref(), LspDisplayMode::Hover)
-        });
+        let definitions_formatted = if self.definitions.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "\n---\n**{} definitions**\n{}\n",
+                self.definitions.len(),
+                self.definitions.join("\n")
+            )
+        };
+        let (type_display, truncated) = match &self.display {
+            Some(display) => (display.clone(), self.display_truncated),
+            None => render_hover_type(
+                &self.type_,
+                self.name.as_deref(),
+                self.max_type_length,
+                self.fully_qualified_names,
+            ),
+        };
+        let truncation_note = match self.max_type_length {
+            Some(max_len) if truncated => format!(
+                "\n---\n_Type truncated to {max_len} characters. Use Go to Type Definition to explore the full type._\n"
+            ),
+            _ => String::new(),
+        };
 
         Hover {
             contents: HoverContents::Markup(MarkupContent {
                 kind: MarkupKind::Markdown,
                 value: format!(
-                    "```python\n{}{}{}\n```{}{}{}{}",
+                    "```python\n{}{}{}\n```{}{}{}{}{}{}{}",
                     kind_formatted,
                     name_formatted,
                     type_display,
+                    truncation_note,
+                    original_signature_formatted,
                     type_source_formatted,
+                    definitions_formatted,
                     docstring_formatted,
                     parameter_doc_formatted,
                     symbol_def_formatted
@@ -296,6 +362,25 @@ impl HoverValue {
     }
 }
 
+/// Render `ty` for hover, cut down to `max_type_length` characters if it is longer. Also returns
+/// whether it was truncated.
+fn render_hover_type(
+    ty: &Type,
+    name: Option<&str>,
+    max_type_length: Option<usize>,
+    fully_qualified_names: bool,
+) -> (String, bool) {
+    match max_type_length.and_then(|max_len| {
+        ty.as_truncated_lsp_string(name, LspDisplayMode::Hover, fully_qualified_names, max_len)
+    }) {
+        Some(display) => (display, true),
+        None => (
+            ty.as_lsp_string_with_options(name, LspDisplayMode::Hover, fully_qualified_names),
+            false,
+        ),
+    }
+}
+
 /// Gets all suppressed errors that overlap with the given line.
 ///
 /// This function filters the suppressed errors for a specific handle to find
@@ -536,6 +621,7 @@ fn class_hover_display(
     solver: &AnswersSolver<TransactionHandle<'_>>,
     type_: &Type,
     name_for_display: Option<&str>,
+    fully_qualified: bool,
 ) -> Option<String> {
     let enum_class = match type_ {
         Type::ClassDef(cls) => Some(cls),
@@ -559,10 +645,11 @@ fn class_hover_display(
         } else {
             solver.heap.mk_union(members)
         };
-        return Some(
-            enum_display_type
-                .as_lsp_string_with_fallback_name(name_for_display, LspDisplayMode::Hover),
-        );
+        return Some(enum_display_type.as_lsp_string_with_options(
+            name_for_display,
+            LspDisplayMode::Hover,
+            fully_qualified,
+        ));
     }
 
     let mut constructor = match type_ {
@@ -578,7 +665,11 @@ fn class_hover_display(
         _ => None,
     }?;
     constructor.transform_toplevel_callable(|c| expand_callable_kwargs_for_hover(solver, c));
-    Some(constructor.as_lsp_string_with_fallback_name(name_for_display, LspDisplayMode::Hover))
+    Some(constructor.as_lsp_string_with_options(
+        name_for_display,
+        LspDisplayMode::Hover,
+        fully_qualified,
+    ))
 }
 
 fn parameter_documentation_for_callee(
@@ -683,11 +774,225 @@ fn in_keyword_in_iteration_at(
     None
 }
 
+/// A short description of a Python keyword, or of `self`/`cls`, for hovers where type
+/// information alone would be unhelpful.
+fn keyword_documentation(word: &str) -> Option<&'static str> {
+    Some(match word {
+        "None" => "The sole value of `NoneType`, used to signify the absence of a value.",
+        "True" | "False" => "One of the two values of type `bool`.",
+        "and" => "Boolean operator: evaluates to the first falsy operand, or the last operand.",
+        "or" => "Boolean operator: evaluates to the first truthy operand, or the last operand.",
+        "not" => "Boolean operator: `True` if its operand is falsy, `False` otherwise.",
+        "in" => "Membership test, implemented by `__contains__`.",
+        "is" => "Identity comparison: `True` if both operands are the same object.",
+        "as" => "Binds a name in `import`, `with`, `except` and `case` statements.",
+        "assert" => "Raises `AssertionError` if the condition is falsy.",
+        "async" => "Defines a coroutine function, or an asynchronous `for` or `with` statement.",
+        "await" => {
+            "Suspends the enclosing coroutine until the awaitable completes, and evaluates to its result."
+        }
+        "break" => "Exits the innermost enclosing `for` or `while` loop.",
+        "continue" => "Skips to the next iteration of the innermost enclosing loop.",
+        "class" => "Defines a class.",
+        "def" => "Defines a function.",
+        "del" => "Deletes names, attributes, or items.",
+        "if" | "elif" | "else" => "Conditional execution.",
+        "for" => "Iterates over the items of an iterable.",
+        "while" => "Repeats its body while the condition is truthy.",
+        "try" | "except" | "finally" => "Handles exceptions raised in the `try` block.",
+        "raise" => "Raises an exception.",
+        "from" => "Imports names from a module, or chains an exception in `raise ... from ...`.",
+        "import" => "Imports a module.",
+        "global" => "Declares names that refer to module-level variables.",
+        "nonlocal" => {
+            "Declares names that refer to variables in the nearest enclosing function scope."
+        }
+        "lambda" => "Creates an anonymous function.",
+        "pass" => "Does nothing; a placeholder where a statement is required.",
+        "return" => "Exits the function, returning the given value (or `None`).",
+        "with" => "Runs a block inside a context manager's `__enter__` and `__exit__`.",
+        "yield" => {
+            "Produces a value from a generator, suspending it until the next value is requested."
+        }
+        "self" => "The instance a method was called on.",
+        "cls" => "The class a class method was called on.",
+        _ => return None,
+    })
+}
+
+/// The keyword (or `self`/`cls`) under the cursor, with its documentation. Words inside
+/// comments and string literals, and names other than `self`/`cls`, are ignored.
+fn keyword_at(
+    transaction: &Transaction<'_>,
+    handle: &Handle,
+    position: TextSize,
+) -> Option<(String, &'static str)> {
+    let module = transaction.get_module_info(handle)?;
+    let contents = module.contents();
+    let offset = position.to_usize().min(contents.len());
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let start = contents[..offset].trim_end_matches(is_word).len();
+    let end = contents.len() - contents[offset..].trim_start_matches(is_word).len();
+    let word = &contents[start..end];
+    let documentation = keyword_documentation(word)?;
+    if !matches!(word, "self" | "cls") && transaction.identifier_at(handle, position).is_some() {
+        return None;
+    }
+    let display_pos = module.display_pos(position);
+    let line_text = module.lined_buffer().content_in_line_range(
+        display_pos.line_within_file(),
+        display_pos.line_within_file(),
+    );
+    if let Some(comment_offset) = find_comment_start_in_line(line_text)
+        && display_pos.column().get() >= comment_offset as u32
+    {
+        return None;
+    }
+    let ast = transaction.get_ast(handle)?;
+    if Ast::locate_node(&ast, position).iter().any(|node| {
+        matches!(
+            node,
+            AnyNodeRef::ExprStringLiteral(_)
+                | AnyNodeRef::ExprBytesLiteral(_)
+                | AnyNodeRef::ExprFString(_)
+        )
+    }) {
+        return None;
+    }
+    Some((word.to_owned(), documentation))
+}
+
+fn keyword_hover(word: &str, documentation: &str) -> Hover {
+    let kind = if matches!(word, "self" | "cls") {
+        "parameter"
+    } else {
+        "keyword"
+    };
+    Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: format!("```python\n({kind}) {word}\n```\n---\n{documentation}"),
+        }),
+        range: None,
+    }
+}
+
+/// The range of each name bound by `stmt` that is called `name`, with the source of its
+/// annotation when it has one.
+fn stmt_bindings_of(stmt: &Stmt, name: &str, module: &Module) -> Vec<(TextRange, Option<String>)> {
+    let annotation = |annotation: &Expr| Some(module.code_at(annotation.range()).to_owned());
+    match stmt {
+        Stmt::Assign(x) => x
+            .targets
+            .iter()
+            .filter_map(|target| match target {
+                Expr::Name(target) if target.id.as_str() == name => Some((target.range, None)),
+                _ => None,
+            })
+            .collect(),
+        Stmt::AnnAssign(x) => match &*x.target {
+            Expr::Name(target) if target.id.as_str() == name => {
+                vec![(target.range, annotation(&x.annotation))]
+            }
+            _ => Vec::new(),
+        },
+        Stmt::FunctionDef(x) if x.name.as_str() == name => vec![(x.name.range, None)],
+        Stmt::ClassDef(x) if x.name.as_str() == name => vec![(x.name.range, None)],
+        Stmt::ImportFrom(x) => x
+            .names
+            .iter()
+            .map(|alias| alias.asname.as_ref().unwrap_or(&alias.name))
+            .filter(|bound| bound.as_str() == name)
+            .map(|bound| (bound.range, None))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// When `definition` is bound directly in one branch of an `if` statement that doesn't contain
+/// `position`, every binding of the same name made directly in any branch of that statement, in
+/// source order. This is how a name bound both under `if TYPE_CHECKING:` and at runtime is found:
+/// the checker skips statically false branches, so their bindings carry their annotation instead.
+fn conditional_bindings(
+    ast: &ModModule,
+    module: &Module,
+    definition: TextRange,
+    position: TextSize,
+) -> Vec<(TextRange, Option<String>)> {
+    let name = module.code_at(definition);
+    let Some(stmt_if) = Ast::locate_node(ast, definition.start())
+        .into_iter()
+        .find_map(|node| match node {
+            AnyNodeRef::StmtIf(x) => Some(x),
+            _ => None,
+        })
+    else {
+        return Vec::new();
+    };
+    if stmt_if.range.contains(position) {
+        return Vec::new();
+    }
+    let bindings: Vec<_> = Ast::if_branches(stmt_if)
+        .flat_map(|(_, body)| body)
+        .flat_map(|stmt| stmt_bindings_of(stmt, name, module))
+        .collect();
+    // The definition must be one of the bindings, rather than nested deeper inside a branch.
+    if bindings.len() > 1 && bindings.iter().any(|(range, _)| *range == definition) {
+        bindings
+    } else {
+        Vec::new()
+    }
+}
+
+/// Describe each definition of a symbol that resolves to several (e.g. an attribute on a union, or
+/// a name bound in more than one branch): its location, plus its type when the definitions don't all
+/// share one. A definition the checker skipped shows its annotation, if any.
+fn definitions_hover_lines(
+    transaction: &Transaction<'_>,
+    handle: &Handle,
+    definitions: &[(FindDefinitionItemWithDocstring, Option<String>)],
+    fully_qualified_names: bool,
+) -> Vec<String> {
+    let types = definitions.map(|(definition, annotation)| {
+        let definition_handle = Handle::new(
+            definition.module.name(),
+            definition.module.path().dupe(),
+            handle.sys_info().dupe(),
+        );
+        transaction
+            .get_type_at_for_display(&definition_handle, definition.definition_range.start())
+            .map(|ty| {
+                ty.as_lsp_string_with_options(None, LspDisplayMode::Hover, fully_qualified_names)
+            })
+            .or_else(|| annotation.clone())
+    });
+    let types_differ = types.iter().any(|ty| ty != &types[0]);
+    definitions
+        .iter()
+        .zip(types)
+        .map(|((definition, _), ty)| {
+            let location = format!(
+                "`{}:{}`",
+                definition.module.name(),
+                definition
+                    .module
+                    .display_pos(definition.definition_range.start())
+            );
+            match ty {
+                Some(ty) if types_differ => format!("- `{ty}` at {location}"),
+                _ => format!("- {location}"),
+            }
+        })
+        .collect()
+}
+
 pub fn get_hover(
     transaction: &Transaction<'_>,
     handle: &Handle,
     position: TextSize,
     show_go_to_links: bool,
+    max_type_length: Option<usize>,
+    fully_qualified_names: bool,
 ) -> Option<Hover> {
     // Handle hovering over an ignore comment
     if let Some(module) = transaction.get_module_info(handle) {
@@ -741,14 +1046,25 @@ pub fn get_hover(
         });
     }
 
-    let mut type_ = transaction
+    let type_ = transaction
         .subscript_operator_type_at(handle, position)
         .or_else(|| transaction.get_type_at_for_display(handle, position))
-        .or_else(|| transaction.operator_type_at(handle, position))?;
+        .or_else(|| transaction.operator_type_at(handle, position));
+
+    // The type found for a keyword is the literal itself or the enclosing expression's (e.g. the
+    // result of `await`): show it first, with the keyword's description below. Membership tests
+    // keep their `__contains__` hover, and `self`/`cls` their type. The description is shown on
+    // its own only when there is no type.
+    let keyword = keyword_at(transaction, handle, position);
+    let Some(mut type_) = type_ else {
+        return keyword.map(|(word, documentation)| keyword_hover(&word, documentation));
+    };
+    let keyword_doc = keyword
+        .filter(|(word, _)| !matches!(word.as_str(), "in" | "not" | "self" | "cls"))
+        .map(|(_, documentation)| documentation);
 
     // Helper function to check if we're hovering over a callee and get its range
     let find_callee_range_at_position = || -> Option<TextRange> {
-        use ruff_python_ast::Expr;
         let mod_module = transaction.get_ast(handle)?;
         let mut result = None;
         mod_module.visit(&mut |expr: &Expr| {
@@ -779,13 +1095,7 @@ pub fn get_hover(
     }
 
     let fallback_name_from_type = fallback_hover_name_from_type(&type_);
-    let (kind, name, docstring_range, module) = if let Some(FindDefinitionItemWithDocstring {
-        metadata,
-        definition_range: definition_location,
-        module,
-        docstring_range,
-        display_name,
-    }) = transaction
+    let definitions = transaction
         .find_definition(
             handle,
             position,
@@ -795,10 +1105,58 @@ pub fn get_hover(
             },
         )
         .map(Vec1::into_vec)
-        .unwrap_or_default()
-        // TODO: handle more than 1 definition
-        .into_iter()
-        .next()
+        .unwrap_or_default();
+    // When the definition is an implementation with a `.pyi` next to it, link to both.
+    let definition_links = match definitions.first() {
+        Some(def) if show_go_to_links => {
+            let source = TextRangeWithModule::new(def.module.dupe(), def.definition_range);
+            match transaction.search_sibling_stub_definition(handle, &source) {
+                Some(stub) => vec![source, stub],
+                None => Vec::new(),
+            }
+        }
+        _ => Vec::new(),
+    };
+    // A name use resolves to the binding that reaches it along one branch; pick up the bindings
+    // made in the other branches of the same `if` statement.
+    let conditional = match definitions.as_slice() {
+        [definition] if definition.module.path() == handle.path() => transaction
+            .get_ast(handle)
+            .map(|ast| {
+                conditional_bindings(
+                    &ast,
+                    &definition.module,
+                    definition.definition_range,
+                    position,
+                )
+            })
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    // The first definition supplies the kind, name and docstring; the others are listed.
+    let definition_lines = if !conditional.is_empty() {
+        let listed = conditional.into_map(|(range, annotation)| {
+            let definition = FindDefinitionItemWithDocstring {
+                definition_range: range,
+                docstring_range: None,
+                ..definitions[0].clone()
+            };
+            (definition, annotation)
+        });
+        definitions_hover_lines(transaction, handle, &listed, fully_qualified_names)
+    } else if definitions.len() > 1 {
+        let listed = definitions.map(|definition| (definition.clone(), None));
+        definitions_hover_lines(transaction, handle, &listed, fully_qualified_names)
+    } else {
+        Vec::new()
+    };
+    let (kind, name, docstring_range, module) = if let Some(FindDefinitionItemWithDocstring {
+        metadata,
+        definition_range: definition_location,
+        module,
+        docstring_range,
+        display_name,
+    }) = definitions.into_iter().next()
     {
         let kind = metadata.symbol_kind();
         let name = {
@@ -827,27 +1185,61 @@ pub fn get_hover(
         && hover_identifier
             .as_ref()
             .is_some_and(|id| !matches!(id.context, IdentifierContext::ClassDef { .. }));
+    // A property getter is read as a value, so show the type it produces and keep
+    // the getter's own signature as a secondary block.
+    let is_property_getter = type_.is_property_getter();
+    let original_signature = is_property_getter.then(|| {
+        type_.as_lsp_string_with_options(
+            name.as_deref(),
+            LspDisplayMode::Hover,
+            fully_qualified_names,
+        )
+    });
     let type_display = transaction.ad_hoc_solve(handle, "hover_display", {
         let mut cloned = type_.clone();
         move |solver| {
+            if is_property_getter && let Some(ret) = cloned.callable_return_type(solver.heap) {
+                return render_hover_type(&ret, None, max_type_length, fully_qualified_names);
+            }
             if let Some(owner) = &type_parameter_owner_class
                 && let Some(display) = type_parameter_hover_display(&solver, &cloned, owner)
             {
-                return display;
+                return (display, false);
             }
             if show_constructor
-                && let Some(display) =
-                    class_hover_display(&solver, &cloned, name_for_display.as_deref())
+                && let Some(display) = class_hover_display(
+                    &solver,
+                    &cloned,
+                    name_for_display.as_deref(),
+                    fully_qualified_names,
+                )
             {
-                return display;
+                return (display, false);
+            }
+            // Show the declared type parameters of a generic class, e.g. `type[Box[T]]`.
+            if let Type::ClassDef(cls) = &cloned
+                && !solver.get_class_tparams(cls).is_empty()
+                && !solver.get_metadata_for_class(cls).is_typed_dict()
+            {
+                cloned = solver.heap.mk_type(
+                    solver
+                        .heap
+                        .mk_class_type(solver.as_class_type_unchecked(cls)),
+                );
             }
             cloned.transform_toplevel_callable(|c| expand_callable_kwargs_for_hover(&solver, c));
-            cloned.as_lsp_string_with_fallback_name(
+            render_hover_type(
+                &cloned,
                 name_for_display.as_deref(),
-                LspDisplayMode::Hover,
+                max_type_length,
+                fully_qualified_names,
             )
         }
     });
+    let (type_display, display_truncated) = match type_display {
+        Some((display, truncated)) => (Some(display), truncated),
+        None => (None, false),
+    };
 
     let docstring = if let (Some(docstring), Some(module)) = (docstring_range, module) {
         Some(Docstring(docstring, module))
@@ -880,19 +1272,29 @@ pub fn get_hover(
         }
     }
 
-    Some(
-        HoverValue {
-            kind,
-            name,
-            type_,
-            docstring,
-            parameter_doc,
-            type_sources: type_sources_for_hover(transaction, handle, position),
-            display: type_display,
-            show_go_to_links,
-        }
-        .format(transaction, handle),
-    )
+    let mut hover = HoverValue {
+        kind,
+        name,
+        type_,
+        docstring,
+        parameter_doc,
+        type_sources: type_sources_for_hover(transaction, handle, position),
+        display: type_display,
+        display_truncated,
+        original_signature,
+        definitions: definition_lines,
+        show_go_to_links,
+        definition_links,
+        max_type_length,
+        fully_qualified_names,
+    }
+    .format(transaction, handle);
+    if let Some(documentation) = keyword_doc
+        && let HoverContents::Markup(markup) = &mut hover.contents
+    {
+        markup.value.push_str(&format!("\n---\n{documentation}"));
+    }
+    Some(hover)
 }
 
 #[cfg(test)]
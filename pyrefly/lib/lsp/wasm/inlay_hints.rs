@@ -388,6 +388,8 @@ impl<'a> Transaction<'a> {
                                 && let Some(param_match) =
                                     Self::param_name_for_positional_argument(&params, arg_idx)
                                 && !param_match.is_vararg_repeat
+                                // `f(name)` for a parameter called `name` is already self-describing
+                                && !matches!(arg, Expr::Name(name) if name.id == *param_match.name)
                                 && param_match.name.as_str() != "self"
                                 && param_match.name.as_str() != "cls"
                                 && param_match.name.as_str() != "_cls"
@@ -0,0 +1,54 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use pyrefly_build::handle::Handle;
+use pyrefly_python::symbol_kind::SymbolKind;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+
+use crate::binding::binding::Key;
+use crate::state::state::Transaction;
+
+impl<'a> Transaction<'a> {
+    /// The inferred types of the variables and parameters defined in `range`, as
+    /// `(name range, "name: type")` pairs in source order. Debug UIs show these inline when
+    /// they don't have a runtime value for the variable.
+    pub fn inline_value_types(
+        &self,
+        handle: &Handle,
+        range: TextRange,
+    ) -> Option<Vec<(TextRange, String)>> {
+        let bindings = self.get_bindings(handle)?;
+        let module_info = self.get_module_info(handle)?;
+        let mut res = Vec::new();
+        for idx in bindings.keys::<Key>() {
+            let key = bindings.idx_to_key(idx);
+            let Key::Definition(id) = key else {
+                continue;
+            };
+            let name_range = id.range();
+            if !range.contains_range(name_range) {
+                continue;
+            }
+            if !matches!(
+                bindings.get(idx).symbol_kind(),
+                Some(SymbolKind::Variable | SymbolKind::Constant | SymbolKind::Parameter)
+            ) {
+                continue;
+            }
+            let Some(ty) = self.get_type_for_display(handle, key) else {
+                continue;
+            };
+            res.push((
+                name_range,
+                format!("{}: {ty}", module_info.code_at(name_range)),
+            ));
+        }
+        res.sort_by_key(|(range, _)| range.start());
+        Some(res)
+    }
+}
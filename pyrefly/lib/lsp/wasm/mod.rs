@@ -8,6 +8,7 @@
 pub mod completion;
 pub mod hover;
 pub mod inlay_hints;
+pub mod inline_values;
 pub mod notebook;
 pub mod provide_type;
 pub mod semantic_tokens;
@@ -17,6 +17,7 @@ use lsp_types::request::Request;
 use pyrefly_build::handle::Handle;
 use pyrefly_types::display::LspDisplayMode;
 use pyrefly_types::display::TypeDisplayContext;
+use ruff_source_file::PositionEncoding;
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -49,6 +50,7 @@ pub fn provide_type(
     handle: &Handle,
     positions: Vec<Position>,
     notebook_cell: Option<usize>,
+    position_encoding: PositionEncoding,
 ) -> Option<ProvideTypeResponse> {
     // This LSP method works for unopened files.
     // Check if the file is already loaded in memory. If not, load it.
@@ -60,7 +62,7 @@ pub fn provide_type(
     let mut contents = Vec::new();
 
     for position in positions {
-        let text_size = info.from_lsp_position(position, notebook_cell);
+        let text_size = info.from_lsp_position(position, notebook_cell, position_encoding);
         if let Some(ty) = transaction.get_result_type_at_for_display(handle, text_size) {
             let mut c = TypeDisplayContext::new(&[&ty]);
             c.set_lsp_display_mode(LspDisplayMode::ProvideType);
@@ -7,6 +7,7 @@
 
 use lsp_types::SemanticToken;
 use pyrefly_build::handle::Handle;
+use ruff_source_file::PositionEncoding;
 use ruff_text_size::TextRange;
 
 use crate::binding::binding::Key;
@@ -24,6 +25,7 @@ impl Transaction<'_> {
         limit_range: Option<TextRange>,
         limit_cell_idx: Option<usize>,
         include_syntax_tokens: bool,
+        position_encoding: PositionEncoding,
     ) -> Option<Vec<SemanticToken>> {
         let module_info = self.get_module_info(handle)?;
         let parsed = self.get_parsed_module(handle)?;
@@ -56,6 +58,7 @@ impl Transaction<'_> {
             module_info,
             limit_range,
             limit_cell_idx,
+            position_encoding,
         ))
     }
 }
@@ -95,6 +95,53 @@ pub trait BundledStub {
         Ok(temp_dir)
     }
 
+    /// Obtain a path on disk for the single bundled stub file at `relative_path`, writing just that
+    /// file the first time. This is a fallback for when `materialized_path_on_disk` fails, so it
+    /// writes to a separate, per-process directory. That directory may have been left behind by an
+    /// earlier process with the same pid, so an existing file is only reused if its contents match.
+    fn materialized_module_on_disk(&self, relative_path: &Path) -> anyhow::Result<PathBuf> {
+        static WRITTEN_MODULES: LazyLock<Mutex<HashMap<(String, PathBuf), PathBuf>>> =
+            LazyLock::new(|| Mutex::new(HashMap::new()));
+
+        let key = (self.get_path_name(), relative_path.to_owned());
+        let mut written_modules = WRITTEN_MODULES.lock();
+        if let Some(module_path) = written_modules.get(&key) {
+            return Ok(module_path.clone());
+        }
+        let contents = self
+            .load(relative_path)
+            .with_context(|| format!("No bundled stub file at `{}`", relative_path.display()))?;
+        let module_path = env::temp_dir()
+            .join(format!("{}-modules-{}", key.0, std::process::id()))
+            .join(relative_path);
+        let parent = module_path
+            .parent()
+            .with_context(|| format!("No parent directory for `{}`", module_path.display()))?;
+        fs_anyhow::create_dir_all(parent)?;
+        if fs::read(&module_path).ok().as_deref() != Some(contents.as_bytes()) {
+            // A stale file may be readonly, so write a tempfile and atomically rename it over the
+            // destination rather than writing to it directly.
+            let mut temp_file = NamedTempFile::new_in(parent).with_context(|| {
+                format!("When creating temp file for `{}`", module_path.display())
+            })?;
+            temp_file.write_all(contents.as_bytes()).with_context(|| {
+                format!("When writing to temp file for `{}`", module_path.display())
+            })?;
+            temp_file.flush().with_context(|| {
+                format!("When flushing to temp file for `{}`", module_path.display())
+            })?;
+            // Renaming over a readonly file fails on Windows, so clear the flag first.
+            let _ = set_readonly(&module_path, false);
+            temp_file.persist(&module_path).with_context(|| {
+                format!("When persisting temp file to `{}`", module_path.display())
+            })?;
+            // As with `write`, make the file readonly as a guardrail and ignore failures.
+            let _ = set_readonly(&module_path, true);
+        }
+        written_modules.insert(key, module_path.clone());
+        Ok(module_path)
+    }
+
     /// Writes all bundled stub files to a directory on disk.
     ///
     /// File writes are atomic (using temp files and rename) to prevent corruption.
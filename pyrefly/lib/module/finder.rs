@@ -583,6 +583,26 @@ pub fn find_import_filtered(
     )
 }
 
+/// Like [`find_import`], but also returns every candidate path that was probed and found
+/// missing, in the order the lookup tried them. Bypasses all caches, so it always reflects
+/// the current state of the file system.
+pub fn trace_import(
+    config: &ConfigFile,
+    module: ModuleName,
+    origin: Option<&ModulePath>,
+) -> (FindingOrError<ModulePath>, Vec<PathBuf>) {
+    let mut missing = Vec::new();
+    let result = find_import(
+        config,
+        module,
+        origin,
+        Some(&mut missing),
+        &DirEntryCache::new(),
+        None,
+    );
+    (result, missing)
+}
+
 /// Find all legitimate imports that start with `module`
 pub fn find_import_prefixes(config: &ConfigFile, module: ModuleName) -> Vec<ModuleName> {
     let mut results = find_module_prefixes(
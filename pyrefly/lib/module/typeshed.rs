@@ -115,6 +115,7 @@ pub fn stdlib_search_path() -> Option<PathBuf> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::module::bundled::set_readonly;
 
     #[test]
     fn test_typeshed_materialize() {
@@ -124,4 +125,47 @@ mod tests {
         typeshed.materialized_path_on_disk().unwrap();
         typeshed.write(&path).unwrap();
     }
+
+    #[test]
+    fn test_typeshed_materialize_module() {
+        let typeshed = typeshed().unwrap();
+        let builtins = Path::new("builtins.pyi");
+        let path = typeshed.materialized_module_on_disk(builtins).unwrap();
+        assert!(path.ends_with(builtins));
+        assert!(
+            std::fs::read_to_string(&path)
+                .unwrap()
+                .contains("class list(")
+        );
+        // The extracted path is cached.
+        assert_eq!(
+            typeshed.materialized_module_on_disk(builtins).unwrap(),
+            path
+        );
+    }
+
+    #[test]
+    fn test_typeshed_materialize_module_replaces_stale_file() {
+        let typeshed = typeshed().unwrap();
+        let module = Path::new("keyword.pyi");
+        // Simulate a readonly file left behind by an earlier process with the same pid.
+        let stale = env::temp_dir()
+            .join(format!(
+                "{}-modules-{}",
+                typeshed.get_path_name(),
+                std::process::id()
+            ))
+            .join(module);
+        std::fs::create_dir_all(stale.parent().unwrap()).unwrap();
+        let _ = set_readonly(&stale, false);
+        std::fs::write(&stale, "stale").unwrap();
+        set_readonly(&stale, true).unwrap();
+
+        let path = typeshed.materialized_module_on_disk(module).unwrap();
+        assert_eq!(path, stale);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            *typeshed.load(module).unwrap()
+        );
+    }
 }
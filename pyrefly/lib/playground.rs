@@ -35,6 +35,7 @@ use pyrefly_util::lined_buffer::DisplayRange;
 use pyrefly_util::lined_buffer::LineNumber;
 use pyrefly_util::prelude::VecExt;
 use pyrefly_util::thread_pool::ThreadCount;
+use ruff_source_file::PositionEncoding;
 use ruff_text_size::TextSize;
 use serde::Deserialize;
 use serde::Serialize;
@@ -585,7 +586,7 @@ impl Playground {
         let handle = self.handles.get(&self.active_filename)?;
         let transaction = self.state.transaction();
         let position = self.to_text_size(&transaction, pos)?;
-        let hover = get_hover(&transaction, handle, position, true)?;
+        let hover = get_hover(&transaction, handle, position, true, None, false)?;
         Some(MonacoHover {
             contents: vec![hover.contents],
         })
@@ -603,7 +604,7 @@ impl Playground {
         Some(SemanticTokensResult::Tokens(SemanticTokens {
             result_id: None,
             data: transaction
-                .semantic_tokens(handle, range, None, true)
+                .semantic_tokens(handle, range, None, true, PositionEncoding::Utf16)
                 .unwrap_or_default(),
         }))
     }
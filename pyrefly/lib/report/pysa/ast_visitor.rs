@@ -542,3 +542,21 @@ pub fn visit_module_ast<V: AstScopedVisitor>(
     visitor.exit_toplevel_scope(&module_context.answers_context.ast, &scopes);
     scopes
 }
+
+/// Visit a subset of the top-level statements of the module, as `visit_module_ast`
+/// would. Each top-level statement is visited independently of the others, which
+/// allows splitting a module into partitions visited by different visitors.
+/// `enter_toplevel_scope` and `exit_toplevel_scope` are not called.
+pub fn visit_module_statements<V: AstScopedVisitor>(
+    visitor: &mut V,
+    statements: &[Stmt],
+    module_context: &ModuleContext,
+) {
+    let mut scopes = Scopes {
+        stack: vec![Scope::TopLevel],
+    };
+    visitor.on_scope_update(&scopes);
+    for stmt in statements {
+        visit_statement(stmt, visitor, &mut scopes, module_context);
+    }
+}
@@ -82,6 +82,7 @@ use crate::report::pysa::class::ClassId;
 use crate::report::pysa::class::ClassRef;
 use crate::report::pysa::class::get_super_class_member;
 use crate::report::pysa::collect::CollectNoDuplicateKeys;
+use crate::report::pysa::collect::serialize_sorted_map;
 use crate::report::pysa::context::ModuleAnswersContext;
 use crate::report::pysa::context::ModuleContext;
 use crate::report::pysa::function::FunctionBaseDefinition;
@@ -605,7 +606,10 @@ pub struct CallCallees<Function: FunctionTrait> {
     pub init_targets: Vec<PysaCallTarget<Function>>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub new_targets: Vec<PysaCallTarget<Function>>,
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    #[serde(
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_sorted_map"
+    )]
     pub higher_order_parameters: HashMap<u32, HigherOrderParameter<Function>>,
     #[serde(skip_serializing_if = "Unresolved::is_resolved")]
     pub unresolved: Unresolved,
@@ -1146,6 +1150,7 @@ impl<Function: FunctionTrait> ExpressionCallees<Function> {
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct CallGraph<ExpressionId: ExpressionIdTrait, Function: FunctionTrait>(
+    #[serde(serialize_with = "serialize_sorted_map")]
     HashMap<ExpressionId, ExpressionCallees<Function>>,
 );
 
@@ -920,11 +920,14 @@ fn set_project_module(
     builder.set_failed_to_load(module.failed_to_load);
 }
 
-/// Write the project file in Cap'n Proto format.
+/// Write the project file in Cap'n Proto format. There is no typeshed digest, since typeshed can
+/// only be skipped with the JSON format.
 pub fn write_project_file<W: Write>(writer: W, project: &PysaProjectFile) -> anyhow::Result<()> {
     let mut message = capnp::message::Builder::new_default();
     {
         let mut root = message.init_root::<pysa_report_capnp::project_file::Builder>();
+        root.set_format_version(project.format_version);
+        root.set_min_reader_version(project.min_reader_version);
         // builtin_module_ids: List(UInt32)
         {
             let mut list = root
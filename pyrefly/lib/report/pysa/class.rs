@@ -43,6 +43,7 @@ use crate::binding::binding::KeyClassSynthesizedFields;
 use crate::report::pysa::call_graph::Target;
 use crate::report::pysa::call_graph::resolve_decorator_callees;
 use crate::report::pysa::collect::CollectNoDuplicateKeys;
+use crate::report::pysa::collect::serialize_sorted_map;
 use crate::report::pysa::context::ModuleAnswersContext;
 use crate::report::pysa::context::ModuleContext;
 use crate::report::pysa::function::FunctionRef;
@@ -203,9 +204,15 @@ pub struct ClassDefinition {
     pub is_named_tuple: bool,
     #[serde(skip_serializing_if = "<&bool>::not")]
     pub is_typed_dict: bool,
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    #[serde(
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_sorted_map"
+    )]
     pub fields: HashMap<Name, PysaClassField>,
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    #[serde(
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_sorted_map"
+    )]
     pub decorator_callees: HashMap<PysaLocation, Vec<Target<FunctionRef>>>,
 }
 
@@ -5,9 +5,13 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::hash::Hash;
 
+use serde::Serialize;
+use serde::Serializer;
+
 /// Collects all key-value pairs from the iterator into a HashMap,
 /// but fails if there are duplicate keys.
 /// This is similar to `.collect::<HashMap<K, V>>()`.
@@ -50,3 +54,14 @@ where
         collect_no_duplicate_keys(self)
     }
 }
+
+/// Serializes a HashMap with its entries sorted by key, so that the output is
+/// deterministic across runs. Use with `#[serde(serialize_with = "...")]`.
+pub fn serialize_sorted_map<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Ord + Serialize,
+    V: Serialize,
+    S: Serializer,
+{
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
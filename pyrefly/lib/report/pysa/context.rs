@@ -99,6 +99,19 @@ impl<'a> PysaResolver<'a> {
         }
     }
 
+    /// Create a resolver for the same module, starting from a copy of this
+    /// resolver's cache. Useful to resolve from several threads, since a resolver
+    /// cannot be shared between threads.
+    pub fn fork(&self) -> Self {
+        Self {
+            transaction: self.transaction,
+            module_ids: self.module_ids,
+            current_handle: self.current_handle.dupe(),
+            current_module_solutions: self.current_module_solutions.dupe(),
+            cache: RefCell::new(self.cache.borrow().clone()),
+        }
+    }
+
     /// Resolve pysa solutions for a given module, demanding it to Solutions
     /// if needed. Caches the result for subsequent lookups by ModuleId.
     pub fn resolve_pysa_solutions(&self, module: &Module) -> Arc<PysaSolutions> {
@@ -203,6 +216,27 @@ pub struct ModuleAnswersContext {
 pub struct ModuleContext<'a> {
     pub answers_context: ModuleAnswersContext,
     pub resolver: &'a PysaResolver<'a>,
+    pub options: PysaExportOptions,
+}
+
+/// Optional behaviours of the Pysa export. The defaults match what Pysa consumes today.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PysaExportOptions {
+    /// Whether exported type strings include the module of builtins too
+    /// (`builtins.int` rather than `int`).
+    pub fully_qualified_type_names: bool,
+    /// Whether to record, next to the (promoted) type of each exported expression,
+    /// the literal value it had before promotion (e.g. `'SELECT 1'` for a `str`).
+    /// Only supported by the JSON format.
+    pub literal_values: bool,
+    /// Whether to skip copying the bundled typeshed stubs into the output directory,
+    /// for callers that resolve typeshed themselves. The project file then records
+    /// the digest of the bundled typeshed so a matching copy can be located.
+    /// Only supported by the JSON format.
+    pub skip_typeshed: bool,
+    /// Whether to keep a manifest of the exported modules and definitions across runs, and
+    /// write what changed since the previous run to `pyrefly.pysa.changes.json`.
+    pub watch: bool,
 }
 
 impl ModuleAnswersContext {
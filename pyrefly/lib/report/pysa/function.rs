@@ -46,6 +46,7 @@ use crate::report::pysa::class::ClassRef;
 use crate::report::pysa::class::get_all_classes;
 use crate::report::pysa::class::get_class_field_declaration;
 use crate::report::pysa::class::get_class_fields;
+use crate::report::pysa::collect::serialize_sorted_map;
 use crate::report::pysa::context::ModuleAnswersContext;
 use crate::report::pysa::location::PysaLocation;
 use crate::report::pysa::module::ModuleId;
@@ -234,7 +235,10 @@ pub struct FunctionDefinition {
     pub undecorated_signatures: Vec<FunctionSignature>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub captured_variables: Vec<CapturedVariableRef<FunctionRef>>,
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    #[serde(
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_sorted_map"
+    )]
     pub decorator_callees: HashMap<PysaLocation, Vec<Target<FunctionRef>>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     /// If the method directly overrides a method in a parent class, we record that class.
@@ -308,7 +312,9 @@ impl FunctionDefinition {
 }
 
 #[derive(Debug, Clone, Serialize)]
+#[serde(bound(serialize = "GenericFunctionDefinition: Serialize"))]
 pub struct ModuleFunctionDefinitions<GenericFunctionDefinition>(
+    #[serde(serialize_with = "serialize_sorted_map")]
     HashMap<FunctionId, GenericFunctionDefinition>,
 );
 
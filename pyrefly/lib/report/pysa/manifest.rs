@@ -0,0 +1,241 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Support for incremental consumers of the Pysa export. Each run records a manifest of the
+//! exported modules and their definitions; when a manifest from a previous run exists, the
+//! difference between the two is written to `pyrefly.pysa.changes.json`.
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::path::Path;
+
+use pyrefly_build::handle::Handle;
+use pyrefly_python::module::Module;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::visitor::Visitor;
+use ruff_python_ast::visitor::walk_stmt;
+use ruff_text_size::Ranged;
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::report::pysa::PYSA_FORMAT_VERSION;
+use crate::report::pysa::PYSA_MIN_READER_VERSION;
+use crate::report::pysa::step_logger::StepLogger;
+use crate::report::pysa::verify_format_version;
+use crate::state::state::Transaction;
+
+pub const PYSA_MANIFEST_FILENAME: &str = "pyrefly.pysa.manifest.json";
+pub const PYSA_CHANGES_FILENAME: &str = "pyrefly.pysa.changes.json";
+
+/// Format of the file `pyrefly.pysa.manifest.json`, describing the result of a run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PysaManifest {
+    pub format_version: u32,
+    /// Oldest reader version able to consume this manifest. Missing from manifests written before
+    /// it was added, which any reader can consume.
+    #[serde(default)]
+    pub min_reader_version: u32,
+    /// Exported modules, keyed by module name.
+    pub modules: BTreeMap<String, PysaManifestModule>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PysaManifestModule {
+    pub source_hash: String,
+    /// Hash of the source of each class and function, keyed by its dotted name within the module
+    /// (e.g. `Foo.bar`).
+    pub definitions: BTreeMap<String, String>,
+}
+
+/// Format of the file `pyrefly.pysa.changes.json`, listing what changed since the previous run.
+/// Definitions are written as `module:dotted.name`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PysaChanges {
+    pub format_version: u32,
+    pub added_modules: Vec<String>,
+    pub removed_modules: Vec<String>,
+    pub modified_modules: Vec<String>,
+    pub added_definitions: Vec<String>,
+    pub removed_definitions: Vec<String>,
+    pub modified_definitions: Vec<String>,
+}
+
+fn hash(text: &str) -> String {
+    blake3::hash(text.as_bytes()).to_hex().to_string()
+}
+
+struct DefinitionCollector<'a> {
+    module: &'a Module,
+    scope: Vec<String>,
+    sources: BTreeMap<String, String>,
+}
+
+impl<'a> Visitor<'a> for DefinitionCollector<'_> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        let name = match stmt {
+            Stmt::FunctionDef(function_def) => &function_def.name,
+            Stmt::ClassDef(class_def) => &class_def.name,
+            _ => {
+                walk_stmt(self, stmt);
+                return;
+            }
+        };
+        self.scope.push(name.id.to_string());
+        // Redefinitions (e.g. overloads) share a name, so their sources are combined.
+        self.sources
+            .entry(self.scope.join("."))
+            .or_default()
+            .push_str(self.module.code_at(stmt.range()));
+        walk_stmt(self, stmt);
+        self.scope.pop();
+    }
+}
+
+fn manifest_module(
+    module: &Module,
+    transaction: &Transaction,
+    handle: &Handle,
+) -> PysaManifestModule {
+    let mut collector = DefinitionCollector {
+        module,
+        scope: Vec::new(),
+        sources: BTreeMap::new(),
+    };
+    if let Some(ast) = transaction.get_ast(handle) {
+        collector.visit_body(&ast.body);
+    }
+    PysaManifestModule {
+        source_hash: hash(module.contents()),
+        definitions: collector
+            .sources
+            .into_iter()
+            .map(|(name, source)| (name, hash(&source)))
+            .collect(),
+    }
+}
+
+/// Build the manifest of the given modules. When several handles share a module name, the
+/// first one in sorted order is used.
+pub fn build_manifest(handles: &[Handle], transaction: &Transaction) -> PysaManifest {
+    let mut sorted_handles = handles.iter().collect::<Vec<_>>();
+    sorted_handles.sort();
+
+    let mut modules = BTreeMap::new();
+    for handle in sorted_handles {
+        let module_name = handle.module().to_string();
+        if modules.contains_key(&module_name) {
+            continue;
+        }
+        if let Some(module) = transaction.get_module_info(handle) {
+            modules.insert(module_name, manifest_module(&module, transaction, handle));
+        }
+    }
+    PysaManifest {
+        format_version: PYSA_FORMAT_VERSION,
+        min_reader_version: PYSA_MIN_READER_VERSION,
+        modules,
+    }
+}
+
+/// Compute the changes between the manifests of two runs.
+pub fn diff_manifests(previous: &PysaManifest, current: &PysaManifest) -> PysaChanges {
+    let mut changes = PysaChanges {
+        format_version: PYSA_FORMAT_VERSION,
+        ..Default::default()
+    };
+    let qualified = |module_name: &str, name: &str| format!("{module_name}:{name}");
+
+    for (module_name, module) in &current.modules {
+        let Some(previous_module) = previous.modules.get(module_name) else {
+            changes.added_modules.push(module_name.clone());
+            changes.added_definitions.extend(
+                module
+                    .definitions
+                    .keys()
+                    .map(|name| qualified(module_name, name)),
+            );
+            continue;
+        };
+        if previous_module.source_hash == module.source_hash {
+            continue;
+        }
+        changes.modified_modules.push(module_name.clone());
+        for (name, definition_hash) in &module.definitions {
+            match previous_module.definitions.get(name) {
+                None => changes.added_definitions.push(qualified(module_name, name)),
+                Some(previous_hash) if previous_hash != definition_hash => changes
+                    .modified_definitions
+                    .push(qualified(module_name, name)),
+                Some(_) => {}
+            }
+        }
+        changes.removed_definitions.extend(
+            previous_module
+                .definitions
+                .keys()
+                .filter(|name| !module.definitions.contains_key(*name))
+                .map(|name| qualified(module_name, name)),
+        );
+    }
+    for (module_name, module) in &previous.modules {
+        if !current.modules.contains_key(module_name) {
+            changes.removed_modules.push(module_name.clone());
+            changes.removed_definitions.extend(
+                module
+                    .definitions
+                    .keys()
+                    .map(|name| qualified(module_name, name)),
+            );
+        }
+    }
+    changes
+}
+
+/// Write `manifest` to the results directory. If the directory holds the manifest of a previous
+/// run, first write the changes since that run to `pyrefly.pysa.changes.json`.
+pub fn write_manifest_and_changes(
+    results_directory: &Path,
+    manifest: &PysaManifest,
+) -> anyhow::Result<()> {
+    let step = StepLogger::start("Writing manifest and changes", "Wrote manifest and changes");
+
+    let manifest_path = results_directory.join(PYSA_MANIFEST_FILENAME);
+    let changes_path = results_directory.join(PYSA_CHANGES_FILENAME);
+    let previous = File::open(&manifest_path)
+        .ok()
+        .and_then(|file| serde_json::from_reader::<_, PysaManifest>(BufReader::new(file)).ok())
+        .filter(|previous| {
+            match verify_format_version(previous.format_version, previous.min_reader_version) {
+                Ok(()) => true,
+                Err(error) => {
+                    warn!("Ignoring the previous Pysa manifest: {error:#}");
+                    false
+                }
+            }
+        });
+    match previous {
+        Some(previous) => {
+            let changes = diff_manifests(&previous, manifest);
+            let writer = BufWriter::new(File::create(&changes_path)?);
+            serde_json::to_writer(writer, &changes)?;
+        }
+        None => {
+            // Without a usable previous manifest, a stale changes file would be misleading.
+            if changes_path.exists() {
+                std::fs::remove_file(&changes_path)?;
+            }
+        }
+    }
+    let writer = BufWriter::new(File::create(&manifest_path)?);
+    serde_json::to_writer(writer, manifest)?;
+
+    step.finish();
+    Ok(())
+}
@@ -16,6 +16,7 @@ pub mod function;
 pub mod global_variable;
 pub mod is_test_module;
 pub mod location;
+pub mod manifest;
 pub mod module;
 pub mod module_index;
 pub mod override_graph;
@@ -37,6 +38,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use dupe::Dupe;
+use itertools::Itertools;
 use pyrefly_build::handle::Handle;
 use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::module_path::ModulePathDetails;
@@ -64,8 +66,10 @@ use crate::report::pysa::class::ClassId;
 use crate::report::pysa::class::ClassRef;
 use crate::report::pysa::class::export_all_classes;
 use crate::report::pysa::collect::CollectNoDuplicateKeys;
+use crate::report::pysa::collect::serialize_sorted_map;
 use crate::report::pysa::context::ModuleAnswersContext;
 use crate::report::pysa::context::ModuleContext;
+use crate::report::pysa::context::PysaExportOptions;
 use crate::report::pysa::context::PysaResolver;
 use crate::report::pysa::function::FunctionBaseDefinition;
 use crate::report::pysa::function::FunctionDefinition;
@@ -79,12 +83,15 @@ use crate::report::pysa::global_variable::ModuleGlobalVariables;
 use crate::report::pysa::global_variable::collect_global_variables_for_module;
 use crate::report::pysa::global_variable::export_global_variables;
 use crate::report::pysa::location::PysaLocation;
+use crate::report::pysa::manifest::build_manifest;
+use crate::report::pysa::manifest::write_manifest_and_changes;
 use crate::report::pysa::module::ModuleId;
 use crate::report::pysa::module::ModuleIds;
 use crate::report::pysa::module_index::PysaModuleIndex;
 use crate::report::pysa::override_graph::ModuleReversedOverrideGraph;
 use crate::report::pysa::override_graph::create_reversed_override_graph_for_module;
 use crate::report::pysa::step_logger::StepLogger;
+use crate::report::pysa::type_of_expression::StreamedTypeOfExpressions;
 use crate::report::pysa::type_of_expression::export_type_of_expressions;
 use crate::report::pysa::types::PysaType;
 use crate::state::state::Transaction;
@@ -117,10 +124,37 @@ pub struct PysaProjectModule {
     pub failed_to_load: bool, // Source file could not be loaded (common case: non-UTF-8 encoding)
 }
 
+/// Version of the format of the files written by the Pysa exporter.
+/// Bump this whenever the format changes.
+pub const PYSA_FORMAT_VERSION: u32 = 1;
+
+/// Oldest reader version able to consume files written with
+/// `PYSA_FORMAT_VERSION`. Only bump this for changes an older reader would
+/// misinterpret, not for purely additive ones.
+pub const PYSA_MIN_READER_VERSION: u32 = 1;
+
+/// Check that a reader supporting `PYSA_FORMAT_VERSION` can consume a file
+/// written with `format_version`, which requires readers of at least
+/// `min_reader_version`.
+pub fn verify_format_version(format_version: u32, min_reader_version: u32) -> anyhow::Result<()> {
+    if min_reader_version > PYSA_FORMAT_VERSION {
+        anyhow::bail!(
+            "Pysa format version {format_version} requires a reader supporting version {min_reader_version} or newer, but this reader only supports version {PYSA_FORMAT_VERSION}"
+        );
+    }
+    Ok(())
+}
+
 /// Format of the index file `pyrefly.pysa.json`
 #[derive(Debug, Clone, Serialize)]
 pub struct PysaProjectFile {
     pub format_version: u32,
+    pub min_reader_version: u32,
+    /// SHA-256 digest of the bundled typeshed, set when typeshed files were not
+    /// written to the output directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub typeshed_digest: Option<String>,
+    #[serde(serialize_with = "serialize_sorted_map")]
     pub modules: HashMap<ModuleId, PysaProjectModule>,
     pub builtin_module_ids: Vec<ModuleId>,
     pub object_class_refs: Vec<ClassRef>,
@@ -137,7 +171,9 @@ pub struct PysaModuleDefinitions {
     pub module_name: ModuleName,
     pub source_path: ModulePathDetails,
     pub function_definitions: ModuleFunctionDefinitions<FunctionDefinition>,
+    #[serde(serialize_with = "serialize_sorted_map")]
     pub class_definitions: HashMap<ClassId, ClassDefinition>,
+    #[serde(serialize_with = "serialize_sorted_map")]
     pub global_variables: HashMap<Name, GlobalVariable>,
 }
 
@@ -151,7 +187,15 @@ pub struct FunctionTypeOfExpressions {
     /// Deduplicated type table. `LocalTypeId(n)` refers to `type_table[n]`.
     pub type_table: Vec<PysaType>,
     /// Map from expression location to its LocalTypeId in the type table.
+    #[serde(serialize_with = "serialize_sorted_map")]
     pub locations: HashMap<PysaLocation, LocalTypeId>,
+    /// Map from expression location to the literal value the expression had before
+    /// literal promotion. Only populated with `PysaExportOptions::literal_values`.
+    #[serde(
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_sorted_map"
+    )]
+    pub literal_of_expression: HashMap<PysaLocation, String>,
 }
 
 /// Format of the file `type_of_expressions/my.module:id.json` containing type of expressions
@@ -161,9 +205,20 @@ pub struct PysaModuleTypeOfExpressions {
     pub module_id: ModuleId,
     pub module_name: ModuleName,
     pub source_path: ModulePathDetails,
+    #[serde(serialize_with = "serialize_sorted_map")]
     pub functions: HashMap<FunctionId, FunctionTypeOfExpressions>,
 }
 
+/// Same format as `PysaModuleTypeOfExpressions`, with functions serialized as they are visited.
+#[derive(Serialize)]
+struct PysaModuleTypeOfExpressionsStream<'a> {
+    format_version: u32,
+    module_id: ModuleId,
+    module_name: ModuleName,
+    source_path: ModulePathDetails,
+    functions: StreamedTypeOfExpressions<'a>,
+}
+
 /// Format of the file `call_graphs/my.module:id.json` containing module call graphs
 #[derive(Debug, Clone, Serialize)]
 pub struct PysaModuleCallGraphs {
@@ -171,6 +226,7 @@ pub struct PysaModuleCallGraphs {
     pub module_id: ModuleId,
     pub module_name: ModuleName,
     pub source_path: ModulePathDetails,
+    #[serde(serialize_with = "serialize_sorted_map")]
     pub call_graphs: HashMap<FunctionId, CallGraph<ExpressionIdentifier, FunctionRef>>,
 }
 
@@ -220,6 +276,7 @@ pub struct PysaReporter {
     pub type_of_expressions_directory: PathBuf,
     pub call_graphs_directory: PathBuf,
     pub format: PysaFormat,
+    pub options: PysaExportOptions,
 }
 
 impl PysaReporter {
@@ -228,7 +285,18 @@ impl PysaReporter {
         pysa_directory: &Path,
         handles: &[Handle],
         format: PysaFormat,
+        options: PysaExportOptions,
     ) -> anyhow::Result<Box<Self>> {
+        if options.literal_values && matches!(format, PysaFormat::Capnp) {
+            anyhow::bail!(
+                "Exporting literal values to Pysa is only supported with the json format"
+            );
+        }
+        if options.skip_typeshed && matches!(format, PysaFormat::Capnp) {
+            anyhow::bail!(
+                "Skipping typeshed in the Pysa export is only supported with the json format"
+            );
+        }
         tracing::debug!("Writing pysa results to `{}`", pysa_directory.display());
 
         pyrefly_util::fs_anyhow::create_dir_all(pysa_directory)?;
@@ -248,6 +316,7 @@ impl PysaReporter {
             type_of_expressions_directory,
             call_graphs_directory,
             format,
+            options,
         }))
     }
 
@@ -288,6 +357,7 @@ impl PysaReporter {
                     &self.module_ids,
                 ),
                 resolver: &resolver,
+                options: self.options,
             };
 
             let captured_variables = collect_captured_variables_for_module(&context);
@@ -306,18 +376,18 @@ impl PysaReporter {
                     .expect("Failed to write definitions file"),
             }
 
-            let module_type_of_expressions = export_module_type_of_expressions(&context);
             let writer = BufWriter::new(
                 File::create(self.type_of_expressions_directory.join(info_filename))
                     .expect("Failed to create type_of_expressions file"),
             );
             match self.format {
-                PysaFormat::Json => serde_json::to_writer(writer, &module_type_of_expressions)
+                PysaFormat::Json => write_module_type_of_expressions_json(writer, &context)
                     .expect("Failed to write type_of_expressions file"),
-                PysaFormat::Capnp => {
-                    capnp_writer::write_type_of_expressions(writer, &module_type_of_expressions)
-                        .expect("Failed to write type_of_expressions file")
-                }
+                PysaFormat::Capnp => capnp_writer::write_type_of_expressions(
+                    writer,
+                    &export_module_type_of_expressions(&context),
+                )
+                .expect("Failed to write type_of_expressions file"),
             }
 
             let module_call_graphs = export_module_call_graphs(&context, &captured_variables);
@@ -370,7 +440,7 @@ pub fn export_module_definitions(
     let function_definitions =
         export_function_definitions(&captured_variables, reversed_override_graph, context);
     PysaModuleDefinitions {
-        format_version: 1,
+        format_version: PYSA_FORMAT_VERSION,
         module_id: context.answers_context.module_id,
         module_name: context.answers_context.module_info.name(),
         source_path: absolutize_source_path(context.answers_context.module_info.path().details()),
@@ -380,10 +450,32 @@ pub fn export_module_definitions(
     }
 }
 
+/// Write the JSON `type_of_expressions` file of a module. Unlike
+/// `export_module_type_of_expressions`, each function is serialized as soon as
+/// it has been visited, so the types of the whole module are never held in
+/// memory. The output is the same, with functions sorted.
+pub fn write_module_type_of_expressions_json(
+    writer: impl std::io::Write,
+    context: &ModuleContext,
+) -> serde_json::Result<()> {
+    serde_json::to_writer(
+        writer,
+        &PysaModuleTypeOfExpressionsStream {
+            format_version: PYSA_FORMAT_VERSION,
+            module_id: context.answers_context.module_id,
+            module_name: context.answers_context.module_info.name(),
+            source_path: absolutize_source_path(
+                context.answers_context.module_info.path().details(),
+            ),
+            functions: StreamedTypeOfExpressions::new(context),
+        },
+    )
+}
+
 pub fn export_module_type_of_expressions(context: &ModuleContext) -> PysaModuleTypeOfExpressions {
     let functions = export_type_of_expressions(context);
     PysaModuleTypeOfExpressions {
-        format_version: 1,
+        format_version: PYSA_FORMAT_VERSION,
         module_id: context.answers_context.module_id,
         module_name: context.answers_context.module_info.name(),
         source_path: absolutize_source_path(context.answers_context.module_info.path().details()),
@@ -401,7 +493,7 @@ pub fn export_module_call_graphs(
         .collect_no_duplicate_keys()
         .expect("Found multiple call graphs for the same function");
     PysaModuleCallGraphs {
-        format_version: 1,
+        format_version: PYSA_FORMAT_VERSION,
         module_id: context.answers_context.module_id,
         module_name: context.answers_context.module_info.name(),
         source_path: absolutize_source_path(context.answers_context.module_info.path().details()),
@@ -409,8 +501,10 @@ pub fn export_module_call_graphs(
     }
 }
 
+/// Build the module list of the project file. Module ids are keyed on the full
+/// handle, so distinct files sharing a module name get separate entries.
 fn build_module_mapping(
-    handles: &Vec<Handle>,
+    handles: &[Handle],
     project_handles: &[Handle],
     module_ids: &ModuleIds,
     transaction: &Transaction,
@@ -422,7 +516,7 @@ fn build_module_mapping(
     let project_handles: HashSet<&Handle> = project_handles.iter().collect();
 
     let mut project_modules = HashMap::new();
-    for handle in handles {
+    for handle in handles.iter().unique() {
         let module_id = module_ids.get_from_handle(handle);
         let failed_to_load = transaction
             .get_load(handle)
@@ -471,32 +565,27 @@ fn build_module_mapping(
             handle.sys_info().type_checking(),
             "Expected type_checking to be true for handle"
         );
-        assert!(
-            project_modules
-                .insert(
-                    module_id,
-                    PysaProjectModule {
-                        module_id,
-                        module_name,
-                        source_path: absolutize_source_path(module_path.details()),
-                        relative_source_path,
-                        info_filename: info_filename.clone(),
-                        is_test: transaction
-                            .get_solutions(handle)
-                            .expect("missing solutions")
-                            .pysa_solutions()
-                            .expect("missing pysa solutions")
-                            .is_test_module,
-                        is_interface: handle.path().is_interface(),
-                        is_init: handle.path().is_init(),
-                        is_internal: project_handles.contains(handle),
-                        failed_to_load,
-                        python_version: handle.sys_info().version(),
-                        platform: handle.sys_info().platform().clone(),
-                    }
-                )
-                .is_none(),
-            "Found multiple handles with the same module id"
+        project_modules.insert(
+            module_id,
+            PysaProjectModule {
+                module_id,
+                module_name,
+                source_path: absolutize_source_path(module_path.details()),
+                relative_source_path,
+                info_filename: info_filename.clone(),
+                is_test: transaction
+                    .get_solutions(handle)
+                    .expect("missing solutions")
+                    .pysa_solutions()
+                    .expect("missing pysa solutions")
+                    .is_test_module,
+                is_interface: handle.path().is_interface(),
+                is_init: handle.path().is_init(),
+                is_internal: project_handles.contains(handle),
+                failed_to_load,
+                python_version: handle.sys_info().version(),
+                platform: handle.sys_info().platform().clone(),
+            },
         );
     }
 
@@ -523,17 +612,24 @@ fn write_bundle_stubs(bundle: &impl BundledStub, directory: &Path) -> anyhow::Re
 }
 
 // Dump all bundled stub files, so we can parse them.
-fn write_typeshed_files(results_directory: &Path) -> anyhow::Result<()> {
+/// Write the bundled stubs into the output directory. Stubs coming from typeshed
+/// are skipped with `PysaExportOptions::skip_typeshed`.
+pub fn write_typeshed_files(
+    results_directory: &Path,
+    options: PysaExportOptions,
+) -> anyhow::Result<()> {
     let step = StepLogger::start("Exporting typeshed files", "Exported typeshed files");
 
-    let typeshed = typeshed()?;
-    write_bundle_stubs(typeshed, &results_directory.join("typeshed"))?;
+    if !options.skip_typeshed {
+        let typeshed = typeshed()?;
+        write_bundle_stubs(typeshed, &results_directory.join("typeshed"))?;
 
-    if let Ok(typeshed_third_party) = typeshed_third_party() {
-        write_bundle_stubs(
-            typeshed_third_party,
-            &results_directory.join("typeshed_third_party"),
-        )?;
+        if let Ok(typeshed_third_party) = typeshed_third_party() {
+            write_bundle_stubs(
+                typeshed_third_party,
+                &results_directory.join("typeshed_third_party"),
+            )?;
+        }
     }
 
     if let Ok(bundled_third_party) = get_bundled_third_party() {
@@ -567,7 +663,7 @@ fn write_errors_file(
     let step = StepLogger::start("Exporting type errors", "Exported type errors");
 
     let errors = PysaTypeErrorsFile {
-        format_version: 1,
+        format_version: PYSA_FORMAT_VERSION,
         errors: errors
             .iter()
             .map(|error| PysaTypeError {
@@ -612,7 +708,7 @@ pub fn write_project_file(
     let format = pysa_reporter.format;
     let file_extension = pysa_reporter.file_extension();
 
-    write_typeshed_files(results_directory)?;
+    write_typeshed_files(results_directory, pysa_reporter.options)?;
     write_errors_file(results_directory, errors, format)?;
 
     let project_filename = format!("pyrefly.pysa.{file_extension}");
@@ -685,7 +781,12 @@ pub fn write_project_file(
         .collect::<Vec<_>>();
 
     let project_file = PysaProjectFile {
-        format_version: 1,
+        format_version: PYSA_FORMAT_VERSION,
+        min_reader_version: PYSA_MIN_READER_VERSION,
+        typeshed_digest: pysa_reporter
+            .options
+            .skip_typeshed
+            .then(|| faster_hex::hex_string(pyrefly_bundled::BUNDLED_TYPESHED_DIGEST)),
         modules: project_modules,
         builtin_module_ids,
         object_class_refs,
@@ -704,7 +805,11 @@ pub fn write_project_file(
             capnp_writer::write_project_file(writer, &project_file)?;
         }
     }
-
     step.finish();
+
+    if pysa_reporter.options.watch {
+        let manifest = build_manifest(&handles, transaction);
+        write_manifest_and_changes(results_directory, &manifest)?;
+    }
     Ok(())
 }
@@ -65,20 +65,20 @@ impl ModuleIds {
             })
             .collect();
         typeshed_handles.sort();
+        typeshed_handles.dedup();
 
-        // Build sorted project handles.
+        // Build sorted project handles. The same handle may be listed more than
+        // once, in which case it gets a single ID.
         let mut sorted_handles = handles.to_vec();
         sorted_handles.sort();
+        sorted_handles.dedup();
 
         let map = DashMap::new();
         let mut current_id = 1u32;
 
         // Assign typeshed IDs first.
         for handle in typeshed_handles {
-            assert!(
-                map.insert(handle, ModuleId(current_id)).is_none(),
-                "Found multiple typeshed modules with the same module name, path, and sys_info"
-            );
+            map.insert(handle, ModuleId(current_id));
             current_id += 1;
         }
 
@@ -92,10 +92,7 @@ impl ModuleIds {
             if map.contains_key(&handle) {
                 continue;
             }
-            assert!(
-                map.insert(handle, ModuleId(current_id)).is_none(),
-                "Found multiple handles with the same module name, path, and sys_info"
-            );
+            map.insert(handle, ModuleId(current_id));
             current_id += 1;
         }
 
@@ -5,12 +5,24 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 
+use dupe::Dupe;
+use pyrefly_types::types::Type;
+use rayon::prelude::*;
 use ruff_python_ast::Expr;
 use ruff_python_ast::ExprAttribute;
 use ruff_python_ast::ExprCall;
+use ruff_python_ast::StmtClassDef;
+use ruff_python_ast::StmtFunctionDef;
 use ruff_text_size::Ranged;
+use serde::Serialize;
+use serde::Serializer;
+use serde::ser::Error as _;
+use serde::ser::SerializeMap;
 
 use crate::report::pysa::FunctionTypeOfExpressions;
 use crate::report::pysa::LocalTypeId;
@@ -21,6 +33,7 @@ use crate::report::pysa::ast_visitor::ExportFunctionDecorators;
 use crate::report::pysa::ast_visitor::ScopeExportedFunctionFlags;
 use crate::report::pysa::ast_visitor::Scopes;
 use crate::report::pysa::ast_visitor::visit_module_ast;
+use crate::report::pysa::ast_visitor::visit_module_statements;
 use crate::report::pysa::context::ModuleContext;
 use crate::report::pysa::function::FunctionId;
 use crate::report::pysa::location::PysaLocation;
@@ -31,6 +44,7 @@ struct FunctionTypeOfExpressionsBuilder {
     type_table: Vec<PysaType>,
     type_to_id: HashMap<PysaType, LocalTypeId>,
     locations: HashMap<PysaLocation, LocalTypeId>,
+    literal_of_expression: HashMap<PysaLocation, String>,
 }
 
 impl FunctionTypeOfExpressionsBuilder {
@@ -39,6 +53,7 @@ impl FunctionTypeOfExpressionsBuilder {
             type_table: Vec::new(),
             type_to_id: HashMap::new(),
             locations: HashMap::new(),
+            literal_of_expression: HashMap::new(),
         }
     }
 
@@ -62,21 +77,95 @@ impl FunctionTypeOfExpressionsBuilder {
         }
     }
 
+    /// Record the literal value of the expression at a location. Skips duplicates.
+    fn add_literal(&mut self, location: PysaLocation, literal: String) {
+        self.literal_of_expression
+            .entry(location)
+            .or_insert(literal);
+    }
+
+    /// Merge the entries of a builder for statements visited after the ones of `self`.
+    /// Locations already present are kept, as in `add_location`.
+    fn merge(&mut self, other: Self) {
+        // Insert in type id order, so that the type table is the same as if
+        // all statements had been visited by a single builder.
+        let mut locations = other.locations.into_iter().collect::<Vec<_>>();
+        locations.sort_by_key(|(_, id)| id.0);
+        for (location, id) in locations {
+            self.add_location(location, other.type_table[id.0 as usize].clone());
+        }
+        for (location, literal) in other.literal_of_expression {
+            self.add_literal(location, literal);
+        }
+    }
+
     fn build(self) -> FunctionTypeOfExpressions {
         FunctionTypeOfExpressions {
             type_table: self.type_table,
             locations: self.locations,
+            literal_of_expression: self.literal_of_expression,
         }
     }
 }
 
+/// The function that the type of the expressions visited in `scopes` are exported for.
+fn current_function_id(module_context: &ModuleContext, scopes: &Scopes) -> Option<FunctionId> {
+    scopes
+        .current_exported_function(
+            module_context.answers_context.module_id,
+            module_context.answers_context.module_info.name(),
+            &ScopeExportedFunctionFlags {
+                include_top_level: true,
+                include_class_top_level: true,
+                include_function_decorators: ExportFunctionDecorators::InParentScope,
+                include_class_decorators: ExportClassDecorators::InParentScope,
+                include_default_arguments: ExportDefaultArguments::InFunction,
+            },
+        )
+        .map(|func_ref| func_ref.function_id)
+}
+
 struct TypeOfExpressionVisitor<'a> {
     module_context: &'a ModuleContext<'a>,
     current_function: Option<FunctionId>,
     result: HashMap<FunctionId, FunctionTypeOfExpressionsBuilder>,
+    /// If set, each function is removed from `result` and passed to this callback
+    /// as soon as all of its expressions have been visited.
+    on_function_done: Option<&'a mut dyn FnMut(FunctionId, FunctionTypeOfExpressions)>,
 }
 
 impl<'a> TypeOfExpressionVisitor<'a> {
+    fn new(module_context: &'a ModuleContext<'a>) -> Self {
+        Self {
+            module_context,
+            current_function: None,
+            result: HashMap::new(),
+            on_function_done: None,
+        }
+    }
+
+    /// Called when leaving the scope of the current function, after which no
+    /// expression can be added to it.
+    fn current_function_done(&mut self) {
+        if let Some(on_function_done) = &mut self.on_function_done
+            && let Some(function_id) = &self.current_function
+            && let Some(builder) = self.result.remove(function_id)
+        {
+            on_function_done(function_id.clone(), builder.build());
+        }
+    }
+
+    /// Pass the remaining functions (e.g, the module top level) to `on_function_done`.
+    fn all_functions_done(&mut self) {
+        if let Some(on_function_done) = &mut self.on_function_done {
+            let mut remaining = self.result.drain().collect::<Vec<_>>();
+            remaining.sort_by(|(left, _), (right, _)| left.cmp(right));
+            for (function_id, builder) in remaining {
+                on_function_done(function_id, builder.build());
+            }
+        }
+    }
+
     /// Export the type of a single expression, if it has one.
     fn maybe_export_type(&mut self, e: &Expr) {
         let function_id = match &self.current_function {
@@ -95,29 +184,24 @@ impl<'a> TypeOfExpressionVisitor<'a> {
                 &self.module_context.answers_context.module_info,
             );
             let pysa_type = PysaType::from_type(&type_, self.module_context);
-            self.result
+            let builder = self
+                .result
                 .entry(function_id.clone())
-                .or_insert_with(FunctionTypeOfExpressionsBuilder::new)
-                .add_location(location, pysa_type);
+                .or_insert_with(FunctionTypeOfExpressionsBuilder::new);
+            // The exported type has its literals promoted, so keep the original value too.
+            if self.module_context.options.literal_values
+                && let Type::Literal(literal) = &type_
+            {
+                builder.add_literal(location.clone(), literal.value.to_string());
+            }
+            builder.add_location(location, pysa_type);
         }
     }
 }
 
 impl AstScopedVisitor for TypeOfExpressionVisitor<'_> {
     fn on_scope_update(&mut self, scopes: &Scopes) {
-        self.current_function = scopes
-            .current_exported_function(
-                self.module_context.answers_context.module_id,
-                self.module_context.answers_context.module_info.name(),
-                &ScopeExportedFunctionFlags {
-                    include_top_level: true,
-                    include_class_top_level: true,
-                    include_function_decorators: ExportFunctionDecorators::InParentScope,
-                    include_class_decorators: ExportClassDecorators::InParentScope,
-                    include_default_arguments: ExportDefaultArguments::InFunction,
-                },
-            )
-            .map(|func_ref| func_ref.function_id);
+        self.current_function = current_function_id(self.module_context, scopes);
         if let Some(function_id) = &self.current_function {
             // Always insert an empty entry for the function.
             // This way we can error on missing type-of-expressions in pysa.
@@ -153,24 +237,170 @@ impl AstScopedVisitor for TypeOfExpressionVisitor<'_> {
         }
     }
 
+    fn exit_function_scope(&mut self, _function_def: &StmtFunctionDef, _scopes: &Scopes) {
+        self.current_function_done();
+    }
+
+    fn exit_class_scope(&mut self, _class_def: &StmtClassDef, _scopes: &Scopes) {
+        self.current_function_done();
+    }
+
     fn visit_type_annotations() -> bool {
         false
     }
 }
 
+/// Collects the functions that `TypeOfExpressionVisitor` creates an entry for,
+/// without looking up any type.
+struct FunctionIdCollector<'a> {
+    module_context: &'a ModuleContext<'a>,
+    function_ids: BTreeSet<FunctionId>,
+}
+
+impl AstScopedVisitor for FunctionIdCollector<'_> {
+    fn on_scope_update(&mut self, scopes: &Scopes) {
+        if let Some(function_id) = current_function_id(self.module_context, scopes) {
+            self.function_ids.insert(function_id);
+        }
+    }
+
+    fn visit_type_annotations() -> bool {
+        false
+    }
+}
+
+/// Serializes the type of expressions of a module as a JSON map from function to
+/// `FunctionTypeOfExpressions`, without building the whole map first. The functions
+/// are collected up front, so that each one can be written, sorted like
+/// `PysaModuleTypeOfExpressions`, as soon as it and every function before it have
+/// been visited. Only functions visited ahead of their turn (e.g, nested functions,
+/// class bodies and the module top level) are kept until then. The module is visited
+/// sequentially; only `export_type_of_expressions` visits partitions in parallel.
+pub struct StreamedTypeOfExpressions<'a> {
+    context: &'a ModuleContext<'a>,
+}
+
+impl<'a> StreamedTypeOfExpressions<'a> {
+    pub fn new(context: &'a ModuleContext<'a>) -> Self {
+        Self { context }
+    }
+}
+
+impl Serialize for StreamedTypeOfExpressions<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut collector = FunctionIdCollector {
+            module_context: self.context,
+            function_ids: BTreeSet::new(),
+        };
+        visit_module_ast(&mut collector, self.context);
+        let function_ids = collector.function_ids;
+        let mut order = function_ids.iter().peekable();
+        let mut map = serializer.serialize_map(Some(function_ids.len()))?;
+        let mut pending: BTreeMap<FunctionId, FunctionTypeOfExpressions> = BTreeMap::new();
+        let mut result = Ok(());
+        let mut on_function_done =
+            |function_id: FunctionId, function: FunctionTypeOfExpressions| {
+                if result.is_err() {
+                    return;
+                }
+                if !function_ids.contains(&function_id) {
+                    result = Err(S::Error::custom(format!(
+                        "Found type of expressions for unexpected function `{function_id:?}`"
+                    )));
+                    return;
+                }
+                // Functions before the next one to write have already been written.
+                if pending.contains_key(&function_id)
+                    || order.peek().is_none_or(|next| function_id < **next)
+                {
+                    result = Err(S::Error::custom(format!(
+                        "Found multiple type of expressions for function `{function_id:?}`"
+                    )));
+                    return;
+                }
+                pending.insert(function_id, function);
+                while let Some(&next) = order.peek() {
+                    let Some(function) = pending.remove(next) else {
+                        break;
+                    };
+                    if let Err(error) = map.serialize_entry(next, &function) {
+                        result = Err(error);
+                        return;
+                    }
+                    order.next();
+                }
+            };
+        let mut visitor = TypeOfExpressionVisitor::new(self.context);
+        visitor.on_function_done = Some(&mut on_function_done);
+        visit_module_ast(&mut visitor, self.context);
+        visitor.all_functions_done();
+        drop(visitor);
+        result?;
+        if let Some(missing) = order.next() {
+            return Err(S::Error::custom(format!(
+                "Missing type of expressions for function `{missing:?}`"
+            )));
+        }
+        map.end()
+    }
+}
+
+/// Number of consecutive top-level statements visited together. Modules with more
+/// top-level statements are visited in parallel, one partition per task, so that a
+/// single huge module does not hold up the export. This only applies to
+/// `export_type_of_expressions` (used by the capnp format), since the JSON format
+/// is written by `StreamedTypeOfExpressions` while visiting the module in order.
+const PARTITION_SIZE: usize = 256;
+
 pub fn export_type_of_expressions(
     context: &ModuleContext,
 ) -> HashMap<FunctionId, FunctionTypeOfExpressions> {
-    let mut visitor = TypeOfExpressionVisitor {
-        module_context: context,
-        current_function: None,
-        result: HashMap::new(),
-    };
+    export_type_of_expressions_in_partitions(context, PARTITION_SIZE)
+}
 
-    visit_module_ast(&mut visitor, context);
+/// Export type of expressions, visiting partitions of `partition_size` top-level
+/// statements in parallel. The result does not depend on `partition_size`.
+pub fn export_type_of_expressions_in_partitions(
+    context: &ModuleContext,
+    partition_size: usize,
+) -> HashMap<FunctionId, FunctionTypeOfExpressions> {
+    let body = &context.answers_context.ast.body;
+    let partitions = if body.len() <= partition_size {
+        let mut visitor = TypeOfExpressionVisitor::new(context);
+        visit_module_ast(&mut visitor, context);
+        vec![visitor.result]
+    } else {
+        let answers_context = &context.answers_context;
+        let options = context.options;
+        body.chunks(partition_size)
+            .map(|statements| (statements, context.resolver.fork()))
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|(statements, resolver)| {
+                let context = ModuleContext {
+                    answers_context: answers_context.dupe(),
+                    resolver: &resolver,
+                    options,
+                };
+                let mut visitor = TypeOfExpressionVisitor::new(&context);
+                visit_module_statements(&mut visitor, statements, &context);
+                visitor.result
+            })
+            .collect::<Vec<_>>()
+    };
 
-    visitor
-        .result
+    let mut result: HashMap<FunctionId, FunctionTypeOfExpressionsBuilder> = HashMap::new();
+    for partition in partitions {
+        for (function_id, builder) in partition {
+            match result.entry(function_id) {
+                Entry::Occupied(mut entry) => entry.get_mut().merge(builder),
+                Entry::Vacant(entry) => {
+                    entry.insert(builder);
+                }
+            }
+        }
+    }
+    result
         .into_iter()
         .map(|(id, builder)| (id, builder.build()))
         .collect()
@@ -149,8 +149,18 @@ impl ClassNamesFromType {
 }
 
 pub fn string_for_type(type_: &Type) -> String {
+    string_for_type_with_qualification(type_, false)
+}
+
+/// Like [`string_for_type`], but when `fully_qualified` is set builtins are also
+/// prefixed with their module, e.g. `builtins.int` rather than `int`.
+pub fn string_for_type_with_qualification(type_: &Type, fully_qualified: bool) -> String {
     let mut ctx = TypeDisplayContext::new(&[type_]);
-    ctx.always_display_module_name_except_builtins();
+    if fully_qualified {
+        ctx.always_display_module_name();
+    } else {
+        ctx.always_display_module_name_except_builtins();
+    }
     ctx.display(type_).to_string()
 }
 
@@ -378,7 +388,8 @@ impl PysaType {
 
     pub fn from_type(type_: &Type, context: &ModuleContext) -> PysaType {
         let type_ = preprocess_type(type_, &context.answers_context);
-        let string = string_for_type(&type_);
+        let string =
+            string_for_type_with_qualification(&type_, context.options.fully_qualified_type_names);
 
         PysaType {
             string,
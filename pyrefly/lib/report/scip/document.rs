@@ -0,0 +1,329 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Collect the SCIP occurrences of a module: a definition occurrence for each name it binds
+//! and a reference occurrence for each name, attribute and import it resolves through
+//! go-to-definition (with the same preferences as the Pysa call graph export).
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::ast::Ast;
+use pyrefly_python::module::Module;
+use pyrefly_python::module_path::ModulePath;
+use pyrefly_python::module_path::ModulePathDetails;
+use pyrefly_python::symbol_kind::SymbolKind;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ExprContext;
+use ruff_python_ast::ModModule;
+use ruff_python_ast::Parameter;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::visitor::Visitor;
+use ruff_python_ast::visitor::walk_expr;
+use ruff_python_ast::visitor::walk_parameter;
+use ruff_python_ast::visitor::walk_stmt;
+use ruff_source_file::PositionEncoding;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use starlark_map::small_set::SmallSet;
+
+use crate::report::scip::symbol::global_symbol;
+use crate::report::scip::symbol::module_symbol;
+use crate::state::lsp::DefinitionMetadata;
+use crate::state::lsp::FindDefinitionItemWithDocstring;
+use crate::state::lsp::FindPreference;
+use crate::state::state::Transaction;
+
+/// `SymbolRole.Definition`.
+pub const SYMBOL_ROLE_DEFINITION: i32 = 0x1;
+/// `SymbolRole.Import`.
+pub const SYMBOL_ROLE_IMPORT: i32 = 0x2;
+/// `SymbolRole.WriteAccess`.
+pub const SYMBOL_ROLE_WRITE_ACCESS: i32 = 0x4;
+/// `SymbolRole.ReadAccess`.
+pub const SYMBOL_ROLE_READ_ACCESS: i32 = 0x8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScipOccurrence {
+    /// `[start line, start column, end line, end column]`, zero-based with columns in UTF-8
+    /// code units, or `[line, start column, end column]` for a range on a single line.
+    pub range: Vec<i32>,
+    pub symbol: String,
+    pub roles: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScipDocument {
+    pub relative_path: String,
+    pub occurrences: Vec<ScipOccurrence>,
+    /// The global symbols defined in this document.
+    pub symbols: Vec<String>,
+}
+
+/// ASTs of the other modules that definitions resolve into, shared by every document of an
+/// index so that each module is parsed at most once.
+#[derive(Default)]
+pub struct ScipAsts(HashMap<ModulePath, Arc<ModModule>>);
+
+fn scip_find_preference() -> FindPreference {
+    FindPreference {
+        disable_style_fallback: true,
+        ..FindPreference::default()
+    }
+}
+
+/// Export the occurrences of the module behind `handle`. Returns `None` for modules without a
+/// source file or whose AST is no longer available, and an error for files outside
+/// `project_root`, since SCIP document paths must be relative to it.
+pub fn export_document(
+    transaction: &Transaction,
+    handle: &Handle,
+    project_root: &Path,
+    asts: &mut ScipAsts,
+) -> anyhow::Result<Option<ScipDocument>> {
+    let path = match handle.path().details() {
+        ModulePathDetails::FileSystem(path) | ModulePathDetails::Memory(path) => path,
+        _ => return Ok(None),
+    };
+    let Ok(relative_path) = path.strip_prefix(project_root) else {
+        anyhow::bail!(
+            "Cannot add `{}` to the SCIP index: it is not under the project root `{}`",
+            path.display(),
+            project_root.display()
+        );
+    };
+    let Some(module) = transaction.get_module_info(handle) else {
+        return Ok(None);
+    };
+    let Some(ast) = transaction.get_ast(handle) else {
+        return Ok(None);
+    };
+    let mut visitor = DocumentVisitor {
+        transaction,
+        handle,
+        module: &module,
+        ast: ast.dupe(),
+        other_asts: asts,
+        locals: HashMap::new(),
+        occurrences: Vec::new(),
+        symbols: SmallSet::new(),
+    };
+    visitor.visit_body(&ast.body);
+    let mut occurrences = visitor.occurrences;
+    occurrences.sort_by(|a, b| (&a.range, &a.symbol).cmp(&(&b.range, &b.symbol)));
+    Ok(Some(ScipDocument {
+        relative_path: relative_path.to_string_lossy().replace('\\', "/"),
+        occurrences,
+        symbols: visitor.symbols.into_iter().collect(),
+    }))
+}
+
+struct DocumentVisitor<'a, 't> {
+    transaction: &'a Transaction<'t>,
+    handle: &'a Handle,
+    module: &'a Module,
+    ast: Arc<ModModule>,
+    other_asts: &'a mut ScipAsts,
+    /// Document-local symbols, keyed by the range of their definition.
+    locals: HashMap<TextRange, String>,
+    occurrences: Vec<ScipOccurrence>,
+    symbols: SmallSet<String>,
+}
+
+impl DocumentVisitor<'_, '_> {
+    fn ast_of(&mut self, module: &Module) -> Arc<ModModule> {
+        if module.path() == self.module.path() {
+            return self.ast.dupe();
+        }
+        if let Some(ast) = self.other_asts.0.get(module.path()) {
+            return ast.dupe();
+        }
+        let handle = Handle::new(
+            module.name(),
+            module.path().dupe(),
+            self.handle.sys_info().dupe(),
+        );
+        // The AST isn't retained for modules we didn't check, so parse those again.
+        let ast = self
+            .transaction
+            .get_ast(&handle)
+            .unwrap_or_else(|| Ast::parse(module.contents(), module.source_type()).0.into());
+        self.other_asts.0.insert(module.path().dupe(), ast.dupe());
+        ast
+    }
+
+    /// The symbol for the name defined at `range` in `module`: a global symbol if it has one,
+    /// else a local one if it is in this document.
+    fn symbol_at(&mut self, module: &Module, range: TextRange) -> Option<String> {
+        let ast = self.ast_of(module);
+        if let Some(symbol) = global_symbol(module.name(), &ast, range, module.code_at(range)) {
+            return Some(symbol);
+        }
+        if module.path() != self.module.path() {
+            return None;
+        }
+        let next = self.locals.len();
+        Some(
+            self.locals
+                .entry(range)
+                .or_insert_with(|| format!("local {next}"))
+                .clone(),
+        )
+    }
+
+    fn symbol_of_definition(
+        &mut self,
+        definition: &FindDefinitionItemWithDocstring,
+    ) -> Option<String> {
+        if matches!(definition.metadata, DefinitionMetadata::Module)
+            || definition.metadata.symbol_kind() == Some(SymbolKind::Module)
+        {
+            return Some(module_symbol(definition.module.name()));
+        }
+        self.symbol_at(&definition.module, definition.definition_range)
+    }
+
+    fn push(&mut self, range: TextRange, symbol: String, roles: i32) {
+        let lsp_range = self.module.to_lsp_range(range, PositionEncoding::Utf8);
+        let (start, end) = (lsp_range.start, lsp_range.end);
+        let range = if start.line == end.line {
+            vec![
+                start.line as i32,
+                start.character as i32,
+                end.character as i32,
+            ]
+        } else {
+            vec![
+                start.line as i32,
+                start.character as i32,
+                end.line as i32,
+                end.character as i32,
+            ]
+        };
+        self.occurrences.push(ScipOccurrence {
+            range,
+            symbol,
+            roles,
+        });
+    }
+
+    /// A name bound at `range` in this document.
+    fn definition(&mut self, range: TextRange) {
+        let module = self.module.dupe();
+        if let Some(symbol) = self.symbol_at(&module, range) {
+            if !symbol.starts_with("local ") {
+                self.symbols.insert(symbol.clone());
+            }
+            self.push(range, symbol, SYMBOL_ROLE_DEFINITION);
+        }
+    }
+
+    /// A use at `range` that resolves to `definitions`. A use that resolves to itself (e.g.
+    /// the first assignment to `self.x`) is its definition.
+    fn references(
+        &mut self,
+        range: TextRange,
+        definitions: Vec<FindDefinitionItemWithDocstring>,
+        roles: i32,
+    ) {
+        for definition in definitions {
+            let is_self = definition.definition_range == range
+                && definition.module.path() == self.module.path();
+            if let Some(symbol) = self.symbol_of_definition(&definition) {
+                if is_self {
+                    self.symbols.insert(symbol.clone());
+                    self.push(range, symbol, SYMBOL_ROLE_DEFINITION);
+                } else {
+                    self.push(range, symbol, roles);
+                }
+            }
+        }
+    }
+
+    fn definitions_at(&self, range: TextRange) -> Vec<FindDefinitionItemWithDocstring> {
+        self.transaction
+            .find_definition(self.handle, range.start(), scip_find_preference())
+            .map(|definitions| definitions.into_vec())
+            .unwrap_or_default()
+    }
+}
+
+impl<'a> Visitor<'a> for DocumentVisitor<'_, '_> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::FunctionDef(function_def) => self.definition(function_def.name.range),
+            Stmt::ClassDef(class_def) => self.definition(class_def.name.range),
+            Stmt::Import(import) => {
+                for alias in &import.names {
+                    let definitions = self.definitions_at(alias.name.range);
+                    self.references(alias.name.range, definitions, SYMBOL_ROLE_IMPORT);
+                    if let Some(asname) = &alias.asname {
+                        self.definition(asname.range);
+                    }
+                }
+            }
+            Stmt::ImportFrom(import_from) => {
+                for alias in &import_from.names {
+                    if alias.name.as_str() == "*" {
+                        continue;
+                    }
+                    let definitions = self.definitions_at(alias.name.range);
+                    self.references(alias.name.range, definitions, SYMBOL_ROLE_IMPORT);
+                    if let Some(asname) = &alias.asname {
+                        self.definition(asname.range);
+                    }
+                }
+            }
+            _ => {}
+        }
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match expr {
+            Expr::Name(name) if name.ctx == ExprContext::Store => self.definition(name.range),
+            Expr::Name(name) => {
+                let identifier = Ast::expr_name_identifier(name.clone());
+                if let Ok(Some(definition)) = self.transaction.find_definition_for_name_use(
+                    self.handle,
+                    &identifier,
+                    scip_find_preference(),
+                ) {
+                    self.references(name.range, vec![definition], SYMBOL_ROLE_READ_ACCESS);
+                }
+            }
+            Expr::Attribute(attribute) => {
+                let roles = if attribute.ctx == ExprContext::Store {
+                    SYMBOL_ROLE_WRITE_ACCESS
+                } else {
+                    SYMBOL_ROLE_READ_ACCESS
+                };
+                let definitions = self
+                    .transaction
+                    .find_definition_for_attribute(
+                        self.handle,
+                        attribute.value.range(),
+                        &attribute.attr.id,
+                        scip_find_preference(),
+                    )
+                    .map(|definitions| definitions.into_vec())
+                    .unwrap_or_default();
+                self.references(attribute.attr.range, definitions, roles);
+            }
+            _ => {}
+        }
+        walk_expr(self, expr);
+    }
+
+    fn visit_parameter(&mut self, parameter: &'a Parameter) {
+        self.definition(parameter.name.range);
+        walk_parameter(self, parameter);
+    }
+}
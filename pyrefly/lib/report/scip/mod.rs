@@ -0,0 +1,61 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Export a [SCIP](https://github.com/sourcegraph/scip) index of the project, the format
+//! Sourcegraph uses for precise code navigation.
+
+pub mod document;
+pub mod proto;
+pub mod symbol;
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use pyrefly_build::handle::Handle;
+use pyrefly_python::module_path::ModulePathDetails;
+
+use crate::report::scip::document::ScipAsts;
+use crate::report::scip::document::export_document;
+use crate::state::state::Transaction;
+
+/// The directory to root the index at: the deepest ancestor of `root` that contains every file
+/// in `handles`, so that files from several projects (e.g. with their own configs) all get a
+/// path relative to it. Errors if there is no such directory, e.g. across Windows drives.
+pub fn project_root(root: &Path, handles: &[Handle]) -> anyhow::Result<PathBuf> {
+    let mut project_root = root.to_owned();
+    for handle in handles {
+        let path = match handle.path().details() {
+            ModulePathDetails::FileSystem(path) | ModulePathDetails::Memory(path) => path,
+            _ => continue,
+        };
+        while !path.starts_with(&project_root) {
+            if !project_root.pop() {
+                anyhow::bail!(
+                    "Cannot write SCIP index: `{}` and `{}` have no common root directory",
+                    path.display(),
+                    root.display()
+                );
+            }
+        }
+    }
+    Ok(project_root)
+}
+
+/// Build the `index.scip` protobuf for `handles`, with paths relative to the
+/// [`project_root`] of `root` and `handles`.
+pub fn scip(transaction: &Transaction, handles: &[Handle], root: &Path) -> anyhow::Result<Vec<u8>> {
+    let project_root = project_root(root, handles)?;
+    let mut asts = ScipAsts::default();
+    let mut documents = Vec::new();
+    for handle in handles {
+        if let Some(document) = export_document(transaction, handle, &project_root, &mut asts)? {
+            documents.push(document);
+        }
+    }
+    documents.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    Ok(proto::encode_index(&project_root, &documents))
+}
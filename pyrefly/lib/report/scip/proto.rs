@@ -0,0 +1,165 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! A protobuf encoder for the subset of the SCIP schema we emit.
+//!
+//! Field numbers follow `scip.proto`. As in proto3, fields holding their default value are
+//! omitted.
+
+use std::path::Path;
+
+use lsp_types::Url;
+
+use crate::report::scip::document::ScipDocument;
+use crate::report::scip::document::ScipOccurrence;
+
+/// `TextEncoding.UTF8`.
+const TEXT_ENCODING_UTF8: i32 = 1;
+/// `PositionEncoding.UTF8CodeUnitOffsetFromLineStart`.
+const POSITION_ENCODING_UTF8: i32 = 1;
+
+const WIRE_VARINT: u64 = 0;
+const WIRE_LEN: u64 = 2;
+
+#[derive(Default)]
+pub struct ProtoWriter {
+    buf: Vec<u8>,
+}
+
+impl ProtoWriter {
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buf.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.buf.push(value as u8);
+    }
+
+    fn key(&mut self, field: u32, wire_type: u64) {
+        self.varint(((field as u64) << 3) | wire_type);
+    }
+
+    fn bytes(&mut self, field: u32, bytes: &[u8]) {
+        self.key(field, WIRE_LEN);
+        self.varint(bytes.len() as u64);
+        self.buf.extend_from_slice(bytes);
+    }
+
+    pub fn int32(&mut self, field: u32, value: i32) {
+        if value != 0 {
+            self.key(field, WIRE_VARINT);
+            // Negative values are sign-extended to 64 bits.
+            self.varint(value as i64 as u64);
+        }
+    }
+
+    pub fn string(&mut self, field: u32, value: &str) {
+        if !value.is_empty() {
+            self.bytes(field, value.as_bytes());
+        }
+    }
+
+    pub fn packed_int32(&mut self, field: u32, values: &[i32]) {
+        if !values.is_empty() {
+            let mut packed = ProtoWriter::default();
+            for value in values {
+                packed.varint(*value as i64 as u64);
+            }
+            self.bytes(field, &packed.buf);
+        }
+    }
+
+    pub fn message(&mut self, field: u32, message: ProtoWriter) {
+        self.bytes(field, &message.buf);
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+fn encode_occurrence(occurrence: &ScipOccurrence) -> ProtoWriter {
+    let mut w = ProtoWriter::default();
+    w.packed_int32(1, &occurrence.range);
+    w.string(2, &occurrence.symbol);
+    w.int32(3, occurrence.roles);
+    w
+}
+
+fn encode_document(document: &ScipDocument) -> ProtoWriter {
+    let mut w = ProtoWriter::default();
+    w.string(1, &document.relative_path);
+    for occurrence in &document.occurrences {
+        w.message(2, encode_occurrence(occurrence));
+    }
+    for symbol in &document.symbols {
+        // `SymbolInformation`, of which we only fill in the symbol.
+        let mut info = ProtoWriter::default();
+        info.string(1, symbol);
+        w.message(3, info);
+    }
+    w.string(4, "python");
+    w.int32(6, POSITION_ENCODING_UTF8);
+    w
+}
+
+/// Encode an `Index` message holding `documents`.
+pub fn encode_index(project_root: &Path, documents: &[ScipDocument]) -> Vec<u8> {
+    let mut tool_info = ProtoWriter::default();
+    tool_info.string(1, "pyrefly");
+    let mut metadata = ProtoWriter::default();
+    metadata.message(2, tool_info);
+    let root = Url::from_directory_path(project_root).map_or_else(
+        |()| project_root.display().to_string(),
+        |url| url.to_string(),
+    );
+    metadata.string(3, &root);
+    metadata.int32(4, TEXT_ENCODING_UTF8);
+
+    let mut index = ProtoWriter::default();
+    index.message(1, metadata);
+    for document in documents {
+        index.message(2, encode_document(document));
+    }
+    index.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_occurrence() {
+        let occurrence = ScipOccurrence {
+            range: vec![1, 4, 7],
+            symbol: "local 0".to_owned(),
+            roles: 1,
+        };
+        assert_eq!(
+            encode_occurrence(&occurrence).into_bytes(),
+            [
+                vec![0x0a, 3, 1, 4, 7],
+                vec![0x12, 7],
+                b"local 0".to_vec(),
+                vec![0x18, 1],
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn test_varint_and_negative_int32() {
+        let mut w = ProtoWriter::default();
+        w.int32(1, 300);
+        w.int32(2, 0);
+        w.int32(3, -1);
+        let mut expected = vec![0x08, 0xac, 0x02, 0x18];
+        expected.extend([0xff; 9]);
+        expected.push(0x01);
+        assert_eq!(w.into_bytes(), expected);
+    }
+}
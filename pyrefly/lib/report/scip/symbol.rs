@@ -0,0 +1,128 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! SCIP symbol strings: `<scheme> <manager> <package-name> <version> <descriptors>`.
+//!
+//! Symbols are keyed on module names rather than packages, like the Pysa module ids: the
+//! package name and version are left as `.` (SCIP's placeholder for an empty value), and the
+//! module is the first descriptor, e.g. `pyrefly python . . `pkg.mod`/Class#method().`.
+
+use pyrefly_python::ast::Ast;
+use pyrefly_python::module_name::ModuleName;
+use ruff_python_ast::AnyNodeRef;
+use ruff_python_ast::ModModule;
+use ruff_text_size::TextRange;
+
+const SCHEME_AND_PACKAGE: &str = "pyrefly python . .";
+
+/// Escape a descriptor name with backticks unless it only holds characters SCIP allows bare.
+fn escape(name: &str) -> String {
+    if !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '+' | '-' | '$'))
+    {
+        name.to_owned()
+    } else {
+        format!("`{}`", name.replace('`', "``"))
+    }
+}
+
+/// The symbol for a module.
+pub fn module_symbol(module_name: ModuleName) -> String {
+    format!("{SCHEME_AND_PACKAGE} {}/", escape(module_name.as_str()))
+}
+
+/// The symbol for `name`, defined at `range` in the module `module_name` with AST `ast`.
+/// Classes are types (`#`), functions and methods are methods (`().`) and anything else is a
+/// term (`.`), nested under their enclosing classes. Attributes assigned through `self` in a
+/// method belong to the class.
+///
+/// Returns `None` for a name local to a function, lambda or comprehension, which SCIP
+/// represents with a document-local symbol instead.
+pub fn global_symbol(
+    module_name: ModuleName,
+    ast: &ModModule,
+    range: TextRange,
+    name: &str,
+) -> Option<String> {
+    let mut suffix = ".";
+    let mut is_attribute = false;
+    let mut scopes = Vec::new();
+    // Innermost nodes come first.
+    for node in Ast::locate_node(ast, range.start()) {
+        match node {
+            AnyNodeRef::StmtClassDef(class_def) if class_def.name.range == range => suffix = "#",
+            AnyNodeRef::StmtFunctionDef(function_def) if function_def.name.range == range => {
+                suffix = "()."
+            }
+            AnyNodeRef::ExprAttribute(attribute) if attribute.attr.range == range => {
+                is_attribute = true
+            }
+            AnyNodeRef::StmtClassDef(class_def) => {
+                scopes.push(format!("{}#", escape(class_def.name.as_str())));
+                is_attribute = false;
+            }
+            AnyNodeRef::StmtFunctionDef(_) if is_attribute => {}
+            AnyNodeRef::StmtFunctionDef(_)
+            | AnyNodeRef::ExprLambda(_)
+            | AnyNodeRef::ExprListComp(_)
+            | AnyNodeRef::ExprSetComp(_)
+            | AnyNodeRef::ExprDictComp(_)
+            | AnyNodeRef::ExprGenerator(_) => return None,
+            _ => {}
+        }
+    }
+    if is_attribute {
+        // An attribute assigned outside of any class, e.g. on a module object.
+        return None;
+    }
+    scopes.reverse();
+    Some(format!(
+        "{}{}{}{suffix}",
+        module_symbol(module_name),
+        scopes.concat(),
+        escape(name)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use ruff_python_ast::PySourceType;
+    use ruff_text_size::TextSize;
+
+    use super::*;
+
+    fn symbol_of(code: &str, name: &str, occurrence: usize) -> Option<String> {
+        let (ast, _, _) = Ast::parse(code, PySourceType::Python);
+        let start = code.match_indices(name).nth(occurrence).unwrap().0;
+        let range = TextRange::at(TextSize::new(start as u32), TextSize::of(name));
+        global_symbol(ModuleName::from_str("pkg.mod"), &ast, range, name)
+    }
+
+    #[test]
+    fn test_global_symbols() {
+        let code = "class C:\n    def m(self):\n        self.attr = 1\n        local = 2\nx = C\n";
+        assert_eq!(
+            symbol_of(code, "C", 0).as_deref(),
+            Some("pyrefly python . . `pkg.mod`/C#")
+        );
+        assert_eq!(
+            symbol_of(code, "m", 0).as_deref(),
+            Some("pyrefly python . . `pkg.mod`/C#m().")
+        );
+        assert_eq!(
+            symbol_of(code, "attr", 0).as_deref(),
+            Some("pyrefly python . . `pkg.mod`/C#attr.")
+        );
+        assert_eq!(symbol_of(code, "local", 0), None);
+        assert_eq!(
+            symbol_of(code, "x", 0).as_deref(),
+            Some("pyrefly python . . `pkg.mod`/x.")
+        );
+    }
+}
@@ -379,6 +379,18 @@ impl Errors {
         Self::merge_display_errors(ordinary, collected.directives)
     }
 
+    /// Unused `# pyrefly: ignore` comments whose `unused-ignore` error is disabled by the config,
+    /// so it never reaches the ordinary results. Tooling can still point these comments out
+    /// without reporting them as errors.
+    pub fn collect_disabled_unused_ignore_errors(&self) -> Vec<Error> {
+        let collected = self.collect_errors();
+        self.collect_unused_ignore_errors_for_display(&collected)
+            .disabled
+            .into_iter()
+            .filter(|error| error.error_kind() == ErrorKind::UnusedIgnore)
+            .collect()
+    }
+
     pub fn collect_ignores(&self) -> SmallMap<&ModulePath, &Ignore> {
         let mut ignore_collection: SmallMap<&ModulePath, &Ignore> = SmallMap::new();
         for (load, _, _) in &self.loads {
@@ -56,10 +56,12 @@ use ruff_python_ast::ModModule;
 use ruff_python_ast::StmtImportFrom;
 use ruff_python_ast::UnaryOp;
 use ruff_python_ast::name::Name;
+use ruff_source_file::PositionEncoding;
 use ruff_text_size::Ranged;
 use ruff_text_size::TextRange;
 use ruff_text_size::TextSize;
 use serde::Deserialize;
+use serde::Serialize;
 use starlark_map::ordered_set::OrderedSet;
 use starlark_map::small_map::SmallMap;
 use vec1::Vec1;
@@ -131,6 +133,14 @@ fn default_true() -> bool {
     true
 }
 
+/// Whether `stub` is the `.pyi` that sits next to the `.py` file `source`, e.g. `foo.pyi` and
+/// `foo.py` in the same directory.
+fn is_sibling_stub(stub: &ModulePath, source: &ModulePath) -> bool {
+    stub.is_interface()
+        && !source.is_interface()
+        && stub.as_path().with_extension("") == source.as_path().with_extension("")
+}
+
 #[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum AllOffPartial {
@@ -690,6 +700,18 @@ pub struct FindDefinitionItemWithDocstring {
     pub display_name: Option<String>,
 }
 
+/// How a reference uses the symbol it refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReferenceKind {
+    /// The definition that references were requested for.
+    Declaration,
+    /// A use that only reads the symbol.
+    Read,
+    /// A use that binds or deletes the symbol, e.g. an assignment or an import.
+    Write,
+}
+
 #[derive(Debug)]
 pub struct FindDefinitionItem {
     pub metadata: DefinitionMetadata,
@@ -1748,6 +1770,39 @@ impl<'a> Transaction<'a> {
         Some((executable_module, def_range, docstring_range))
     }
 
+    /// Find the definition in the `.pyi` sitting next to `source`'s `.py` file that corresponds
+    /// to `source`. Module-level names are looked up in the stub's exports. Names nested in a
+    /// class need the stub's AST, which is only kept for modules checked at `Require::Everything`,
+    /// so they are only found when the stub has been fully checked (e.g. it is open).
+    pub(crate) fn search_sibling_stub_definition(
+        &self,
+        request_handle: &Handle,
+        source: &TextRangeWithModule,
+    ) -> Option<TextRangeWithModule> {
+        if source.module.path().is_interface() {
+            return None;
+        }
+        let stub_handle = self
+            .import_handle(request_handle, source.module.name(), None)
+            .finding()?;
+        if !is_sibling_stub(stub_handle.path(), source.module.path()) {
+            return None;
+        }
+        let context = AttributeContext::from_module(&source.module, source.range)?;
+        let name = Name::new(source.module.code_at(source.range));
+        let range = if context.parent_classes.is_empty() {
+            match self.get_exports(&stub_handle).get(&name)? {
+                ExportLocation::ThisModule(export) => export.location,
+                ExportLocation::OtherModule(..) => return None,
+            }
+        } else {
+            let ast = self.get_ast(&stub_handle)?;
+            definition_from_executable_ast(ast.as_ref(), &context, &name)?.0
+        };
+        let stub_module = self.get_module_info(&stub_handle)?;
+        Some(TextRangeWithModule::new(stub_module, range))
+    }
+
     pub fn key_to_export(
         &self,
         handle: &Handle,
@@ -2397,11 +2452,27 @@ impl<'a> Transaction<'a> {
     }
 
     /// Find the definition, metadata and optionally the docstring for the given position.
+    ///
+    /// Each `(module, range)` location is reported once: several resolution paths (e.g. two
+    /// union members inheriting the same re-exported method) can land on the same definition.
     pub fn find_definition(
         &self,
         handle: &Handle,
         position: TextSize,
         preference: FindPreference,
+    ) -> Result<Vec1<FindDefinitionItemWithDocstring>, EmptyResponseReason> {
+        let definitions = self.find_definition_candidates(handle, position, preference)?;
+        let mut seen = HashSet::new();
+        let mut definitions = definitions.into_vec();
+        definitions.retain(|item| seen.insert((item.module.path().dupe(), item.definition_range)));
+        Ok(Vec1::try_from_vec(definitions).expect("the first definition is always kept"))
+    }
+
+    fn find_definition_candidates(
+        &self,
+        handle: &Handle,
+        position: TextSize,
+        preference: FindPreference,
     ) -> Result<Vec1<FindDefinitionItemWithDocstring>, EmptyResponseReason> {
         let Some(mod_module) = self.get_ast(handle) else {
             return Err(EmptyResponseReason::AstNotFound);
@@ -2788,12 +2859,26 @@ impl<'a> Transaction<'a> {
                 prefer_pyi: false,
                 ..Default::default()
             },
-        );
+        )?;
+        let mut targets = definitions
+            .into_vec()
+            .into_map(|item| TextRangeWithModule::new(item.module, item.definition_range));
 
-        definitions.map(|defs| {
-            defs.into_vec()
-                .into_map(|item| TextRangeWithModule::new(item.module, item.definition_range))
-        })
+        // When the implementation has a stub sitting next to it (`foo.py` and `foo.pyi`),
+        // offer the stub's definition as a second target, so users can navigate to either.
+        let stub_targets = targets
+            .iter()
+            .filter_map(|target| self.search_sibling_stub_definition(handle, target))
+            .collect_vec();
+        for stub_target in stub_targets {
+            if !targets.iter().any(|target| {
+                target.module.path() == stub_target.module.path()
+                    && target.range == stub_target.range
+            }) {
+                targets.push(stub_target);
+            }
+        }
+        Ok(targets)
     }
 
     pub fn goto_declaration(
@@ -3262,6 +3347,32 @@ impl<'a> Transaction<'a> {
         }
     }
 
+    pub fn add_type_annotation_code_actions(
+        &self,
+        handle: &Handle,
+        selection: TextRange,
+        import_format: ImportFormat,
+    ) -> Option<Vec<LocalRefactorCodeAction>> {
+        quick_fixes::add_type_annotation::add_type_annotation_code_actions(
+            self,
+            handle,
+            selection,
+            import_format,
+        )
+    }
+
+    pub fn add_missing_return_annotations_edits(
+        &self,
+        handle: &Handle,
+        import_format: ImportFormat,
+    ) -> Option<Vec<(Module, TextRange, String)>> {
+        quick_fixes::add_return_annotations::add_missing_return_annotations_edits(
+            self,
+            handle,
+            import_format,
+        )
+    }
+
     pub fn pytest_fixture_type_annotation_code_actions(
         &self,
         handle: &Handle,
@@ -3439,6 +3550,14 @@ impl<'a> Transaction<'a> {
         quick_fixes::convert_dict::convert_dict_code_actions(self, handle, selection)
     }
 
+    pub fn convert_to_fstring_code_actions(
+        &self,
+        handle: &Handle,
+        selection: TextRange,
+    ) -> Option<Vec<LocalRefactorCodeAction>> {
+        quick_fixes::convert_to_fstring::convert_to_fstring_code_actions(self, handle, selection)
+    }
+
     /// Determines whether a module is a third-party package.
     ///
     /// Checks if the module's path is located within any of the configured
@@ -3588,6 +3707,22 @@ impl<'a> Transaction<'a> {
         Some(identifier_context?.identifier.range)
     }
 
+    /// If the identifier at `position` names a module in an import statement, return the
+    /// module it resolves to.
+    pub fn imported_module_at(&self, handle: &Handle, position: TextSize) -> Option<ModuleInfo> {
+        let IdentifierWithContext {
+            context: IdentifierContext::ImportedModule { .. },
+            ..
+        } = self.identifier_at(handle, position)?
+        else {
+            return None;
+        };
+        let definitions = self
+            .find_definition(handle, position, FindPreference::default())
+            .ok()?;
+        Some(definitions.first().module.dupe())
+    }
+
     pub fn find_local_references(
         &self,
         handle: &Handle,
@@ -3625,6 +3760,124 @@ impl<'a> Transaction<'a> {
         .concat()
     }
 
+    /// Like [`Self::find_local_references`], but also classifies each reference.
+    pub fn find_local_references_with_kind(
+        &self,
+        handle: &Handle,
+        position: TextSize,
+        include_declaration: bool,
+    ) -> Vec<(TextRange, ReferenceKind)> {
+        self.find_definition(
+            handle,
+            position,
+            FindPreference {
+                import_behavior: ImportBehavior::StopAtRenamedImports,
+                ..Default::default()
+            },
+        )
+        .map(Vec1::into_vec)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|definition| {
+            let references = self.local_references_from_definition(
+                handle,
+                definition.metadata,
+                definition.definition_range,
+                &definition.module,
+                include_declaration,
+            )?;
+            let definition =
+                TextRangeWithModule::new(definition.module, definition.definition_range);
+            Some(
+                references
+                    .into_map(|range| (range, self.reference_kind(handle, range, &definition))),
+            )
+        })
+        .concat()
+    }
+
+    /// Classify the reference at `range` in `handle` to the symbol defined at `definition`.
+    pub fn reference_kind(
+        &self,
+        handle: &Handle,
+        range: TextRange,
+        definition: &TextRangeWithModule,
+    ) -> ReferenceKind {
+        if handle.path() == definition.module.path() && range == definition.range {
+            ReferenceKind::Declaration
+        } else if self
+            .identifier_at(handle, range.start())
+            .is_some_and(|id| id.context.is_write())
+        {
+            ReferenceKind::Write
+        } else {
+            ReferenceKind::Read
+        }
+    }
+
+    /// Classify the results of [`Self::find_global_references_from_definition`].
+    pub fn classify_references(
+        &self,
+        sys_info: &SysInfo,
+        definition: &TextRangeWithModule,
+        references: Vec<(Module, Vec<TextRange>)>,
+    ) -> Vec<(Module, Vec<(TextRange, ReferenceKind)>)> {
+        references.into_map(|(module, ranges)| {
+            let handle = Handle::new(module.name(), module.path().dupe(), sys_info.dupe());
+            let ranges =
+                ranges.into_map(|range| (range, self.reference_kind(&handle, range, definition)));
+            (module, ranges)
+        })
+    }
+
+    /// The ranges to edit together when the name at `position` is changed: every occurrence of a
+    /// variable defined inside a function or lambda of this module. Module-level names and names
+    /// defined elsewhere are excluded, since other files may refer to them.
+    pub fn linked_editing_ranges(
+        &self,
+        handle: &Handle,
+        position: TextSize,
+    ) -> Option<Vec<TextRange>> {
+        let ast = self.get_ast(handle)?;
+        let definitions = self
+            .find_definition(
+                handle,
+                position,
+                FindPreference {
+                    import_behavior: ImportBehavior::StopAtRenamedImports,
+                    ..Default::default()
+                },
+            )
+            .ok()?;
+        // Parameters are excluded: renaming one would also need the keyword arguments at its
+        // call sites, which linked editing cannot touch.
+        let is_local = |definition: &FindDefinitionItemWithDocstring| {
+            if !matches!(definition.metadata, DefinitionMetadata::Variable(_))
+                || definition.module.path() != handle.path()
+            {
+                return false;
+            }
+            let nodes = Ast::locate_node(&ast, definition.definition_range.start());
+            !nodes
+                .iter()
+                .any(|node| matches!(node, AnyNodeRef::Parameter(_)))
+                && nodes.iter().any(|node| match node {
+                    AnyNodeRef::StmtFunctionDef(func) => {
+                        func.name.range != definition.definition_range
+                    }
+                    AnyNodeRef::ExprLambda(_) => true,
+                    _ => false,
+                })
+        };
+        if definitions.is_empty() || !definitions.iter().all(is_local) {
+            return None;
+        }
+        let mut ranges = self.find_local_references(handle, position, true);
+        ranges.sort_by_key(|range| range.start());
+        ranges.dedup();
+        (!ranges.is_empty()).then_some(ranges)
+    }
+
     /// Find references to an external definition within the given handle's module.
     /// When the exact byte-range comparison fails (e.g. CRLF/LF differences),
     /// falls back to comparing line numbers, which are encoding-invariant.
@@ -3639,7 +3892,11 @@ impl<'a> Transaction<'a> {
         let mut references = Vec::new();
 
         // Lazily computed line number for fallback comparison.
-        let definition_line = || module.to_lsp_position(definition_range.start()).line;
+        let definition_line = || {
+            module
+                .to_lsp_position(definition_range.start(), PositionEncoding::Utf16)
+                .line
+        };
 
         for ((imported_module_name, imported_name), ranges) in index
             .externally_defined_variable_references
@@ -3653,7 +3910,10 @@ impl<'a> Transaction<'a> {
                 FindPreference::default(),
             ) && imported_handle.path().as_path() == module.path().as_path()
                 && (export.location == definition_range
-                    || module.to_lsp_position(export.location.start()).line == definition_line())
+                    || module
+                        .to_lsp_position(export.location.start(), PositionEncoding::Utf16)
+                        .line
+                        == definition_line())
             {
                 references.extend(ranges.iter().copied());
             }
@@ -3664,7 +3924,10 @@ impl<'a> Transaction<'a> {
             if attribute_module_path == module.path() {
                 for (def_range, ref_range) in def_and_ref_ranges {
                     if *def_range == definition_range
-                        || module.to_lsp_position(def_range.start()).line == definition_line()
+                        || module
+                            .to_lsp_position(def_range.start(), PositionEncoding::Utf16)
+                            .line
+                            == definition_line()
                     {
                         references.push(*ref_range);
                     }
@@ -4260,6 +4523,7 @@ trait RdepTransaction {
     fn module_info(&self, handle: &Handle) -> Option<Module>;
     fn transitive_rdeps(&self, handle: Handle) -> HashSet<Handle>;
     fn run_for_handles(&mut self, handles: &[Handle], require: Require) -> Result<(), Cancelled>;
+    fn is_cancelled(&self) -> bool;
     fn local_references_from_definition(
         &self,
         handle: &Handle,
@@ -4289,6 +4553,10 @@ impl<'a> RdepTransaction for Transaction<'a> {
         Ok(())
     }
 
+    fn is_cancelled(&self) -> bool {
+        self.get_cancellation_handle().is_cancelled()
+    }
+
     fn local_references_from_definition(
         &self,
         handle: &Handle,
@@ -4326,6 +4594,10 @@ impl<'a> RdepTransaction for CancellableTransaction<'a> {
         self.run(handles, require, None)
     }
 
+    fn is_cancelled(&self) -> bool {
+        self.get_cancellation_handle().is_cancelled()
+    }
+
     fn local_references_from_definition(
         &self,
         handle: &Handle,
@@ -4442,8 +4714,8 @@ fn patch_definition_for_handle_impl<T: RdepTransaction>(
             };
             // Remap range from in-memory to on-disk byte offsets so that
             // module and range stay consistent (e.g. when CRLF/LF differ).
-            let lsp_range = module.to_lsp_range(*range);
-            let range = new_module.from_lsp_range(lsp_range, None);
+            let lsp_range = module.to_lsp_range(*range, PositionEncoding::Utf16);
+            let range = new_module.from_lsp_range(lsp_range, None, PositionEncoding::Utf16);
             TextRangeWithModule {
                 module: new_module,
                 range,
@@ -4464,6 +4736,11 @@ fn process_rdeps_with_definition_impl<T: RdepTransaction, R>(
 
     let mut results = Vec::new();
     for handle in candidate_handles {
+        // Searching every rdep of a widely used symbol can take a while, so stop early if the
+        // request has been cancelled.
+        if transaction.is_cancelled() {
+            return Err(Cancelled);
+        }
         let patched_definition = patch_definition_for_handle_impl(transaction, &handle, definition);
         if let Some(result) = process_fn(transaction, &handle, &patched_definition) {
             results.push(result);
@@ -4479,12 +4756,19 @@ fn find_global_references_from_definition_impl<T: RdepTransaction>(
     definition_kind: DefinitionMetadata,
     definition: TextRangeWithModule,
     include_declaration: bool,
-) -> Result<Vec<(Module, Vec<TextRange>)>, Cancelled> {
+    max_results: Option<usize>,
+) -> Result<(Vec<(Module, Vec<TextRange>)>, bool), Cancelled> {
+    let mut found = 0;
+    let mut truncated = false;
     let results = process_rdeps_with_definition_impl(
         transaction,
         sys_info,
         &definition,
         |transaction, handle, patched_definition| {
+            if max_results.is_some_and(|max_results| found >= max_results) {
+                truncated = true;
+                return None;
+            }
             let mut module_refs: Vec<(Module, Vec<TextRange>)> = Vec::new();
 
             let references = transaction
@@ -4520,6 +4804,10 @@ fn find_global_references_from_definition_impl<T: RdepTransaction>(
             if module_refs.is_empty() {
                 None
             } else {
+                found += module_refs
+                    .iter()
+                    .map(|(_, ranges)| ranges.len())
+                    .sum::<usize>();
                 Some(module_refs)
             }
         },
@@ -4544,7 +4832,19 @@ fn find_global_references_from_definition_impl<T: RdepTransaction>(
         references.dedup();
     }
 
-    Ok(global_references)
+    if let Some(max_results) = max_results {
+        let mut remaining = max_results;
+        for (_, references) in &mut global_references {
+            if references.len() > remaining {
+                references.truncate(remaining);
+                truncated = true;
+            }
+            remaining -= references.len();
+        }
+        global_references.retain(|(_, references)| !references.is_empty());
+    }
+
+    Ok((global_references, truncated))
 }
 
 impl<'a> Transaction<'a> {
@@ -4562,7 +4862,9 @@ impl<'a> Transaction<'a> {
             definition_kind,
             definition,
             include_declaration,
+            None,
         )
+        .map(|(references, _)| references)
     }
 }
 
@@ -4594,12 +4896,36 @@ impl<'a> CancellableTransaction<'a> {
             definition_kind,
             definition,
             include_declaration,
+            None,
+        )
+        .map(|(references, _)| references)
+    }
+
+    /// Like [`Self::find_global_references_from_definition`], but stops once `max_results`
+    /// references have been found. The returned flag is true if some references may have been
+    /// left out.
+    pub fn find_global_references_from_definition_with_limit(
+        &mut self,
+        sys_info: SysInfo,
+        definition_kind: DefinitionMetadata,
+        definition: TextRangeWithModule,
+        include_declaration: bool,
+        max_results: Option<usize>,
+    ) -> Result<(Vec<(Module, Vec<TextRange>)>, bool), Cancelled> {
+        find_global_references_from_definition_impl(
+            self,
+            sys_info,
+            definition_kind,
+            definition,
+            include_declaration,
+            max_results,
         )
     }
 
     /// Finds all implementations (child class methods) of the definition at the given position.
     /// This searches through transitive reverse dependencies to find all child classes that
-    /// implement the method.
+    /// implement the method. Only explicit subclasses count, so a class that matches a
+    /// `Protocol` structurally without inheriting from it is not an implementation.
     /// Returns Err if the request is canceled in the middle of a run.
     pub fn find_global_implementations_from_definition(
         &mut self,
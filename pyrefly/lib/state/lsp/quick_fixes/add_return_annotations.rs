@@ -0,0 +1,84 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::collections::HashSet;
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::module::Module;
+use pyrefly_types::display::LspDisplayMode;
+use pyrefly_util::visit::Visit;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::StmtFunctionDef;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+
+use crate::state::lsp::ImportFormat;
+use crate::state::lsp::quick_fixes::return_annotation::import_edits_for_type;
+use crate::state::lsp::quick_fixes::return_annotation::inferred_return_type;
+use crate::state::lsp::quick_fixes::return_annotation::should_skip_annotation;
+use crate::state::state::Transaction;
+
+fn collect_unannotated_functions<'a>(stmt: &'a Stmt, out: &mut Vec<&'a StmtFunctionDef>) {
+    if let Stmt::FunctionDef(func) = stmt
+        && func.returns.is_none()
+    {
+        out.push(func);
+    }
+    stmt.recurse(&mut |stmt| collect_unannotated_functions(stmt, out));
+}
+
+/// Edits that add an inferred `-> T` annotation to every function in the file that has no
+/// return annotation, along with any imports the annotations need. Functions whose inferred
+/// return type can't be written as an annotation (e.g. `Any`) are left alone.
+///
+/// Returns `None` if there is nothing to annotate.
+pub(crate) fn add_missing_return_annotations_edits(
+    transaction: &Transaction<'_>,
+    handle: &Handle,
+    import_format: ImportFormat,
+) -> Option<Vec<(Module, TextRange, String)>> {
+    let ast = transaction.get_ast(handle)?;
+    let module_info = transaction.get_module_info(handle)?;
+    let mut functions = Vec::new();
+    for stmt in &ast.body {
+        collect_unannotated_functions(stmt, &mut functions);
+    }
+    let mut edits = Vec::new();
+    let mut seen_imports = HashSet::new();
+    for func in functions {
+        let Some(ty) = inferred_return_type(transaction, handle, func) else {
+            continue;
+        };
+        let rendered = ty.as_lsp_string(LspDisplayMode::SignatureHelp);
+        if should_skip_annotation(&rendered, &ty) {
+            continue;
+        }
+        edits.push((
+            module_info.dupe(),
+            TextRange::empty(func.parameters.range.end()),
+            format!(" -> {rendered}"),
+        ));
+        for (position, text) in import_edits_for_type(
+            transaction,
+            &ast,
+            handle,
+            module_info.contents().as_str(),
+            import_format,
+            &ty,
+        ) {
+            if seen_imports.insert(text.clone()) {
+                edits.push((
+                    module_info.dupe(),
+                    TextRange::at(position, TextSize::new(0)),
+                    text,
+                ));
+            }
+        }
+    }
+    (!edits.is_empty()).then_some(edits)
+}
@@ -0,0 +1,128 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::ast::Ast;
+use pyrefly_types::display::LspDisplayMode;
+use ruff_python_ast::AnyNodeRef;
+use ruff_python_ast::Expr;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+
+use crate::state::lsp::ImportFormat;
+use crate::state::lsp::LocalRefactorCodeAction;
+use crate::state::lsp::quick_fixes::return_annotation::import_edits_for_type;
+use crate::state::lsp::quick_fixes::return_annotation::should_skip_annotation;
+use crate::state::state::Transaction;
+
+/// An unannotated name that can take an annotation: the name's range, and the range to
+/// replace with the annotation (empty, unless a parameter default's `=` must be respaced).
+struct AnnotationTarget {
+    name_range: TextRange,
+    edit_range: TextRange,
+    has_default: bool,
+}
+
+/// Find the unannotated assignment target or parameter whose name contains `selection`.
+fn annotation_target(
+    transaction: &Transaction<'_>,
+    handle: &Handle,
+    selection: TextRange,
+) -> Option<AnnotationTarget> {
+    let ast = transaction.get_ast(handle)?;
+    Ast::locate_node(&ast, selection.start())
+        .into_iter()
+        .find_map(|node| match node {
+            AnyNodeRef::StmtAssign(assign) => match assign.targets.as_slice() {
+                [Expr::Name(name)] if name.range.contains_range(selection) => {
+                    Some(AnnotationTarget {
+                        name_range: name.range,
+                        edit_range: TextRange::empty(name.range.end()),
+                        has_default: false,
+                    })
+                }
+                _ => None,
+            },
+            AnyNodeRef::StmtFunctionDef(func) => func
+                .parameters
+                .iter_non_variadic_params()
+                .find(|param| param.parameter.name.range.contains_range(selection))
+                .filter(|param| {
+                    param.annotation().is_none() && !matches!(param.name().as_str(), "self" | "cls")
+                })
+                .map(|param| {
+                    let name_range = param.parameter.name.range;
+                    match param.default() {
+                        Some(default) => AnnotationTarget {
+                            name_range,
+                            edit_range: TextRange::new(name_range.end(), default.range().start()),
+                            has_default: true,
+                        },
+                        None => AnnotationTarget {
+                            name_range,
+                            edit_range: TextRange::empty(name_range.end()),
+                            has_default: false,
+                        },
+                    }
+                }),
+            _ => None,
+        })
+}
+
+/// Builds a code action that annotates the unannotated variable or parameter under the cursor
+/// with its inferred type.
+pub(crate) fn add_type_annotation_code_actions(
+    transaction: &Transaction<'_>,
+    handle: &Handle,
+    selection: TextRange,
+    import_format: ImportFormat,
+) -> Option<Vec<LocalRefactorCodeAction>> {
+    let AnnotationTarget {
+        name_range,
+        edit_range,
+        has_default,
+    } = annotation_target(transaction, handle, selection)?;
+    let ast = transaction.get_ast(handle)?;
+    let module_info = transaction.get_module_info(handle)?;
+    let stdlib = transaction.get_stdlib(handle);
+    let ty = transaction
+        .get_type_at_for_display(handle, name_range.start())?
+        .promote_implicit_literals(&stdlib)
+        .explicit_any()
+        .clean_var();
+    let rendered = ty.as_lsp_string(LspDisplayMode::SignatureHelp);
+    if should_skip_annotation(&rendered, &ty) {
+        return None;
+    }
+    let insert_text = if has_default {
+        format!(": {rendered} = ")
+    } else {
+        format!(": {rendered}")
+    };
+    let mut edits = vec![(module_info.dupe(), edit_range, insert_text)];
+    for (position, text) in import_edits_for_type(
+        transaction,
+        &ast,
+        handle,
+        module_info.contents().as_str(),
+        import_format,
+        &ty,
+    ) {
+        edits.push((
+            module_info.dupe(),
+            TextRange::at(position, TextSize::new(0)),
+            text,
+        ));
+    }
+    Some(vec![LocalRefactorCodeAction {
+        title: format!("Add type annotation `{rendered}`"),
+        edits,
+        kind: lsp_types::CodeActionKind::QUICKFIX,
+    }])
+}
@@ -0,0 +1,299 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use dupe::Dupe;
+use lsp_types::CodeActionKind;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::ast::Ast;
+use ruff_python_ast::AnyNodeRef;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ExprBinOp;
+use ruff_python_ast::ExprCall;
+use ruff_python_ast::ExprStringLiteral;
+use ruff_python_ast::Operator;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+
+use super::extract_shared::code_at_range;
+use super::extract_shared::selection_anchor;
+use super::types::LocalRefactorCodeAction;
+use crate::state::lsp::Transaction;
+use crate::types::types::Type;
+
+/// Builds a code action that rewrites the `"...".format(...)` call or `"..." % (...)` expression
+/// under the cursor as an f-string.
+pub(crate) fn convert_to_fstring_code_actions(
+    transaction: &Transaction<'_>,
+    handle: &Handle,
+    selection: TextRange,
+) -> Option<Vec<LocalRefactorCodeAction>> {
+    let module_info = transaction.get_module_info(handle)?;
+    let ast = transaction.get_ast(handle)?;
+    let source = module_info.contents();
+    let anchor = selection_anchor(source, selection);
+    for node in Ast::locate_node(ast.as_ref(), anchor) {
+        let replacement = match node {
+            AnyNodeRef::ExprCall(call) => format_call_to_fstring(source, call),
+            AnyNodeRef::ExprBinOp(bin_op) if bin_op.op == Operator::Mod => {
+                percent_format_to_fstring(transaction, handle, source, bin_op)
+            }
+            _ => continue,
+        };
+        if let Some(replacement) = replacement {
+            return Some(vec![LocalRefactorCodeAction {
+                title: "Convert to f-string".to_owned(),
+                edits: vec![(module_info.dupe(), node.range(), replacement)],
+                kind: CodeActionKind::REFACTOR_REWRITE,
+            }]);
+        }
+    }
+    None
+}
+
+/// The pieces of a single-part string literal, as written in the source.
+struct TemplateLiteral<'a> {
+    prefix: &'a str,
+    quote: &'a str,
+    body: &'a str,
+}
+
+impl TemplateLiteral<'_> {
+    fn to_fstring(&self, body: &str) -> String {
+        format!("{}f{}{body}{}", self.prefix, self.quote, self.quote)
+    }
+}
+
+/// Split a plain or raw string literal into prefix, quotes and body. Implicitly concatenated
+/// literals and `u` strings (which can't be combined with `f`) are not supported.
+fn template_literal<'a>(
+    source: &'a str,
+    literal: &ExprStringLiteral,
+) -> Option<TemplateLiteral<'a>> {
+    if literal.value.is_implicit_concatenated() {
+        return None;
+    }
+    let text = code_at_range(source, literal.range())?;
+    let prefix_len = text.find(['"', '\''])?;
+    let prefix = &text[..prefix_len];
+    if !(prefix.is_empty() || prefix.eq_ignore_ascii_case("r")) {
+        return None;
+    }
+    let rest = &text[prefix_len..];
+    let quote = if rest.starts_with("\"\"\"") || rest.starts_with("'''") {
+        &rest[..3]
+    } else {
+        &rest[..1]
+    };
+    let body = rest.strip_prefix(quote)?.strip_suffix(quote)?;
+    // Named unicode escapes (`\N{...}`) use braces that an f-string would read as a field.
+    if prefix.is_empty() && body.contains("\\N") {
+        return None;
+    }
+    Some(TemplateLiteral {
+        prefix,
+        quote,
+        body,
+    })
+}
+
+/// The source of `expr` if it can be placed inside a replacement field of an f-string delimited
+/// by `quote` without changing its meaning on any supported Python version.
+fn interpolated_source<'a>(source: &'a str, expr: &Expr, quote: &str) -> Option<&'a str> {
+    if matches!(
+        expr,
+        Expr::Starred(_) | Expr::Lambda(_) | Expr::Named(_) | Expr::Yield(_) | Expr::YieldFrom(_)
+    ) {
+        return None;
+    }
+    let text = code_at_range(source, expr.range())?;
+    let quote_char = &quote[..1];
+    if text.starts_with('{') || text.contains(['\\', '\n', '\r', '#']) || text.contains(quote_char)
+    {
+        return None;
+    }
+    Some(text)
+}
+
+/// Convert `"...".format(args)` where every replacement field maps to exactly one argument.
+fn format_call_to_fstring(source: &str, call: &ExprCall) -> Option<String> {
+    let Expr::Attribute(attribute) = call.func.as_ref() else {
+        return None;
+    };
+    let Expr::StringLiteral(literal) = attribute.value.as_ref() else {
+        return None;
+    };
+    if attribute.attr.as_str() != "format" {
+        return None;
+    }
+    let template = template_literal(source, literal)?;
+    let positional = call
+        .arguments
+        .args
+        .iter()
+        .map(|arg| interpolated_source(source, arg, template.quote))
+        .collect::<Option<Vec<_>>>()?;
+    let mut keywords = HashMap::new();
+    for keyword in &call.arguments.keywords {
+        let name = keyword.arg.as_ref()?;
+        keywords.insert(
+            name.as_str(),
+            interpolated_source(source, &keyword.value, template.quote)?,
+        );
+    }
+
+    let mut used_positional = vec![false; positional.len()];
+    let mut used_keywords = HashSet::new();
+    let mut next_auto_index = 0;
+    let mut saw_auto = false;
+    let mut saw_manual = false;
+    let mut result = String::new();
+    let mut chars = template.body.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' if chars.peek().is_some_and(|(_, next)| *next == '{') => {
+                chars.next();
+                result.push_str("{{");
+            }
+            '}' if chars.peek().is_some_and(|(_, next)| *next == '}') => {
+                chars.next();
+                result.push_str("}}");
+            }
+            '{' => {
+                let end = i + template.body[i..].find('}')?;
+                let field = &template.body[i + 1..end];
+                if field.contains('{') {
+                    return None;
+                }
+                while chars.peek().is_some_and(|(j, _)| *j <= end) {
+                    chars.next();
+                }
+                let name_end = field.find(['!', ':']).unwrap_or(field.len());
+                let (name, suffix) = field.split_at(name_end);
+                let arg = if name.is_empty() {
+                    saw_auto = true;
+                    next_auto_index += 1;
+                    positional_use(&positional, &mut used_positional, next_auto_index - 1)?
+                } else if let Ok(index) = name.parse::<usize>() {
+                    saw_manual = true;
+                    positional_use(&positional, &mut used_positional, index)?
+                } else {
+                    let arg = *keywords.get(name)?;
+                    if !used_keywords.insert(name) {
+                        return None;
+                    }
+                    arg
+                };
+                result.push('{');
+                result.push_str(arg);
+                result.push_str(suffix);
+                result.push('}');
+            }
+            '}' => return None,
+            _ => result.push(c),
+        }
+    }
+    if (saw_auto && saw_manual)
+        || used_positional.contains(&false)
+        || used_keywords.len() != keywords.len()
+    {
+        return None;
+    }
+    Some(template.to_fstring(&result))
+}
+
+/// Mark positional argument `index` as used, failing if it doesn't exist or was already used.
+fn positional_use<'a>(positional: &[&'a str], used: &mut [bool], index: usize) -> Option<&'a str> {
+    let arg = *positional.get(index)?;
+    if std::mem::replace(&mut used[index], true) {
+        return None;
+    }
+    Some(arg)
+}
+
+/// Convert `"..." % (args)` where the template only uses `%s`, `%r` and `%a` placeholders and
+/// the right-hand side is a tuple with one element per placeholder, or a single operand that
+/// can't be a tuple filling the only placeholder.
+fn percent_format_to_fstring(
+    transaction: &Transaction<'_>,
+    handle: &Handle,
+    source: &str,
+    bin_op: &ExprBinOp,
+) -> Option<String> {
+    let Expr::StringLiteral(literal) = bin_op.left.as_ref() else {
+        return None;
+    };
+    let template = template_literal(source, literal)?;
+    let operands = match bin_op.right.as_ref() {
+        Expr::Tuple(tuple) => tuple.elts.iter().collect::<Vec<_>>(),
+        operand if is_non_tuple_operand(transaction, handle, operand) => vec![operand],
+        _ => return None,
+    };
+    let args = operands
+        .into_iter()
+        .map(|arg| interpolated_source(source, arg, template.quote))
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut args = args.into_iter();
+    let mut result = String::new();
+    let mut chars = template.body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '%' => {
+                let conversion = match chars.next()? {
+                    '%' => {
+                        result.push('%');
+                        continue;
+                    }
+                    's' => "",
+                    'r' => "!r",
+                    'a' => "!a",
+                    _ => return None,
+                };
+                result.push('{');
+                result.push_str(args.next()?);
+                result.push_str(conversion);
+                result.push('}');
+            }
+            '{' => result.push_str("{{"),
+            '}' => result.push_str("}}"),
+            _ => result.push(c),
+        }
+    }
+    if args.next().is_some() {
+        return None;
+    }
+    Some(template.to_fstring(&result))
+}
+
+/// Whether a non-tuple right-hand side of `%` is formatted as a single value. Python unpacks a
+/// tuple operand into the placeholders, so this only holds for literals, and for names whose
+/// type neither is nor could hold a tuple.
+fn is_non_tuple_operand(transaction: &Transaction<'_>, handle: &Handle, operand: &Expr) -> bool {
+    match operand {
+        Expr::StringLiteral(_)
+        | Expr::BytesLiteral(_)
+        | Expr::FString(_)
+        | Expr::NumberLiteral(_)
+        | Expr::BooleanLiteral(_)
+        | Expr::NoneLiteral(_) => true,
+        Expr::Name(name) => {
+            let Some(ty) = transaction.get_type_trace(handle, name.range()) else {
+                return false;
+            };
+            let tuple = Type::unbounded_tuple(Type::any_implicit());
+            transaction
+                .ad_hoc_solve(handle, "convert_to_fstring_non_tuple_operand", |solver| {
+                    !solver.is_subset_eq(&ty, &tuple) && !solver.is_subset_eq(&tuple, &ty)
+                })
+                .unwrap_or(false)
+        }
+        _ => false,
+    }
+}
@@ -6,8 +6,11 @@
  */
 
 pub(crate) mod add_override;
+pub(crate) mod add_return_annotations;
+pub(crate) mod add_type_annotation;
 pub(crate) mod convert_dict;
 pub(crate) mod convert_star_import;
+pub(crate) mod convert_to_fstring;
 pub(crate) mod enum_member;
 pub(crate) mod extract_field;
 pub(crate) mod extract_function;
@@ -25,6 +28,7 @@ pub(crate) mod move_module;
 pub(crate) mod pyrefly_ignore;
 pub(crate) mod pytest_fixture;
 pub(crate) mod redundant_cast;
+pub(crate) mod return_annotation;
 pub(crate) mod safe_delete;
 pub(crate) mod types;
 pub(crate) mod unnecessary_type_conversion;
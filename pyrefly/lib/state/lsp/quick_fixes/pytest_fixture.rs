@@ -14,7 +14,6 @@ use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::module_name::ModuleNameWithKind;
 use pyrefly_python::module_path::ModulePath;
 use pyrefly_python::module_path::ModulePathDetails;
-use pyrefly_python::short_identifier::ShortIdentifier;
 use pyrefly_types::display::LspDisplayMode;
 use pyrefly_types::types::Type;
 use ruff_python_ast::Expr;
@@ -28,10 +27,11 @@ use ruff_text_size::Ranged;
 use ruff_text_size::TextRange;
 use ruff_text_size::TextSize;
 
-use crate::binding::binding::Key;
-use crate::state::ide::insert_import_edit;
 use crate::state::lsp::ImportFormat;
 use crate::state::lsp::LocalRefactorCodeAction;
+use crate::state::lsp::quick_fixes::return_annotation::import_edits_for_type;
+use crate::state::lsp::quick_fixes::return_annotation::inferred_return_type;
+use crate::state::lsp::quick_fixes::return_annotation::should_skip_annotation;
 use crate::state::state::Transaction;
 
 #[derive(Debug, Default)]
@@ -108,14 +108,6 @@ fn collect_fixture_functions<'a>(stmts: &'a [Stmt], out: &mut Vec<&'a StmtFuncti
     }
 }
 
-fn should_skip_annotation(rendered: &str, ty: &Type) -> bool {
-    ty.is_any()
-        || rendered.contains("Any")
-        || rendered.contains("Unknown")
-        || rendered.contains("Never")
-        || rendered.contains('@')
-}
-
 fn is_test_name(name: &Name) -> bool {
     name.as_str().starts_with("test_")
 }
@@ -160,36 +152,6 @@ struct FixtureParamAnnotationEdit {
     import_edits: Vec<(TextSize, String)>,
 }
 
-fn fixture_return_type(
-    transaction: &Transaction<'_>,
-    handle: &Handle,
-    func: &StmtFunctionDef,
-) -> Option<Type> {
-    let return_key = Key::ReturnType(ShortIdentifier::new(&func.name));
-    let mut ty = transaction.get_type(handle, &return_key)?;
-    if func.is_async
-        && let Some(Some((_, _, return_ty))) =
-            transaction.ad_hoc_solve(handle, "pytest_fixture_unwrap_coroutine", |solver| {
-                solver.unwrap_coroutine(&ty)
-            })
-    {
-        ty = return_ty;
-    }
-    if let Some(display_ty) =
-        transaction.ad_hoc_solve(handle, "pytest_fixture_for_display", |solver| {
-            solver.for_display(ty.clone())
-        })
-    {
-        ty = display_ty;
-    }
-    let stdlib = transaction.get_stdlib(handle);
-    Some(
-        ty.promote_implicit_literals(&stdlib)
-            .explicit_any()
-            .clean_var(),
-    )
-}
-
 fn fixture_types_for_module(transaction: &Transaction<'_>, handle: &Handle) -> HashMap<Name, Type> {
     let Some(ast) = transaction.get_ast(handle) else {
         return HashMap::new();
@@ -202,7 +164,7 @@ fn fixture_types_for_module(transaction: &Transaction<'_>, handle: &Handle) -> H
         if !is_pytest_fixture_function(func, &aliases) {
             continue;
         }
-        let Some(ty) = fixture_return_type(transaction, handle, func) else {
+        let Some(ty) = inferred_return_type(transaction, handle, func) else {
             continue;
         };
         let rendered = ty.as_lsp_string(LspDisplayMode::SignatureHelp);
@@ -256,57 +218,6 @@ fn conftest_handles(transaction: &Transaction<'_>, handle: &Handle) -> Vec<Handl
     handles
 }
 
-fn import_edits_for_type(
-    transaction: &Transaction<'_>,
-    ast: &ModModule,
-    handle: &Handle,
-    module_contents: &str,
-    import_format: ImportFormat,
-    ty: &Type,
-) -> Vec<(TextSize, String)> {
-    let mut import_edits = Vec::new();
-    let mut seen_imports = HashSet::new();
-    ty.universe(&mut |ty| {
-        let Some(qname) = ty.qname() else {
-            return;
-        };
-        if !qname.parent().is_toplevel() {
-            return;
-        }
-        let module = qname.module_name();
-        if module == handle.module() || module.as_str() == "builtins" {
-            return;
-        }
-        let Some(handle_to_import_from) = transaction.import_handle(handle, module, None).finding()
-        else {
-            return;
-        };
-        let import_edit = insert_import_edit(
-            ast,
-            transaction.config_finder(),
-            handle.dupe(),
-            handle_to_import_from,
-            qname.id().as_str(),
-            import_format,
-        );
-        let position = import_edit.range.start();
-        let insert_text = import_edit.insert_text;
-        // Only dedup against full import lines: merge edits have `new_text` like
-        // `, X`, and a substring check for that would spuriously match unrelated
-        // code (function args, type annotations). Merge edits are already
-        // deduplicated inside `merge_range_for_import`.
-        if (insert_text.starts_with("from ") || insert_text.starts_with("import "))
-            && module_contents.contains(&insert_text)
-        {
-            return;
-        }
-        if seen_imports.insert(insert_text.clone()) {
-            import_edits.push((position, insert_text));
-        }
-    });
-    import_edits
-}
-
 /// Builds code actions that add inferred return annotations to pytest fixtures.
 pub(crate) fn pytest_fixture_type_annotation_code_actions(
     transaction: &Transaction<'_>,
@@ -329,7 +240,7 @@ pub(crate) fn pytest_fixture_type_annotation_code_actions(
         if !is_pytest_fixture_function(func, &aliases) {
             continue;
         }
-        let Some(ty) = fixture_return_type(transaction, handle, func) else {
+        let Some(ty) = inferred_return_type(transaction, handle, func) else {
             continue;
         };
         let rendered = ty.as_lsp_string(LspDisplayMode::SignatureHelp);
@@ -0,0 +1,114 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::collections::HashSet;
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::short_identifier::ShortIdentifier;
+use pyrefly_types::types::Type;
+use ruff_python_ast::ModModule;
+use ruff_python_ast::StmtFunctionDef;
+use ruff_text_size::TextSize;
+
+use crate::binding::binding::Key;
+use crate::state::ide::insert_import_edit;
+use crate::state::lsp::ImportFormat;
+use crate::state::state::Transaction;
+
+/// The inferred return type of `func`, unwrapped from its coroutine if `func` is async and
+/// prepared for display as an annotation.
+pub(crate) fn inferred_return_type(
+    transaction: &Transaction<'_>,
+    handle: &Handle,
+    func: &StmtFunctionDef,
+) -> Option<Type> {
+    let return_key = Key::ReturnType(ShortIdentifier::new(&func.name));
+    let mut ty = transaction.get_type(handle, &return_key)?;
+    if func.is_async
+        && let Some(Some((_, _, return_ty))) =
+            transaction.ad_hoc_solve(handle, "return_annotation_unwrap_coroutine", |solver| {
+                solver.unwrap_coroutine(&ty)
+            })
+    {
+        ty = return_ty;
+    }
+    if let Some(display_ty) =
+        transaction.ad_hoc_solve(handle, "return_annotation_for_display", |solver| {
+            solver.for_display(ty.clone())
+        })
+    {
+        ty = display_ty;
+    }
+    let stdlib = transaction.get_stdlib(handle);
+    Some(
+        ty.promote_implicit_literals(&stdlib)
+            .explicit_any()
+            .clean_var(),
+    )
+}
+
+/// Whether a rendered inferred type shouldn't be written back as an annotation.
+pub(crate) fn should_skip_annotation(rendered: &str, ty: &Type) -> bool {
+    ty.is_any()
+        || rendered.contains("Any")
+        || rendered.contains("Unknown")
+        || rendered.contains("Never")
+        || rendered.contains('@')
+}
+
+/// Edits importing every top-level name `ty` refers to that isn't already imported.
+pub(crate) fn import_edits_for_type(
+    transaction: &Transaction<'_>,
+    ast: &ModModule,
+    handle: &Handle,
+    module_contents: &str,
+    import_format: ImportFormat,
+    ty: &Type,
+) -> Vec<(TextSize, String)> {
+    let mut import_edits = Vec::new();
+    let mut seen_imports = HashSet::new();
+    ty.universe(&mut |ty| {
+        let Some(qname) = ty.qname() else {
+            return;
+        };
+        if !qname.parent().is_toplevel() {
+            return;
+        }
+        let module = qname.module_name();
+        if module == handle.module() || module.as_str() == "builtins" {
+            return;
+        }
+        let Some(handle_to_import_from) = transaction.import_handle(handle, module, None).finding()
+        else {
+            return;
+        };
+        let import_edit = insert_import_edit(
+            ast,
+            transaction.config_finder(),
+            handle.dupe(),
+            handle_to_import_from,
+            qname.id().as_str(),
+            import_format,
+        );
+        let position = import_edit.range.start();
+        let insert_text = import_edit.insert_text;
+        // Only dedup against full import lines: merge edits have `new_text` like
+        // `, X`, and a substring check for that would spuriously match unrelated
+        // code (function args, type annotations). Merge edits are already
+        // deduplicated inside `merge_range_for_import`.
+        if (insert_text.starts_with("from ") || insert_text.starts_with("import "))
+            && module_contents.contains(&insert_text)
+        {
+            return;
+        }
+        if seen_imports.insert(insert_text.clone()) {
+            import_edits.push((position, insert_text));
+        }
+    });
+    import_edits
+}
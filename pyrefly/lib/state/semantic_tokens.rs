@@ -32,6 +32,7 @@ use ruff_python_ast::StmtImportFrom;
 use ruff_python_ast::name::Name;
 use ruff_python_ast::token::TokenKind;
 use ruff_python_ast::token::Tokens;
+use ruff_source_file::PositionEncoding;
 use ruff_text_size::Ranged;
 use ruff_text_size::TextRange;
 use ruff_text_size::TextSize;
@@ -129,6 +130,7 @@ impl SemanticTokensLegends {
         module_info: Module,
         limit_range: Option<TextRange>,
         limit_cell_idx: Option<usize>,
+        position_encoding: PositionEncoding,
     ) -> Vec<SemanticToken> {
         let mut previous_line = 0;
         let mut previous_col = 0;
@@ -147,8 +149,9 @@ impl SemanticTokensLegends {
                 if cell_idx != limit_cell_idx {
                     return;
                 }
-                let start_pos = module_info.to_lsp_position(segment_range.start());
-                let end_pos = module_info.to_lsp_position(segment_range.end());
+                let start_pos =
+                    module_info.to_lsp_position(segment_range.start(), position_encoding);
+                let end_pos = module_info.to_lsp_position(segment_range.end(), position_encoding);
                 debug_assert_eq!(
                     start_pos.line, end_pos.line,
                     "Semantic token segment should be on a single line"
@@ -2236,6 +2236,12 @@ impl<'a> Transaction<'a> {
         }
     }
 
+    /// Recompute every module, e.g. so that a reporter set on this transaction sees each module
+    /// reach the solutions step again.
+    pub fn invalidate_all(&mut self) {
+        self.invalidate(|_| true, |state| state.set_dirty_deps());
+    }
+
     /// Invalidate based on what a watcher told you.
     pub fn invalidate_events(&mut self, events: &CategorizedEvents) {
         // If any files were added or removed, we need to invalidate the find step.
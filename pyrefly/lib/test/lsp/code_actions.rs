@@ -276,6 +276,22 @@ fn compute_convert_dict_actions(
     (module_info, edit_sets, titles)
 }
 
+fn compute_convert_to_fstring_actions(
+    code: &str,
+    selection: TextRange,
+) -> (ModuleInfo, Vec<Vec<(Module, TextRange, String)>>) {
+    let (handles, state) =
+        mk_multi_file_state_assert_no_errors(&[("main", code)], Require::Everything);
+    let handle = handles.get("main").unwrap();
+    let transaction = state.transaction();
+    let module_info = transaction.get_module_info(handle).unwrap();
+    let actions = transaction
+        .convert_to_fstring_code_actions(handle, selection)
+        .unwrap_or_default();
+    let edit_sets = actions.iter().map(|action| action.edits.clone()).collect();
+    (module_info, edit_sets)
+}
+
 fn assert_no_invert_boolean_action_allow_errors(code: &str, selection: TextRange) {
     let (_, actions, _) = compute_invert_boolean_actions_allow_errors(code, selection);
     assert!(
@@ -307,6 +323,20 @@ fn apply_first_inline_variable_action(code: &str) -> Option<String> {
     Some(apply_refactor_edits_for_module(&module_info, &edits))
 }
 
+fn apply_first_add_type_annotation_action(code: &str) -> Option<String> {
+    let (handles, state) =
+        mk_multi_file_state_assert_no_errors(&[("main", code)], Require::Everything);
+    let handle = handles.get("main").unwrap();
+    let transaction = state.transaction();
+    let module_info = transaction.get_module_info(handle).unwrap();
+    let selection = cursor_selection(code);
+    let actions = transaction
+        .add_type_annotation_code_actions(handle, selection, ImportFormat::Absolute)
+        .unwrap_or_default();
+    let edits = actions.first()?.edits.clone();
+    Some(apply_refactor_edits_for_module(&module_info, &edits))
+}
+
 fn apply_first_inline_method_action(code: &str) -> Option<String> {
     let (handles, state) =
         mk_multi_file_state_assert_no_errors(&[("main", code)], Require::Everything);
@@ -2337,6 +2367,102 @@ fn convert_dict_rejects_non_literal_keys() {
     );
 }
 
+#[test]
+fn convert_to_fstring_format_call() {
+    let code = r#"def greet(name, count):
+    return "Hello {}, you have {n:>3} new {}".format(name, 'messages', n=count)
+"#;
+    let format_start = find_nth_range(code, "format", 1).start();
+    let selection = TextRange::new(format_start, format_start);
+    let (module_info, actions) = compute_convert_to_fstring_actions(code, selection);
+    assert_eq!(1, actions.len());
+    let updated = apply_refactor_edits_for_module(&module_info, &actions[0]);
+    let expected = r#"def greet(name, count):
+    return f"Hello {name}, you have {count:>3} new {'messages'}"
+"#;
+    assert_eq!(expected.trim(), updated.trim());
+}
+
+#[test]
+fn convert_to_fstring_explicit_indices_and_escaped_braces() {
+    let code = r#"def show(a, b):
+    return '{{{1}}} {0!r}'.format(a, b)
+"#;
+    let anchor = find_nth_range(code, "'{{", 1).start();
+    let selection = TextRange::new(anchor, anchor);
+    let (module_info, actions) = compute_convert_to_fstring_actions(code, selection);
+    assert_eq!(1, actions.len());
+    let updated = apply_refactor_edits_for_module(&module_info, &actions[0]);
+    let expected = r#"def show(a, b):
+    return f'{{{b}}} {a!r}'
+"#;
+    assert_eq!(expected.trim(), updated.trim());
+}
+
+#[test]
+fn convert_to_fstring_percent_format() {
+    let code = r#"def describe(key, value):
+    return "%s = %r (100%% {sure})" % (key, value)
+"#;
+    let anchor = find_nth_range(code, "%s", 1).start();
+    let selection = TextRange::new(anchor, anchor);
+    let (module_info, actions) = compute_convert_to_fstring_actions(code, selection);
+    assert_eq!(1, actions.len());
+    let updated = apply_refactor_edits_for_module(&module_info, &actions[0]);
+    let expected = r#"def describe(key, value):
+    return f"{key} = {value!r} (100% {{sure}})"
+"#;
+    assert_eq!(expected.trim(), updated.trim());
+}
+
+#[test]
+fn convert_to_fstring_percent_format_single_operand() {
+    for (code, expected) in [
+        (
+            "def f(name: str):\n    return \"name: %s\" % name\n",
+            "def f(name: str):\n    return f\"name: {name}\"\n",
+        ),
+        (
+            "def f():\n    return \"%r\" % 42\n",
+            "def f():\n    return f\"{42!r}\"\n",
+        ),
+    ] {
+        let anchor = find_nth_range(code, "\"", 1).start();
+        let selection = TextRange::new(anchor, anchor);
+        let (module_info, actions) = compute_convert_to_fstring_actions(code, selection);
+        assert_eq!(1, actions.len());
+        let updated = apply_refactor_edits_for_module(&module_info, &actions[0]);
+        assert_eq!(expected.trim(), updated.trim());
+    }
+}
+
+#[test]
+fn convert_to_fstring_rejects_unmatched_placeholders() {
+    for code in [
+        "def f(a, b):\n    return \"{} {}\".format(a)\n",
+        "def f(a, b):\n    return \"{}\".format(a, b)\n",
+        "def f(a):\n    return \"{0} {0}\".format(a)\n",
+        "def f(a, b):\n    return \"{x}{x}\".format(x=a(), y=b())\n",
+        "def f(a):\n    return \"{x}\".format(x=a, y=a)\n",
+        "def f(a, b):\n    return \"%s\" % (a, b)\n",
+        "def f(a):\n    return \"%d\" % (a,)\n",
+        "def f(a):\n    return \"{0.real}\".format(a)\n",
+        "def f(a: str):\n    return \"%s %s\" % a\n",
+        "def f(a):\n    return \"%s\" % a\n",
+        "def f(a: tuple[int]):\n    return \"%s\" % a\n",
+        "def f(a: object):\n    return \"%s\" % a\n",
+    ] {
+        let anchor = find_nth_range(code, "\"", 1).start();
+        let selection = TextRange::new(anchor, anchor);
+        let (_, actions) = compute_convert_to_fstring_actions(code, selection);
+        assert!(
+            actions.is_empty(),
+            "expected no f-string action for {code:?}, found {}",
+            actions.len()
+        );
+    }
+}
+
 #[test]
 fn extract_function_basic_refactor() {
     let code = r#"
@@ -4957,6 +5083,69 @@ def test_one(answer: int, user: str):
     assert_eq!(expected.trim(), updated_all.trim());
 }
 
+#[test]
+fn add_type_annotation_to_local_variable() {
+    let code = r#"
+def compute():
+    value = [1, 2]
+#   ^
+    return value
+"#;
+    let updated =
+        apply_first_add_type_annotation_action(code).expect("expected add type annotation action");
+    let expected = r#"
+def compute():
+    value: list[int] = [1, 2]
+#   ^
+    return value
+"#;
+    assert_eq!(expected, updated);
+}
+
+#[test]
+fn add_type_annotation_to_parameter() {
+    let code = r#"
+from typing import override
+
+class Base:
+    def run(self, count: int) -> None: ...
+
+class Child(Base):
+    @override
+    def run(self, count) -> None: ...
+#                 ^
+"#;
+    let updated =
+        apply_first_add_type_annotation_action(code).expect("expected add type annotation action");
+    let expected = r#"
+from typing import override
+
+class Base:
+    def run(self, count: int) -> None: ...
+
+class Child(Base):
+    @override
+    def run(self, count: int) -> None: ...
+#                 ^
+"#;
+    assert_eq!(expected, updated);
+}
+
+#[test]
+fn add_type_annotation_skips_annotated_and_unknown() {
+    let annotated = r#"
+value: int = 1
+# ^
+"#;
+    assert!(apply_first_add_type_annotation_action(annotated).is_none());
+    let unknown = r#"
+def f(arg):
+#     ^
+    return arg
+"#;
+    assert!(apply_first_add_type_annotation_action(unknown).is_none());
+}
+
 /// Returns the edits of the "Add `@override` decorator" quick fix for the method
 /// at the last `def foo` in `code`, or `None` if the fix is not offered.
 fn add_override_quickfix_edits(
@@ -5130,3 +5319,50 @@ class Derived(Base):
 ";
     assert_eq!(expected, after);
 }
+
+#[test]
+fn add_missing_return_annotations_annotates_every_function() {
+    let code = r#"
+from typing import Any
+
+def name():
+    return "alice"
+
+class Counter:
+    def increment(self, by: int):
+        return by + 1
+
+def annotated() -> int:
+    return 1
+
+def untyped(x: Any):
+    return x
+"#;
+    let (handles, state) =
+        mk_multi_file_state_assert_no_errors(&[("main", code)], Require::Everything);
+    let handle = handles.get("main").unwrap();
+    let transaction = state.transaction();
+    let module_info = transaction.get_module_info(handle).unwrap();
+    let edits = transaction
+        .add_missing_return_annotations_edits(handle, ImportFormat::Absolute)
+        .expect("expected return annotation edits");
+    assert_eq!(2, edits.len());
+    let updated = apply_refactor_edits_for_module(&module_info, &edits);
+    let expected = r#"
+from typing import Any
+
+def name() -> str:
+    return "alice"
+
+class Counter:
+    def increment(self, by: int) -> int:
+        return by + 1
+
+def annotated() -> int:
+    return 1
+
+def untyped(x: Any):
+    return x
+"#;
+    assert_eq!(expected, updated);
+}
@@ -13,6 +13,7 @@ use pyrefly_build::handle::Handle;
 use pyrefly_python::sys_info::PythonVersion;
 use ruff_text_size::TextSize;
 
+use crate::lsp::wasm::completion::CompletionOptions;
 use crate::state::lsp::ImportFormat;
 use crate::state::require::Require;
 use crate::state::state::State;
@@ -209,6 +210,54 @@ Completion Results:
     );
 }
 
+#[test]
+fn completion_item_kind_follows_symbol_kind() {
+    let code = r#"
+class MyClass:
+    Nested = int
+
+def my_function() -> None: ...
+
+MY_CONSTANT = 1
+
+def use(my_param: int) -> None:
+    my
+#     ^
+    MyClass.Ne
+#             ^
+"#;
+    let (handles, state) = mk_multi_file_state(&[("main", code)], Require::Exports, true);
+    let handle = handles.get("main").unwrap();
+    let positions = extract_cursors_for_test(code);
+    let txn = state.transaction();
+    let kind_of = |position: TextSize, label: &str| {
+        txn.completion(handle, position, ImportFormat::Absolute, true, None)
+            .into_iter()
+            .find(|item| item.label == label)
+            .and_then(|item| item.kind)
+    };
+    assert_eq!(
+        kind_of(positions[0], "MyClass"),
+        Some(CompletionItemKind::CLASS)
+    );
+    assert_eq!(
+        kind_of(positions[0], "my_function"),
+        Some(CompletionItemKind::FUNCTION)
+    );
+    assert_eq!(
+        kind_of(positions[0], "MY_CONSTANT"),
+        Some(CompletionItemKind::CONSTANT)
+    );
+    assert_eq!(
+        kind_of(positions[0], "my_param"),
+        Some(CompletionItemKind::VARIABLE)
+    );
+    assert_eq!(
+        kind_of(positions[1], "Nested"),
+        Some(CompletionItemKind::CLASS)
+    );
+}
+
 #[test]
 fn dict_key_completion_from_nested_literal() {
     let code = r#"
@@ -272,6 +321,32 @@ Completion Results:
     );
 }
 
+#[test]
+fn dict_key_completion_from_typed_dict_parameter_empty_subscript() {
+    let code = r#"
+from typing import TypedDict
+
+class AB(TypedDict):
+    a: int
+    b: str
+
+def f(d: AB) -> None:
+    d[]
+#     ^
+"#;
+    let report =
+        get_batched_lsp_operations_report_allow_error(&[("main", code)], get_default_test_report());
+    let report = strip_ansi(&report);
+    assert!(
+        report.contains("- (Field) a: int inserting `\"a\"`"),
+        "{report}"
+    );
+    assert!(
+        report.contains("- (Field) b: str inserting `\"b\"`"),
+        "{report}"
+    );
+}
+
 #[test]
 fn dict_key_completion_from_typed_dict_get() {
     let code = r#"
@@ -409,9 +484,9 @@ te
 12 | te
        ^
 Completion Results:
-- (Class) deprecated: type[deprecated]
 - (Function) test2: () -> None
 - (Function) [DEPRECATED] test1: () -> None
+- (Class) deprecated: type[deprecated]
 "#
         .trim(),
         report.trim(),
@@ -980,9 +1055,9 @@ foo(x
 4 | foo(x
          ^
 Completion Results:
+- (Variable) xyz: Literal[5]
 - (Variable) a=: int
 - (Variable) b=: str
-- (Variable) xyz: Literal[5]
 "#
         .trim(),
         report.trim(),
@@ -1146,6 +1221,42 @@ Completion Results:
     );
 }
 
+#[test]
+fn kwargs_completion_empty_call() {
+    let code = r#"
+def foo(bar, baz): ...
+foo()
+#   ^
+"#;
+    let report =
+        get_batched_lsp_operations_report_allow_error(&[("main", code)], get_default_test_report());
+    assert!(report.contains("- (Variable) bar="), "{report}");
+    assert!(report.contains("- (Variable) baz="), "{report}");
+}
+
+#[test]
+fn kwargs_completion_deprioritizes_supplied_params() {
+    let code = r#"
+def foo(a: int, b: str, c: bool): ...
+foo(1, c=True, )
+#              ^
+"#;
+    let (handles, state) = mk_multi_file_state(&[("main", code)], Require::Exports, false);
+    let handle = handles.get("main").unwrap();
+    let position = extract_cursors_for_test(code)[0];
+    let txn = state.transaction();
+    let sort_text_of = |label: &str| {
+        txn.completion(handle, position, ImportFormat::Absolute, true, None)
+            .into_iter()
+            .find(|item| item.label == label)
+            .and_then(|item| item.sort_text)
+            .unwrap_or_else(|| panic!("missing completion `{label}`"))
+    };
+    let missing = sort_text_of("b=");
+    assert!(missing < sort_text_of("a="));
+    assert!(missing < sort_text_of("c="));
+}
+
 #[test]
 fn kwargs_completion_method() {
     let code = r#"
@@ -1480,6 +1591,151 @@ a.zz
     );
 }
 
+#[test]
+fn attribute_completion_ranks_prefix_matches_first() {
+    let code = r#"
+x = [1]
+x.ap
+#   ^
+"#;
+    let (handles, state) = mk_multi_file_state(&[("main", code)], Require::Exports, false);
+    let handle = handles.get("main").unwrap();
+    let position = extract_cursors_for_test(code)[0];
+    let txn = state.transaction();
+    let (completions, is_incomplete) = txn.completion_with_incomplete(
+        handle,
+        position,
+        ImportFormat::Absolute,
+        CompletionOptions::default(),
+        None,
+    );
+    // Nothing was dropped, so the client can keep filtering the list as the user types.
+    assert!(!is_incomplete);
+    assert_eq!(
+        completions.first().map(|item| item.label.as_str()),
+        Some("append")
+    );
+    // Names that don't match what was typed are ranked last but still offered.
+    assert!(completions.iter().any(|item| item.label == "sort"));
+}
+
+#[test]
+fn identifier_completion_ranks_prefix_matches_first() {
+    let code = r#"
+def tap() -> None: ...
+def apply() -> None: ...
+ap
+# ^
+"#;
+    let (handles, state) = mk_multi_file_state(&[("main", code)], Require::Exports, false);
+    let handle = handles.get("main").unwrap();
+    let position = extract_cursors_for_test(code)[0];
+    let txn = state.transaction();
+    let completions = txn.completion(handle, position, ImportFormat::Absolute, true, None);
+    let index_of = |label: &str| {
+        completions
+            .iter()
+            .position(|item| item.label == label)
+            .unwrap_or_else(|| panic!("missing completion `{label}`"))
+    };
+    assert!(index_of("apply") < index_of("tap"));
+}
+
+#[test]
+fn identifier_completion_drops_non_matches_over_the_cap() {
+    let mut code = String::new();
+    for i in 0..300 {
+        code.push_str(&format!("name_{i} = {i}\n"));
+    }
+    code.push_str("matching_one = 1\nmatching_two = 2\nmat\n#  ^\n");
+    let (handles, state) = mk_multi_file_state(&[("main", code.as_str())], Require::Exports, false);
+    let handle = handles.get("main").unwrap();
+    let position = extract_cursors_for_test(&code)[0];
+    let txn = state.transaction();
+    let (completions, is_incomplete) = txn.completion_with_incomplete(
+        handle,
+        position,
+        ImportFormat::Absolute,
+        CompletionOptions::default(),
+        None,
+    );
+    assert!(is_incomplete);
+    assert!(completions.iter().any(|item| item.label == "matching_one"));
+    assert!(completions.iter().any(|item| item.label == "matching_two"));
+    assert!(
+        !completions.iter().any(|item| item.label == "name_0"),
+        "names that don't match what was typed should be dropped first"
+    );
+}
+
+#[test]
+fn attribute_completion_lists_public_before_dunder() {
+    let code = r#"
+class A:
+    def __init__(self) -> None: ...
+    def _helper(self) -> None: ...
+    def run(self) -> None: ...
+
+obj = A()
+obj.
+#   ^
+"#;
+    let (handles, state) = mk_multi_file_state(&[("main", code)], Require::Exports, false);
+    let handle = handles.get("main").unwrap();
+    let position = extract_cursors_for_test(code)[0];
+    let txn = state.transaction();
+    let completions = txn.completion(handle, position, ImportFormat::Absolute, true, None);
+    let index_of = |label: &str| {
+        completions
+            .iter()
+            .position(|item| item.label == label)
+            .unwrap_or_else(|| panic!("missing completion `{label}`"))
+    };
+    assert!(index_of("run") < index_of("_helper"));
+    assert!(index_of("_helper") < index_of("__init__"));
+}
+
+#[test]
+fn attribute_completion_filter_text_strips_underscores() {
+    let code = r#"
+class A:
+    def __init__(self) -> None: ...
+
+obj = A()
+obj.i
+#    ^
+obj._
+#    ^
+obj.
+#   ^
+"#;
+    let (handles, state) = mk_multi_file_state(&[("main", code)], Require::Exports, false);
+    let handle = handles.get("main").unwrap();
+    let positions = extract_cursors_for_test(code);
+    let txn = state.transaction();
+    let filter_text_of_init = |position| {
+        txn.completion(handle, position, ImportFormat::Absolute, true, None)
+            .into_iter()
+            .find(|item| item.label == "__init__")
+            .and_then(|item| item.filter_text)
+    };
+    assert_eq!(filter_text_of_init(positions[0]), Some("init__".to_owned()));
+    assert_eq!(filter_text_of_init(positions[1]), None);
+    // Nothing is typed yet, and the next character may be a `_`.
+    assert_eq!(filter_text_of_init(positions[2]), None);
+    // No completion was dropped, so the client can keep filtering without asking again.
+    for position in positions {
+        let (_, is_incomplete) = txn.completion_with_incomplete(
+            handle,
+            position,
+            ImportFormat::Absolute,
+            CompletionOptions::default(),
+            None,
+        );
+        assert!(!is_incomplete);
+    }
+}
+
 #[test]
 fn builtins_doesnt_autoimport() {
     let code = r#"
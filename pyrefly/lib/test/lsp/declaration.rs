@@ -157,3 +157,90 @@ Declaration Result:
         report.trim()
     );
 }
+
+fn get_declaration_and_definition_report(
+    state: &State,
+    handle: &Handle,
+    position: TextSize,
+) -> String {
+    let transaction = state.transaction();
+    let format_results = |kind: &str, results: Vec<TextRangeWithModule>| {
+        results
+            .into_iter()
+            .map(|TextRangeWithModule { module, range }| {
+                format!(
+                    "{kind} Result ({}):\n{}",
+                    module.name(),
+                    code_frame_of_source_at_range(module.contents(), range)
+                )
+            })
+            .join("\n")
+    };
+    format!(
+        "{}\n{}",
+        format_results(
+            "Declaration",
+            transaction
+                .goto_declaration(handle, position)
+                .unwrap_or_default()
+        ),
+        format_results(
+            "Definition",
+            transaction
+                .goto_definition(handle, position)
+                .unwrap_or_default()
+        ),
+    )
+}
+
+#[test]
+fn goto_declaration_vs_definition() {
+    let code_a: &str = r#"
+def b():
+    pass
+"#;
+    let code_test: &str = r#"
+from a import b
+
+def local():
+    pass
+
+def f():
+    b()
+#   ^
+    local()
+#   ^
+"#;
+
+    let report = get_batched_lsp_operations_report(
+        &[("main", code_test), ("a", code_a)],
+        get_declaration_and_definition_report,
+    );
+    assert_eq!(
+        r#"
+# main.py
+8 |     b()
+        ^
+Declaration Result (main):
+2 | from a import b
+                  ^
+Definition Result (a):
+2 | def b():
+        ^
+
+10 |     local()
+         ^
+Declaration Result (main):
+4 | def local():
+        ^^^^^
+Definition Result (main):
+4 | def local():
+        ^^^^^
+
+
+# a.py
+"#
+        .trim(),
+        report.trim()
+    );
+}
@@ -1784,6 +1784,52 @@ Definition Result:
     );
 }
 
+#[test]
+fn union_method_inherited_from_reexport_is_deduplicated_test() {
+    // Both union members inherit `run` from the same class, re-exported through
+    // `pkg/__init__.py`, so they resolve to one location that is reported once.
+    let code_pkg_init = r#"from pkg.impl import Base as Base
+"#;
+    let code_pkg_impl = r#"class Base:
+    def run(self) -> None: ...
+"#;
+    let code_test = r#"
+from pkg import Base
+
+class Left(Base): pass
+class Right(Base): pass
+
+def f(x: Left | Right):
+    x.run()
+#     ^
+"#;
+    let report = get_batched_lsp_operations_report(
+        &[
+            ("main", code_test),
+            ("pkg", code_pkg_init),
+            ("pkg.impl", code_pkg_impl),
+        ],
+        get_test_report,
+    );
+    assert_eq!(
+        r#"
+# main.py
+8 |     x.run()
+          ^
+Definition Result:
+2 |     def run(self) -> None: ...
+            ^^^
+
+
+# pkg.py
+
+# pkg.impl.py
+"#
+        .trim(),
+        report.trim()
+    );
+}
+
 #[test]
 fn union_method_access_test() {
     let code = r#"
@@ -7,6 +7,7 @@
 
 use pretty_assertions::assert_eq;
 use pyrefly_build::handle::Handle;
+use ruff_source_file::PositionEncoding;
 
 use crate::lsp::non_wasm::document_symbols::flatten_to_symbol_information;
 use crate::state::state::State;
@@ -21,7 +22,7 @@ fn get_combined_report(state: &State, handle: &Handle) -> String {
 
 fn get_hierarchical_symbol_report(state: &State, handle: &Handle) -> String {
     let transaction = state.transaction();
-    if let Some(symbols) = transaction.symbols(handle, None) {
+    if let Some(symbols) = transaction.symbols(handle, None, PositionEncoding::Utf16) {
         serde_json::to_string_pretty(&symbols).unwrap()
     } else {
         "No document symbols found".to_owned()
@@ -31,7 +32,7 @@ fn get_hierarchical_symbol_report(state: &State, handle: &Handle) -> String {
 fn get_flat_symbol_report(state: &State, handle: &Handle) -> String {
     let transactions = state.transaction();
     let uri = lsp_types::Url::parse("file:///main.py").unwrap();
-    if let Some(symbols) = transactions.symbols(handle, None) {
+    if let Some(symbols) = transactions.symbols(handle, None, PositionEncoding::Utf16) {
         let flat = flatten_to_symbol_information(symbols, &uri);
         serde_json::to_string_pretty(&flat).unwrap()
     } else {
@@ -8,6 +8,7 @@
 use lsp_types::FoldingRangeKind;
 use pretty_assertions::assert_eq;
 use pyrefly_build::handle::Handle;
+use ruff_source_file::PositionEncoding;
 use serde::Serialize;
 
 use crate::state::state::State;
@@ -30,7 +31,7 @@ fn get_folding_ranges_report(state: &State, handle: &Handle) -> String {
     let mut folding_ranges: Vec<FoldingRangeInfo> = ranges
         .into_iter()
         .map(|(text_range, kind)| {
-            let range = module.to_lsp_range(text_range);
+            let range = module.to_lsp_range(text_range, PositionEncoding::Utf16);
             FoldingRangeInfo {
                 start_line: range.start.line,
                 end_line: range.end.line,
@@ -56,7 +57,7 @@ fn get_docstring_ranges_report(state: &State, handle: &Handle) -> String {
     let lines: Vec<(u32, u32)> = ranges
         .into_iter()
         .map(|text_range| {
-            let range = module.to_lsp_range(text_range);
+            let range = module.to_lsp_range(text_range, PositionEncoding::Utf16);
             (range.start.line, range.end.line)
         })
         .collect();
@@ -0,0 +1,116 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use itertools::Itertools;
+use pretty_assertions::assert_eq;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::module::TextRangeWithModule;
+use pyrefly_util::prelude::VecExt;
+use ruff_text_size::TextSize;
+use vec1::Vec1;
+
+use crate::state::lsp::FindPreference;
+use crate::state::state::State;
+use crate::test::util::code_frame_of_source_at_range;
+use crate::test::util::get_batched_lsp_operations_report;
+
+fn get_limited_references_report(
+    state: &State,
+    handle: &Handle,
+    position: TextSize,
+    max_results: Option<usize>,
+) -> String {
+    let mut transaction = state.cancellable_transaction();
+    let Some(definition) = transaction
+        .as_ref()
+        .find_definition(handle, position, FindPreference::default())
+        .map(Vec1::into_vec)
+        .unwrap_or_default()
+        .into_iter()
+        .next()
+    else {
+        return "References: None".to_owned();
+    };
+    let (references, truncated) = transaction
+        .find_global_references_from_definition_with_limit(
+            *handle.sys_info(),
+            definition.metadata,
+            TextRangeWithModule::new(definition.module, definition.definition_range),
+            false,
+            max_results,
+        )
+        .unwrap();
+    format!(
+        "References (truncated: {truncated}):\n{}",
+        references
+            .into_iter()
+            .flat_map(|(module, ranges)| {
+                ranges.into_map(|range| code_frame_of_source_at_range(module.contents(), range))
+            })
+            .join("\n")
+    )
+}
+
+const HEAVILY_USED: &str = r#"
+def f() -> None: ...
+#   ^
+f()
+f()
+f()
+f()
+f()
+"#;
+
+#[test]
+fn references_are_capped_at_limit() {
+    let report =
+        get_batched_lsp_operations_report(&[("main", HEAVILY_USED)], |state, handle, position| {
+            get_limited_references_report(state, handle, position, Some(2))
+        });
+    assert_eq!(
+        r#"
+# main.py
+2 | def f() -> None: ...
+        ^
+References (truncated: true):
+4 | f()
+    ^
+5 | f()
+    ^
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn references_under_limit_are_not_truncated() {
+    let report =
+        get_batched_lsp_operations_report(&[("main", HEAVILY_USED)], |state, handle, position| {
+            get_limited_references_report(state, handle, position, Some(10))
+        });
+    assert_eq!(
+        r#"
+# main.py
+2 | def f() -> None: ...
+        ^
+References (truncated: false):
+4 | f()
+    ^
+5 | f()
+    ^
+6 | f()
+    ^
+7 | f()
+    ^
+8 | f()
+    ^
+"#
+        .trim(),
+        report.trim(),
+    );
+}
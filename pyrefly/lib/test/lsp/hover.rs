@@ -7,6 +7,7 @@
 
 use lsp_types::Hover;
 use lsp_types::HoverContents;
+use lsp_types::Url;
 use pretty_assertions::assert_eq;
 use pyrefly_build::handle::Handle;
 use ruff_text_size::TextSize;
@@ -20,7 +21,7 @@ use crate::test::util::get_batched_lsp_operations_report;
 use crate::test::util::get_batched_lsp_operations_report_allow_error;
 
 fn get_test_report(state: &State, handle: &Handle, position: TextSize) -> String {
-    match get_hover(&state.transaction(), handle, position, true) {
+    match get_hover(&state.transaction(), handle, position, true, None, false) {
         Some(Hover {
             contents: HoverContents::Markup(markup),
             ..
@@ -87,6 +88,51 @@ xyz = [foo.meth]
     );
 }
 
+#[test]
+fn hover_over_property_getter_shows_property() {
+    let code = r#"
+class Foo:
+    @property
+    def prop(self) -> int:
+#       ^
+        return 1
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert!(
+        report.contains("(property) prop: int"),
+        "Expected property hover, got: {report}"
+    );
+    assert!(
+        !report.contains("(method)"),
+        "Expected no method hover, got: {report}"
+    );
+    assert!(
+        report.contains("**Original signature**\n```python\ndef prop(self"),
+        "Expected original getter signature, got: {report}"
+    );
+}
+
+#[test]
+fn hover_stdlib_type_link_points_at_existing_file() {
+    let code = r#"
+xy = 1
+#^
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    let start = report
+        .find("[int](")
+        .unwrap_or_else(|| panic!("Expected 'Go to [int]' link, got: {report}"))
+        + "[int](".len();
+    let end = start + report[start..].find(')').unwrap();
+    let url = Url::parse(&report[start..end]).unwrap();
+    let path = url.to_file_path().unwrap();
+    assert!(
+        path.exists(),
+        "Expected link to an existing file, got: {}",
+        path.display()
+    );
+}
+
 #[test]
 fn hover_on_union_method_call_shows_method_signature() {
     let code = r#"
@@ -160,6 +206,96 @@ def f(x: A | B) -> None:
     );
 }
 
+#[test]
+fn hover_on_ambiguous_attribute_lists_every_definition() {
+    let code = r#"
+class A:
+    value: int = 0
+
+class B:
+    value: str = ""
+
+def f(flag: bool) -> None:
+    x = A() if flag else B()
+    x.value
+#     ^
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert!(
+        report.contains("**2 definitions**"),
+        "Expected hover to note both definitions, got: {report}"
+    );
+    assert!(
+        report.contains("- `int` at `main:3:5`") && report.contains("- `str` at `main:6:5`"),
+        "Expected hover to show the type of each definition, got: {report}"
+    );
+}
+
+#[test]
+fn hover_on_type_checking_conditional_name_lists_every_definition() {
+    let code = r#"
+from typing import TYPE_CHECKING
+
+if TYPE_CHECKING:
+    value: int = 0
+else:
+    value: str = ""
+
+value
+# ^
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert!(
+        report.contains("**2 definitions**"),
+        "Expected hover to note both definitions, got: {report}"
+    );
+    assert!(
+        report.contains("- `int` at `main:5:5`") && report.contains("- `str` at `main:7:5`"),
+        "Expected hover to show the type of each branch, got: {report}"
+    );
+}
+
+#[test]
+fn hover_on_runtime_conditional_name_lists_every_definition() {
+    let code = r#"
+import random
+
+if random.random() > 0.5:
+    def handler() -> int: ...
+else:
+    def handler() -> str: ...
+
+handler
+# ^
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert!(
+        report.contains("**2 definitions**"),
+        "Expected hover to note both definitions, got: {report}"
+    );
+    assert!(
+        report.contains("at `main:5:9`") && report.contains("at `main:7:9`"),
+        "Expected hover to list both branches, got: {report}"
+    );
+}
+
+#[test]
+fn hover_on_single_definition_omits_definition_list() {
+    let code = r#"
+class A:
+    value: int = 0
+
+def f(x: A) -> None:
+    x.value
+#     ^
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert!(
+        !report.contains("definitions**"),
+        "Expected no definition list for a single definition, got: {report}"
+    );
+}
+
 #[test]
 fn hover_on_class_attribute_shows_class() {
     let code = r#"
@@ -371,7 +507,7 @@ def f(x: int | None) -> None:
 #   ^
 "#;
     let report = get_batched_lsp_operations_report(&[("main", code)], |state, handle, position| {
-        match get_hover(&state.transaction(), handle, position, false) {
+        match get_hover(&state.transaction(), handle, position, false, None, false) {
             Some(Hover {
                 contents: HoverContents::Markup(markup),
                 ..
@@ -428,7 +564,7 @@ def f(x: int | str | None) -> None:
 #       ^
 "#;
     let report = get_batched_lsp_operations_report(&[("main", code)], |state, handle, position| {
-        match get_hover(&state.transaction(), handle, position, false) {
+        match get_hover(&state.transaction(), handle, position, false, None, false) {
             Some(Hover {
                 contents: HoverContents::Markup(markup),
                 ..
@@ -458,7 +594,7 @@ def f(c: C) -> None:
 #         ^
 "#;
     let report = get_batched_lsp_operations_report(&[("main", code)], |state, handle, position| {
-        match get_hover(&state.transaction(), handle, position, false) {
+        match get_hover(&state.transaction(), handle, position, false, None, false) {
             Some(Hover {
                 contents: HoverContents::Markup(markup),
                 ..
@@ -491,7 +627,7 @@ def f(c: C) -> None:
 #         ^
 "#;
     let report = get_batched_lsp_operations_report(&[("main", code)], |state, handle, position| {
-        match get_hover(&state.transaction(), handle, position, false) {
+        match get_hover(&state.transaction(), handle, position, false, None, false) {
             Some(Hover {
                 contents: HoverContents::Markup(markup),
                 ..
@@ -516,7 +652,7 @@ def f() -> None:
 #   ^
 "#;
     let report = get_batched_lsp_operations_report(&[("main", code)], |state, handle, position| {
-        match get_hover(&state.transaction(), handle, position, false) {
+        match get_hover(&state.transaction(), handle, position, false, None, false) {
             Some(Hover {
                 contents: HoverContents::Markup(markup),
                 ..
@@ -1167,7 +1303,7 @@ from http import HTTPStatus
 #                ^
 "#;
     let report = get_batched_lsp_operations_report(&[("main", code)], |state, handle, position| {
-        match get_hover(&state.transaction(), handle, position, false) {
+        match get_hover(&state.transaction(), handle, position, false, None, false) {
             Some(Hover {
                 contents: HoverContents::Markup(markup),
                 ..
@@ -1281,7 +1417,7 @@ x: dict[str, int]
 #  ^
 "#;
     let report = get_batched_lsp_operations_report(&[("main", code)], |state, handle, position| {
-        match get_hover(&state.transaction(), handle, position, false) {
+        match get_hover(&state.transaction(), handle, position, false, None, false) {
             Some(Hover {
                 contents: HoverContents::Markup(markup),
                 ..
@@ -1533,7 +1669,14 @@ Test()
 "#;
     let report = get_batched_lsp_operations_report_allow_error(
         &[("main", code)],
-        |state, handle, position| match get_hover(&state.transaction(), handle, position, false) {
+        |state, handle, position| match get_hover(
+            &state.transaction(),
+            handle,
+            position,
+            false,
+            None,
+            false,
+        ) {
             Some(Hover {
                 contents: HoverContents::Markup(markup),
                 ..
@@ -1838,7 +1981,7 @@ def unannotated():
     let handle = handle_fn("main");
     let cursors = extract_cursors_for_test(code);
     assert_eq!(cursors.len(), 1);
-    let result = get_hover(&state.transaction(), &handle, cursors[0], true);
+    let result = get_hover(&state.transaction(), &handle, cursors[0], true, None, false);
     match result {
         Some(Hover {
             contents: HoverContents::Markup(markup),
@@ -1971,3 +2114,263 @@ method
     assert_sphinx_resolved_as_code(&report, "py-meth", "test");
     assert_sphinx_resolved_as_code(&report, "c-func", "other");
 }
+
+/// Asserts that hovering a keyword with a type shows the type first, with the keyword's
+/// description below it.
+fn assert_keyword_hover_shows_type_then_description(code: &str, description: &str) {
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    let type_start = report
+        .find("```python")
+        .unwrap_or_else(|| panic!("Expected a type in the hover, got: {report}"));
+    let description_start = report
+        .find(description)
+        .unwrap_or_else(|| panic!("Expected the keyword description in the hover, got: {report}"));
+    assert!(
+        type_start < description_start && !report.contains("(keyword)"),
+        "Expected the type before the keyword description, got: {report}"
+    );
+}
+
+#[test]
+fn hover_over_keyword_with_type_shows_type_then_description() {
+    assert_keyword_hover_shows_type_then_description(
+        r#"
+x = None
+#   ^
+"#,
+        "The sole value of `NoneType`",
+    );
+    assert_keyword_hover_shows_type_then_description(
+        r#"
+x = True
+#   ^
+"#,
+        "One of the two values of type `bool`",
+    );
+    assert_keyword_hover_shows_type_then_description(
+        r#"
+f = lambda: 1
+#   ^
+"#,
+        "Creates an anonymous function",
+    );
+    assert_keyword_hover_shows_type_then_description(
+        r#"
+async def g() -> int:
+    return 1
+async def f():
+    x = await g()
+#       ^
+"#,
+        "Suspends the enclosing coroutine",
+    );
+    assert_keyword_hover_shows_type_then_description(
+        r#"
+def f():
+    x = yield 1
+#       ^
+"#,
+        "Produces a value from a generator",
+    );
+}
+
+#[test]
+fn hover_over_keyword_without_type_shows_description() {
+    let code = r#"
+def f():
+    pass
+#   ^
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+3 |     pass
+        ^
+```python
+(keyword) pass
+```
+---
+Does nothing; a placeholder where a statement is required.
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn hover_truncates_long_type_to_max_length() {
+    let code = r#"
+x: dict[str, dict[str, dict[str, list[tuple[int, str, bytes, float]]]]] = {}
+x
+^
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], |state, handle, position| {
+        match get_hover(
+            &state.transaction(),
+            handle,
+            position,
+            false,
+            Some(40),
+            false,
+        ) {
+            Some(Hover {
+                contents: HoverContents::Markup(markup),
+                ..
+            }) => markup.value,
+            _ => "None".to_owned(),
+        }
+    });
+    let type_line = report
+        .lines()
+        .find_map(|line| line.strip_prefix("(variable) x: "))
+        .unwrap_or_else(|| panic!("Expected a variable hover, got: {report}"));
+    assert!(
+        type_line.len() <= 40 && type_line.ends_with("..."),
+        "Expected the type to be truncated to 40 characters, got: {type_line}"
+    );
+    assert!(
+        type_line.starts_with("dict[str, dict[str,"),
+        "Expected truncation at a type boundary, got: {type_line}"
+    );
+    assert!(
+        report.contains("Type truncated to 40 characters"),
+        "Expected a truncation note, got: {report}"
+    );
+}
+
+#[test]
+fn hover_does_not_truncate_short_type() {
+    let code = r#"
+x: dict[str, int] = {}
+x
+^
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], |state, handle, position| {
+        match get_hover(
+            &state.transaction(),
+            handle,
+            position,
+            false,
+            Some(40),
+            false,
+        ) {
+            Some(Hover {
+                contents: HoverContents::Markup(markup),
+                ..
+            }) => markup.value,
+            _ => "None".to_owned(),
+        }
+    });
+    assert!(
+        report.contains("(variable) x: dict[str, int]\n") && !report.contains("truncated"),
+        "Expected the full type, got: {report}"
+    );
+}
+
+#[test]
+fn hover_shows_generic_type_arguments() {
+    let code = r#"
+x: list[int] = []
+x
+^
+class Box[T]:
+#     ^
+    value: T
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert!(
+        report.contains("(variable) x: list[int]\n"),
+        "Expected the type arguments of the instance, got: {report}"
+    );
+    assert!(
+        report.contains("(class) Box: type[Box[T]]\n"),
+        "Expected the type parameters of the class, got: {report}"
+    );
+}
+
+#[test]
+fn hover_fully_qualified_names() {
+    let lib = r#"
+class Widget: ...
+"#;
+    let code = r#"
+from lib import Widget
+w = Widget()
+w
+^
+"#;
+    let hover_with = |fully_qualified_names| {
+        get_batched_lsp_operations_report(
+            &[("main", code), ("lib", lib)],
+            |state, handle, position| match get_hover(
+                &state.transaction(),
+                handle,
+                position,
+                false,
+                None,
+                fully_qualified_names,
+            ) {
+                Some(Hover {
+                    contents: HoverContents::Markup(markup),
+                    ..
+                }) => markup.value,
+                _ => "None".to_owned(),
+            },
+        )
+    };
+    let unqualified = hover_with(false);
+    assert!(
+        unqualified.contains("(variable) w: Widget\n"),
+        "Expected an unqualified name, got: {unqualified}"
+    );
+    let qualified = hover_with(true);
+    assert!(
+        qualified.contains("(variable) w: lib.Widget\n"),
+        "Expected a qualified name, got: {qualified}"
+    );
+}
+
+#[test]
+fn hover_fully_qualified_names_in_definition_list() {
+    let lib = r#"
+class Widget: ...
+class Gadget: ...
+"#;
+    let code = r#"
+from lib import Gadget, Widget
+
+class A:
+    value: Widget = Widget()
+
+class B:
+    value: Gadget = Gadget()
+
+def f(flag: bool) -> None:
+    x = A() if flag else B()
+    x.value
+#     ^
+"#;
+    let report = get_batched_lsp_operations_report(
+        &[("main", code), ("lib", lib)],
+        |state, handle, position| match get_hover(
+            &state.transaction(),
+            handle,
+            position,
+            false,
+            None,
+            true,
+        ) {
+            Some(Hover {
+                contents: HoverContents::Markup(markup),
+                ..
+            }) => markup.value,
+            _ => "None".to_owned(),
+        },
+    );
+    assert!(
+        report.contains("- `lib.Widget` at `main:5:5`")
+            && report.contains("- `lib.Gadget` at `main:8:5`"),
+        "Expected qualified names in the definition list, got: {report}"
+    );
+}
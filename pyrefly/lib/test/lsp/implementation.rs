@@ -103,6 +103,76 @@ Implementation Result:
     );
 }
 
+#[test]
+fn go_to_implementations_of_abstract_method_test() {
+    let code = r#"
+from abc import ABC, abstractmethod
+
+class Shape(ABC):
+    @abstractmethod
+    def area(self) -> float: ...
+#       ^
+
+class Square(Shape):
+    def area(self) -> float:
+        return 1.0
+
+class Circle(Shape):
+    def area(self) -> float:
+        return 3.14
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_implementations_report);
+    assert_eq!(
+        r#"
+# main.py
+6 |     def area(self) -> float: ...
+            ^
+Implementation Result:
+10 |     def area(self) -> float:
+             ^^^^
+Implementation Result:
+14 |     def area(self) -> float:
+             ^^^^
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn go_to_implementations_of_protocol_member_only_explicit_subclasses_test() {
+    let code = r#"
+from typing import Protocol
+
+class Drawable(Protocol):
+    def draw(self) -> None: ...
+#       ^
+
+class Canvas(Drawable):
+    def draw(self) -> None:
+        pass
+
+class Sketch:
+    def draw(self) -> None:
+        pass
+"#;
+    // `Sketch` conforms to `Drawable` structurally, but isn't reported since it doesn't
+    // inherit from it.
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_implementations_report);
+    assert_eq!(
+        r#"
+# main.py
+5 |     def draw(self) -> None: ...
+            ^
+Implementation Result:
+9 |     def draw(self) -> None:
+            ^^^^
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
 #[test]
 fn go_to_implementations_on_call_test() {
     let code = r#"
@@ -438,6 +438,46 @@ foo("hello", 1, 2, 3, 5, a=1, b=2, t=4)
     );
 }
 
+#[test]
+fn test_parameter_name_hints_skip_matching_argument_names() {
+    let code = r#"
+def foo(a: int, b: int) -> None:
+    pass
+
+a = 1
+b = 2
+foo(1, 2)
+foo(a, b)
+foo(b, a)
+"#;
+    assert_eq!(
+        r#"
+# main.py
+7 | foo(1, 2)
+        ^ inlay-hint: `a= `
+
+7 | foo(1, 2)
+           ^ inlay-hint: `b= `
+
+9 | foo(b, a)
+        ^ inlay-hint: `a= `
+
+9 | foo(b, a)
+           ^ inlay-hint: `b= `
+"#
+        .trim(),
+        generate_inlay_hint_report(
+            code,
+            InlayHintConfig {
+                call_argument_names: AllOffPartial::All,
+                variable_types: false,
+                ..Default::default()
+            }
+        )
+        .trim()
+    );
+}
+
 /// todo(jvansch): Update test once parameter hints have locations.
 #[test]
 fn test_parameter_hints_do_not_have_locations() {
@@ -0,0 +1,128 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use itertools::Itertools;
+use pretty_assertions::assert_eq;
+use pyrefly_build::handle::Handle;
+use ruff_text_size::TextSize;
+
+use crate::state::state::State;
+use crate::test::util::code_frame_of_source_at_range;
+use crate::test::util::get_batched_lsp_operations_report;
+use crate::test::util::get_batched_lsp_operations_report_allow_error;
+
+fn get_test_report(state: &State, handle: &Handle, position: TextSize) -> String {
+    let transaction = state.transaction();
+    let module_info = transaction.get_module_info(handle).unwrap();
+    match transaction.linked_editing_ranges(handle, position) {
+        Some(ranges) => format!(
+            "Linked ranges:\n{}",
+            ranges
+                .into_iter()
+                .map(|range| code_frame_of_source_at_range(module_info.contents(), range))
+                .join("\n")
+        ),
+        None => "Linked ranges: None".to_owned(),
+    }
+}
+
+#[test]
+fn linked_editing_range_function_local_variable() {
+    let code = r#"
+def f(items: list[int]) -> int:
+    total = 0
+    for item in items:
+        total += item
+#       ^
+    return total
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+5 |         total += item
+            ^
+Linked ranges:
+3 |     total = 0
+        ^^^^^
+5 |         total += item
+            ^^^^^
+7 |     return total
+               ^^^^^
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn linked_editing_range_skips_module_level_and_imported_names() {
+    let code = r#"
+from typing import List
+x: List[int] = []
+#  ^
+def f() -> None:
+    print(x)
+#         ^
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+3 | x: List[int] = []
+       ^
+Linked ranges: None
+
+6 |     print(x)
+              ^
+Linked ranges: None
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn linked_editing_range_skips_parameters() {
+    let code = r#"
+def f(items: list[int]) -> int:
+    return len(items)
+#               ^
+f(items=[])
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+3 |     return len(items)
+                    ^
+Linked ranges: None
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn linked_editing_range_skips_names_without_definition() {
+    let code = r#"
+def f() -> None:
+    print(undefined_name)
+#           ^
+"#;
+    let report = get_batched_lsp_operations_report_allow_error(&[("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+3 |     print(undefined_name)
+                ^
+Linked ranges: None
+"#
+        .trim(),
+        report.trim(),
+    );
+}
@@ -517,3 +517,72 @@ References:
         without_declaration.trim(),
     );
 }
+
+fn get_classified_test_report(
+    state: &State,
+    handle: &Handle,
+    position: TextSize,
+    include_declaration: bool,
+) -> String {
+    let transaction = state.transaction();
+    let references =
+        transaction.find_local_references_with_kind(handle, position, include_declaration);
+    let module_info = transaction.get_module_info(handle).unwrap();
+    format!(
+        "References:\n{}",
+        references
+            .into_iter()
+            .map(|(range, kind)| format!(
+                "{kind:?}\n{}",
+                code_frame_of_source_at_range(module_info.contents(), range)
+            ))
+            .join("\n")
+    )
+}
+
+#[test]
+fn classified_references() {
+    let code = r#"
+xy = 1
+#^
+print(xy)
+"#;
+    let with_declaration =
+        get_batched_lsp_operations_report(&[("main", code)], |state, handle, position| {
+            get_classified_test_report(state, handle, position, true)
+        });
+    assert_eq!(
+        r#"
+# main.py
+2 | xy = 1
+     ^
+References:
+Declaration
+2 | xy = 1
+    ^^
+Read
+4 | print(xy)
+          ^^
+"#
+        .trim(),
+        with_declaration.trim(),
+    );
+
+    let without_declaration =
+        get_batched_lsp_operations_report(&[("main", code)], |state, handle, position| {
+            get_classified_test_report(state, handle, position, false)
+        });
+    assert_eq!(
+        r#"
+# main.py
+2 | xy = 1
+     ^
+References:
+Read
+4 | print(xy)
+          ^^
+"#
+        .trim(),
+        without_declaration.trim(),
+    );
+}
@@ -40,7 +40,7 @@ fn test_initialize_basic() {
             "definitionProvider": true,
             "typeDefinitionProvider": true,
             "codeActionProvider": {
-                "codeActionKinds": ["quickfix", "refactor.extract", "refactor.rewrite", "refactor.delete", "refactor.move", "refactor.inline", "source.fixAll", "source.fixAll.pyrefly"]
+                "codeActionKinds": ["quickfix", "refactor.extract", "refactor.rewrite", "refactor.delete", "refactor.move", "refactor.inline", "source.fixAll", "source.fixAll.pyrefly", "source.addReturnAnnotations.pyrefly"]
             },
             "codeLensProvider": {
                 "resolveProvider": false,
@@ -51,16 +51,32 @@ fn test_initialize_basic() {
             },
             "declarationProvider": true,
             "documentHighlightProvider": true,
+            "linkedEditingRangeProvider": true,
+            "monikerProvider": true,
             "signatureHelpProvider": {
                 "triggerCharacters": ["(", ","]
             },
             "hoverProvider": true,
-            "implementationProvider": true,
             "inlayHintProvider": true,
+            "inlineValueProvider": true,
             "notebookDocumentSync":{"notebookSelector":[{"cells":[{"language":"python"}]}]},
             "documentSymbolProvider": true,
             "foldingRangeProvider":true,
-            "workspaceSymbolProvider": true,
+            "documentOnTypeFormattingProvider": {
+                "firstTriggerCharacter": "\n"
+            },
+            "workspaceSymbolProvider": {"resolveProvider": true},
+            "semanticTokensProvider": {
+                "legend": {
+                    "tokenTypes": ["namespace", "type", "class", "enum", "interface", "struct", "typeParameter", "parameter", "variable", "property", "enumMember", "event", "function", "method", "macro", "keyword", "modifier", "comment", "string", "number", "regexp", "operator", "decorator"],
+                    "tokenModifiers": ["declaration", "definition", "readonly", "static", "deprecated", "abstract", "async", "modification", "documentation", "defaultLibrary", "selfParameter"]
+                },
+                "full": { "delta": true },
+                "range": true
+            },
+            "executeCommandProvider": {
+                "commands": ["pyrefly.explainImport", "pyrefly.exportPysa", "pyrefly.printInternalType"]
+            },
             "workspace": {
                 "workspaceFolders": {
                     "supported": true,
@@ -267,6 +283,74 @@ fn test_unknown_request() {
         .unwrap();
 }
 
+#[test]
+fn test_request_timing_telemetry() {
+    let interaction = LspInteraction::new();
+    interaction
+        .initialize(InitializeSettings {
+            initialization_options: Some(json!({"requestTimingTelemetry": true})),
+            ..Default::default()
+        })
+        .unwrap();
+    interaction.client.send_message(Message::Request(Request {
+        id: RequestId::from(1),
+        method: "fake-method".to_owned(),
+        params: json!(null),
+        activity_key: None,
+    }));
+    interaction
+        .client
+        .expect_message("telemetry/event for fake-method", |msg| {
+            if let Message::Notification(x) = msg
+                && x.method == "telemetry/event"
+            {
+                assert_eq!(x.params["method"], "fake-method");
+                assert!(x.params["durationMs"].is_u64());
+                Some(Ok(()))
+            } else {
+                None
+            }
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_request_timing_telemetry_sent_with_async_response() {
+    let interaction = LspInteraction::new();
+    interaction
+        .initialize(InitializeSettings {
+            initialization_options: Some(json!({"requestTimingTelemetry": true})),
+            ..Default::default()
+        })
+        .unwrap();
+    // workspace/symbol is answered off the main loop, so its duration is only known once the
+    // response is sent.
+    interaction.client.send_message(Message::Request(Request {
+        id: RequestId::from(1),
+        method: "workspace/symbol".to_owned(),
+        params: json!({"query": "anything"}),
+        activity_key: None,
+    }));
+    let mut responded = false;
+    interaction
+        .client
+        .expect_message("telemetry/event for workspace/symbol", |msg| match msg {
+            Message::Response(x) if x.id == RequestId::from(1) => {
+                responded = true;
+                None
+            }
+            Message::Notification(x)
+                if x.method == "telemetry/event" && x.params["method"] == "workspace/symbol" =>
+            {
+                assert!(responded, "timing was sent before the response");
+                assert!(x.params["durationMs"].is_u64());
+                Some(Ok(()))
+            }
+            _ => None,
+        })
+        .unwrap();
+}
+
 #[test]
 fn test_connection_closed_server_stops() {
     let mut interaction = LspInteraction::new();
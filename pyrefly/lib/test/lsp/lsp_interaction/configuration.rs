@@ -15,13 +15,19 @@ use std::path::Path;
 use std::path::PathBuf;
 
 use lsp_types::Url;
+use lsp_types::notification::DidChangeConfiguration;
 use lsp_types::notification::DidChangeWorkspaceFolders;
 use lsp_types::request::WorkspaceConfiguration;
+use pyrefly::commands::lsp::IndexingMode;
+use pyrefly::commands::lsp::LspArgs;
+use pyrefly::lsp::non_wasm::protocol::Message;
 use pyrefly_util::fs_anyhow::write;
 use serde_json::json;
+use tempfile::TempDir;
 
 use crate::object_model::InitializeSettings;
 use crate::object_model::LspInteraction;
+use crate::object_model::LspInteractionArgs;
 use crate::util::get_test_files_root;
 
 #[test]
@@ -188,6 +194,72 @@ fn test_pythonpath_change() {
     interaction.shutdown().expect("Failed to shutdown");
 }
 
+// Only run this test on unix since windows has no way to mock a .exe without compiling something
+// (we call python with python.exe)
+#[cfg(unix)]
+#[test]
+fn test_pythonpath_pushed_in_did_change_configuration() {
+    let test_files_root = get_test_files_root();
+    let custom_interpreter_path = test_files_root.path().join("custom_interpreter");
+    let interpreter_path = setup_dummy_interpreter(&custom_interpreter_path);
+
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(test_files_root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings {
+            configuration: Some(Some(
+                json!([{"pyrefly": {"displayTypeErrors": "force-on"}}]),
+            )),
+            initialization_options: Some(json!({
+                "pyrefly": {"streamDiagnostics": false},
+            })),
+            ..Default::default()
+        })
+        .expect("Failed to initialize");
+
+    interaction.client.did_open("custom_interpreter/src/foo.py");
+    interaction
+        .client
+        .expect_publish_diagnostics_eventual_error_count(
+            test_files_root.path().join("custom_interpreter/src/foo.py"),
+            1,
+        )
+        .expect("Failed to receive publish diagnostics");
+
+    // The client supports `workspace/configuration` but also pushes the interpreter in the
+    // notification. The pulled settings don't mention it, so only the pushed one can apply.
+    interaction
+        .client
+        .send_notification::<DidChangeConfiguration>(json!({
+            "settings": {"python": {"pythonPath": interpreter_path.to_str().unwrap()}}
+        }));
+    interaction
+        .client
+        .expect_request::<WorkspaceConfiguration>(json!({"items":[{"section":"python"}]}))
+        .expect("")
+        .send_configuration_response(json!([{}]));
+    interaction
+        .client
+        .expect_publish_diagnostics_eventual_error_count(
+            test_files_root.path().join("custom_interpreter/src/foo.py"),
+            0,
+        )
+        .expect("Failed to receive publish diagnostics");
+    interaction
+        .client
+        .definition("custom_interpreter/src/foo.py", 5, 31)
+        .expect_definition_response_from_root(
+            "custom_interpreter/bin/site-packages/custom_module.py",
+            6,
+            6,
+            6,
+            17,
+        )
+        .unwrap();
+
+    interaction.shutdown().expect("Failed to shutdown");
+}
+
 // Only run this test on unix since windows has no way to mock a .exe without compiling something
 // (we call python with python.exe)
 #[cfg(unix)]
@@ -937,6 +1009,43 @@ fn test_diagnostics_default_workspace() {
     interaction.shutdown().expect("Failed to shutdown");
 }
 
+/// `typeCheckingMode` picks the preset for files without a Pyrefly
+/// config: `basic` silences `unsupported-operation`, while switching to
+/// `strict` invalidates the config and surfaces it on recheck.
+#[test]
+fn test_type_checking_mode_basic_to_strict() {
+    let root = get_test_files_root();
+    let type_errors_path = root.path().join("type_errors.py");
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings {
+            configuration: Some(Some(json!([{"pyrefly": {"typeCheckingMode": "basic"}}]))),
+            ..Default::default()
+        })
+        .expect("Failed to initialize");
+
+    interaction.client.did_open("type_errors.py");
+
+    interaction
+        .client
+        .expect_publish_diagnostics_eventual_error_count(type_errors_path.clone(), 0)
+        .expect("Failed to receive basic diagnostics");
+
+    interaction.client.did_change_configuration();
+    interaction
+        .client
+        .expect_configuration_request(None)
+        .expect("Failed to receive configuration request")
+        .send_configuration_response(json!([{"pyrefly": {"typeCheckingMode": "strict"}}]));
+    interaction
+        .client
+        .expect_publish_diagnostics_eventual_error_count(type_errors_path.clone(), 1)
+        .expect("Failed to receive strict diagnostics");
+
+    interaction.shutdown().expect("Failed to shutdown");
+}
+
 #[test]
 fn test_diagnostics_default_workspace_with_config() {
     let test_root = get_test_files_root();
@@ -1094,6 +1203,51 @@ fn test_diagnostics_file_in_excludes() {
     interaction.shutdown().expect("Failed to shutdown");
 }
 
+#[test]
+fn test_published_diagnostics_file_in_excludes() {
+    let root = get_test_files_root();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings {
+            configuration: Some(Some(
+                json!([{"pyrefly": {"displayTypeErrors": "force-on"}}]),
+            )),
+            initialization_options: Some(json!({
+                "pyrefly": {"streamDiagnostics": false},
+            })),
+            ..Default::default()
+        })
+        .expect("Failed to initialize");
+
+    // The excluded file has a type error, but it is not published.
+    interaction
+        .client
+        .did_open("diagnostics_file_in_excludes/type_errors_exclude.py");
+    interaction
+        .client
+        .expect_publish_diagnostics_eventual_error_count(
+            root.path()
+                .join("diagnostics_file_in_excludes/type_errors_exclude.py"),
+            0,
+        )
+        .expect("Failed to receive publish diagnostics");
+
+    interaction
+        .client
+        .did_open("diagnostics_file_in_excludes/type_errors_include.py");
+    interaction
+        .client
+        .expect_publish_diagnostics_eventual_error_count(
+            root.path()
+                .join("diagnostics_file_in_excludes/type_errors_include.py"),
+            1,
+        )
+        .expect("Failed to receive publish diagnostics");
+
+    interaction.shutdown().expect("Failed to shutdown");
+}
+
 #[test]
 fn test_initialization_options_respected() {
     let test_files_root = get_test_files_root();
@@ -1130,6 +1284,37 @@ fn test_initialization_options_respected() {
     interaction.shutdown().expect("Failed to shutdown");
 }
 
+#[test]
+fn test_initialization_options_set_interpreter_before_first_diagnostics() {
+    let test_files_root = get_test_files_root();
+    let interpreter_path =
+        setup_dummy_interpreter(&test_files_root.path().join("custom_interpreter"));
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(test_files_root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings {
+            initialization_options: Some(json!({
+                "pythonPath": interpreter_path.to_str().unwrap(),
+                "pyrefly": {"displayTypeErrors": "force-on"},
+            })),
+            configuration: Some(None),
+            ..Default::default()
+        })
+        .unwrap();
+
+    // Without the interpreter, `custom_module` fails to import. The very first diagnostics
+    // must already be clean, so the interpreter was applied before the first check.
+    interaction.client.did_open("custom_interpreter/src/foo.py");
+    interaction
+        .client
+        .expect_publish_diagnostics_must_have_error_count(
+            test_files_root.path().join("custom_interpreter/src/foo.py"),
+            0,
+        )
+        .unwrap();
+    interaction.shutdown().unwrap();
+}
+
 #[test]
 fn test_initialization_options_without_workspace_folders() {
     let test_files_root = get_test_files_root();
@@ -1190,3 +1375,74 @@ fn test_fallback_search_path_heuristics_nested() {
         .unwrap();
     interaction.shutdown().unwrap();
 }
+
+/// Expect the next `window/showMessage`, asserting it is the `maxIndexedFiles` warning for
+/// `max` of `total` project files.
+fn expect_max_indexed_files_warning(interaction: &LspInteraction, max: usize, total: usize) {
+    interaction
+        .client
+        .expect_message("window/showMessage for maxIndexedFiles", |msg| {
+            if let Message::Notification(x) = msg
+                && x.method == "window/showMessage"
+            {
+                // MessageType::WARNING
+                assert_eq!(x.params["type"], 2, "{}", x.params["message"]);
+                let message = x.params["message"].as_str().unwrap();
+                assert!(
+                    message.contains(&format!("indexed only {max} of {total} project files")),
+                    "unexpected warning: {message}"
+                );
+                Some(Ok(()))
+            } else {
+                None
+            }
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_max_indexed_files_warns_once_per_config_and_reindexes_on_change() {
+    let root = TempDir::new().unwrap();
+    let root_path = root.path().to_path_buf();
+    let scope_uri = Url::from_file_path(&root_path).unwrap();
+    std::fs::write(root_path.join("pyrefly.toml"), "").unwrap();
+    std::fs::write(root_path.join("main.py"), "x = 1\n").unwrap();
+    for i in 0..4 {
+        std::fs::write(root_path.join(format!("module_{i}.py")), "x = 1\n").unwrap();
+    }
+
+    let mut interaction = LspInteraction::new_with_args(LspInteractionArgs {
+        args: LspArgs {
+            indexing_mode: IndexingMode::LazyNonBlockingBackground,
+            workspace_indexing_limit: 0,
+            ..LspInteractionArgs::default().args
+        },
+        ..Default::default()
+    });
+    interaction.set_root(root_path.clone());
+    interaction
+        .initialize(InitializeSettings {
+            workspace_folders: Some(vec![("test".to_owned(), scope_uri.clone())]),
+            configuration: Some(Some(json!([{"pyrefly": {"maxIndexedFiles": 2}}, {}]))),
+            ..Default::default()
+        })
+        .unwrap();
+
+    interaction.client.did_open("main.py");
+    expect_max_indexed_files_warning(&interaction, 2, 5);
+
+    // Editing the config indexes the project again under the same cap, which doesn't warn again:
+    // the next warning has to be the one for the new cap below.
+    std::fs::write(root_path.join("pyrefly.toml"), "# edited\n").unwrap();
+    interaction.client.file_modified("pyrefly.toml");
+
+    interaction.client.did_change_configuration();
+    interaction
+        .client
+        .expect_configuration_request(Some(vec![&scope_uri]))
+        .unwrap()
+        .send_configuration_response(json!([{"pyrefly": {"maxIndexedFiles": 3}}, {}]));
+    expect_max_indexed_files_warning(&interaction, 3, 5);
+
+    interaction.shutdown().unwrap();
+}
@@ -5,6 +5,7 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -18,6 +19,7 @@ use pyrefly::commands::lsp::IndexingMode;
 use pyrefly::commands::lsp::LspArgs;
 use pyrefly::lsp::non_wasm::protocol::Message;
 use pyrefly::lsp::non_wasm::protocol::Request;
+use serde_json::Value;
 use serde_json::json;
 use tempfile::TempDir;
 
@@ -71,6 +73,23 @@ fn test_go_to_def(
     }
 }
 
+fn location_from_root(
+    root: &Path,
+    file: &str,
+    line_start: u32,
+    char_start: u32,
+    line_end: u32,
+    char_end: u32,
+) -> Value {
+    json!({
+        "uri": Url::from_file_path(root.join(file)).unwrap().to_string(),
+        "range": {
+            "start": {"line": line_start, "character": char_start},
+            "end": {"line": line_end, "character": char_end}
+        },
+    })
+}
+
 #[test]
 fn definition_on_attr_of_pyi_assignment_goes_to_py() {
     let root = get_test_files_root();
@@ -87,25 +106,47 @@ fn definition_on_attr_of_pyi_assignment_goes_to_py() {
     interaction
         .client
         .definition(file, 7, 8)
-        .expect_definition_response_from_root(
-            "attributes_of_py/lib_with_assignments.py",
-            7,
-            4,
-            7,
-            5,
-        )
+        .expect_response(json!([
+            location_from_root(
+                root.path(),
+                "attributes_of_py/lib_with_assignments.py",
+                7,
+                4,
+                7,
+                5
+            ),
+            location_from_root(
+                root.path(),
+                "attributes_of_py/lib_with_assignments.pyi",
+                6,
+                4,
+                6,
+                5
+            ),
+        ]))
         .unwrap();
     // Test regular assignment (y = "world")
     interaction
         .client
         .definition(file, 8, 8)
-        .expect_definition_response_from_root(
-            "attributes_of_py/lib_with_assignments.py",
-            8,
-            4,
-            8,
-            5,
-        )
+        .expect_response(json!([
+            location_from_root(
+                root.path(),
+                "attributes_of_py/lib_with_assignments.py",
+                8,
+                4,
+                8,
+                5
+            ),
+            location_from_root(
+                root.path(),
+                "attributes_of_py/lib_with_assignments.pyi",
+                7,
+                4,
+                7,
+                5
+            ),
+        ]))
         .unwrap();
     interaction.shutdown().unwrap();
 }
@@ -221,7 +262,31 @@ fn definition_on_attr_of_pyi_goes_to_py() {
     interaction
         .client
         .definition(file, 7, 4)
-        .expect_definition_response_from_root("attributes_of_py/lib.py", 7, 8, 7, 9)
+        .expect_response(json!([
+            location_from_root(root.path(), "attributes_of_py/lib.py", 7, 8, 7, 9),
+            location_from_root(root.path(), "attributes_of_py/lib.pyi", 6, 8, 6, 9),
+        ]))
+        .unwrap();
+    interaction.shutdown().unwrap();
+}
+
+#[test]
+fn definition_offers_both_sibling_stub_and_source() {
+    let root = get_test_files_root();
+    let test_root = root.path().join("stub_and_source");
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(test_root.clone());
+    interaction
+        .initialize(InitializeSettings::default())
+        .unwrap();
+    interaction.client.did_open("main.py");
+    interaction
+        .client
+        .definition("main.py", 7, 0)
+        .expect_response(json!([
+            location_from_root(&test_root, "foo.py", 6, 4, 6, 7),
+            location_from_root(&test_root, "foo.pyi", 5, 4, 5, 7),
+        ]))
         .unwrap();
     interaction.shutdown().unwrap();
 }
@@ -475,6 +540,27 @@ fn goto_type_def_on_list_of_primitives_shows_selector() {
         .unwrap();
 }
 
+#[test]
+fn goto_def_on_list_goes_to_builtins_stub_on_disk() {
+    let root = get_test_files_root();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings {
+            ..Default::default()
+        })
+        .unwrap();
+    interaction.client.did_open("primitive_type_test.py");
+    // The stub is read back from disk, so this fails unless the returned path is a real file.
+    interaction
+        .client
+        .definition("primitive_type_test.py", 11, 3)
+        .expect_response_with(|response| {
+            expect_definition_points_to_symbol(response.as_ref(), "builtins.pyi", "class list")
+        })
+        .unwrap();
+}
+
 #[test]
 fn test_go_to_def_constructor_calls() {
     // go-to-definition on constructor calls should go to __init__
@@ -911,3 +997,34 @@ fn definition_site_packages_relative_import() {
 
     interaction.shutdown().unwrap();
 }
+
+#[test]
+fn definition_with_utf8_position_encoding() {
+    let root = get_test_files_root();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings {
+            capabilities: Some(json!({
+                "general": {
+                    "positionEncodings": ["utf-8"],
+                },
+            })),
+            ..Default::default()
+        })
+        .unwrap();
+    interaction.client.did_open("text_document.py");
+    // The emoji is four UTF-8 bytes but only two UTF-16 code units, so `x` starts at UTF-8
+    // column 12 rather than UTF-16 column 10.
+    interaction
+        .client
+        .did_change("text_document.py", "s = \"😀\"; x = 1\ny = x\n");
+
+    interaction
+        .client
+        .definition("text_document.py", 1, 4)
+        .expect_definition_response_from_root("text_document.py", 0, 12, 0, 13)
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
@@ -5,6 +5,9 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::collections::HashMap;
+use std::path::PathBuf;
+
 use lsp_server::RequestId;
 use lsp_types::DocumentDiagnosticReportResult;
 use lsp_types::PublishDiagnosticsParams;
@@ -22,6 +25,7 @@ use pyrefly::lsp::non_wasm::protocol::Request;
 use pyrefly_util::stdlib::register_stdlib_paths;
 use serde_json::Value;
 use serde_json::json;
+use tempfile::TempDir;
 
 use crate::object_model::InitializeSettings;
 use crate::object_model::LspInteraction;
@@ -333,6 +337,184 @@ fn test_stream_diagnostics_after_save() {
     interaction.shutdown().unwrap();
 }
 
+#[test]
+fn test_publish_dependent_diagnostics_on_save() {
+    let root = get_test_files_root();
+    let root_path = root.path().join("streaming");
+    let mut interaction = LspInteraction::new_with_args(LspInteractionArgs {
+        args: LspArgs {
+            indexing_mode: IndexingMode::LazyBlocking,
+            ..LspInteractionArgs::default().args
+        },
+        ..Default::default()
+    });
+    interaction.set_root(root_path.clone());
+    interaction
+        .initialize(InitializeSettings {
+            configuration: Some(Some(json!([{
+                "pyrefly": {
+                    "displayTypeErrors": "force-on",
+                    "publishDependentDiagnosticsOnSave": true,
+                }
+            }]))),
+            workspace_folders: Some(vec![(
+                "streaming".to_owned(),
+                Url::from_file_path(root_path.clone()).unwrap(),
+            )]),
+            ..Default::default()
+        })
+        .unwrap();
+    let b_path = root_path.join("b.py");
+    let d_path = root_path.join("d.py");
+    let b_contents = std::fs::read_to_string(&b_path).unwrap();
+    interaction.client.did_open("b.py");
+    interaction
+        .client
+        .expect_publish_diagnostics_eventual_error_count(b_path.clone(), 0)
+        .expect("Failed to receive initial diagnostics for b");
+    // `d.py` is never opened, but it depends on `b.py` through `c.py`. Its error is only
+    // reported if the save checks it at the errors level rather than reusing its exports.
+    let new_contents = b_contents.replace("1", "''");
+    interaction.client.edit_file("b.py", &new_contents);
+    interaction
+        .client
+        .expect_publish_diagnostics_eventual_error_count(d_path.clone(), 1)
+        .expect("Failed to receive diagnostics for d after saving b");
+    interaction.shutdown().unwrap();
+}
+
+/// Wait until every file in `paths` has been published with errors on exactly `error_lines`,
+/// and return each file's errors in the order they were published.
+fn expect_errors_for_files(
+    interaction: &LspInteraction,
+    paths: &[PathBuf],
+    error_lines: &[u32],
+) -> Vec<Vec<lsp_types::Diagnostic>> {
+    let mut expected_lines = error_lines.to_vec();
+    expected_lines.sort();
+    let mut published: HashMap<PathBuf, Vec<lsp_types::Diagnostic>> = HashMap::new();
+    interaction
+        .client
+        .expect_message(
+            &format!("errors on lines {error_lines:?} for {} files", paths.len()),
+            |msg| {
+                if let Message::Notification(x) = msg
+                    && x.method == PublishDiagnostics::METHOD
+                {
+                    let params =
+                        serde_json::from_value::<PublishDiagnosticsParams>(x.params).unwrap();
+                    let errors: Vec<_> = params
+                        .diagnostics
+                        .into_iter()
+                        .filter(|d| d.severity == Some(lsp_types::DiagnosticSeverity::ERROR))
+                        .collect();
+                    let mut lines: Vec<_> = errors.iter().map(|d| d.range.start.line).collect();
+                    lines.sort();
+                    let path = params.uri.to_file_path().unwrap();
+                    if lines == expected_lines {
+                        published.insert(path, errors);
+                    } else {
+                        published.remove(&path);
+                    }
+                }
+                paths
+                    .iter()
+                    .all(|path| published.contains_key(path))
+                    .then_some(Ok(()))
+            },
+        )
+        .unwrap();
+    paths
+        .iter()
+        .map(|path| published.remove(path).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_diagnostics_for_many_open_files_are_published_in_source_order() {
+    let root = TempDir::new().unwrap();
+    let root_path = root.path().to_path_buf();
+    std::fs::write(root_path.join("pyrefly.toml"), "").unwrap();
+    std::fs::write(root_path.join("shared.py"), "VALUE = 1\n").unwrap();
+    let contents = [
+        "from shared import VALUE",
+        "",
+        "a: str = VALUE",
+        "b: int = ''",
+        "c: str = VALUE",
+        "d: int = ''",
+        "e: str = VALUE",
+    ]
+    .join("\n");
+    let paths: Vec<PathBuf> = (0..50)
+        .map(|i| root_path.join(format!("module_{i}.py")))
+        .collect();
+    for path in &paths {
+        std::fs::write(path, &contents).unwrap();
+    }
+
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root_path.clone());
+    interaction
+        .initialize(InitializeSettings {
+            workspace_folders: Some(vec![(
+                "test".to_owned(),
+                Url::from_file_path(&root_path).unwrap(),
+            )]),
+            ..Default::default()
+        })
+        .unwrap();
+    for path in &paths {
+        interaction
+            .client
+            .did_open_uri(&Url::from_file_path(path).unwrap(), "python", &contents);
+    }
+
+    // Each open file's errors are collected in parallel, but must still come out in source
+    // order, and the same way every time the files are republished together.
+    let assert_source_order = |published: &[Vec<lsp_types::Diagnostic>], lines: &[u32]| {
+        for (path, errors) in paths.iter().zip(published) {
+            assert_eq!(
+                errors
+                    .iter()
+                    .map(|d| d.range.start.line)
+                    .collect::<Vec<_>>(),
+                lines,
+                "errors for {} are out of order",
+                path.display()
+            );
+        }
+    };
+    let every_line = [2, 3, 4, 5, 6];
+    let first = expect_errors_for_files(&interaction, &paths, &every_line);
+    assert_source_order(&first, &every_line);
+
+    // Changing `shared.py` rechecks and republishes every open file in one pass.
+    std::fs::write(root_path.join("shared.py"), "VALUE = ''\n").unwrap();
+    interaction.client.file_modified("shared.py");
+    let int_lines = [3, 5];
+    let second = expect_errors_for_files(&interaction, &paths, &int_lines);
+    assert_source_order(&second, &int_lines);
+
+    std::fs::write(root_path.join("shared.py"), "VALUE = 1\n").unwrap();
+    interaction.client.file_modified("shared.py");
+    let third = expect_errors_for_files(&interaction, &paths, &every_line);
+    let ranges_and_messages = |published: &[Vec<lsp_types::Diagnostic>]| {
+        published
+            .iter()
+            .map(|errors| {
+                errors
+                    .iter()
+                    .map(|d| (d.range, d.message.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ranges_and_messages(&first), ranges_and_messages(&third));
+
+    interaction.shutdown().unwrap();
+}
+
 #[test]
 fn test_stream_diagnostics_no_flicker_after_undo_edit() {
     let root = get_test_files_root();
@@ -740,6 +922,73 @@ fn test_error_documentation_links() {
     interaction.shutdown().unwrap();
 }
 
+#[test]
+fn test_diagnostic_severity_override() {
+    let test_files_root = get_test_files_root();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(test_files_root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings {
+            configuration: Some(None),
+            ..Default::default()
+        })
+        .unwrap();
+
+    interaction.client.did_change_configuration();
+
+    interaction
+        .client
+        .expect_configuration_request(None)
+        .unwrap()
+        .send_configuration_response(json!([{"pyrefly": {
+            "displayTypeErrors": "force-on",
+            "diagnosticSeverity": {
+                "bad-assignment": "warning",
+                "bad-context-manager": "none"
+            }
+        }}]));
+
+    interaction.client.did_open("error_docs_test.py");
+
+    interaction
+        .client
+        .diagnostic("error_docs_test.py")
+        .expect_response(json!({
+            "items": [
+                {
+                    "code": "bad-assignment",
+                    "codeDescription": {
+                        "href": "https://pyrefly.org/en/docs/error-kinds/#bad-assignment"
+                    },
+                    "message": "`Literal['']` is not assignable to `int`",
+                    "range": {
+                        "end": {"character": 11, "line": 9},
+                        "start": {"character": 9, "line": 9}
+                    },
+                    "severity": 2,
+                    "source": "Pyrefly"
+                },
+                {
+                    "code": "missing-attribute",
+                    "codeDescription": {
+                        "href": "https://pyrefly.org/en/docs/error-kinds/#missing-attribute"
+                    },
+                    "message": "Object of class `object` has no attribute `nonexistent_method`",
+                    "range": {
+                        "end": {"character": 22, "line": 22},
+                        "start": {"character": 0, "line": 22}
+                    },
+                    "severity": 1,
+                    "source": "Pyrefly"
+                }
+            ],
+            "kind": "full"
+        }))
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
+
 #[test]
 fn test_unreachable_branch_diagnostic() {
     let test_files_root = get_test_files_root();
@@ -788,6 +1037,85 @@ fn test_unreachable_branch_diagnostic() {
     interaction.shutdown().unwrap();
 }
 
+#[test]
+fn test_python_platform_override_changes_diagnostics() {
+    let test_files_root = get_test_files_root();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(test_files_root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings {
+            configuration: Some(None),
+            ..Default::default()
+        })
+        .unwrap();
+
+    interaction.client.did_change_configuration();
+    interaction
+        .client
+        .expect_configuration_request(None)
+        .unwrap()
+        .send_configuration_response(json!([
+            {"pyrefly": {"displayTypeErrors": "force-on", "pythonPlatform": "linux"}}
+        ]));
+
+    interaction.client.did_open("platform_guarded.py");
+
+    interaction
+        .client
+        .diagnostic("platform_guarded.py")
+        .expect_response(json!({
+            "items": [
+                {
+                    "code": "unreachable-code",
+                    "message": "This code is unreachable for the current configuration",
+                    "range": {
+                        "end": {"character": 15, "line": 8},
+                        "start": {"character": 4, "line": 8}
+                    },
+                    "severity": 4,
+                    "source": "Pyrefly",
+                    "tags": [1]
+                }
+            ],
+            "kind": "full"
+        }))
+        .unwrap();
+
+    interaction.client.did_change_configuration();
+    interaction
+        .client
+        .expect_configuration_request(None)
+        .unwrap()
+        .send_configuration_response(json!([
+            {"pyrefly": {"displayTypeErrors": "force-on", "pythonPlatform": "win32"}}
+        ]));
+
+    interaction
+        .client
+        .diagnostic("platform_guarded.py")
+        .expect_response(json!({
+            "items": [
+                {
+                    "code": "bad-assignment",
+                    "codeDescription": {
+                        "href": "https://pyrefly.org/en/docs/error-kinds/#bad-assignment"
+                    },
+                    "message": "`Literal['']` is not assignable to `int`",
+                    "range": {
+                        "end": {"character": 15, "line": 8},
+                        "start": {"character": 13, "line": 8}
+                    },
+                    "severity": 1,
+                    "source": "Pyrefly"
+                }
+            ],
+            "kind": "full"
+        }))
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
+
 #[test]
 fn test_unused_parameter_diagnostic() {
     let test_files_root = get_test_files_root();
@@ -873,6 +1201,33 @@ fn test_unused_parameter_no_report() {
     interaction.shutdown().unwrap();
 }
 
+#[test]
+fn test_ignore_errors_file_publishes_no_diagnostics() {
+    let test_files_root = get_test_files_root();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(test_files_root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings {
+            configuration: Some(Some(json!([
+                {"pyrefly": {"displayTypeErrors": "force-on"}}
+            ]))),
+            ..Default::default()
+        })
+        .unwrap();
+
+    interaction.client.did_open("ignore_errors_file.py");
+    interaction
+        .client
+        .diagnostic("ignore_errors_file.py")
+        .expect_response(json!({
+            "items": [],
+            "kind": "full"
+        }))
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
+
 #[test]
 fn test_unused_import_diagnostic() {
     let test_files_root = get_test_files_root();
@@ -1959,6 +2314,50 @@ fn test_unused_ignore_diagnostic_default_severity() {
     interaction.shutdown().unwrap();
 }
 
+#[test]
+fn test_unused_ignore_diagnostic_opt_in() {
+    let test_files_root = get_test_files_root();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(test_files_root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings {
+            configuration: Some(Some(json!([{
+                "pyrefly": {
+                    "showUnusedIgnores": true,
+                }
+            }]))),
+            ..Default::default()
+        })
+        .unwrap();
+
+    interaction.client.did_open("unused_ignore_no_config.py");
+
+    // `unused-ignore` is still disabled by the config, but the client asked to see unused
+    // comments, so the comment is pointed out as information rather than reported as an error.
+    interaction
+        .client
+        .diagnostic("unused_ignore_no_config.py")
+        .expect_response(json!({
+            "items": [
+                {
+                    "code": "unused-ignore",
+                    "message": "Unused `# pyrefly: ignore` comment",
+                    "range": {
+                        "start": {"line": 5, "character": 0},
+                        "end": {"line": 5, "character": 17}
+                    },
+                    "severity": 3,
+                    "source": "Pyrefly",
+                    "tags": [1]
+                }
+            ],
+            "kind": "full"
+        }))
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
+
 #[test]
 fn test_unused_type_ignore_diagnostic() {
     let root = get_test_files_root();
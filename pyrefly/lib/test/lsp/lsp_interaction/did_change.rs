@@ -5,12 +5,18 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::time::Duration;
+
 use lsp_types::Url;
 use lsp_types::notification::DidChangeTextDocument;
+use pyrefly::commands::lsp::LspArgs;
+use pyrefly_util::telemetry::TelemetryEventKind;
 use serde_json::json;
 
 use crate::object_model::InitializeSettings;
 use crate::object_model::LspInteraction;
+use crate::object_model::LspInteractionArgs;
+use crate::object_model::TestTelemetry;
 use crate::util::get_test_files_root;
 
 #[test]
@@ -182,3 +188,47 @@ fn test_text_document_did_change_unicode() {
 
     interaction.shutdown().unwrap();
 }
+
+#[test]
+fn test_text_document_did_change_debounce() {
+    let root = get_test_files_root();
+    let telemetry = TestTelemetry::new();
+    let rx = telemetry.subscribe();
+    let mut interaction = LspInteraction::new_with_args(LspInteractionArgs {
+        args: LspArgs {
+            did_change_debounce_ms: 1000,
+            ..LspInteractionArgs::default().args
+        },
+        telemetry: Box::new(telemetry),
+        ..Default::default()
+    });
+    interaction.set_root(root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings::default())
+        .unwrap();
+
+    interaction.client.did_open("text_document.py");
+    interaction.client.did_change("text_document.py", "x = 1\n");
+    interaction
+        .client
+        .did_change("text_document.py", "x = 12\n");
+    interaction
+        .client
+        .did_change("text_document.py", "x = 123\n");
+
+    // Only the last edit in the burst should trigger validation.
+    let mut validated = Vec::new();
+    while validated.len() < 3 {
+        let event = rx
+            .recv_timeout(Duration::from_secs(30))
+            .expect("timed out waiting for didChange telemetry");
+        if let TelemetryEventKind::LspEvent(name) = &event.event.kind
+            && name == "DidChangeTextDocument"
+        {
+            validated.push(event.event.validate.is_some());
+        }
+    }
+    assert_eq!(validated, vec![false, false, true]);
+
+    interaction.shutdown().unwrap();
+}
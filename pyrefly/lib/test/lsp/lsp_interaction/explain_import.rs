@@ -0,0 +1,81 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use lsp_types::Url;
+use lsp_types::request::ExecuteCommand;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+
+use crate::object_model::InitializeSettings;
+use crate::object_model::LspInteraction;
+
+fn setup() -> (LspInteraction, TempDir, Url) {
+    let root = TempDir::new().unwrap();
+    let path = root.path().join("main.py");
+    std::fs::write(&path, "import missing_module\nimport present\n").unwrap();
+    std::fs::write(root.path().join("present.py"), "").unwrap();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings::default())
+        .unwrap();
+    let uri = Url::from_file_path(&path).unwrap();
+    (interaction, root, uri)
+}
+
+fn explain_import_args(module: &str, uri: &Url) -> Value {
+    json!({
+        "command": "pyrefly.explainImport",
+        "arguments": [module, uri.to_string()],
+    })
+}
+
+#[test]
+fn test_explain_import_lists_attempted_paths_for_unresolved_module() {
+    let (interaction, _root, uri) = setup();
+
+    interaction
+        .client
+        .send_request::<ExecuteCommand>(explain_import_args("missing_module", &uri))
+        .expect_response_with(|response| {
+            let Some(trace) = response.as_ref().and_then(Value::as_str) else {
+                return false;
+            };
+            trace.starts_with("Resolving `missing_module` from ")
+                && trace.lines().any(|line| {
+                    line.starts_with("  [missing]") && line.ends_with("missing_module.py")
+                })
+                && trace.lines().any(|line| {
+                    line.starts_with("  [missing]") && line.ends_with("missing_module.pyi")
+                })
+                && !trace.contains("[found]")
+                && trace.contains("`missing_module` could not be resolved.")
+        })
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
+
+#[test]
+fn test_explain_import_ends_at_found_module() {
+    let (interaction, _root, uri) = setup();
+
+    interaction
+        .client
+        .send_request::<ExecuteCommand>(explain_import_args("present", &uri))
+        .expect_response_with(|response| {
+            response
+                .as_ref()
+                .and_then(Value::as_str)
+                .and_then(|trace| trace.lines().last())
+                .is_some_and(|line| line.starts_with("  [found]") && line.ends_with("present.py"))
+        })
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
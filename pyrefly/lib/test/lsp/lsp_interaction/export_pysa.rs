@@ -0,0 +1,133 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use lsp_types::request::ExecuteCommand;
+use pyrefly::commands::lsp::IndexingMode;
+use pyrefly::commands::lsp::LspArgs;
+use pyrefly::lsp::non_wasm::protocol::Message;
+use serde_json::json;
+use tempfile::TempDir;
+
+use crate::object_model::InitializeSettings;
+use crate::object_model::LspInteraction;
+use crate::object_model::LspInteractionArgs;
+use crate::util::get_test_files_root;
+
+#[test]
+fn test_export_pysa_command() {
+    let root = get_test_files_root();
+    let root_path = root.path().join("tests_requiring_config");
+    let output = TempDir::new().unwrap();
+
+    let mut interaction = LspInteraction::new_with_args(LspInteractionArgs {
+        args: LspArgs {
+            indexing_mode: IndexingMode::LazyBlocking,
+            ..LspInteractionArgs::default().args
+        },
+        ..Default::default()
+    });
+    interaction.set_root(root_path);
+    interaction
+        .initialize(InitializeSettings::default())
+        .unwrap();
+    interaction.client.did_open("foo.py");
+
+    interaction
+        .client
+        .send_request::<ExecuteCommand>(json!({
+            "command": "pyrefly.exportPysa",
+            "arguments": [output.path().to_str().unwrap()],
+        }))
+        .expect_response(json!(null))
+        .unwrap();
+    interaction
+        .client
+        .expect_message("window/showMessage for pyrefly.exportPysa", |msg| {
+            if let Message::Notification(x) = msg
+                && x.method == "window/showMessage"
+            {
+                // MessageType::INFO
+                assert_eq!(x.params["type"], 3, "{}", x.params["message"]);
+                Some(Ok(()))
+            } else {
+                None
+            }
+        })
+        .unwrap();
+    assert!(output.path().join("pyrefly.pysa.capnp.bin").exists());
+
+    interaction.shutdown().unwrap();
+}
+
+/// Sends `pyrefly.exportPysa` and expects an error response whose message contains `expected`.
+fn expect_export_pysa_error(interaction: &LspInteraction, output: &TempDir, expected: &str) {
+    interaction.client.send_request::<ExecuteCommand>(json!({
+        "command": "pyrefly.exportPysa",
+        "arguments": [output.path().to_str().unwrap()],
+    }));
+    interaction
+        .client
+        .expect_message("error response for pyrefly.exportPysa", |msg| {
+            if let Message::Response(x) = msg
+                && let Some(error) = &x.error
+            {
+                assert!(error.message.contains(expected), "{}", error.message);
+                Some(Ok(()))
+            } else {
+                None
+            }
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_export_pysa_command_requires_indexing() {
+    let root = get_test_files_root();
+    let output = TempDir::new().unwrap();
+
+    let mut interaction = LspInteraction::new_with_args(LspInteractionArgs {
+        args: LspArgs {
+            indexing_mode: IndexingMode::None,
+            ..LspInteractionArgs::default().args
+        },
+        ..Default::default()
+    });
+    interaction.set_root(root.path().join("tests_requiring_config"));
+    interaction
+        .initialize(InitializeSettings::default())
+        .unwrap();
+    interaction.client.did_open("foo.py");
+
+    expect_export_pysa_error(&interaction, &output, "requires project indexing");
+    assert!(!output.path().join("pyrefly.pysa.capnp.bin").exists());
+
+    interaction.shutdown().unwrap();
+}
+
+#[test]
+fn test_export_pysa_command_without_project_files() {
+    let root = get_test_files_root();
+    let output = TempDir::new().unwrap();
+
+    let mut interaction = LspInteraction::new_with_args(LspInteractionArgs {
+        args: LspArgs {
+            indexing_mode: IndexingMode::LazyBlocking,
+            ..LspInteractionArgs::default().args
+        },
+        ..Default::default()
+    });
+    interaction.set_root(root.path().join("tests_requiring_config"));
+    interaction
+        .initialize(InitializeSettings::default())
+        .unwrap();
+
+    // No file was opened, so no project has been indexed yet.
+    expect_export_pysa_error(&interaction, &output, "no indexed project files");
+    assert!(!output.path().join("pyrefly.pysa.capnp.bin").exists());
+
+    interaction.shutdown().unwrap();
+}
@@ -6,6 +6,7 @@
  */
 
 use std::collections::HashSet;
+use std::time::Duration;
 
 use lsp_types::RegistrationParams;
 use lsp_types::Url;
@@ -14,6 +15,7 @@ use lsp_types::request::Request as _;
 use pyrefly::commands::lsp::IndexingMode;
 use pyrefly::commands::lsp::LspArgs;
 use pyrefly::lsp::non_wasm::protocol::Message;
+use pyrefly_util::telemetry::TelemetryEventKind;
 use serde::Deserialize;
 use serde_json::json;
 use tempfile::TempDir;
@@ -22,6 +24,7 @@ use crate::object_model::InitializeSettings;
 use crate::object_model::LspInteraction;
 use crate::object_model::LspInteractionArgs;
 use crate::object_model::LspMessageError;
+use crate::object_model::TestTelemetry;
 use crate::util::get_test_files_root;
 
 pub fn expect_watched_files(
@@ -180,3 +183,118 @@ fn test_consecutive_file_watcher_events() {
 
     interaction.shutdown().unwrap();
 }
+
+/// Editing the config re-indexes project files that are not open, so a closed file whose
+/// import only resolves under the new search path has its error cleared without being opened.
+#[test]
+fn test_config_search_path_change_resolves_import() {
+    let root = TempDir::new().unwrap();
+    let root_path = root.path().to_path_buf();
+    std::fs::write(root_path.join("pyrefly.toml"), "").unwrap();
+    std::fs::write(root_path.join("main.py"), "x = 1\n").unwrap();
+    std::fs::write(root_path.join("uses_foo.py"), "import foo\n").unwrap();
+    std::fs::create_dir(root_path.join("lib")).unwrap();
+    std::fs::write(root_path.join("lib/foo.py"), "").unwrap();
+
+    let mut interaction = LspInteraction::new_with_args(LspInteractionArgs {
+        args: LspArgs {
+            indexing_mode: IndexingMode::LazyBlocking,
+            ..LspInteractionArgs::default().args
+        },
+        ..Default::default()
+    });
+    interaction.set_root(root_path.clone());
+    interaction
+        .initialize(InitializeSettings {
+            workspace_folders: Some(vec![(
+                "test".to_owned(),
+                Url::from_file_path(root_path.clone()).unwrap(),
+            )]),
+            configuration: Some(Some(
+                json!([{"pyrefly": {"diagnosticMode": "workspace", "displayTypeErrors": "force-on"}}]),
+            )),
+            ..Default::default()
+        })
+        .unwrap();
+
+    // Opening main.py indexes the project, which publishes diagnostics for uses_foo.py.
+    interaction.client.did_open("main.py");
+    let uses_foo_path = root_path.join("uses_foo.py");
+    interaction
+        .client
+        .expect_publish_diagnostics_eventual_error_count(uses_foo_path.clone(), 1)
+        .expect("`foo` should not resolve before it is on the search path");
+
+    // uses_foo.py is never opened, so only re-indexing after the config change can clear it.
+    std::fs::write(root_path.join("pyrefly.toml"), "search-path = [\"lib\"]\n").unwrap();
+    interaction.client.file_modified("pyrefly.toml");
+
+    interaction
+        .client
+        .expect_publish_diagnostics_eventual_error_count(uses_foo_path, 0)
+        .expect("`foo` should resolve after adding `lib` to the search path");
+
+    interaction.shutdown().unwrap();
+}
+
+/// Editing the config while the project is still being indexed cancels that pass, rather than
+/// letting it run to completion before the pass for the new config starts.
+#[test]
+fn test_config_change_cancels_project_indexing() {
+    let root = TempDir::new().unwrap();
+    let root_path = root.path().to_path_buf();
+    std::fs::write(root_path.join("pyrefly.toml"), "").unwrap();
+    std::fs::write(root_path.join("main.py"), "x = 1\n").unwrap();
+    // Enough files that indexing is still going when the config change arrives.
+    for i in 0..200 {
+        std::fs::write(
+            root_path.join(format!("module_{i}.py")),
+            format!("def f_{i}() -> int:\n    return {i}\n"),
+        )
+        .unwrap();
+    }
+
+    let telemetry = TestTelemetry::new();
+    let events = telemetry.subscribe();
+    let mut interaction = LspInteraction::new_with_args(LspInteractionArgs {
+        args: LspArgs {
+            indexing_mode: IndexingMode::LazyNonBlockingBackground,
+            workspace_indexing_limit: 0,
+            ..LspInteractionArgs::default().args
+        },
+        telemetry: Box::new(telemetry),
+        ..Default::default()
+    });
+    interaction.set_root(root_path.clone());
+    interaction
+        .initialize(InitializeSettings {
+            workspace_folders: Some(vec![(
+                "test".to_owned(),
+                Url::from_file_path(root_path.clone()).unwrap(),
+            )]),
+            ..Default::default()
+        })
+        .unwrap();
+
+    // Opening main.py queues a pass over the project, and the config change right behind it
+    // cancels that pass and queues a new one.
+    interaction.client.did_open("main.py");
+    std::fs::write(root_path.join("pyrefly.toml"), "search-path = [\"lib\"]\n").unwrap();
+    interaction.client.file_modified("pyrefly.toml");
+
+    let cancelled = (0..2)
+        .map(|_| {
+            loop {
+                let event = events
+                    .recv_timeout(Duration::from_secs(30))
+                    .expect("timed out waiting for a project indexing pass to finish");
+                if matches!(event.event.kind, TelemetryEventKind::PopulateProjectFiles) {
+                    break event.event.canceled;
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(cancelled, vec![true, false]);
+
+    interaction.shutdown().unwrap();
+}
@@ -280,3 +280,22 @@ fn test_hover_suppressed_error_deprecated_alias() {
 
     interaction.shutdown().unwrap();
 }
+
+#[test]
+fn hover_links_to_both_sibling_stub_and_source() {
+    let root = get_test_files_root();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().join("stub_and_source"));
+    interaction
+        .initialize(InitializeSettings::default())
+        .unwrap();
+    interaction.client.did_open("main.py");
+    interaction
+        .client
+        .hover("main.py", 7, 0)
+        .expect_hover_response_with_markup(|value| {
+            value.is_some_and(|text| text.contains("foo.py#") && text.contains("foo.pyi#"))
+        })
+        .unwrap();
+    interaction.shutdown().unwrap();
+}
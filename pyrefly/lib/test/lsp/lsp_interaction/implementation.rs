@@ -8,6 +8,7 @@
 use lsp_types::Url;
 use pyrefly::commands::lsp::IndexingMode;
 use pyrefly::commands::lsp::LspArgs;
+use serde_json::json;
 
 use crate::object_model::InitializeSettings;
 use crate::object_model::LspInteraction;
@@ -93,3 +94,78 @@ fn implementation_on_call_test() {
 
     interaction.shutdown().unwrap();
 }
+
+#[test]
+fn implementation_falls_back_to_definition_test() {
+    let root = get_test_files_root();
+    let mut interaction = LspInteraction::new_with_args(LspInteractionArgs {
+        args: LspArgs {
+            indexing_mode: IndexingMode::LazyBlocking,
+            ..LspInteractionArgs::default().args
+        },
+        ..Default::default()
+    });
+    let test_root = root.path().join("references_cross_file_method_inheritance");
+    interaction.set_root(test_root.clone());
+    interaction
+        .initialize(InitializeSettings {
+            workspace_folders: Some(vec![(
+                "test".to_owned(),
+                Url::from_file_path(test_root.clone()).unwrap(),
+            )]),
+            ..Default::default()
+        })
+        .unwrap();
+
+    interaction.client.did_open("usage.py");
+
+    // Nothing overrides the variable `b`, so the request returns its definition.
+    interaction
+        .client
+        .implementation("usage.py", 8, 0)
+        .expect_response(json!({
+            "uri": Url::from_file_path(test_root.join("usage.py")).unwrap().to_string(),
+            "range": {
+                "start": {"line": 7, "character": 0},
+                "end": {"line": 7, "character": 1}
+            },
+        }))
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
+
+#[test]
+fn implementation_without_indexing_test() {
+    let root = get_test_files_root();
+    let mut interaction = LspInteraction::new_with_args(LspInteractionArgs {
+        args: LspArgs {
+            indexing_mode: IndexingMode::None,
+            ..LspInteractionArgs::default().args
+        },
+        ..Default::default()
+    });
+    let test_root = root.path().join("references_cross_file_method_inheritance");
+    interaction.set_root(test_root.clone());
+    interaction
+        .initialize(InitializeSettings {
+            workspace_folders: Some(vec![(
+                "test".to_owned(),
+                Url::from_file_path(test_root.clone()).unwrap(),
+            )]),
+            ..Default::default()
+        })
+        .unwrap();
+
+    interaction.client.did_open("base.py");
+
+    // Implementations need the project-wide class graph, so without indexing the request is
+    // answered with no result even though `Base.method` has overrides.
+    interaction
+        .client
+        .implementation("base.py", 7, 8)
+        .expect_response(json!(null))
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
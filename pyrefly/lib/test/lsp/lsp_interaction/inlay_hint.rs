@@ -5,6 +5,7 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use pyrefly::lsp::non_wasm::protocol::Message;
 use serde_json::json;
 
 use crate::object_model::InitializeSettings;
@@ -896,3 +897,72 @@ fn test_inlay_hint_unpack_has_location() {
 
     interaction.shutdown().unwrap();
 }
+
+#[test]
+fn test_inlay_hint_settings_change_refreshes_hints() {
+    let root = get_test_files_root();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings {
+            configuration: Some(Some(
+                json!([{"pyrefly": {"displayTypeErrors": "force-on"}}]),
+            )),
+            capabilities: Some(json!({
+                "workspace": {
+                    "inlayHint": {
+                        "refreshSupport": true
+                    }
+                }
+            })),
+            ..Default::default()
+        })
+        .unwrap();
+
+    interaction.client.did_open("inlay_hint_test.py");
+
+    interaction
+        .client
+        .inlay_hint("inlay_hint_test.py", 0, 0, 100, 0)
+        .expect_response_with(|result| result.is_some_and(|hints| hints.len() == 3))
+        .unwrap();
+
+    interaction.client.did_change_configuration();
+    interaction
+        .client
+        .expect_configuration_request(None)
+        .unwrap()
+        .send_configuration_response(json!([{
+            "pyrefly": {"displayTypeErrors": "force-on"},
+            "analysis": {
+                "inlayHints": {
+                    "variableTypes": false
+                },
+            }
+        }]));
+    interaction
+        .client
+        .expect_message("workspace/inlayHint/refresh", |msg| {
+            if let Message::Request(x) = msg
+                && x.method == "workspace/inlayHint/refresh"
+            {
+                Some(Ok(()))
+            } else {
+                None
+            }
+        })
+        .unwrap();
+
+    // The variable type hint is gone, but the return type hints remain.
+    interaction
+        .client
+        .inlay_hint("inlay_hint_test.py", 0, 0, 100, 0)
+        .expect_response_with(|result| {
+            result.is_some_and(|hints| {
+                hints.len() == 2 && hints[0].position.line == 6 && hints[1].position.line == 14
+            })
+        })
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
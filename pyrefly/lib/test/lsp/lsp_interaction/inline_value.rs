@@ -0,0 +1,70 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use lsp_types::Url;
+use lsp_types::request::InlineValueRequest;
+use serde_json::json;
+
+use crate::object_model::InitializeSettings;
+use crate::object_model::LspInteraction;
+use crate::util::get_test_files_root;
+
+#[test]
+fn test_inline_values_are_inferred_types() {
+    let test_files_root = get_test_files_root();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(test_files_root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings::default())
+        .unwrap();
+
+    interaction.client.did_open("inline_value_test.py");
+
+    let uri = Url::from_file_path(test_files_root.path().join("inline_value_test.py")).unwrap();
+    interaction
+        .client
+        .send_request::<InlineValueRequest>(json!({
+            "textDocument": { "uri": uri },
+            "range": {
+                "start": { "line": 5, "character": 0 },
+                "end": { "line": 9, "character": 0 }
+            },
+            "context": {
+                "frameId": 1,
+                "stoppedLocation": {
+                    "start": { "line": 8, "character": 4 },
+                    "end": { "line": 8, "character": 15 }
+                }
+            }
+        }))
+        .expect_response(json!([
+            {
+                "range": {
+                    "start": { "line": 5, "character": 10 },
+                    "end": { "line": 5, "character": 16 }
+                },
+                "text": "factor: int"
+            },
+            {
+                "range": {
+                    "start": { "line": 6, "character": 4 },
+                    "end": { "line": 6, "character": 9 }
+                },
+                "text": "total: int"
+            },
+            {
+                "range": {
+                    "start": { "line": 7, "character": 4 },
+                    "end": { "line": 7, "character": 8 }
+                },
+                "text": "text: str"
+            }
+        ]))
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
@@ -20,11 +20,14 @@ mod diagnostic;
 mod did_change;
 mod document_symbols;
 mod empty_response_reason;
+mod explain_import;
+mod export_pysa;
 mod file_watcher;
 mod folding_range;
 mod hover;
 mod implementation;
 mod inlay_hint;
+mod inline_value;
 mod io;
 mod move_symbol_new_file;
 mod no_config_warnings;
@@ -46,12 +49,16 @@ mod notebook_tokens;
 mod notebook_type_definition;
 mod notebook_type_error_display_status;
 mod object_model;
+mod on_type_formatting;
 mod open_file_benchmark;
+mod open_files_diagnostics_benchmark;
+mod print_internal_type;
 mod progress;
 mod provide_type;
 mod pytorch_benchmark;
 mod references;
 mod rename;
+mod request_timeout;
 mod safe_delete_file;
 mod semantic_tokens;
 mod type_definition;
@@ -71,6 +71,7 @@ use pretty_assertions::assert_eq;
 use pyrefly::commands::lsp::IndexingMode;
 use pyrefly::commands::lsp::LspArgs;
 use pyrefly::commands::lsp::run_lsp;
+use pyrefly::lsp::non_wasm::external_provider::ExternalProvider;
 use pyrefly::lsp::non_wasm::external_provider::NoExternalProvider;
 use pyrefly::lsp::non_wasm::module_helpers::ThriftRemapper;
 use pyrefly::lsp::non_wasm::protocol::JsonRpcMessage;
@@ -1368,6 +1369,7 @@ pub struct LspInteractionArgs {
     pub telemetry: Box<dyn Telemetry>,
     pub thread_count: ThreadCount,
     pub thrift_remapper: Option<ThriftRemapper>,
+    pub external_provider: Arc<dyn ExternalProvider>,
 }
 
 impl Default for LspInteractionArgs {
@@ -1376,11 +1378,15 @@ impl Default for LspInteractionArgs {
             args: LspArgs {
                 indexing_mode: IndexingMode::None,
                 workspace_indexing_limit: 50,
+                project_indexing_batch_size: None,
                 build_system_blocking: false,
+                did_change_debounce_ms: 0,
+                request_timeout_ms: 0,
             },
             telemetry: Box::new(NoTelemetry),
             thread_count: TEST_THREAD_COUNT,
             thrift_remapper: None,
+            external_provider: Arc::new(NoExternalProvider),
         }
     }
 }
@@ -1401,6 +1407,7 @@ impl LspInteraction {
             telemetry,
             thread_count,
             thrift_remapper,
+            external_provider,
         } = args;
 
         let ((conn_client, _client_reader), (conn_server, server_reader)) = Connection::memory();
@@ -1418,7 +1425,7 @@ impl LspInteraction {
                 None,
                 thrift_remapper,
                 &*telemetry,
-                Arc::new(NoExternalProvider),
+                external_provider,
                 None,
                 thread_count,
             );
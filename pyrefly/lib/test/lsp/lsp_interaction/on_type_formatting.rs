@@ -0,0 +1,47 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use lsp_types::Url;
+use lsp_types::request::OnTypeFormatting;
+use serde_json::json;
+use tempfile::TempDir;
+
+use super::object_model::InitializeSettings;
+use super::object_model::LspInteraction;
+
+#[test]
+fn test_on_type_formatting_indents_after_def() {
+    let root = TempDir::new().unwrap();
+    let file = root.path().join("test.py");
+    std::fs::write(&file, "def foo():\n\n").unwrap();
+
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings::default())
+        .unwrap();
+    interaction.client.did_open("test.py");
+
+    interaction
+        .client
+        .send_request::<OnTypeFormatting>(json!({
+            "textDocument": { "uri": Url::from_file_path(&file).unwrap().to_string() },
+            "position": { "line": 1, "character": 0 },
+            "ch": "\n",
+            "options": { "tabSize": 4, "insertSpaces": true },
+        }))
+        .expect_response(json!([{
+            "range": {
+                "start": { "line": 1, "character": 0 },
+                "end": { "line": 1, "character": 0 },
+            },
+            "newText": "    ",
+        }]))
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
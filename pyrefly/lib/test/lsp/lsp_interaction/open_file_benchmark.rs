@@ -94,9 +94,13 @@ fn test_open_file_time_to_first_diagnostics() {
         // this isolates the deterministic cost of opening one file.
         indexing_mode: IndexingMode::None,
         workspace_indexing_limit: 0,
+        project_indexing_batch_size: None,
         // Block on the build system's source DB so imports resolve, matching the
         // realistic IDE experience rather than fallback heuristics.
         build_system_blocking: true,
+        // Recheck as soon as the file is opened rather than waiting for more edits.
+        did_change_debounce_ms: 0,
+        request_timeout_ms: 0,
     };
     // Use every available core. `ThreadCount::AllThreads` caps at 64, so to truly
     // use the maximum on a high-core box we pass the raw core count explicitly.
@@ -0,0 +1,158 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Benchmark: republishing diagnostics for many open files.
+//!
+//! A change to a module that every open file imports rechecks all of them and
+//! publishes their diagnostics together, which is where the per-file work of
+//! collecting diagnostics adds up. The project is generated into a temporary
+//! directory: a `shared.py` and `PYREFLY_BENCH_OPEN_FILES` (default 50) modules
+//! that import it, each with a handful of errors that depend on `shared.py`.
+//! The timer covers the `shared.py` change until every open file's
+//! `publishDiagnostics` has arrived.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+
+use lsp_types::PublishDiagnosticsParams;
+use lsp_types::Url;
+use lsp_types::notification::Notification as _;
+use lsp_types::notification::PublishDiagnostics;
+use pyrefly::lsp::non_wasm::protocol::Message;
+use tempfile::TempDir;
+
+use crate::object_model::InitializeSettings;
+use crate::object_model::LspInteraction;
+
+const ITERATIONS: usize = 10;
+
+/// Wait until every file in `paths` has been published with exactly `error_count` errors.
+fn expect_error_count_for_files(
+    interaction: &LspInteraction,
+    paths: &[PathBuf],
+    error_count: usize,
+) {
+    let mut remaining: HashSet<&PathBuf> = paths.iter().collect();
+    interaction
+        .client
+        .expect_message(
+            &format!("{error_count} errors for {} files", paths.len()),
+            |msg| {
+                if let Message::Notification(x) = msg
+                    && x.method == PublishDiagnostics::METHOD
+                {
+                    let params =
+                        serde_json::from_value::<PublishDiagnosticsParams>(x.params).unwrap();
+                    let errors = params
+                        .diagnostics
+                        .iter()
+                        .filter(|d| d.severity == Some(lsp_types::DiagnosticSeverity::ERROR))
+                        .count();
+                    if errors == error_count {
+                        remaining.remove(&params.uri.to_file_path().unwrap());
+                    }
+                }
+                remaining.is_empty().then_some(Ok(()))
+            },
+        )
+        .unwrap();
+}
+
+/// Open `PYREFLY_BENCH_OPEN_FILES` (default 50) files that all import `shared.py`, then
+/// repeatedly change `shared.py` and measure how long it takes for every open file's
+/// diagnostics to be republished.
+///
+/// This is `#[ignore]`d, so run it explicitly (build in release for realistic
+/// numbers):
+/// ```text
+/// cargo test --release test_open_files_republish_diagnostics -- --ignored --nocapture
+/// ```
+#[test]
+#[ignore] // Manual benchmark.
+fn test_open_files_republish_diagnostics() {
+    let file_count = std::env::var("PYREFLY_BENCH_OPEN_FILES")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(50);
+
+    let root = TempDir::new().unwrap();
+    let root_path = root.path().to_path_buf();
+    std::fs::write(root_path.join("pyrefly.toml"), "").unwrap();
+    std::fs::write(root_path.join("shared.py"), "VALUE = 1\n").unwrap();
+    // With `VALUE = 1` every assignment is an error; with `VALUE = ''` only the `int` ones are.
+    let contents = [
+        "from shared import VALUE",
+        "",
+        "a: str = VALUE",
+        "b: int = ''",
+        "c: str = VALUE",
+        "d: int = ''",
+        "e: str = VALUE",
+    ]
+    .join("\n");
+    let paths: Vec<PathBuf> = (0..file_count)
+        .map(|i| root_path.join(format!("module_{i}.py")))
+        .collect();
+    for path in &paths {
+        std::fs::write(path, &contents).unwrap();
+    }
+
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root_path.clone());
+    interaction
+        .initialize(InitializeSettings {
+            workspace_folders: Some(vec![(
+                "workspace".to_owned(),
+                Url::from_file_path(&root_path).unwrap(),
+            )]),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let start = Instant::now();
+    for path in &paths {
+        interaction
+            .client
+            .did_open_uri(&Url::from_file_path(path).unwrap(), "python", &contents);
+    }
+    expect_error_count_for_files(&interaction, &paths, 5);
+    let open_elapsed = start.elapsed();
+
+    let mut republish_times: Vec<Duration> = Vec::with_capacity(ITERATIONS);
+    for i in 0..ITERATIONS {
+        let (shared, error_count) = if i % 2 == 0 {
+            ("VALUE = ''\n", 2)
+        } else {
+            ("VALUE = 1\n", 5)
+        };
+        std::fs::write(root_path.join("shared.py"), shared).unwrap();
+        let start = Instant::now();
+        interaction.client.file_modified("shared.py");
+        expect_error_count_for_files(&interaction, &paths, error_count);
+        republish_times.push(start.elapsed());
+    }
+    republish_times.sort();
+
+    eprintln!("\n========================================");
+    eprintln!("  open-files diagnostics benchmark");
+    eprintln!("  Open files:                   {}", file_count);
+    eprintln!("  Open all and diagnose:        {:?}", open_elapsed);
+    eprintln!("  Republish after change (min): {:?}", republish_times[0]);
+    eprintln!(
+        "  Republish after change (med): {:?}",
+        republish_times[ITERATIONS / 2]
+    );
+    eprintln!(
+        "  Republish after change (max): {:?}",
+        republish_times[ITERATIONS - 1]
+    );
+    eprintln!("========================================\n");
+
+    interaction.shutdown().unwrap();
+}
@@ -0,0 +1,91 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use lsp_types::Url;
+use lsp_types::request::ExecuteCommand;
+use pyrefly::lsp::non_wasm::protocol::Message;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+
+use crate::object_model::InitializeSettings;
+use crate::object_model::LspInteraction;
+
+fn print_internal_type_args(uri: &Url) -> Value {
+    json!({
+        "command": "pyrefly.printInternalType",
+        "arguments": [{
+            "textDocument": {"uri": uri.to_string()},
+            "position": {"line": 0, "character": 0},
+        }],
+    })
+}
+
+fn setup(configuration: Option<Value>) -> (LspInteraction, TempDir, Url) {
+    let root = TempDir::new().unwrap();
+    let path = root.path().join("main.py");
+    std::fs::write(&path, "x = [1]\n").unwrap();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings {
+            configuration: configuration.map(Some),
+            ..Default::default()
+        })
+        .unwrap();
+    interaction.client.did_open("main.py");
+    let uri = Url::from_file_path(&path).unwrap();
+    (interaction, root, uri)
+}
+
+#[test]
+fn test_print_internal_type() {
+    let (interaction, _root, uri) = setup(Some(json!([{
+        "pyrefly": {"enableDebugCommands": true}
+    }])));
+
+    interaction
+        .client
+        .send_request::<ExecuteCommand>(print_internal_type_args(&uri))
+        .expect_response_with(|response| {
+            response
+                .as_ref()
+                .and_then(Value::as_str)
+                .is_some_and(|debug| debug.contains("ClassType") && debug.contains("list"))
+        })
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
+
+#[test]
+fn test_print_internal_type_requires_debug_flag() {
+    let (interaction, _root, uri) = setup(None);
+
+    interaction
+        .client
+        .send_request::<ExecuteCommand>(print_internal_type_args(&uri));
+    interaction
+        .client
+        .expect_message("error response for pyrefly.printInternalType", |msg| {
+            if let Message::Response(x) = msg
+                && let Some(error) = x.error
+            {
+                assert!(
+                    error.message.contains("enableDebugCommands"),
+                    "{}",
+                    error.message
+                );
+                Some(Ok(()))
+            } else {
+                None
+            }
+        })
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
@@ -43,7 +43,10 @@ fn test_pytorch_error_propagation_latency() {
     let args = LspArgs {
         indexing_mode: IndexingMode::LazyBlocking,
         workspace_indexing_limit: 50,
+        project_indexing_batch_size: None,
         build_system_blocking: false,
+        did_change_debounce_ms: 0,
+        request_timeout_ms: 0,
     };
     // Use all available cores for realistic benchmarking
     let mut interaction = LspInteraction::new_with_args(LspInteractionArgs {
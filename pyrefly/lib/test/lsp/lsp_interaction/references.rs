@@ -5,9 +5,17 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::sync::Arc;
+use std::time::Duration;
+
+use lsp_types::Position;
+use lsp_types::Range;
+use lsp_types::SymbolInformation;
 use lsp_types::Url;
 use pyrefly::commands::lsp::IndexingMode;
 use pyrefly::commands::lsp::LspArgs;
+use pyrefly::lsp::non_wasm::external_provider::ExternalProvider;
+use pyrefly_util::telemetry::SubTaskTelemetry;
 use serde_json::json;
 
 use crate::object_model::InitializeSettings;
@@ -1068,3 +1076,85 @@ fn test_references_cross_file_with_module_docstring() {
 
     interaction.shutdown().unwrap();
 }
+
+/// Reports fixed references, in UTF-16 like a real external index.
+struct FixedExternalProvider(Vec<(Url, Vec<Range>)>);
+
+impl ExternalProvider for FixedExternalProvider {
+    fn find_references(
+        &self,
+        _qualified_name: &str,
+        _source_uri: &Url,
+        _timeout: Duration,
+        _telemetry: Option<SubTaskTelemetry>,
+    ) -> anyhow::Result<Vec<(Url, Vec<Range>)>> {
+        Ok(self.0.clone())
+    }
+
+    fn workspace_symbols(
+        &self,
+        _query: &str,
+        _workspace_uri: &Url,
+        _timeout: Duration,
+        _telemetry: Option<SubTaskTelemetry>,
+    ) -> anyhow::Result<Vec<SymbolInformation>> {
+        Ok(Vec::new())
+    }
+}
+
+#[test]
+fn test_external_references_with_utf8_position_encoding() {
+    let root = get_test_files_root();
+    let root_path = root.path().join("external_references_utf8");
+    let main = Url::from_file_path(root_path.join("main.py")).unwrap();
+    let external = Url::from_file_path(root_path.join("external.py")).unwrap();
+    let utf16_range = |line, character| {
+        Range::new(
+            Position::new(line, character),
+            Position::new(line, character + 1),
+        )
+    };
+    // The emoji before `x` is two UTF-16 code units but four UTF-8 bytes. The first range is
+    // also found locally, so it must not be reported twice.
+    let provider = FixedExternalProvider(vec![
+        (main.clone(), vec![utf16_range(5, 10)]),
+        (external.clone(), vec![utf16_range(5, 21)]),
+    ]);
+    let mut interaction = LspInteraction::new_with_args(LspInteractionArgs {
+        external_provider: Arc::new(provider),
+        ..Default::default()
+    });
+    interaction.set_root(root_path);
+    interaction
+        .initialize(InitializeSettings {
+            capabilities: Some(json!({
+                "general": {
+                    "positionEncodings": ["utf-8"],
+                },
+            })),
+            ..Default::default()
+        })
+        .unwrap();
+    interaction.client.did_open("main.py");
+
+    interaction
+        .client
+        .references("main.py", 6, 6, true)
+        .expect_response(json!([
+            {
+                "range": {"start":{"line":5,"character":12},"end":{"line":5,"character":13}},
+                "uri": main.to_string()
+            },
+            {
+                "range": {"start":{"line":6,"character":6},"end":{"line":6,"character":7}},
+                "uri": main.to_string()
+            },
+            {
+                "range": {"start":{"line":5,"character":23},"end":{"line":5,"character":24}},
+                "uri": external.to_string()
+            },
+        ]))
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
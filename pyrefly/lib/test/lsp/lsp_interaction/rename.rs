@@ -8,10 +8,13 @@
 use lsp_types::Url;
 use lsp_types::request::PrepareRenameRequest;
 use lsp_types::request::Rename;
+use pyrefly::commands::lsp::IndexingMode;
+use pyrefly::commands::lsp::LspArgs;
 use serde_json::json;
 
 use crate::object_model::InitializeSettings;
 use crate::object_model::LspInteraction;
+use crate::object_model::LspInteractionArgs;
 use crate::util::get_test_files_root;
 
 #[test]
@@ -47,6 +50,41 @@ fn test_prepare_rename() {
     interaction.shutdown().unwrap();
 }
 
+#[test]
+fn test_rename_to_keyword_is_rejected() {
+    let root = get_test_files_root();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().join("basic"));
+    interaction
+        .initialize(InitializeSettings::default())
+        .unwrap();
+
+    interaction.client.did_open("foo.py");
+
+    let path = root.path().join("basic/foo.py");
+
+    interaction
+        .client
+        .send_request::<Rename>(json!({
+            "textDocument": {
+                "uri": Url::from_file_path(&path).unwrap().to_string()
+            },
+            "position": {
+                "line": 6,
+                "character": 16
+            },
+            "newName": "class"
+        }))
+        .expect_response_error(json!({
+            "code": -32602,
+            "message": "`class` is not a valid Python identifier",
+            "data": null,
+        }))
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
+
 #[test]
 fn test_rename_third_party_symbols_in_venv_is_not_allowed() {
     let root = get_test_files_root();
@@ -276,3 +314,147 @@ fn test_rename() {
 
     interaction.shutdown().unwrap();
 }
+
+#[test]
+fn test_rename_module_renames_file_and_updates_importers() {
+    let root = get_test_files_root();
+    let root_path = root.path().join("rename_module");
+    let scope_uri = Url::from_file_path(root_path.clone()).unwrap();
+
+    let mut interaction = LspInteraction::new_with_args(LspInteractionArgs {
+        args: LspArgs {
+            indexing_mode: IndexingMode::LazyBlocking,
+            ..LspInteractionArgs::default().args
+        },
+        ..Default::default()
+    });
+    interaction.set_root(root_path.clone());
+    interaction
+        .initialize(InitializeSettings {
+            workspace_folders: Some(vec![("test".to_owned(), scope_uri)]),
+            capabilities: Some(json!({
+                "workspace": {
+                    "workspaceEdit": {
+                        "documentChanges": true,
+                        "resourceOperations": ["rename"]
+                    }
+                }
+            })),
+            ..Default::default()
+        })
+        .unwrap();
+
+    interaction.client.did_open("first.py");
+
+    let uri = |name: &str| {
+        Url::from_file_path(root_path.join(name))
+            .unwrap()
+            .to_string()
+    };
+
+    // Rename `helper` from the `from helper import VALUE` statement in first.py.
+    interaction
+        .client
+        .send_request::<Rename>(json!({
+            "textDocument": {
+                "uri": uri("first.py")
+            },
+            "position": {
+                "line": 5,
+                "character": 7
+            },
+            "newName": "utils"
+        }))
+        .expect_response(json!({
+            "documentChanges": [
+                {
+                    "textDocument": {"uri": uri("first.py"), "version": null},
+                    "edits": [{
+                        "newText": "utils",
+                        "range": {
+                            "start": {"line": 5, "character": 5},
+                            "end": {"line": 5, "character": 11}
+                        }
+                    }]
+                },
+                {
+                    "textDocument": {"uri": uri("second.py"), "version": null},
+                    "edits": [{
+                        "newText": "utils",
+                        "range": {
+                            "start": {"line": 5, "character": 7},
+                            "end": {"line": 5, "character": 13}
+                        }
+                    }]
+                },
+                {
+                    "kind": "rename",
+                    "oldUri": uri("helper.py"),
+                    "newUri": uri("utils.py")
+                }
+            ]
+        }))
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
+
+#[test]
+fn test_rename_module_is_refused_when_an_importer_uses_qualified_names() {
+    let root = get_test_files_root();
+    let root_path = root.path().join("rename_module_qualified");
+    let scope_uri = Url::from_file_path(root_path.clone()).unwrap();
+
+    let mut interaction = LspInteraction::new_with_args(LspInteractionArgs {
+        args: LspArgs {
+            indexing_mode: IndexingMode::LazyBlocking,
+            ..LspInteractionArgs::default().args
+        },
+        ..Default::default()
+    });
+    interaction.set_root(root_path.clone());
+    interaction
+        .initialize(InitializeSettings {
+            workspace_folders: Some(vec![("test".to_owned(), scope_uri)]),
+            capabilities: Some(json!({
+                "workspace": {
+                    "workspaceEdit": {
+                        "documentChanges": true,
+                        "resourceOperations": ["rename"]
+                    }
+                }
+            })),
+            ..Default::default()
+        })
+        .unwrap();
+
+    interaction.client.did_open("first.py");
+
+    let first_uri = Url::from_file_path(root_path.join("first.py")).unwrap();
+
+    // Rewriting `import helper` to `import utils` would leave `helper.VALUE` unresolved, so the
+    // rename must be refused rather than applied.
+    interaction
+        .client
+        .send_request::<Rename>(json!({
+            "textDocument": {
+                "uri": first_uri.to_string()
+            },
+            "position": {
+                "line": 5,
+                "character": 9
+            },
+            "newName": "utils"
+        }))
+        .expect_response_error(json!({
+            "code": -32600,
+            "message": format!(
+                "Cannot rename module `helper`: `{first_uri}` imports it without `as`, and its \
+                 qualified uses of the module would not be updated"
+            ),
+            "data": null,
+        }))
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
@@ -0,0 +1,125 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use lsp_server::ErrorCode;
+use lsp_types::Range;
+use lsp_types::SymbolInformation;
+use lsp_types::Url;
+use lsp_types::WorkspaceSymbolResponse;
+use pyrefly::commands::lsp::LspArgs;
+use pyrefly::lsp::non_wasm::external_provider::ExternalProvider;
+use pyrefly::lsp::non_wasm::protocol::Message;
+use pyrefly_util::telemetry::SubTaskTelemetry;
+use tempfile::TempDir;
+
+use crate::object_model::InitializeSettings;
+use crate::object_model::LspInteraction;
+use crate::object_model::LspInteractionArgs;
+
+/// An external index whose first workspace symbol search takes longer than the request timeout.
+#[derive(Default)]
+struct SlowExternalProvider {
+    searched: AtomicBool,
+}
+
+impl ExternalProvider for SlowExternalProvider {
+    fn find_references(
+        &self,
+        _qualified_name: &str,
+        _source_uri: &Url,
+        _timeout: Duration,
+        _telemetry: Option<SubTaskTelemetry>,
+    ) -> anyhow::Result<Vec<(Url, Vec<Range>)>> {
+        Ok(Vec::new())
+    }
+
+    fn workspace_symbols(
+        &self,
+        _query: &str,
+        _workspace_uri: &Url,
+        _timeout: Duration,
+        _telemetry: Option<SubTaskTelemetry>,
+    ) -> anyhow::Result<Vec<SymbolInformation>> {
+        if !self.searched.swap(true, Ordering::SeqCst) {
+            std::thread::sleep(Duration::from_secs(3));
+        }
+        Ok(Vec::new())
+    }
+}
+
+#[test]
+fn test_request_after_timed_out_request_succeeds() {
+    let root = TempDir::new().unwrap();
+    let path = root.path().join("main.py");
+    std::fs::write(&path, "x = [1]\n").unwrap();
+    let mut interaction = LspInteraction::new_with_args(LspInteractionArgs {
+        args: LspArgs {
+            request_timeout_ms: 1000,
+            ..LspInteractionArgs::default().args
+        },
+        external_provider: Arc::new(SlowExternalProvider::default()),
+        ..Default::default()
+    });
+    interaction.set_root(root.path().to_path_buf());
+    let scope_uri = Url::from_file_path(root.path()).unwrap();
+    interaction
+        .initialize(InitializeSettings {
+            workspace_folders: Some(vec![("test".to_owned(), scope_uri)]),
+            ..Default::default()
+        })
+        .unwrap();
+    interaction.client.did_open("main.py");
+
+    // The search outlasts the timeout, so the watchdog answers it.
+    let slow = interaction.client.send_workspace_symbol("x");
+    let slow_id = slow.id.clone();
+    interaction
+        .client
+        .expect_message("timeout error for the slow request", |msg| {
+            if let Message::Response(x) = msg
+                && x.id == slow_id
+                && let Some(error) = x.error
+            {
+                assert_eq!(error.code, ErrorCode::RequestFailed as i32);
+                assert!(
+                    error.message.contains("request timeout"),
+                    "{}",
+                    error.message
+                );
+                Some(Ok(()))
+            } else {
+                None
+            }
+        })
+        .unwrap();
+
+    // The main loop isn't held up while the slow search is still running.
+    interaction.client.did_change("main.py", "x = 'a'\n");
+    interaction
+        .client
+        .hover("main.py", 0, 0)
+        .expect_hover_response_with_markup(|value| value.is_some_and(|text| text.contains("str")))
+        .unwrap();
+
+    // The transaction the slow request cancelled must not be reused for later searches.
+    interaction
+        .client
+        .send_workspace_symbol("x")
+        .expect_response_with(|response| match response {
+            Some(WorkspaceSymbolResponse::Flat(symbols)) => !symbols.is_empty(),
+            Some(WorkspaceSymbolResponse::Nested(symbols)) => !symbols.is_empty(),
+            None => false,
+        })
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
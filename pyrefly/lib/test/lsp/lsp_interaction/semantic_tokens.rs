@@ -5,10 +5,13 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::cell::RefCell;
 use std::fs;
 
+use lsp_types::SemanticTokensFullDeltaResult;
 use lsp_types::SemanticTokensResult;
 use lsp_types::Url;
+use lsp_types::request::SemanticTokensFullDeltaRequest;
 use lsp_types::request::SemanticTokensFullRequest;
 use pyrefly::state::semantic_tokens::SemanticTokensLegends;
 use serde_json::json;
@@ -93,3 +96,115 @@ fn semantic_tokens_import_submodule_alias() {
 
     interaction.shutdown().unwrap();
 }
+
+#[test]
+fn semantic_tokens_delta_after_edit() {
+    let root = get_test_files_root();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings {
+            configuration: Some(None),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let uri = Url::from_file_path(root.path().join("text_document.py")).unwrap();
+    interaction.client.did_open("text_document.py");
+    interaction
+        .client
+        .did_change("text_document.py", "def f(x):\n    return x\n");
+
+    let captured = RefCell::new(None);
+    interaction
+        .client
+        .send_request::<SemanticTokensFullRequest>(json!({
+            "textDocument": { "uri": uri.to_string() }
+        }))
+        .expect_response_with(|response| match response {
+            Some(SemanticTokensResult::Tokens(tokens)) => {
+                *captured.borrow_mut() = tokens.result_id;
+                captured.borrow().is_some()
+            }
+            _ => false,
+        })
+        .unwrap();
+    let result_id = captured.into_inner().unwrap();
+
+    interaction.client.did_change(
+        "text_document.py",
+        "def f(x):\n    return x\n\ndef g(y):\n    return y\n",
+    );
+
+    // With the previous result cached, only the tokens for `g` are sent.
+    interaction
+        .client
+        .send_request::<SemanticTokensFullDeltaRequest>(json!({
+            "textDocument": { "uri": uri.to_string() },
+            "previousResultId": result_id,
+        }))
+        .expect_response_with(|response| match response {
+            Some(SemanticTokensFullDeltaResult::TokensDelta(delta)) => {
+                delta.result_id.is_some_and(|id| id != result_id)
+                    && delta.edits.len() == 1
+                    && delta.edits[0].delete_count == 0
+            }
+            _ => false,
+        })
+        .unwrap();
+
+    // An unknown baseline falls back to the full token array.
+    interaction
+        .client
+        .send_request::<SemanticTokensFullDeltaRequest>(json!({
+            "textDocument": { "uri": uri.to_string() },
+            "previousResultId": "unknown",
+        }))
+        .expect_response_with(|response| {
+            matches!(response, Some(SemanticTokensFullDeltaResult::Tokens(_)))
+        })
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
+
+#[test]
+fn semantic_tokens_include_keywords_for_non_augmenting_client() {
+    let root = get_test_files_root();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings {
+            configuration: Some(None),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let uri = Url::from_file_path(root.path().join("text_document.py")).unwrap();
+    interaction.client.did_open("text_document.py");
+    interaction
+        .client
+        .did_change("text_document.py", "def f(x):\n    return x\n");
+
+    let legend = SemanticTokensLegends::lsp_semantic_token_legends();
+    let keyword = legend
+        .token_types
+        .iter()
+        .position(|token_type| token_type.as_str() == "keyword")
+        .unwrap() as u32;
+    // Without `augmentsSyntaxTokens`, the client has no highlighting of its own for `def`.
+    interaction
+        .client
+        .send_request::<SemanticTokensFullRequest>(json!({
+            "textDocument": { "uri": uri.to_string() }
+        }))
+        .expect_response_with(|response| match response {
+            Some(SemanticTokensResult::Tokens(tokens)) => tokens.data.first().is_some_and(|t| {
+                t.delta_line == 0 && t.delta_start == 0 && t.length == 3 && t.token_type == keyword
+            }),
+            _ => false,
+        })
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
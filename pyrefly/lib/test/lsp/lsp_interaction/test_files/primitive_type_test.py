@@ -9,3 +9,4 @@ z: bool = True
 a: bytes = b"123"
 b = [1, 2, 3]
 c = None
+d: list[int] = []
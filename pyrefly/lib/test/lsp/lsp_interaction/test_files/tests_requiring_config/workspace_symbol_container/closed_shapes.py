@@ -0,0 +1,13 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is licensed under the MIT license found in the
+# LICENSE file in the root directory of this source tree.
+
+
+def workspace_symbol_closed_function() -> None:
+    pass
+
+
+class Square:
+    def workspace_symbol_closed_method(self) -> float:
+        return 0.0
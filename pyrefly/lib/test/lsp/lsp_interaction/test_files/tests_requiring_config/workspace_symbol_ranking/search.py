@@ -0,0 +1,16 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is licensed under the MIT license found in the
+# LICENSE file in the root directory of this source tree.
+
+
+def grep() -> None:
+    pass
+
+
+def get_search_paths() -> None:
+    pass
+
+
+def get_search_roots() -> None:
+    pass
@@ -7,6 +7,8 @@
 
 use lsp_types::SemanticTokensResult;
 use lsp_types::Url;
+use lsp_types::notification::DidChangeTextDocument;
+use lsp_types::notification::DidCloseTextDocument;
 use lsp_types::request::Completion;
 use lsp_types::request::SemanticTokensFullRequest;
 use serde_json::json;
@@ -121,3 +123,68 @@ math.
 
     interaction.shutdown().unwrap();
 }
+
+#[test]
+fn test_server_survives_unsupported_uri_scheme() {
+    let interaction = LspInteraction::new();
+    interaction
+        .initialize(InitializeSettings::default())
+        .unwrap();
+
+    // A remote scheme we can't map to a path is logged and skipped, both on
+    // open and on close, without taking down the server.
+    let remote = Url::parse("vscode-vfs://github/owner/repo/remote.py").unwrap();
+    interaction
+        .client
+        .did_open_uri(&remote, "python", "x = 1\n");
+    interaction
+        .client
+        .send_notification::<DidCloseTextDocument>(json!({
+            "textDocument": {"uri": remote.to_string()},
+        }));
+
+    let uri = Url::parse("untitled:Untitled-3").unwrap();
+    interaction
+        .client
+        .did_open_uri(&uri, "python", "import math\nmath.\n");
+    interaction
+        .client
+        .send_request::<Completion>(json!({
+            "textDocument": {"uri": uri.to_string()},
+            "position": {"line": 1, "character": 5}
+        }))
+        .expect_completion_response_with(|list| !list.items.is_empty())
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
+
+#[test]
+fn test_diagnostics_for_unsaved_file_use_original_uri() {
+    let interaction = LspInteraction::new();
+    interaction
+        .initialize(InitializeSettings::default())
+        .unwrap();
+
+    let uri = Url::parse("untitled:Untitled-4").unwrap();
+    interaction
+        .client
+        .did_open_uri(&uri, "python", "x: int = \"oops\"\n");
+    interaction
+        .client
+        .expect_publish_diagnostics_uri(&uri, 1)
+        .unwrap();
+
+    interaction
+        .client
+        .send_notification::<DidChangeTextDocument>(json!({
+            "textDocument": {"uri": uri.to_string(), "version": 2},
+            "contentChanges": [{"text": "x: int = 1\n"}],
+        }));
+    interaction
+        .client
+        .expect_publish_diagnostics_uri(&uri, 0)
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
@@ -509,3 +509,52 @@ fn test_will_rename_files_document_changes() {
 
     interaction.shutdown().unwrap();
 }
+
+#[test]
+fn test_will_rename_files_in_package_when_indexed() {
+    let root = get_test_files_root();
+    let root_path = root.path().join("will_rename_package");
+    let mut interaction = LspInteraction::new_with_args(LspInteractionArgs {
+        args: LspArgs {
+            indexing_mode: IndexingMode::LazyBlocking,
+            ..LspInteractionArgs::default().args
+        },
+        ..Default::default()
+    });
+    interaction.set_root(root_path.clone());
+    interaction
+        .initialize(InitializeSettings::default())
+        .unwrap();
+
+    interaction.client.did_open("a/b.py");
+
+    let importer_path = root_path.join("importer.py");
+
+    // Rename a/b.py to a/c.py. The closed importer is found through the index.
+    interaction
+        .client
+        .will_rename_files("a/b.py", "a/c.py")
+        .expect_response(json!({
+            "changes": {
+                Url::from_file_path(&importer_path).unwrap().to_string(): [
+                    {
+                        "newText": "a.c",
+                        "range": {
+                            "start": {"line": 5, "character": 7},
+                            "end": {"line": 5, "character": 10}
+                        }
+                    },
+                    {
+                        "newText": "a.c",
+                        "range": {
+                            "start": {"line": 6, "character": 5},
+                            "end": {"line": 6, "character": 8}
+                        }
+                    }
+                ]
+            }
+        }))
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
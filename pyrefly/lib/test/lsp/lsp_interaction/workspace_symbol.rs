@@ -5,8 +5,16 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::cell::RefCell;
+
+use lsp_server::ErrorCode;
+use lsp_types::OneOf;
+use lsp_types::SymbolKind;
 use lsp_types::Url;
 use lsp_types::WorkspaceSymbolResponse;
+use lsp_types::notification::Cancel;
+use lsp_types::request::WorkspaceSymbolResolve;
+use pyrefly::lsp::non_wasm::protocol::Message;
 use serde_json::json;
 
 use crate::object_model::InitializeSettings;
@@ -43,7 +51,8 @@ fn test_workspace_symbol() {
                     },
                     "uri": Url::from_file_path(root_path.join("autoimport_provider.py")).unwrap().to_string()
                 },
-                "name": "this_is_a_very_long_function_name_so_we_can_deterministically_test_autoimport_with_fuzzy_search"
+                "name": "this_is_a_very_long_function_name_so_we_can_deterministically_test_autoimport_with_fuzzy_search",
+                "containerName": "autoimport_provider"
             }
         ]))
         .unwrap();
@@ -150,3 +159,370 @@ fn test_workspace_symbol_multibyte_no_panic() {
 
     interaction.shutdown().unwrap();
 }
+
+#[test]
+fn test_workspace_symbol_method_container_name() {
+    let root = get_test_files_root();
+    let root_path = root.path().join("tests_requiring_config");
+    let scope_uri = Url::from_file_path(root_path.clone()).unwrap();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root_path.clone());
+    interaction
+        .initialize(InitializeSettings {
+            workspace_folders: Some(vec![("test".to_owned(), scope_uri)]),
+            configuration: Some(Some(json!([{ "indexing_mode": "lazy_blocking"}]))),
+            ..Default::default()
+        })
+        .unwrap();
+
+    interaction
+        .client
+        .did_open("workspace_symbol_container/shapes.py");
+
+    interaction
+        .client
+        .send_workspace_symbol("workspace_symbol_container_method")
+        .expect_response_with(|result| {
+            let Some(WorkspaceSymbolResponse::Flat(symbols)) = result else {
+                panic!("Unexpected workspace symbol response: {result:?}");
+            };
+            let method = symbols
+                .iter()
+                .find(|symbol| symbol.name == "workspace_symbol_container_method")
+                .expect("expected the method to be returned");
+            assert_eq!(method.kind, SymbolKind::METHOD);
+            assert_eq!(method.container_name.as_deref(), Some("Circle"));
+            true
+        })
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
+
+#[test]
+fn test_workspace_symbol_closed_file_exports_only() {
+    let root = get_test_files_root();
+    let root_path = root.path().join("tests_requiring_config");
+    let scope_uri = Url::from_file_path(root_path.clone()).unwrap();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root_path.clone());
+    interaction
+        .initialize(InitializeSettings {
+            workspace_folders: Some(vec![("test".to_owned(), scope_uri)]),
+            configuration: Some(Some(json!([{ "indexing_mode": "lazy_blocking"}]))),
+            ..Default::default()
+        })
+        .unwrap();
+
+    // Only `shapes.py` is open; `closed_shapes.py` is indexed but its AST is not retained.
+    interaction
+        .client
+        .did_open("workspace_symbol_container/shapes.py");
+
+    interaction
+        .client
+        .send_workspace_symbol("workspace_symbol_closed")
+        .expect_response_with(|result| {
+            let Some(WorkspaceSymbolResponse::Flat(symbols)) = result else {
+                panic!("Unexpected workspace symbol response: {result:?}");
+            };
+            let function = symbols
+                .iter()
+                .find(|symbol| symbol.name == "workspace_symbol_closed_function")
+                .expect("expected the top-level function of the closed file to be returned");
+            assert_eq!(
+                function.container_name.as_deref(),
+                Some("workspace_symbol_container.closed_shapes")
+            );
+            // Methods are only searched in open files.
+            assert!(
+                symbols
+                    .iter()
+                    .all(|symbol| symbol.name != "workspace_symbol_closed_method")
+            );
+            true
+        })
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
+
+#[test]
+fn test_workspace_symbol_cancellation() {
+    let root = get_test_files_root();
+    let root_path = root.path().join("tests_requiring_config");
+    let scope_uri = Url::from_file_path(root_path.clone()).unwrap();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root_path.clone());
+    interaction
+        .initialize(InitializeSettings {
+            workspace_folders: Some(vec![("test".to_owned(), scope_uri)]),
+            configuration: Some(Some(json!([{ "indexing_mode": "lazy_blocking"}]))),
+            ..Default::default()
+        })
+        .unwrap();
+
+    interaction.client.did_open("autoimport_provider.py");
+
+    let query = "this_is_a_very_long_function_name_so_we_can";
+    let first = interaction.client.send_workspace_symbol(query);
+    let cancelled = interaction.client.send_workspace_symbol(query);
+    let cancelled_id = cancelled.id.clone();
+    interaction
+        .client
+        .send_notification::<Cancel>(json!({ "id": cancelled_id }));
+
+    first
+        .expect_response_with(|result| result.is_some())
+        .unwrap();
+    interaction
+        .client
+        .expect_message("cancelled workspace/symbol response", |msg| {
+            if let Message::Response(x) = msg
+                && x.id == cancelled_id
+            {
+                assert_eq!(
+                    x.error.map(|error| error.code),
+                    Some(ErrorCode::RequestCanceled as i32)
+                );
+                Some(Ok(()))
+            } else {
+                None
+            }
+        })
+        .unwrap();
+
+    // The cancellation must not leak into later requests.
+    interaction
+        .client
+        .send_workspace_symbol(query)
+        .expect_response_with(|result| {
+            matches!(result, Some(WorkspaceSymbolResponse::Flat(symbols)) if symbols.len() == 1)
+        })
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
+
+#[test]
+fn test_workspace_symbol_fuzzy_ranking() {
+    let root = get_test_files_root();
+    let root_path = root.path().join("tests_requiring_config");
+    let scope_uri = Url::from_file_path(root_path.clone()).unwrap();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root_path.clone());
+    interaction
+        .initialize(InitializeSettings {
+            workspace_folders: Some(vec![("test".to_owned(), scope_uri)]),
+            configuration: Some(Some(json!([{ "indexing_mode": "lazy_blocking"}]))),
+            ..Default::default()
+        })
+        .unwrap();
+
+    interaction
+        .client
+        .did_open("workspace_symbol_ranking/search.py");
+
+    interaction
+        .client
+        .send_workspace_symbol("gsp")
+        .expect_response_with(|result| {
+            let Some(WorkspaceSymbolResponse::Flat(symbols)) = result else {
+                panic!("Unexpected workspace symbol response: {result:?}");
+            };
+            let names: Vec<&str> = symbols.iter().map(|symbol| symbol.name.as_str()).collect();
+            assert!(names.contains(&"get_search_paths"), "{names:?}");
+            // `grep` has no `s`, so it isn't a subsequence match for `gsp`.
+            assert!(!names.contains(&"grep"), "{names:?}");
+            true
+        })
+        .unwrap();
+
+    // Both names match `gep`, but `get_search_paths` matches `ge` at the start and `p` after an
+    // underscore, while `grep` matches `e` and `p` mid-word, so it must rank first.
+    interaction
+        .client
+        .send_workspace_symbol("gep")
+        .expect_response_with(|result| {
+            let Some(WorkspaceSymbolResponse::Flat(symbols)) = result else {
+                panic!("Unexpected workspace symbol response: {result:?}");
+            };
+            let position = |name: &str| symbols.iter().position(|symbol| symbol.name == name);
+            let get_search_paths =
+                position("get_search_paths").expect("expected `gep` to match get_search_paths");
+            let grep = position("grep").expect("expected `gep` to match grep");
+            assert!(
+                get_search_paths < grep,
+                "expected get_search_paths (rank {get_search_paths}) above grep (rank {grep})"
+            );
+            true
+        })
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
+
+#[test]
+fn test_workspace_symbol_limit() {
+    let root = get_test_files_root();
+    let root_path = root.path().join("tests_requiring_config");
+    let scope_uri = Url::from_file_path(root_path.clone()).unwrap();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root_path.clone());
+    interaction
+        .initialize(InitializeSettings {
+            workspace_folders: Some(vec![("test".to_owned(), scope_uri)]),
+            configuration: Some(Some(json!([{ "indexing_mode": "lazy_blocking"}]))),
+            initialization_options: Some(json!({ "workspaceSymbolLimit": 1 })),
+            ..Default::default()
+        })
+        .unwrap();
+
+    interaction
+        .client
+        .did_open("workspace_symbol_ranking/search.py");
+
+    interaction
+        .client
+        .send_workspace_symbol("get_search")
+        .expect_response_with(|result| {
+            let Some(WorkspaceSymbolResponse::Flat(symbols)) = result else {
+                panic!("Unexpected workspace symbol response: {result:?}");
+            };
+            assert_eq!(symbols.len(), 1);
+            true
+        })
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
+
+#[test]
+fn test_workspace_symbol_resolve_matches_eager_location() {
+    let root = get_test_files_root();
+    let root_path = root.path().join("tests_requiring_config");
+    let scope_uri = Url::from_file_path(root_path.clone()).unwrap();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root_path.clone());
+    interaction
+        .initialize(InitializeSettings {
+            workspace_folders: Some(vec![("test".to_owned(), scope_uri)]),
+            configuration: Some(Some(json!([{ "indexing_mode": "lazy_blocking"}]))),
+            capabilities: Some(json!({
+                "workspace": {
+                    "symbol": {
+                        "resolveSupport": { "properties": ["location.range"] }
+                    }
+                }
+            })),
+            ..Default::default()
+        })
+        .unwrap();
+
+    interaction.client.did_open("autoimport_provider.py");
+
+    // Without a range, the symbol is returned with just the uri of its file.
+    let unresolved = RefCell::new(None);
+    interaction
+        .client
+        .send_workspace_symbol("this_is_a_very_long_function_name_so_we_can")
+        .expect_response_with(|result| {
+            let Some(WorkspaceSymbolResponse::Nested(symbols)) = result else {
+                panic!("Unexpected workspace symbol response: {result:?}");
+            };
+            assert_eq!(symbols.len(), 1);
+            assert!(matches!(symbols[0].location, OneOf::Right(_)));
+            // The module the location is resolved from travels in `data`.
+            assert_eq!(
+                symbols[0].data.as_ref().map(|data| &data["module"]),
+                Some(&json!("autoimport_provider"))
+            );
+            *unresolved.borrow_mut() = Some(symbols[0].clone());
+            true
+        })
+        .unwrap();
+
+    // Resolving fills in the same location the eager path returns.
+    interaction
+        .client
+        .send_request::<WorkspaceSymbolResolve>(
+            serde_json::to_value(unresolved.into_inner().unwrap()).unwrap(),
+        )
+        .expect_response(json!({
+            "kind": 12,
+            "location": {
+                "range": {
+                    "start": {"line": 6, "character": 4},
+                    "end": {"line": 6, "character": 99}
+                },
+                "uri": Url::from_file_path(root_path.join("autoimport_provider.py")).unwrap().to_string()
+            },
+            "name": "this_is_a_very_long_function_name_so_we_can_deterministically_test_autoimport_with_fuzzy_search",
+            "containerName": "autoimport_provider"
+        }))
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
+
+#[test]
+fn test_workspace_symbol_resolve_ignores_bad_range() {
+    let root = get_test_files_root();
+    let root_path = root.path().join("tests_requiring_config");
+    let scope_uri = Url::from_file_path(root_path.clone()).unwrap();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root_path.clone());
+    interaction
+        .initialize(InitializeSettings {
+            workspace_folders: Some(vec![("test".to_owned(), scope_uri)]),
+            configuration: Some(Some(json!([{ "indexing_mode": "lazy_blocking"}]))),
+            capabilities: Some(json!({
+                "workspace": {
+                    "symbol": {
+                        "resolveSupport": { "properties": ["location.range"] }
+                    }
+                }
+            })),
+            ..Default::default()
+        })
+        .unwrap();
+
+    interaction.client.did_open("autoimport_provider.py");
+
+    let unresolved = RefCell::new(None);
+    interaction
+        .client
+        .send_workspace_symbol("this_is_a_very_long_function_name_so_we_can")
+        .expect_response_with(|result| {
+            let Some(WorkspaceSymbolResponse::Nested(symbols)) = result else {
+                panic!("Unexpected workspace symbol response: {result:?}");
+            };
+            assert_eq!(symbols.len(), 1);
+            *unresolved.borrow_mut() = Some(symbols[0].clone());
+            true
+        })
+        .unwrap();
+    let mut symbol = serde_json::to_value(unresolved.into_inner().unwrap()).unwrap();
+
+    // A range whose start is after its end is returned unresolved rather than crashing.
+    symbol["data"]["start"] = json!(10);
+    symbol["data"]["end"] = json!(5);
+    interaction
+        .client
+        .send_request::<WorkspaceSymbolResolve>(symbol.clone())
+        .expect_response(symbol.clone())
+        .unwrap();
+
+    // A range past the end of the file is clamped to it.
+    symbol["data"]["start"] = json!(1_000_000);
+    symbol["data"]["end"] = json!(1_000_000);
+    interaction
+        .client
+        .send_request::<WorkspaceSymbolResolve>(symbol)
+        .expect_response_with(|symbol| {
+            matches!(symbol.location, OneOf::Left(_)) && symbol.data.is_none()
+        })
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
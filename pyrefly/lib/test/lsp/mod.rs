@@ -17,12 +17,16 @@ mod document_highlight;
 mod document_symbols;
 mod expected_type;
 mod folding_ranges;
+mod global_find_refs;
 mod hover;
 mod hover_docstring;
 mod hover_type;
 mod implementation;
 mod inlay_hint;
+mod linked_editing_range;
 mod local_find_refs;
+mod moniker;
+mod peek_definition;
 mod qualified_name;
 mod rename;
 mod semantic_tokens;
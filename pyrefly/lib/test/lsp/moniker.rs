@@ -0,0 +1,109 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use itertools::Itertools;
+use lsp_types::MonikerKind;
+use pretty_assertions::assert_eq;
+use pyrefly_build::handle::Handle;
+use ruff_text_size::TextSize;
+
+use crate::lsp::non_wasm::moniker::monikers;
+use crate::state::state::State;
+use crate::test::util::get_batched_lsp_operations_report;
+
+fn get_test_report(state: &State, handle: &Handle, position: TextSize) -> String {
+    match monikers(&state.transaction(), handle, position) {
+        Some(monikers) => monikers
+            .into_iter()
+            .map(|moniker| {
+                let kind = match moniker.kind {
+                    Some(MonikerKind::Import) => "import",
+                    Some(MonikerKind::Export) => "export",
+                    Some(MonikerKind::Local) => "local",
+                    None => "none",
+                };
+                format!(
+                    "Moniker: {}:{} ({kind}, {:?})",
+                    moniker.scheme, moniker.identifier, moniker.unique
+                )
+            })
+            .join("\n"),
+        None => "Moniker: None".to_owned(),
+    }
+}
+
+#[test]
+fn top_level_function() {
+    let code = r#"
+def foo() -> None: ...
+#   ^
+foo()
+# ^
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+2 | def foo() -> None: ...
+        ^
+Moniker: pyrefly:main.foo (export, Scheme)
+
+4 | foo()
+      ^
+Moniker: pyrefly:main.foo (export, Scheme)
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn imported_method() {
+    let lib = r#"
+class Widget:
+    def render(self) -> None: ...
+"#;
+    let code = r#"
+from lib import Widget
+Widget().render()
+#         ^
+"#;
+    let report =
+        get_batched_lsp_operations_report(&[("main", code), ("lib", lib)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+3 | Widget().render()
+              ^
+Moniker: pyrefly:lib.Widget.render (import, Scheme)
+
+
+# lib.py
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn builtin() {
+    let code = r#"
+len([])
+#^
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+2 | len([])
+     ^
+Moniker: pyrefly:builtins.len (import, Scheme)
+"#
+        .trim(),
+        report.trim(),
+    );
+}
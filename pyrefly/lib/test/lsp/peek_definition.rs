@@ -0,0 +1,91 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use itertools::Itertools;
+use pretty_assertions::assert_eq;
+use pyrefly_build::handle::Handle;
+use ruff_text_size::TextSize;
+
+use crate::lsp::non_wasm::peek_definition::definition_snippets;
+use crate::state::state::State;
+use crate::test::util::get_batched_lsp_operations_report;
+
+fn get_test_report(state: &State, handle: &Handle, position: TextSize) -> String {
+    match definition_snippets(&state.transaction(), handle, position, 1) {
+        Some(snippets) => snippets
+            .into_iter()
+            .map(|snippet| {
+                format!(
+                    "Definition in {} from line {}:\n{}",
+                    snippet.definition.module.name(),
+                    snippet.start_line,
+                    snippet
+                        .text
+                        .lines()
+                        .map(|line| format!("|{line}"))
+                        .join("\n")
+                )
+            })
+            .join("\n"),
+        None => "Definition: None".to_owned(),
+    }
+}
+
+#[test]
+fn snippet_includes_def_and_body() {
+    let code = r#"
+
+def foo() -> int:
+    return 1
+    # unused
+
+
+foo()
+# ^
+"#;
+    let report = get_batched_lsp_operations_report(&[("main", code)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+8 | foo()
+      ^
+Definition in main from line 1:
+|
+|def foo() -> int:
+|    return 1
+"#
+        .trim(),
+        report.trim(),
+    );
+}
+
+#[test]
+fn snippet_is_clamped_to_file() {
+    let lib = "def bar() -> None:\n    pass\n";
+    let code = r#"
+from lib import bar
+bar()
+# ^
+"#;
+    let report =
+        get_batched_lsp_operations_report(&[("main", code), ("lib", lib)], get_test_report);
+    assert_eq!(
+        r#"
+# main.py
+3 | bar()
+      ^
+Definition in lib from line 0:
+|def bar() -> None:
+|    pass
+
+
+# lib.py
+"#
+        .trim(),
+        report.trim(),
+    );
+}
@@ -6,6 +6,7 @@
  */
 
 use pretty_assertions::assert_eq;
+use ruff_source_file::PositionEncoding;
 
 use crate::state::require::Require;
 use crate::state::semantic_tokens::SemanticTokensLegends;
@@ -46,7 +47,13 @@ fn assert_full_semantic_tokens_with_syntax(
         let handle = handles.get(name).unwrap();
         let tokens = state
             .transaction()
-            .semantic_tokens(handle, None, None, include_syntax_tokens)
+            .semantic_tokens(
+                handle,
+                None,
+                None,
+                include_syntax_tokens,
+                PositionEncoding::Utf16,
+            )
             .unwrap();
 
         let mut start_line: usize = 0;
@@ -39,6 +39,7 @@ use crate::report::pysa::class::ClassId;
 use crate::report::pysa::collect::CollectNoDuplicateKeys;
 use crate::report::pysa::context::ModuleAnswersContext;
 use crate::report::pysa::context::ModuleContext;
+use crate::report::pysa::context::PysaExportOptions;
 use crate::report::pysa::context::PysaResolver;
 use crate::report::pysa::function::FunctionId;
 use crate::report::pysa::function::FunctionRef;
@@ -293,6 +294,7 @@ fn test_building_call_graph_for_module(
             &module_ids,
         ),
         resolver: &resolver,
+        options: PysaExportOptions::default(),
     };
 
     let module_captured_variables = collect_captured_variables_for_module(&context);
@@ -4804,6 +4806,39 @@ def bar(l: List[int]):
     }
 );
 
+call_graph_testcase!(
+    test_subscript_of_call_result,
+    TEST_MODULE_NAME,
+    r#"
+def foo() -> list[int]: ...
+def bar():
+  return foo()[0]
+"#,
+    &|context: &ModuleContext| {
+        let getitem_target = vec![
+            create_call_target("builtins.list.__getitem__", TargetType::Overrides)
+                .with_implicit_receiver(ImplicitReceiver::TrueWithObjectReceiver)
+                .with_receiver_class_for_test("builtins.list", context),
+        ];
+        vec![(
+            "test.bar",
+            vec![
+                (
+                    "4:10-4:15",
+                    regular_call_callees(vec![create_call_target(
+                        "test.foo",
+                        TargetType::Function,
+                    )]),
+                ),
+                (
+                    "4:10-4:18|artificial-call|subscript-get-item",
+                    regular_call_callees(getitem_target),
+                ),
+            ],
+        )]
+    }
+);
+
 call_graph_testcase!(
     test_overload_function_return_type,
     TEST_MODULE_NAME,
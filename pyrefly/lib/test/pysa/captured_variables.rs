@@ -19,6 +19,7 @@ use crate::report::pysa::captured_variable::collect_captured_variables_for_modul
 use crate::report::pysa::collect::CollectNoDuplicateKeys;
 use crate::report::pysa::context::ModuleAnswersContext;
 use crate::report::pysa::context::ModuleContext;
+use crate::report::pysa::context::PysaExportOptions;
 use crate::report::pysa::context::PysaResolver;
 use crate::report::pysa::function::FunctionRef;
 use crate::report::pysa::module::ModuleIds;
@@ -125,6 +126,7 @@ fn test_exported_captured_variables(
             &module_ids,
         ),
         resolver: &resolver,
+        options: PysaExportOptions::default(),
     };
 
     let expected_captures = captured_variables_from_expected(expected_captures);
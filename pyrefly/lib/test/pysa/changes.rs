@@ -0,0 +1,93 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::path::Path;
+
+use crate::report::pysa::PYSA_FORMAT_VERSION;
+use crate::report::pysa::manifest::PYSA_CHANGES_FILENAME;
+use crate::report::pysa::manifest::PYSA_MANIFEST_FILENAME;
+use crate::report::pysa::manifest::PysaChanges;
+use crate::report::pysa::manifest::PysaManifest;
+use crate::report::pysa::manifest::build_manifest;
+use crate::report::pysa::manifest::write_manifest_and_changes;
+use crate::state::require::Require;
+use crate::test::util::TestEnv;
+
+fn export_manifest(results_directory: &Path, modules: &[(&str, &str)]) {
+    let mut test_env = TestEnv::new();
+    for (module_name, python_code) in modules {
+        test_env.add(module_name, python_code);
+    }
+    let (state, _) = test_env
+        .with_default_require_level(Require::Everything)
+        .to_state();
+    let transaction = state.transaction();
+    let manifest = build_manifest(&transaction.handles(), &transaction);
+    write_manifest_and_changes(results_directory, &manifest).unwrap();
+}
+
+fn read_changes(results_directory: &Path) -> PysaChanges {
+    serde_json::from_slice(&std::fs::read(results_directory.join(PYSA_CHANGES_FILENAME)).unwrap())
+        .unwrap()
+}
+
+#[test]
+fn test_first_run_writes_no_changes() {
+    let output = tempfile::tempdir().expect("should create temp dir");
+    export_manifest(output.path(), &[("a", "def f(): ...\n")]);
+    assert!(!output.path().join(PYSA_CHANGES_FILENAME).exists());
+}
+
+#[test]
+fn test_changes_list_modified_module() {
+    let output = tempfile::tempdir().expect("should create temp dir");
+    let unchanged = "class A:\n    def m(self) -> int:\n        return 0\n";
+    export_manifest(
+        output.path(),
+        &[
+            ("a", unchanged),
+            (
+                "b",
+                "def f() -> int:\n    return 0\n\ndef g() -> None: ...\n",
+            ),
+        ],
+    );
+    export_manifest(
+        output.path(),
+        &[
+            ("a", unchanged),
+            (
+                "b",
+                "def f() -> int:\n    return 1\n\ndef h() -> None: ...\n",
+            ),
+        ],
+    );
+    let changes = read_changes(output.path());
+    assert!(changes.added_modules.is_empty());
+    assert!(changes.removed_modules.is_empty());
+    assert_eq!(changes.modified_modules, vec!["b".to_owned()]);
+    assert_eq!(changes.added_definitions, vec!["b:h".to_owned()]);
+    assert_eq!(changes.removed_definitions, vec!["b:g".to_owned()]);
+    assert_eq!(changes.modified_definitions, vec!["b:f".to_owned()]);
+}
+
+#[test]
+fn test_previous_manifest_for_newer_reader_is_ignored() {
+    let output = tempfile::tempdir().expect("should create temp dir");
+    let previous = PysaManifest {
+        format_version: PYSA_FORMAT_VERSION + 1,
+        min_reader_version: PYSA_FORMAT_VERSION + 1,
+        ..Default::default()
+    };
+    std::fs::write(
+        output.path().join(PYSA_MANIFEST_FILENAME),
+        serde_json::to_vec(&previous).unwrap(),
+    )
+    .unwrap();
+    export_manifest(output.path(), &[("a", "def f(): ...\n")]);
+    assert!(!output.path().join(PYSA_CHANGES_FILENAME).exists());
+}
@@ -22,6 +22,7 @@ use crate::report::pysa::class::PysaClassMro;
 use crate::report::pysa::class::export_all_classes;
 use crate::report::pysa::context::ModuleAnswersContext;
 use crate::report::pysa::context::ModuleContext;
+use crate::report::pysa::context::PysaExportOptions;
 use crate::report::pysa::context::PysaResolver;
 use crate::report::pysa::location::PysaLocation;
 use crate::report::pysa::module::ModuleIds;
@@ -100,6 +101,7 @@ fn test_exported_classes(
             &module_ids,
         ),
         resolver: &resolver,
+        options: PysaExportOptions::default(),
     };
 
     let expected_class_definitions = create_expected_class_definitions(&context);
@@ -0,0 +1,143 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::path::PathBuf;
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::module_path::ModulePath;
+use pyrefly_util::thread_pool::TEST_THREAD_COUNT;
+use serde_json::json;
+
+use crate::report::pysa::PysaFormat;
+use crate::report::pysa::PysaReporter;
+use crate::report::pysa::context::PysaExportOptions;
+use crate::report::pysa::write_project_file;
+use crate::state::require::Require;
+use crate::state::state::State;
+use crate::test::util::TestEnv;
+
+#[test]
+fn test_export_memory_backed_module() {
+    let mut test_env = TestEnv::new();
+    test_env.add("test", "def f(x: int) -> int:\n    return x\n");
+    let handle = Handle::new(
+        ModuleName::from_str("test"),
+        ModulePath::memory(PathBuf::from("test.py")),
+        test_env.sys_info(),
+    );
+    let handles = [handle.dupe()];
+    let output = tempfile::tempdir().expect("should create temp dir");
+
+    // Pysa files are written as modules are solved, so use a fresh state.
+    let state = State::new(test_env.config_finder(), TEST_THREAD_COUNT);
+    let mut transaction = state.new_committable_transaction(Require::Errors, None);
+    let transaction = transaction.as_mut();
+    transaction.set_memory(test_env.get_memory());
+    transaction.set_pysa_reporter(Some(
+        PysaReporter::new(
+            output.path(),
+            &handles,
+            PysaFormat::Json,
+            PysaExportOptions {
+                skip_typeshed: true,
+                ..Default::default()
+            },
+        )
+        .unwrap(),
+    ));
+    transaction.run(&handles, Require::Errors, None);
+    let reporter = transaction.take_pysa_reporter().unwrap();
+    write_project_file(&reporter, transaction, &handles, &[]).unwrap();
+
+    let module_id = reporter.module_ids.get_from_handle(&handle).to_int();
+    let info_filename = format!("test:{module_id}.json");
+    for directory in ["definitions", "type_of_expressions", "call_graphs"] {
+        assert!(
+            output.path().join(directory).join(&info_filename).exists(),
+            "missing {directory}/{info_filename}"
+        );
+    }
+    let project: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(output.path().join("pyrefly.pysa.json")).unwrap())
+            .unwrap();
+    let module = &project["modules"][module_id.to_string()];
+    assert_eq!(module["source_path"], json!({"Memory": "test.py"}));
+    assert_eq!(module["info_filename"], json!(info_filename));
+}
+
+#[test]
+fn test_literal_values_require_json_format() {
+    let output = tempfile::tempdir().expect("should create temp dir");
+    let options = PysaExportOptions {
+        literal_values: true,
+        ..Default::default()
+    };
+    assert!(PysaReporter::new(output.path(), &[], PysaFormat::Capnp, options).is_err());
+    assert!(PysaReporter::new(output.path(), &[], PysaFormat::Json, options).is_ok());
+}
+
+#[test]
+fn test_skip_typeshed_requires_json_format() {
+    let output = tempfile::tempdir().expect("should create temp dir");
+    let options = PysaExportOptions {
+        skip_typeshed: true,
+        ..Default::default()
+    };
+    assert!(PysaReporter::new(output.path(), &[], PysaFormat::Capnp, options).is_err());
+    assert!(PysaReporter::new(output.path(), &[], PysaFormat::Json, options).is_ok());
+}
+
+#[test]
+fn test_export_modules_sharing_a_module_name() {
+    let mut test_env = TestEnv::new();
+    test_env.add_with_path("test", "root1/test.py", "x: int = 1\n");
+    test_env.add_with_path("test", "root2/test.py", "y: str = \"\"\n");
+    let handles = ["root1/test.py", "root2/test.py"].map(|path| {
+        Handle::new(
+            ModuleName::from_str("test"),
+            ModulePath::memory(PathBuf::from(path)),
+            test_env.sys_info(),
+        )
+    });
+    let output = tempfile::tempdir().expect("should create temp dir");
+
+    let state = State::new(test_env.config_finder(), TEST_THREAD_COUNT);
+    let mut transaction = state.new_committable_transaction(Require::Errors, None);
+    let transaction = transaction.as_mut();
+    transaction.set_memory(test_env.get_memory());
+    transaction.set_pysa_reporter(Some(
+        PysaReporter::new(
+            output.path(),
+            &handles,
+            PysaFormat::Json,
+            PysaExportOptions {
+                skip_typeshed: true,
+                ..Default::default()
+            },
+        )
+        .unwrap(),
+    ));
+    transaction.run(&handles, Require::Errors, None);
+    let reporter = transaction.take_pysa_reporter().unwrap();
+    write_project_file(&reporter, transaction, &handles, &[]).unwrap();
+
+    // Both files are exported under their own module id.
+    let project: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(output.path().join("pyrefly.pysa.json")).unwrap())
+            .unwrap();
+    let module_ids = handles
+        .each_ref()
+        .map(|handle| reporter.module_ids.get_from_handle(handle).to_int());
+    assert_ne!(module_ids[0], module_ids[1]);
+    for (module_id, path) in module_ids.iter().zip(["root1/test.py", "root2/test.py"]) {
+        let module = &project["modules"][module_id.to_string()];
+        assert_eq!(module["module_name"], json!("test"));
+        assert_eq!(module["source_path"], json!({"Memory": path}));
+    }
+}
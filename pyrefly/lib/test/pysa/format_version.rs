@@ -0,0 +1,64 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::collections::HashMap;
+
+use crate::report::pysa::PYSA_FORMAT_VERSION;
+use crate::report::pysa::PYSA_MIN_READER_VERSION;
+use crate::report::pysa::PysaProjectFile;
+use crate::report::pysa::capnp_writer;
+use crate::report::pysa::pysa_report_capnp;
+use crate::report::pysa::verify_format_version;
+
+#[test]
+fn test_current_format_version_is_readable() {
+    assert!(PYSA_MIN_READER_VERSION <= PYSA_FORMAT_VERSION);
+    verify_format_version(PYSA_FORMAT_VERSION, PYSA_MIN_READER_VERSION).unwrap();
+}
+
+#[test]
+fn test_newer_additive_format_version_is_readable() {
+    verify_format_version(PYSA_FORMAT_VERSION + 1, PYSA_FORMAT_VERSION).unwrap();
+}
+
+#[test]
+fn test_newer_incompatible_format_version_is_rejected() {
+    let error =
+        verify_format_version(PYSA_FORMAT_VERSION + 1, PYSA_FORMAT_VERSION + 1).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains(&format!("only supports version {PYSA_FORMAT_VERSION}"))
+    );
+}
+
+#[test]
+fn test_capnp_project_file_records_format_version() {
+    let project = PysaProjectFile {
+        format_version: PYSA_FORMAT_VERSION,
+        min_reader_version: PYSA_MIN_READER_VERSION,
+        typeshed_digest: None,
+        modules: HashMap::new(),
+        builtin_module_ids: Vec::new(),
+        object_class_refs: Vec::new(),
+        dict_class_refs: Vec::new(),
+        typing_module_ids: Vec::new(),
+        typing_mapping_class_refs: Vec::new(),
+    };
+    let mut bytes = Vec::new();
+    capnp_writer::write_project_file(&mut bytes, &project).unwrap();
+
+    let message =
+        capnp::serialize::read_message(&mut bytes.as_slice(), capnp::message::ReaderOptions::new())
+            .unwrap();
+    let root = message
+        .get_root::<pysa_report_capnp::project_file::Reader>()
+        .unwrap();
+    assert_eq!(root.get_format_version(), PYSA_FORMAT_VERSION);
+    assert_eq!(root.get_min_reader_version(), PYSA_MIN_READER_VERSION);
+    verify_format_version(root.get_format_version(), root.get_min_reader_version()).unwrap();
+}
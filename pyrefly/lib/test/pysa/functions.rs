@@ -21,6 +21,7 @@ use crate::report::pysa::call_graph::Target;
 use crate::report::pysa::class::ClassId;
 use crate::report::pysa::context::ModuleAnswersContext;
 use crate::report::pysa::context::ModuleContext;
+use crate::report::pysa::context::PysaExportOptions;
 use crate::report::pysa::context::PysaResolver;
 use crate::report::pysa::function::FunctionBaseDefinition;
 use crate::report::pysa::function::FunctionDefinition;
@@ -106,6 +107,7 @@ fn test_exported_functions(
             &module_ids,
         ),
         resolver: &resolver,
+        options: PysaExportOptions::default(),
     };
 
     let expected_function_definitions = create_expected_function_definitions(&context);
@@ -1012,6 +1014,7 @@ class Foo:
             &module_ids,
         ),
         resolver: &resolver,
+        options: PysaExportOptions::default(),
     };
 
     let field_id = |ref_: &FunctionRef| match &ref_.function_id {
@@ -13,6 +13,7 @@ use ruff_python_ast::name::Name;
 
 use crate::report::pysa::context::ModuleAnswersContext;
 use crate::report::pysa::context::ModuleContext;
+use crate::report::pysa::context::PysaExportOptions;
 use crate::report::pysa::context::PysaResolver;
 use crate::report::pysa::global_variable::GlobalVariable;
 use crate::report::pysa::global_variable::export_global_variables;
@@ -53,6 +54,7 @@ fn test_exported_global_variables(
             &module_ids,
         ),
         resolver: &resolver,
+        options: PysaExportOptions::default(),
     };
 
     let expected_globals = create_expected_globals(&context);
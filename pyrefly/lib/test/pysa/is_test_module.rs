@@ -9,6 +9,7 @@ use dupe::Dupe;
 
 use crate::report::pysa::context::ModuleAnswersContext;
 use crate::report::pysa::context::ModuleContext;
+use crate::report::pysa::context::PysaExportOptions;
 use crate::report::pysa::context::PysaResolver;
 use crate::report::pysa::is_test_module::is_test_module;
 use crate::report::pysa::module::ModuleIds;
@@ -35,6 +36,7 @@ fn test_is_test_module(python_code: &str, expected: bool) {
             &module_ids,
         ),
         resolver: &resolver,
+        options: PysaExportOptions::default(),
     };
 
     let result = is_test_module(&context.answers_context);
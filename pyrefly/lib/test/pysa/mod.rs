@@ -7,9 +7,15 @@
 
 mod call_graph;
 mod captured_variables;
+mod changes;
 mod classes;
+mod export;
+mod format_version;
 mod functions;
 mod global_variables;
 mod is_test_module;
+mod module_ids;
+mod type_of_expressions;
 mod types;
+mod typeshed_files;
 mod utils;
@@ -0,0 +1,42 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::path::PathBuf;
+
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::module_path::ModulePath;
+use pyrefly_python::sys_info::SysInfo;
+
+use crate::report::pysa::module::ModuleIds;
+
+#[test]
+fn test_module_ids_with_shared_module_name() {
+    let module_name = ModuleName::from_str("foo.bar");
+    let first = Handle::new(
+        module_name,
+        ModulePath::filesystem(PathBuf::from("/root1/foo/bar.py")),
+        SysInfo::default(),
+    );
+    let second = Handle::new(
+        module_name,
+        ModulePath::filesystem(PathBuf::from("/root2/foo/bar.pyi")),
+        SysInfo::default(),
+    );
+
+    // Duplicated handles and handles sharing a module name must not panic.
+    let module_ids = ModuleIds::new(&[second.dupe(), first.dupe(), second.dupe(), first.dupe()]);
+    let first_id = module_ids.get_from_handle(&first);
+    let second_id = module_ids.get_from_handle(&second);
+    assert_ne!(first_id, second_id);
+
+    // Ids are assigned in sort order, independently of the input order.
+    let reordered = ModuleIds::new(&[first.dupe(), second.dupe()]);
+    assert_eq!(first_id, reordered.get_from_handle(&first));
+    assert_eq!(second_id, reordered.get_from_handle(&second));
+}
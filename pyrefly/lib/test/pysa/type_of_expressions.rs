@@ -0,0 +1,215 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use dupe::Dupe;
+use pretty_assertions::assert_eq;
+
+use crate::report::pysa::FunctionTypeOfExpressions;
+use crate::report::pysa::context::ModuleAnswersContext;
+use crate::report::pysa::context::ModuleContext;
+use crate::report::pysa::context::PysaExportOptions;
+use crate::report::pysa::context::PysaResolver;
+use crate::report::pysa::export_module_type_of_expressions;
+use crate::report::pysa::module::ModuleIds;
+use crate::report::pysa::type_of_expression::export_type_of_expressions;
+use crate::report::pysa::type_of_expression::export_type_of_expressions_in_partitions;
+use crate::report::pysa::write_module_type_of_expressions_json;
+use crate::test::pysa::utils::create_state;
+use crate::test::pysa::utils::get_function_ref;
+use crate::test::pysa::utils::get_handle_for_module_name;
+
+fn with_module_context(
+    python_code: &str,
+    options: PysaExportOptions,
+    f: impl FnOnce(&ModuleContext),
+) {
+    let state = create_state("test", python_code);
+    let transaction = state.transaction();
+    let handles = transaction.handles();
+    let module_ids = ModuleIds::new(&handles);
+
+    let test_module_handle = get_handle_for_module_name("test", &transaction);
+    let resolver = PysaResolver::new_for_test(
+        &transaction,
+        &module_ids,
+        test_module_handle.dupe(),
+        &handles,
+    );
+    let context = ModuleContext {
+        answers_context: ModuleAnswersContext::create(
+            test_module_handle.dupe(),
+            &transaction,
+            &module_ids,
+        ),
+        resolver: &resolver,
+        options,
+    };
+    f(&context)
+}
+
+fn sorted_literals(function: &FunctionTypeOfExpressions) -> Vec<String> {
+    let mut literals: Vec<String> = function.literal_of_expression.values().cloned().collect();
+    literals.sort();
+    literals
+}
+
+/// Export type-of-expressions for `python_code` and return the sorted literal
+/// values recorded alongside the promoted types, across all functions.
+fn exported_literals(python_code: &str, options: PysaExportOptions) -> Vec<String> {
+    let mut literals = Vec::new();
+    with_module_context(python_code, options, |context| {
+        literals = export_type_of_expressions(context)
+            .values()
+            .flat_map(sorted_literals)
+            .collect();
+    });
+    literals.sort();
+    literals
+}
+
+#[test]
+fn test_export_string_literal_of_expression() {
+    let code = r#"
+def execute(query: str) -> None: ...
+
+def run() -> None:
+    execute("SELECT 1")
+"#;
+    assert_eq!(
+        vec!["'SELECT 1'".to_owned()],
+        exported_literals(
+            code,
+            PysaExportOptions {
+                literal_values: true,
+                ..Default::default()
+            }
+        )
+    );
+    // Literal values are opt-in.
+    assert_eq!(
+        Vec::<String>::new(),
+        exported_literals(code, PysaExportOptions::default())
+    );
+}
+
+#[test]
+fn test_type_of_expressions_grouped_by_enclosing_function() {
+    let code = r#"
+def outer() -> None:
+    def inner() -> None:
+        print("inner")
+    print("outer")
+"#;
+    let options = PysaExportOptions {
+        literal_values: true,
+        ..Default::default()
+    };
+    with_module_context(code, options, |context| {
+        let functions = export_type_of_expressions(context);
+        let outer = get_function_ref("test", "outer", context).function_id;
+        let inner = get_function_ref("test", "inner", context).function_id;
+        // Each expression is attributed to its innermost enclosing function.
+        assert_eq!(
+            vec!["'outer'".to_owned()],
+            sorted_literals(&functions[&outer])
+        );
+        assert_eq!(
+            vec!["'inner'".to_owned()],
+            sorted_literals(&functions[&inner])
+        );
+    });
+}
+
+#[test]
+fn test_type_of_expressions_serialization_is_deterministic() {
+    let code = r#"
+def f0(x: int) -> int:
+    return x + 1
+def f1(x: str) -> str:
+    return x + "a"
+def f2(x: bytes) -> bytes:
+    return x + b"b"
+def f3(x: float) -> float:
+    return x * 2.0
+def f4(x: list[int]) -> int:
+    return x[0] + x[1] + len(x)
+"#;
+    let options = PysaExportOptions {
+        literal_values: true,
+        ..Default::default()
+    };
+    with_module_context(code, options, |context| {
+        // Each export builds fresh hash maps with their own random iteration
+        // order, so identical bytes means entries are sorted on serialization.
+        // The streamed writer is the one producing the JSON files.
+        let streamed = || {
+            let mut streamed = Vec::new();
+            write_module_type_of_expressions_json(&mut streamed, context).unwrap();
+            String::from_utf8(streamed).unwrap()
+        };
+        let first = streamed();
+        for _ in 0..5 {
+            assert_eq!(first, streamed());
+        }
+    });
+}
+
+#[test]
+fn test_type_of_expressions_partitioned_visit_matches_sequential_visit() {
+    // A large synthetic module, with top-level code interleaved with functions
+    // so that the top-level entry is built from several partitions.
+    let mut code = String::new();
+    for i in 0..500 {
+        code.push_str(&format!(
+            "def f{i}(x: int, y: str) -> str:\n    return str(x + {i}) + y\nv{i} = f{i}({i}, \"{i}\")\n"
+        ));
+    }
+    let options = PysaExportOptions {
+        literal_values: true,
+        ..Default::default()
+    };
+    with_module_context(&code, options, |context| {
+        let sequential = export_type_of_expressions_in_partitions(context, usize::MAX);
+        let partitioned = export_type_of_expressions_in_partitions(context, 7);
+        assert_eq!(sequential.len(), partitioned.len());
+        for (function_id, expected) in &sequential {
+            assert_eq!(
+                serde_json::to_string(expected).unwrap(),
+                serde_json::to_string(&partitioned[function_id]).unwrap(),
+                "type of expressions differ for {function_id:?}",
+            );
+        }
+    });
+}
+
+#[test]
+fn test_streamed_type_of_expressions_match_exported_ones() {
+    let code = r#"
+def outer(x: int) -> None:
+    def inner(y: str) -> None:
+        print(y, x)
+    inner("a")
+
+class C:
+    a = outer(1)
+    def method(self, z: bytes) -> None:
+        print(z)
+
+outer(2)
+"#;
+    let options = PysaExportOptions {
+        literal_values: true,
+        ..Default::default()
+    };
+    with_module_context(code, options, |context| {
+        let mut streamed = Vec::new();
+        write_module_type_of_expressions_json(&mut streamed, context).unwrap();
+        // Byte for byte, so that functions are also written in the same sorted order.
+        let exported = serde_json::to_string(&export_module_type_of_expressions(context)).unwrap();
+        assert_eq!(exported, String::from_utf8(streamed).unwrap());
+    });
+}
@@ -29,6 +29,7 @@ use ruff_text_size::TextRange;
 use crate::report::pysa::class::ClassRef;
 use crate::report::pysa::context::ModuleAnswersContext;
 use crate::report::pysa::context::ModuleContext;
+use crate::report::pysa::context::PysaExportOptions;
 use crate::report::pysa::context::PysaResolver;
 use crate::report::pysa::module::ModuleIds;
 use crate::report::pysa::types::ClassNamesFromType;
@@ -83,6 +84,7 @@ class MyTypedDict(TypedDict):
             &module_ids,
         ),
         resolver: &resolver,
+        options: PysaExportOptions::default(),
     };
 
     // Builtin types
@@ -695,3 +697,57 @@ class MyTypedDict(TypedDict):
         ),
     );
 }
+
+#[test]
+fn test_pysa_type_fully_qualified_type_names() {
+    let state = create_state(
+        "test",
+        r#"
+class MyClass:
+    pass
+"#,
+    );
+    let transaction = state.transaction();
+    let handles = transaction.handles();
+    let module_ids = ModuleIds::new(&handles);
+
+    let test_module_handle = get_handle_for_module_name("test", &transaction);
+    let resolver = PysaResolver::new_for_test(
+        &transaction,
+        &module_ids,
+        test_module_handle.dupe(),
+        &handles,
+    );
+    let type_strings = |fully_qualified_type_names: bool| {
+        let context = ModuleContext {
+            answers_context: ModuleAnswersContext::create(
+                test_module_handle.dupe(),
+                &transaction,
+                &module_ids,
+            ),
+            resolver: &resolver,
+            options: PysaExportOptions {
+                fully_qualified_type_names,
+                ..Default::default()
+            },
+        };
+        let int = Type::ClassType(context.answers_context.stdlib.int().clone());
+        let my_class = Type::ClassType(ClassType::new(
+            get_class("test", "MyClass", &context),
+            Default::default(),
+        ));
+        (
+            PysaType::from_type(&int, &context).string,
+            PysaType::from_type(&my_class, &context).string,
+        )
+    };
+
+    assert_eq!(
+        ("int".to_owned(), "test.MyClass".to_owned()),
+        type_strings(false)
+    );
+    assert_eq!(
+        ("builtins.int".to_owned(), "test.MyClass".to_owned()),
+        type_strings(true)
+    );
+}
@@ -0,0 +1,24 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use crate::report::pysa::context::PysaExportOptions;
+use crate::report::pysa::write_typeshed_files;
+
+#[test]
+fn test_skip_typeshed_writes_no_typeshed_files() {
+    let output_dir = tempfile::tempdir().expect("should create temp dir");
+    write_typeshed_files(
+        output_dir.path(),
+        PysaExportOptions {
+            skip_typeshed: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(!output_dir.path().join("typeshed").exists());
+    assert!(!output_dir.path().join("typeshed_third_party").exists());
+}
@@ -0,0 +1,127 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::path::Path;
+
+use dupe::Dupe;
+use pretty_assertions::assert_eq;
+
+use crate::report::scip::document::ScipAsts;
+use crate::report::scip::document::ScipDocument;
+use crate::report::scip::document::export_document;
+use crate::report::scip::project_root;
+use crate::report::scip::scip;
+use crate::state::require::Require;
+use crate::test::util::mk_multi_file_state_assert_no_errors;
+
+const LIB: &str = r#"class Widget:
+    def render(self) -> int:
+        return 1
+"#;
+
+const MAIN: &str = r#"from lib import Widget
+
+def draw(w: Widget) -> int:
+    size = w.render()
+    return size
+"#;
+
+fn export(module: &'static str) -> ScipDocument {
+    let (handles, state) =
+        mk_multi_file_state_assert_no_errors(&[("main", MAIN), ("lib", LIB)], Require::Everything);
+    export_document(
+        &state.transaction(),
+        &handles[module],
+        Path::new(""),
+        &mut ScipAsts::default(),
+    )
+    .unwrap()
+    .unwrap()
+}
+
+fn occurrences(document: &ScipDocument) -> Vec<String> {
+    document
+        .occurrences
+        .iter()
+        .map(|occurrence| {
+            format!(
+                "{:?} {} {}",
+                occurrence.range, occurrence.symbol, occurrence.roles
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_scip_definitions_and_references() {
+    let document = export("main");
+    assert_eq!(document.relative_path, "main.py");
+    assert_eq!(
+        occurrences(&document),
+        vec![
+            "[0, 16, 22] pyrefly python . . lib/Widget# 2",
+            "[2, 4, 8] pyrefly python . . main/draw(). 1",
+            "[2, 9, 10] local 0 1",
+            "[2, 12, 18] pyrefly python . . lib/Widget# 8",
+            "[2, 23, 26] pyrefly python . . builtins/int# 8",
+            "[3, 4, 8] local 1 1",
+            "[3, 11, 12] local 0 8",
+            "[3, 13, 19] pyrefly python . . lib/Widget#render(). 8",
+            "[4, 11, 15] local 1 8",
+        ]
+    );
+    assert_eq!(document.symbols, vec!["pyrefly python . . main/draw()."]);
+}
+
+#[test]
+fn test_scip_class_members() {
+    let document = export("lib");
+    assert_eq!(
+        occurrences(&document),
+        vec![
+            "[0, 6, 12] pyrefly python . . lib/Widget# 1",
+            "[1, 8, 14] pyrefly python . . lib/Widget#render(). 1",
+            "[1, 15, 19] local 0 1",
+            "[1, 24, 27] pyrefly python . . builtins/int# 8",
+        ]
+    );
+    assert_eq!(
+        document.symbols,
+        vec![
+            "pyrefly python . . lib/Widget#",
+            "pyrefly python . . lib/Widget#render().",
+        ]
+    );
+}
+
+#[test]
+fn test_scip_rejects_files_outside_project_root() {
+    let (handles, state) =
+        mk_multi_file_state_assert_no_errors(&[("main", MAIN), ("lib", LIB)], Require::Everything);
+    assert!(
+        export_document(
+            &state.transaction(),
+            &handles["main"],
+            Path::new("src"),
+            &mut ScipAsts::default(),
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn test_scip_project_root_covers_every_file() {
+    let (handles, state) =
+        mk_multi_file_state_assert_no_errors(&[("main", MAIN), ("lib", LIB)], Require::Everything);
+    let handles = vec![handles["main"].dupe(), handles["lib"].dupe()];
+    // Neither file is under `src`, so the index is rooted further up instead of dropping them.
+    assert_eq!(
+        project_root(Path::new("src"), &handles).unwrap(),
+        Path::new("")
+    );
+    assert!(scip(&state.transaction(), &handles, Path::new("src")).is_ok());
+}
@@ -958,6 +958,30 @@ fn test_change_require() {
     assert!(state.transaction().get_bindings(&handle).is_some());
 }
 
+#[test]
+fn test_ast_reused_across_read_transactions() {
+    let mut t = TestEnv::new();
+    t.add("foo", "x = 1");
+    let (state, handle) = t.to_state();
+    let handle = handle("foo");
+
+    // Reads of an unchanged module share the AST kept in the module state, without reparsing.
+    let first = state.transaction().get_ast(&handle).unwrap();
+    let second = state.transaction().get_ast(&handle).unwrap();
+    assert!(Arc::ptr_eq(&first, &second));
+
+    // Changing the contents drops the AST, so the next run parses the new source.
+    let mut t = state.new_committable_transaction(Require::Everything, None);
+    t.as_mut().set_memory(vec![(
+        PathBuf::from("foo.py"),
+        Some(Arc::new(FileContents::from_source("x = 3".to_owned()))),
+    )]);
+    t.as_mut().run(&[handle.dupe()], Require::Everything, None);
+    state.commit_transaction(t, None);
+    let changed = state.transaction().get_ast(&handle).unwrap();
+    assert!(!Arc::ptr_eq(&first, &changed));
+}
+
 #[test]
 fn test_crash_on_search() {
     const REQUIRE: Require = Require::Everything; // Doesn't matter for the test
@@ -1005,6 +1029,23 @@ fn test_search_exports_cancellation() {
     );
 }
 
+#[test]
+fn test_workspace_symbols_cancellation() {
+    let mut t = TestEnv::new();
+    t.add("foo", "def some_function(): ...");
+    let (state, handle) = t.to_state();
+
+    let t = state.new_transaction(Require::Everything, None);
+
+    // Cancelling a workspace symbol request via `$/cancelRequest` cancels its transaction.
+    t.get_cancellation_handle().cancel();
+    assert!(
+        t.workspace_symbols("some_function", &[handle("foo")], None)
+            .is_err(),
+        "workspace_symbols should return Err(Cancelled) when cancelled"
+    );
+}
+
 #[test]
 fn test_compute_stdlib_uses_bundled_typeshed_even_with_custom_path() {
     use std::fs;
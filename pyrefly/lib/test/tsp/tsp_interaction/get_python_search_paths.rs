@@ -122,7 +122,7 @@ fn test_get_python_search_paths_stale_snapshot() {
         error: Some(lsp_server::ResponseError {
             code: lsp_server::ErrorCode::ServerCancelled as i32,
             message: "Snapshot outdated: client sent 9999, server is at 0".to_owned(),
-            data: None,
+            data: Some(serde_json::json!({ "currentSnapshot": 0 })),
         }),
     });
 
@@ -194,3 +194,56 @@ fn test_get_python_search_paths_src_layout() {
 
     tsp.shutdown();
 }
+
+#[test]
+fn test_get_python_search_paths_dedupes_and_skips_missing() {
+    // A directory listed both as a search path and as a site-package path is
+    // returned once, and paths that don't exist on disk are omitted.
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(
+        temp_dir.path().join("pyrefly.toml"),
+        "search-path = [\"lib\", \"missing\"]\nsite-package-path = [\"lib\"]\nskip-interpreter-query = true\n",
+    )
+    .unwrap();
+    let lib_dir = temp_dir.path().join("lib");
+    std::fs::create_dir_all(&lib_dir).unwrap();
+
+    let test_file = temp_dir.path().join("main.py");
+    std::fs::write(&test_file, "x = 1\n").unwrap();
+
+    let mut tsp = TspInteraction::new();
+    tsp.set_root(temp_dir.path().to_path_buf());
+    tsp.initialize(Default::default());
+
+    tsp.server.did_open("main.py");
+    tsp.client.expect_any_message();
+
+    let snapshot = get_current_snapshot(&mut tsp, 2);
+
+    let from_uri = Url::from_file_path(&test_file).unwrap().to_string();
+    tsp.server.get_python_search_paths(&from_uri, snapshot);
+
+    let resp = tsp.client.receive_response_skip_notifications();
+    assert!(
+        resp.error.is_none(),
+        "Expected success, got error: {:?}",
+        resp.error
+    );
+    let result = resp.result.expect("Expected result");
+    let paths: Vec<String> = serde_json::from_value(result).expect("Expected array of strings");
+
+    let lib_uri = Url::from_file_path(lib_dir.canonicalize().unwrap())
+        .unwrap()
+        .to_string();
+    assert_eq!(
+        paths.iter().filter(|p| **p == lib_uri).count(),
+        1,
+        "Expected {lib_uri} exactly once, got: {paths:?}"
+    );
+    assert!(
+        !paths.iter().any(|p| p.ends_with("/missing")),
+        "Expected nonexistent path to be skipped, got: {paths:?}"
+    );
+
+    tsp.shutdown();
+}
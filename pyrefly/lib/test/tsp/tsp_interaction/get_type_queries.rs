@@ -6,12 +6,14 @@
  */
 
 //! Integration tests for the `typeServer/getDeclaredType`,
-//! `typeServer/getComputedType`, and `typeServer/getExpectedType` TSP requests.
+//! `typeServer/getComputedType`, `typeServer/getExpectedType`, and batched
+//! `typeServer/getTypes` TSP requests.
 
 use lsp_types::Url;
 use tempfile::TempDir;
 use tsp_types::TypeKind;
 
+use crate::test::tsp::tsp_interaction::object_model::InitializeSettings;
 use crate::test::tsp::tsp_interaction::object_model::TspInteraction;
 use crate::test::tsp::tsp_interaction::object_model::get_current_snapshot;
 use crate::test::tsp::tsp_interaction::object_model::write_pyproject;
@@ -422,6 +424,46 @@ fn test_get_computed_type_union_annotation() {
     tsp.shutdown();
 }
 
+#[test]
+fn test_get_computed_type_follows_isinstance_narrowing() {
+    // The same variable reports its flow-sensitive type at each use: `str`
+    // inside the `isinstance` branch and `int` once that branch has returned,
+    // while the parameter itself keeps its declared `int | str`.
+    let code = "\
+def f(x: int | str) -> None:
+    if isinstance(x, str):
+        print(x)
+        return
+    print(x)
+";
+    let (mut tsp, file_uri, snapshot) = setup_project(code);
+    let class_name = |result: &serde_json::Value| {
+        assert_kind(result, TypeKind::Class);
+        result
+            .get("declaration")
+            .and_then(|d| d.get("name"))
+            .and_then(|n| n.as_str())
+            .map(str::to_owned)
+    };
+
+    let declared = get_computed_type_ok(&mut tsp, &file_uri, 0, 6, snapshot);
+    assert_kind(&declared, TypeKind::Union);
+
+    // Line 2: `        print(x)` — `x` is at char 14.
+    let inside = get_computed_type_ok(&mut tsp, &file_uri, 2, 14, snapshot);
+    assert_eq!(class_name(&inside).as_deref(), Some("str"));
+    let inside_range = get_computed_type_range_ok(&mut tsp, &file_uri, 2, 14, 2, 15, snapshot);
+    assert_eq!(class_name(&inside_range).as_deref(), Some("str"));
+
+    // Line 4: `    print(x)` — `x` is at char 10.
+    let outside = get_computed_type_ok(&mut tsp, &file_uri, 4, 10, snapshot);
+    assert_eq!(class_name(&outside).as_deref(), Some("int"));
+    let outside_range = get_computed_type_range_ok(&mut tsp, &file_uri, 4, 10, 4, 11, snapshot);
+    assert_eq!(class_name(&outside_range).as_deref(), Some("int"));
+
+    tsp.shutdown();
+}
+
 #[test]
 fn test_get_computed_type_class_definition() {
     // The class name itself should be Instantiable (type[MyClass])
@@ -1834,3 +1876,145 @@ fn test_get_expected_type_dict_value_falls_back_to_container_type() {
 
     tsp.shutdown();
 }
+
+#[test]
+fn test_get_type_queries_past_end_of_file() {
+    // A stale client may send positions beyond the end of a short file. These
+    // are clamped to the file's bounds rather than panicking, and the server
+    // keeps answering subsequent queries.
+    let (mut tsp, file_uri, snapshot) = setup_project("x = 1\n");
+
+    get_computed_type_range_raw(&mut tsp, &file_uri, 50, 0, 60, 3, snapshot);
+
+    tsp.server.get_declared_type(&file_uri, 100, 100, snapshot);
+    let resp = tsp.client.receive_response_skip_notifications();
+    assert!(
+        resp.error.is_none(),
+        "Expected success, got error: {:?}",
+        resp.error
+    );
+
+    let computed = get_computed_type_ok(&mut tsp, &file_uri, 0, 0, snapshot);
+    assert_kind(&computed, TypeKind::Class);
+
+    tsp.shutdown();
+}
+
+#[test]
+fn test_get_computed_type_range_ending_before_start() {
+    // A range that ends before it starts is not a range in the file, which is
+    // reported as invalid params rather than `null`.
+    let (mut tsp, file_uri, snapshot) = setup_project("x = 1\ny = 2\n");
+
+    tsp.server
+        .get_computed_type_range(&file_uri, 1, 0, 0, 1, snapshot);
+    let resp = tsp.client.receive_response_skip_notifications();
+    let error = resp.error.expect("Expected an error, got success");
+    assert_eq!(error.code, lsp_server::ErrorCode::InvalidParams as i32);
+
+    let computed = get_computed_type_ok(&mut tsp, &file_uri, 0, 0, snapshot);
+    assert_kind(&computed, TypeKind::Class);
+
+    tsp.shutdown();
+}
+
+#[test]
+fn test_get_type_queries_language_services_disabled() {
+    // When language services are disabled the server cannot answer type
+    // queries at all, which is reported as an error rather than `null`.
+    let temp_dir = TempDir::new().unwrap();
+    write_pyproject(temp_dir.path());
+    let test_file = temp_dir.path().join("main.py");
+    std::fs::write(&test_file, "x = 1\n").unwrap();
+
+    let mut tsp = TspInteraction::new();
+    tsp.set_root(temp_dir.path().to_path_buf());
+    tsp.initialize(InitializeSettings {
+        configuration: Some(Some(
+            serde_json::json!([{"pyrefly": {"disableLanguageServices": true}}]),
+        )),
+        ..Default::default()
+    });
+
+    tsp.server.did_open("main.py");
+    tsp.client.expect_any_message();
+
+    let snapshot = get_current_snapshot(&mut tsp, 2);
+    let file_uri = Url::from_file_path(&test_file).unwrap().to_string();
+
+    tsp.server.get_declared_type(&file_uri, 0, 0, snapshot);
+    let resp = tsp.client.receive_response_skip_notifications();
+    let error = resp.error.expect("Expected an error, got success");
+    assert_eq!(error.code, lsp_server::ErrorCode::RequestFailed as i32);
+
+    tsp.shutdown();
+}
+
+// =======================================================================
+// getTypes (batched)
+// =======================================================================
+
+#[test]
+fn test_get_types_batches_ten_nodes() {
+    let source = (0..10)
+        .map(|i| {
+            if i % 2 == 0 {
+                format!("v{i} = {i}\n")
+            } else {
+                format!("v{i} = \"s{i}\"\n")
+            }
+        })
+        .collect::<String>();
+    let (mut tsp, file_uri, snapshot) = setup_project(&source);
+    let positions: Vec<(u32, u32)> = (0..10).map(|line| (line, 0)).collect();
+
+    tsp.server.get_types(&file_uri, &positions, snapshot);
+    let resp = tsp.client.receive_response_skip_notifications();
+    assert!(
+        resp.error.is_none(),
+        "Expected success, got error: {:?}",
+        resp.error
+    );
+    let result = resp.result.expect("Expected result");
+    let types = result.as_array().expect("Expected a list of types");
+    assert_eq!(types.len(), 10, "Expected one type per node: {result}");
+
+    // Each entry matches what a standalone getComputedType returns for the node.
+    for (i, ty) in types.iter().enumerate() {
+        assert_kind(ty, TypeKind::Class);
+        let literal_value = ty.get("literalValue");
+        if i % 2 == 0 {
+            assert_eq!(
+                literal_value.and_then(|v| v.as_i64()),
+                Some(i as i64),
+                "Unexpected type for node {i}: {ty}"
+            );
+        } else {
+            assert_eq!(
+                literal_value.and_then(|v| v.as_str()),
+                Some(format!("s{i}").as_str()),
+                "Unexpected type for node {i}: {ty}"
+            );
+        }
+    }
+
+    tsp.shutdown();
+}
+
+#[test]
+fn test_get_types_stale_snapshot() {
+    let (mut tsp, file_uri, snapshot) = setup_project("x = 1\n");
+
+    tsp.server.get_types(&file_uri, &[(0, 0)], 9999);
+
+    let resp = tsp.client.receive_response_skip_notifications();
+    let error = resp.error.expect("Expected error for stale snapshot");
+    assert_eq!(error.code, lsp_server::ErrorCode::ServerCancelled as i32);
+    // The error carries the current snapshot so the client can retry directly.
+    assert_eq!(
+        error.data,
+        Some(serde_json::json!({ "currentSnapshot": snapshot }))
+    );
+
+    tsp.shutdown();
+}
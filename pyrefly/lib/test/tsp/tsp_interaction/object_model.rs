@@ -219,6 +219,33 @@ impl TestTspServer {
         self.send_get_type_request("typeServer/getExpectedType", uri, line, character, snapshot);
     }
 
+    /// Send a batched `typeServer/getTypes` request with one Node arg per
+    /// `(line, character)` position in `uri`.
+    pub fn get_types(&mut self, uri: &str, positions: &[(u32, u32)], snapshot: i32) {
+        let id = self.next_request_id();
+        let args: Vec<Value> = positions
+            .iter()
+            .map(|(line, character)| {
+                serde_json::json!({
+                    "uri": uri,
+                    "range": {
+                        "start": { "line": line, "character": character },
+                        "end": { "line": line, "character": character },
+                    },
+                })
+            })
+            .collect();
+        self.send_message(Message::Request(Request {
+            id,
+            method: "typeServer/getTypes".to_owned(),
+            params: serde_json::json!({
+                "args": args,
+                "snapshot": snapshot,
+            }),
+            activity_key: None,
+        }));
+    }
+
     /// Shared helper for getDeclaredType/getComputedType/getExpectedType.
     fn send_get_type_request(
         &mut self,
@@ -560,18 +587,25 @@ pub struct TspInteraction {
 
 impl TspInteraction {
     pub fn new() -> Self {
-        init_test();
-
-        let ((conn_server, server_reader), (conn_client, _client_reader)) = Connection::memory();
-        let client_receiver = conn_client.channel_receiver().clone();
+        Self::new_with_args(Self::default_args())
+    }
 
-        let args = TspArgs {
+    /// The [`TspArgs`] that [`TspInteraction::new`] starts the server with.
+    pub fn default_args() -> TspArgs {
+        TspArgs {
             indexing_mode: IndexingMode::LazyBlocking,
             workspace_indexing_limit: 0,
+            project_indexing_batch_size: None,
             transport: "stdio".to_owned(),
-        };
+        }
+    }
+
+    /// Create a `TspInteraction` whose server is started with custom [`TspArgs`].
+    pub fn new_with_args(args: TspArgs) -> Self {
+        init_test();
 
-        let args = args.clone();
+        let ((conn_server, server_reader), (conn_client, _client_reader)) = Connection::memory();
+        let client_receiver = conn_client.channel_receiver().clone();
 
         let request_idx = Arc::new(Mutex::new(0));
 
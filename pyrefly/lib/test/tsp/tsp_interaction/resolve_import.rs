@@ -163,6 +163,41 @@ fn test_resolve_import_stale_snapshot() {
         error: Some(lsp_server::ResponseError {
             code: lsp_server::ErrorCode::ServerCancelled as i32,
             message: "Snapshot outdated: client sent 9999, server is at 1".to_owned(),
+            data: Some(serde_json::json!({ "currentSnapshot": 1 })),
+        }),
+    });
+
+    tsp.shutdown();
+}
+
+#[test]
+fn test_resolve_import_source_uri_without_file_path() {
+    // A source URI that does not map to a file is an invalid request, not an
+    // unresolved import.
+    let temp_dir = TempDir::new().unwrap();
+    write_pyproject(temp_dir.path());
+
+    let main_path = temp_dir.path().join("main.py");
+    std::fs::write(&main_path, "# empty\n").unwrap();
+
+    let mut tsp = TspInteraction::new();
+    tsp.set_root(temp_dir.path().to_path_buf());
+    tsp.initialize(Default::default());
+
+    tsp.server.did_open("main.py");
+    tsp.client.expect_any_message();
+
+    let snapshot = get_current_snapshot(&mut tsp, 2);
+
+    tsp.server
+        .resolve_import("http://example.com/main.py", vec!["os"], 0, snapshot);
+
+    tsp.client.expect_response(Response {
+        id: RequestId::from(3),
+        result: None,
+        error: Some(lsp_server::ResponseError {
+            code: lsp_server::ErrorCode::InvalidParams as i32,
+            message: "Invalid params: URI does not map to a file".to_owned(),
             data: None,
         }),
     });
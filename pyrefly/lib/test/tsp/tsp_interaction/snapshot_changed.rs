@@ -7,9 +7,13 @@
 
 //! Tests for TSP snapshotChanged notification
 
+use std::num::NonZeroUsize;
+
 use tempfile::TempDir;
 
+use crate::commands::tsp::TspArgs;
 use crate::test::tsp::tsp_interaction::object_model::TspInteraction;
+use crate::test::tsp::tsp_interaction::object_model::get_current_snapshot;
 
 #[test]
 fn test_tsp_snapshot_changed_notification_on_recheck() {
@@ -76,3 +80,48 @@ fn test_tsp_snapshot_changed_notification_on_did_change() {
 
     tsp.shutdown();
 }
+
+#[test]
+fn test_tsp_snapshot_changed_per_project_indexing_batch() {
+    // Indexing a project that spans several batches commits each batch separately,
+    // and the snapshot should advance after every batch rather than once per pass.
+    let temp_dir = TempDir::new().unwrap();
+    let package_dir = temp_dir.path().join("pkg");
+    std::fs::create_dir(&package_dir).unwrap();
+    let batch_size = NonZeroUsize::new(2).unwrap();
+    let file_count = 2 * batch_size.get() + 1;
+    for i in 0..file_count {
+        std::fs::write(package_dir.join(format!("module_{i}.py")), "x = 1\n").unwrap();
+    }
+    std::fs::write(
+        temp_dir.path().join("pyrefly.toml"),
+        "project-includes = [\"pkg\"]\n",
+    )
+    .unwrap();
+    // Outside `project-includes`, so opening it triggers indexing without any project file
+    // being open.
+    std::fs::write(temp_dir.path().join("main.py"), "x = 1\n").unwrap();
+
+    let mut tsp = TspInteraction::new_with_args(TspArgs {
+        project_indexing_batch_size: Some(batch_size),
+        ..TspInteraction::default_args()
+    });
+    tsp.set_root(temp_dir.path().to_path_buf());
+    tsp.initialize(Default::default());
+
+    tsp.server.did_open("main.py");
+
+    let batches = file_count.div_ceil(batch_size.get()) as i32;
+    for batch in 1..=batches {
+        let params = tsp.client.expect_notification("typeServer/snapshotChanged");
+        assert_eq!(params["old"].as_i64(), Some(i64::from(batch - 1)));
+        assert_eq!(params["new"].as_i64(), Some(i64::from(batch)));
+    }
+    assert_eq!(
+        get_current_snapshot(&mut tsp, 2),
+        batches,
+        "a multi-batch indexing pass should advance the snapshot once per batch"
+    );
+
+    tsp.shutdown();
+}
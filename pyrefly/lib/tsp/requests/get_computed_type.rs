@@ -38,6 +38,6 @@ impl<T: TspInterface> TspConnection<T> {
             start.character,
             end.line,
             end.character,
-        ))
+        )?)
     }
 }
@@ -37,6 +37,6 @@ impl<T: TspInterface> TspConnection<T> {
         let position = params.position();
         Ok(self
             .inner()
-            .type_at_position(params.uri(), position.line, position.character))
+            .type_at_position(params.uri(), position.line, position.character)?)
     }
 }
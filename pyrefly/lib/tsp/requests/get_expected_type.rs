@@ -32,8 +32,10 @@ impl<T: TspInterface> TspConnection<T> {
         // to notebook paths inside expected_type_at_position.
         parse_uri(params.uri())?;
         let position = params.position();
-        Ok(self
-            .inner()
-            .expected_type_at_position(params.uri(), position.line, position.character))
+        Ok(self.inner().expected_type_at_position(
+            params.uri(),
+            position.line,
+            position.character,
+        )?)
     }
 }
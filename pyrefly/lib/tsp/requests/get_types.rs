@@ -0,0 +1,43 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Implementation of the batched `typeServer/getTypes` TSP request.
+
+use lsp_server::ResponseError;
+use tsp_types::GetTypesParams;
+use tsp_types::GetTypesResponse;
+use tsp_types::Range;
+
+use crate::lsp::non_wasm::server::TspInterface;
+use crate::tsp::server::TspConnection;
+use crate::tsp::validation::parse_uri;
+
+impl<T: TspInterface> TspConnection<T> {
+    /// Return the computed type of every requested node, in request order.
+    ///
+    /// Equivalent to one `getComputedType` request per node, but the whole
+    /// batch shares a single transaction, so a client analyzing a file pays
+    /// for loading it once rather than once per node.
+    pub fn handle_get_types(
+        &self,
+        params: GetTypesParams,
+    ) -> Result<GetTypesResponse, ResponseError> {
+        self.validate_snapshot(params.snapshot)?;
+        let mut nodes = Vec::with_capacity(params.args.len());
+        for arg in &params.args {
+            parse_uri(arg.uri())?;
+            nodes.push((
+                arg.uri(),
+                Range {
+                    start: arg.position(),
+                    end: arg.end_position(),
+                },
+            ));
+        }
+        Ok(self.inner().computed_types_at_ranges(&nodes)?)
+    }
+}
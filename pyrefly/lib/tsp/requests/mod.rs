@@ -13,4 +13,5 @@ pub mod get_expected_type;
 pub mod get_python_search_paths;
 pub mod get_snapshot;
 pub mod get_supported_protocol_version;
+pub mod get_types;
 pub mod resolve_import;
@@ -24,6 +24,7 @@ use crate::lsp::module_helpers::to_real_path;
 use crate::lsp::non_wasm::server::TspInterface;
 use crate::lsp::non_wasm::transaction_manager::TransactionManager;
 use crate::tsp::server::TspConnection;
+use crate::tsp::validation::TspRequestError;
 use crate::tsp::validation::invalid_params_error;
 use crate::tsp::validation::parse_uri;
 
@@ -33,7 +34,8 @@ impl<T: TspInterface> TspConnection<T> {
     /// Converts the TSP [`ResolveImportParams`] into pyrefly's internal
     /// [`ModuleName`], resolves via [`Transaction::import_handle`], and returns
     /// the resolved module's file URI as a string (or JSON `null` if the
-    /// module cannot be found).
+    /// module cannot be found). A source URI that does not map to a file is
+    /// rejected with [`TspRequestError::NoFilePath`].
     pub fn handle_resolve_import<'a>(
         &'a self,
         id: RequestId,
@@ -57,8 +59,7 @@ impl<T: TspInterface> TspConnection<T> {
         let source_path = match self.inner().resolve_uri_to_path(&source_url) {
             Some(p) => p,
             None => {
-                // URI cannot be resolved to a filesystem path — return null.
-                self.send_ok::<Option<String>>(id, None);
+                self.send_err(id, TspRequestError::NoFilePath.into());
                 return;
             }
         };
@@ -26,8 +26,10 @@ use tsp_types::ConnectionRequestParams;
 use tsp_types::ConnectionRequestResult;
 use tsp_types::ConnectionTransportKind;
 use tsp_types::GetTypeParams;
+use tsp_types::GetTypesParams;
 use tsp_types::TSPNotificationMethods;
 use tsp_types::TSPRequests;
+use tsp_types::TYPE_SERVER_GET_TYPES_METHOD;
 
 use crate::commands::lsp::IndexingMode;
 use crate::lsp::non_wasm::lsp::new_response;
@@ -276,6 +278,22 @@ impl<T: TspInterface> TspConnection<T> {
             }
         }
     }
+
+    /// Handle the batched `typeServer/getTypes` request. It is not part of the
+    /// generated [`TSPRequests`], so callers match it by method name.
+    fn dispatch_get_types_request(&self, id: RequestId, raw_params: serde_json::Value) {
+        let params = match serde_json::from_value::<GetTypesParams>(raw_params) {
+            Ok(p) => p,
+            Err(e) => {
+                self.send_err(id, invalid_params_error(&e.to_string()));
+                return;
+            }
+        };
+        match self.handle_get_types(params) {
+            Ok(result) => self.send_ok(id, result),
+            Err(err) => self.send_err(id, err),
+        }
+    }
 }
 
 /// The main (stdio) connection. Only this type can manage extra connections
@@ -326,6 +344,9 @@ impl<T: TspInterface> TspMainConnection<T> {
                 Some(msg) => {
                     self.dispatch_tsp_request(ide_transaction_manager, request, msg)?;
                 }
+                None if request.method == TYPE_SERVER_GET_TYPES_METHOD => {
+                    self.dispatch_get_types_request(request.id.clone(), request.params.clone());
+                }
                 None => {
                     self.send_response(Response::new_err(
                         request.id.clone(),
@@ -540,6 +561,12 @@ impl<T: TspInterface> TspExtraConnection<T> {
                                             break;
                                         }
                                     }
+                                    None if request.method == TYPE_SERVER_GET_TYPES_METHOD => {
+                                        self.dispatch_get_types_request(
+                                            request.id.clone(),
+                                            request.params.clone(),
+                                        );
+                                    }
                                     None => {
                                         self.send_response(Response::new_err(
                                             request.id,
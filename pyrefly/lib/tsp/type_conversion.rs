@@ -57,6 +57,7 @@ use pyrefly_types::types::BoundMethodType;
 use pyrefly_types::types::Forallable;
 use pyrefly_types::types::Type as PyreflyType;
 use ruff_python_ast::name::Name;
+use ruff_source_file::PositionEncoding;
 use ruff_text_size::TextRange;
 use tsp_types::BuiltInType;
 use tsp_types::ClassType as TspClassType;
@@ -694,7 +695,7 @@ impl TypeConverter<'_> {
         if let FunctionKind::Def(func_id) = kind
             && let Some(range) = self.resolve_func_range.and_then(|resolve| resolve(func_id))
         {
-            let lsp_range = func_id.module.to_lsp_range(range);
+            let lsp_range = func_id.module.to_lsp_range(range, PositionEncoding::Utf16);
             return Declaration::Regular(RegularDeclaration {
                 category: DeclarationCategory::Function,
                 kind: DeclarationKind::Regular,
@@ -912,7 +913,11 @@ fn convert_literal(lit: &pyrefly_types::literal::Literal) -> TspType {
 fn convert_sentinel(sentinel: &Sentinel) -> TspType {
     let qname = sentinel.qname();
     let node = Node {
-        range: lsp_range_to_tsp(qname.module().to_lsp_range(qname.range())),
+        range: lsp_range_to_tsp(
+            qname
+                .module()
+                .to_lsp_range(qname.range(), PositionEncoding::Utf16),
+        ),
         uri: path_to_uri(qname.module_path()),
     };
     TspType::Class(TspClassType {
@@ -991,7 +996,7 @@ fn make_typevar_declared(qname: &pyrefly_python::qname::QName) -> DeclaredType {
     let module_path = qname.module_path();
     let uri = path_to_uri(module_path);
     let range = qname.range();
-    let lsp_range = qname.module().to_lsp_range(range);
+    let lsp_range = qname.module().to_lsp_range(range, PositionEncoding::Utf16);
 
     DeclaredType {
         declaration: Declaration::Regular(RegularDeclaration {
@@ -1017,7 +1022,7 @@ fn make_class_declaration(cls: &Class) -> RegularDeclaration {
     let module_path = qname.module_path();
     let range = qname.range();
 
-    let lsp_range = module.to_lsp_range(range);
+    let lsp_range = module.to_lsp_range(range, PositionEncoding::Utf16);
     let uri = path_to_uri(module_path);
 
     RegularDeclaration {
@@ -11,6 +11,7 @@
 //! error behavior across the protocol surface. The helpers cover:
 //!
 //! - Canonical TSP error construction (invalid params, internal, etc.)
+//! - The reasons a request about a file cannot be answered
 
 use lsp_server::ErrorCode;
 use lsp_server::ResponseError;
@@ -23,15 +24,16 @@ use lsp_types::Url;
 /// Build a `ResponseError` for a stale snapshot.
 ///
 /// Returned when the client supplies a snapshot version that no longer matches
-/// the server's current snapshot. The client should re-fetch the snapshot and
-/// retry.
+/// the server's current snapshot. The error `data` carries the current snapshot
+/// as `{ "currentSnapshot": number }`, so the client can retry with it directly
+/// instead of re-fetching it first.
 pub fn snapshot_outdated_error(client_snapshot: i32, server_snapshot: i32) -> ResponseError {
     ResponseError {
         code: ErrorCode::ServerCancelled as i32,
         message: format!(
             "Snapshot outdated: client sent {client_snapshot}, server is at {server_snapshot}"
         ),
-        data: None,
+        data: Some(serde_json::json!({ "currentSnapshot": server_snapshot })),
     }
 }
 
@@ -53,6 +55,39 @@ pub fn internal_error(detail: &str) -> ResponseError {
     }
 }
 
+/// Why a request about a location in a file cannot be answered at all.
+///
+/// Handlers answer `null` only when the file was analyzed but nothing was found
+/// at the location, so clients can tell that apart from these failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TspRequestError {
+    /// The URI does not map to a file path.
+    NoFilePath,
+    /// Language services are disabled for the workspace containing the file.
+    LanguageServicesDisabled,
+    /// The module for the file is not loaded by the server.
+    ModuleLoadFailed,
+    /// The requested range is not a range in the file, because it ends before it starts.
+    PositionOutOfRange,
+}
+
+impl From<TspRequestError> for ResponseError {
+    fn from(err: TspRequestError) -> Self {
+        match err {
+            TspRequestError::NoFilePath => invalid_params_error("URI does not map to a file"),
+            TspRequestError::LanguageServicesDisabled => ResponseError {
+                code: ErrorCode::RequestFailed as i32,
+                message: "Language services are disabled for this file".to_owned(),
+                data: None,
+            },
+            TspRequestError::ModuleLoadFailed => internal_error("module is not loaded"),
+            TspRequestError::PositionOutOfRange => {
+                invalid_params_error("range ends before it starts")
+            }
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // URI parsing
 // ---------------------------------------------------------------------------
@@ -85,6 +120,12 @@ mod tests {
         assert!(err.message.contains("7"), "should mention server snapshot");
     }
 
+    #[test]
+    fn test_snapshot_outdated_error_data_carries_current_snapshot() {
+        let err = snapshot_outdated_error(3, 7);
+        assert_eq!(err.data, Some(serde_json::json!({ "currentSnapshot": 7 })));
+    }
+
     #[test]
     fn test_invalid_params_error_code() {
         let err = invalid_params_error("missing field");
@@ -111,8 +152,7 @@ mod tests {
 
     #[test]
     fn test_error_data_is_none() {
-        // All canonical errors should have data = None
-        assert!(snapshot_outdated_error(0, 1).data.is_none());
+        // Canonical errors other than the stale-snapshot one carry no data
         assert!(invalid_params_error("x").data.is_none());
         assert!(internal_error("x").data.is_none());
     }
@@ -128,6 +168,18 @@ mod tests {
         assert_ne!(params, internal);
     }
 
+    #[test]
+    fn test_request_error_codes_are_distinct() {
+        let no_file_path = ResponseError::from(TspRequestError::NoFilePath).code;
+        let disabled = ResponseError::from(TspRequestError::LanguageServicesDisabled).code;
+        let load_failed = ResponseError::from(TspRequestError::ModuleLoadFailed).code;
+        let out_of_range = ResponseError::from(TspRequestError::PositionOutOfRange).code;
+        assert_eq!(no_file_path, ErrorCode::InvalidParams as i32);
+        assert_eq!(out_of_range, ErrorCode::InvalidParams as i32);
+        assert_eq!(disabled, ErrorCode::RequestFailed as i32);
+        assert_eq!(load_failed, ErrorCode::InternalError as i32);
+    }
+
     // --- parse_uri unit tests ---
 
     #[test]